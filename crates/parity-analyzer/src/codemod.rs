@@ -0,0 +1,704 @@
+//! Source patches for findings, the `optimized_code` output of `deep-audit`.
+//!
+//! Each finding is planned into [`Fix`]es that implement one of the
+//! `ANCHOR_PATTERNS` (`access-control`, `checked-math`, `cpi-invocation`,
+//! ...). Fixes are applied one at a time as byte-range edits against the
+//! original text, so formatting and comments outside the edited spans are
+//! preserved. Every patched file must parse again before a diff is emitted.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use parity::context_engine::{FrameworkPattern, ANCHOR_PATTERNS};
+use proc_macro2::{LineColumn, Span};
+use similar::TextDiff;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{
+    BinOp, Expr, FnArg, Field, Item, ItemFn, ItemStruct, Lit, Local, MacroDelimiter, Meta, Pat, ReturnType, Signature,
+    Type, Visibility,
+};
+
+use crate::finding::Finding;
+use crate::ir::{derives, FieldType, ProgramIr, StateField, TypeDeclKind};
+
+/// Rewriting stops after this many passes over a handler body even if
+/// nested arithmetic remains.
+const MAX_ARITHMETIC_PASSES: usize = 16;
+
+/// Methods of an integer receiver that return the same integer type.
+const INTEGER_METHODS: &[&str] = &[
+    "checked_add",
+    "checked_sub",
+    "checked_mul",
+    "saturating_add",
+    "saturating_sub",
+    "saturating_mul",
+    "wrapping_add",
+    "wrapping_sub",
+    "wrapping_mul",
+    "pow",
+    "min",
+    "max",
+];
+
+const INTEGER_TYPES: &[&str] =
+    &["u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize"];
+
+/// Program account types for well-known `*_program` fields.
+const KNOWN_PROGRAMS: &[(&str, &str)] = &[
+    ("system_program", "System"),
+    ("token_program", "Token"),
+    ("token_2022_program", "Token2022"),
+    ("associated_token_program", "AssociatedToken"),
+];
+
+#[derive(Debug, Clone)]
+pub struct CodemodOptions {
+    /// Expression passed to `ok_or` when arithmetic is made checked.
+    pub overflow_error: String,
+}
+
+impl Default for CodemodOptions {
+    fn default() -> Self {
+        CodemodOptions {
+            overflow_error: "ProgramError::ArithmeticOverflow".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fix {
+    /// Adds a constraint such as `has_one = authority` to a context field.
+    AddConstraint { context: String, field: String, constraint: String },
+    /// Replaces the declared type of a context field, e.g. `AccountInfo<'info>`
+    /// with `Account<'info, Vault>`.
+    RetypeField { context: String, field: String, ty: String },
+    /// Rewrites `+`, `-`, `*` and their compound assignments on integers in a
+    /// handler returning `Result` to `checked_*(..).ok_or(..)?`.
+    CheckedArithmetic { handler: String },
+}
+
+impl Fix {
+    /// The `ANCHOR_PATTERNS` entry this fix applies.
+    pub fn framework_pattern(&self) -> Option<&'static FrameworkPattern> {
+        let name = match self {
+            Fix::AddConstraint { .. } => "access-control",
+            Fix::RetypeField { ty, .. } if ty.starts_with("Program<") => "cpi-invocation",
+            Fix::RetypeField { .. } => "access-control",
+            Fix::CheckedArithmetic { .. } => "checked-math",
+        };
+        ANCHOR_PATTERNS.iter().find(|p| p.pattern_name == name)
+    }
+}
+
+impl std::fmt::Display for Fix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Fix::AddConstraint { context, field, constraint } => {
+                write!(f, "add `{}` to {}.{}", constraint, context, field)
+            }
+            Fix::RetypeField { context, field, ty } => write!(f, "declare {}.{} as `{}`", context, field, ty),
+            Fix::CheckedArithmetic { handler } => write!(f, "use checked arithmetic in `{}`", handler),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CodemodError {
+    /// No source was supplied for a file a fix targets.
+    MissingSource(String),
+    /// The original file does not parse.
+    Parse(String, syn::Error),
+    /// The patched file no longer parses; the patch is discarded.
+    Verify(String, syn::Error),
+    /// The item a fix targets is not in the file.
+    NotFound(String, Fix),
+}
+
+impl std::fmt::Display for CodemodError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodemodError::MissingSource(file) => write!(f, "{}: source not available", file),
+            CodemodError::Parse(file, err) => write!(f, "{}: {}", file, err),
+            CodemodError::Verify(file, err) => write!(f, "{}: patched source does not parse: {}", file, err),
+            CodemodError::NotFound(file, fix) => write!(f, "{}: cannot {}: target not found", file, fix),
+        }
+    }
+}
+
+impl std::error::Error for CodemodError {}
+
+/// The patched contents of one file.
+#[derive(Debug, Clone)]
+pub struct Patch {
+    pub file: String,
+    pub fixes: Vec<Fix>,
+    /// Rule ids of the findings the fixes were planned from.
+    pub rule_ids: Vec<String>,
+    pub original: String,
+    pub patched: String,
+}
+
+impl Patch {
+    pub fn unified_diff(&self) -> String {
+        TextDiff::from_lines(&self.original, &self.patched)
+            .unified_diff()
+            .context_radius(3)
+            .header(&format!("a/{}", self.file), &format!("b/{}", self.file))
+            .to_string()
+    }
+}
+
+/// The fixes that resolve `finding`, empty when no automatic fix exists.
+pub fn plan(finding: &Finding, ir: &ProgramIr) -> Vec<Fix> {
+    let field = finding
+        .subject
+        .split_once('.')
+        .and_then(|(context, name)| ir.context(context)?.field(name));
+
+    match (finding.rule_id.as_str(), field) {
+        ("unchecked-arithmetic", _) => vec![Fix::CheckedArithmetic { handler: finding.subject.clone() }],
+        ("missing-signer-check", Some(field)) => {
+            let mut fixes = Vec::new();
+            if is_unchecked(&field.wrapper) {
+                fixes.push(Fix::RetypeField {
+                    context: field.context.clone(),
+                    field: field.name.clone(),
+                    ty: "Signer<'info>".to_string(),
+                });
+            }
+            // Tie every state account that records this authority to it.
+            let constraint = format!("has_one = {}", field.name);
+            for other in ir.context(&field.context).map(|c| c.fields.as_slice()).unwrap_or_default() {
+                let records_authority = other
+                    .inner
+                    .as_deref()
+                    .and_then(|inner| ir.account(inner))
+                    .map(|account| account.fields.iter().any(|f| f.name == field.name))
+                    .unwrap_or(false);
+                let constrained = other
+                    .constraints
+                    .iter()
+                    .any(|c| c.base_name() == "has_one" && c.value.as_deref() == Some(field.name.as_str()));
+                if other.wrapper == "Account" && records_authority && !constrained {
+                    fixes.push(Fix::AddConstraint {
+                        context: field.context.clone(),
+                        field: other.name.clone(),
+                        constraint: constraint.clone(),
+                    });
+                }
+            }
+            fixes
+        }
+        ("insecure-cpi", Some(field)) => KNOWN_PROGRAMS
+            .iter()
+            .find(|(name, _)| *name == field.name)
+            .map(|(_, program)| Fix::RetypeField {
+                context: field.context.clone(),
+                field: field.name.clone(),
+                ty: format!("Program<'info, {}>", program),
+            })
+            .into_iter()
+            .collect(),
+        ("owner-check" | "account-deserialization" | "type-cosplay" | "raw-account-info", Some(field))
+            if is_unchecked(&field.wrapper) =>
+        {
+            // Only retype when the field name identifies an account type.
+            let ty = upper_camel(&field.name);
+            match ir.account(&ty) {
+                Some(_) => vec![Fix::RetypeField {
+                    context: field.context.clone(),
+                    field: field.name.clone(),
+                    ty: format!("Account<'info, {}>", ty),
+                }],
+                None => Vec::new(),
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Plans and applies fixes for `findings`, one [`Patch`] per changed file.
+/// `sources` are `(file name, source)` pairs keyed like [`ProgramIr::from_sources`].
+pub fn generate_patches(
+    ir: &ProgramIr,
+    findings: &[Finding],
+    sources: &[(String, String)],
+    options: &CodemodOptions,
+) -> Result<Vec<Patch>, CodemodError> {
+    let mut planned: BTreeMap<String, (Vec<Fix>, Vec<String>)> = BTreeMap::new();
+    for finding in findings {
+        for fix in plan(finding, ir) {
+            let Some(file) = target_file(&fix, ir) else {
+                continue;
+            };
+            let (fixes, rule_ids) = planned.entry(file).or_default();
+            // The first retype of a field wins; later findings on it are
+            // usually resolved by the same change.
+            let conflicts = fixes.iter().any(|existing| match (existing, &fix) {
+                (
+                    Fix::RetypeField { context: c1, field: f1, .. },
+                    Fix::RetypeField { context: c2, field: f2, .. },
+                ) => c1 == c2 && f1 == f2,
+                (a, b) => a == b,
+            });
+            if !conflicts {
+                fixes.push(fix);
+            }
+            if !rule_ids.contains(&finding.rule_id) {
+                rule_ids.push(finding.rule_id.clone());
+            }
+        }
+    }
+
+    let mut patches = Vec::new();
+    for (file, (fixes, rule_ids)) in planned {
+        if fixes.is_empty() {
+            continue;
+        }
+        let original = sources
+            .iter()
+            .find(|(name, _)| *name == file)
+            .map(|(_, source)| source.clone())
+            .ok_or_else(|| CodemodError::MissingSource(file.clone()))?;
+        let patched = apply(ir, &file, &original, &fixes, options)?;
+        if patched != original {
+            patches.push(Patch { file, fixes, rule_ids, original, patched });
+        }
+    }
+    Ok(patches)
+}
+
+/// Applies `fixes` to one file of `ir` in order and checks the result still
+/// parses.
+pub fn apply(
+    ir: &ProgramIr,
+    file: &str,
+    source: &str,
+    fixes: &[Fix],
+    options: &CodemodOptions,
+) -> Result<String, CodemodError> {
+    syn::parse_file(source).map_err(|e| CodemodError::Parse(file.to_string(), e))?;
+
+    let mut text = source.to_string();
+    for fix in fixes {
+        text = match fix {
+            Fix::AddConstraint { context, field, constraint } => {
+                edit_field(file, &text, fix, context, field, |f, src| add_constraint(f, src, constraint))?
+            }
+            Fix::RetypeField { context, field, ty } => edit_field(file, &text, fix, context, field, |f, src| {
+                vec![Edit::replace(src, f.ty.span(), ty.clone())]
+            })?,
+            Fix::CheckedArithmetic { handler } => checked_arithmetic(ir, file, &text, fix, handler, options)?,
+        };
+    }
+
+    syn::parse_file(&text).map_err(|e| CodemodError::Verify(file.to_string(), e))?;
+    Ok(text)
+}
+
+fn target_file(fix: &Fix, ir: &ProgramIr) -> Option<String> {
+    match fix {
+        Fix::AddConstraint { context, .. } | Fix::RetypeField { context, .. } => {
+            ir.context(context).map(|c| c.location.file.clone())
+        }
+        Fix::CheckedArithmetic { handler } => ir
+            .handlers
+            .iter()
+            .find(|h| h.name == *handler)
+            .map(|h| h.location.file.clone()),
+    }
+}
+
+fn is_unchecked(wrapper: &str) -> bool {
+    matches!(wrapper, "AccountInfo" | "UncheckedAccount")
+}
+
+fn upper_camel(snake: &str) -> String {
+    snake
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// A replacement of `source[start..end]`.
+struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+impl Edit {
+    fn replace(source: &str, span: Span, text: String) -> Self {
+        Edit {
+            start: offset(source, span.start()),
+            end: offset(source, span.end()),
+            text,
+        }
+    }
+
+    fn insert(at: usize, text: String) -> Self {
+        Edit { start: at, end: at, text }
+    }
+}
+
+/// Applies non-overlapping edits.
+fn apply_edits(source: &str, mut edits: Vec<Edit>) -> String {
+    edits.sort_by_key(|e| std::cmp::Reverse(e.start));
+    let mut text = source.to_string();
+    for edit in edits {
+        text.replace_range(edit.start..edit.end, &edit.text);
+    }
+    text
+}
+
+/// Byte offset of a span position; columns count characters.
+fn offset(source: &str, position: LineColumn) -> usize {
+    let line_start: usize = source
+        .split_inclusive('\n')
+        .take(position.line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let line = &source[line_start..];
+    line_start
+        + line
+            .char_indices()
+            .nth(position.column)
+            .map(|(i, _)| i)
+            .unwrap_or(line.len())
+}
+
+fn edit_field(
+    file: &str,
+    source: &str,
+    fix: &Fix,
+    context: &str,
+    field: &str,
+    edits: impl Fn(&Field, &str) -> Vec<Edit>,
+) -> Result<String, CodemodError> {
+    let ast = syn::parse_file(source).map_err(|e| CodemodError::Parse(file.to_string(), e))?;
+    let target = find_context(&ast.items, context)
+        .and_then(|item| item.fields.iter().find(|f| f.ident.as_ref().map(|i| i == field).unwrap_or(false)))
+        .ok_or_else(|| CodemodError::NotFound(file.to_string(), fix.clone()))?;
+    Ok(apply_edits(source, edits(target, source)))
+}
+
+fn find_context<'a>(items: &'a [Item], name: &str) -> Option<&'a ItemStruct> {
+    items.iter().find_map(|item| match item {
+        Item::Struct(s) if s.ident == name && derives(&s.attrs, "Accounts") => Some(s),
+        Item::Mod(m) => m.content.as_ref().and_then(|(_, items)| find_context(items, name)),
+        _ => None,
+    })
+}
+
+fn find_fn<'a>(items: &'a [Item], name: &str) -> Option<&'a ItemFn> {
+    items.iter().find_map(|item| match item {
+        Item::Fn(f) if f.sig.ident == name => Some(f),
+        Item::Mod(m) => m.content.as_ref().and_then(|(_, items)| find_fn(items, name)),
+        _ => None,
+    })
+}
+
+fn add_constraint(field: &Field, source: &str, constraint: &str) -> Vec<Edit> {
+    let normalized = constraint.replace(' ', "");
+    let account_attr = field.attrs.iter().find(|a| a.path().is_ident("account"));
+    match account_attr.map(|a| &a.meta) {
+        Some(Meta::List(list)) => {
+            let tokens = list.tokens.to_string();
+            if tokens.split(',').any(|c| c.replace(' ', "") == normalized) {
+                return Vec::new();
+            }
+            let MacroDelimiter::Paren(paren) = &list.delimiter else {
+                return Vec::new();
+            };
+            let close = offset(source, paren.span.close().start());
+            let separator = if tokens.trim().is_empty() { "" } else { ", " };
+            vec![Edit::insert(close, format!("{}{}", separator, constraint))]
+        }
+        Some(meta @ Meta::Path(_)) => {
+            vec![Edit::replace(source, meta.span(), format!("account({})", constraint))]
+        }
+        Some(Meta::NameValue(_)) => Vec::new(),
+        None => {
+            let anchor = match &field.vis {
+                Visibility::Inherited => field.ident.span(),
+                vis => vis.span(),
+            };
+            let at = offset(source, anchor.start());
+            let line_start = source[..at].rfind('\n').map(|i| i + 1).unwrap_or(0);
+            let indent: String = source[line_start..at].chars().take_while(|c| c.is_whitespace()).collect();
+            vec![Edit::insert(at, format!("#[account({})]\n{}", constraint, indent))]
+        }
+    }
+}
+
+fn checked_arithmetic(
+    ir: &ProgramIr,
+    file: &str,
+    source: &str,
+    fix: &Fix,
+    handler: &str,
+    options: &CodemodOptions,
+) -> Result<String, CodemodError> {
+    let fields = integer_fields(ir);
+    let mut text = source.to_string();
+    for _ in 0..MAX_ARITHMETIC_PASSES {
+        let ast = syn::parse_file(&text).map_err(|e| CodemodError::Verify(file.to_string(), e))?;
+        let item = find_fn(&ast.items, handler).ok_or_else(|| CodemodError::NotFound(file.to_string(), fix.clone()))?;
+        // `?` needs a `Result` to return the overflow error through.
+        if !returns_result(&item.sig) {
+            break;
+        }
+
+        let mut visitor = ArithmeticVisitor {
+            source: &text,
+            error: &options.overflow_error,
+            fields: &fields,
+            locals: integer_params(&item.sig),
+            edits: Vec::new(),
+        };
+        visitor.visit_block(&item.block);
+        if visitor.edits.is_empty() {
+            break;
+        }
+        let edits = visitor.edits;
+        text = apply_edits(&text, edits);
+    }
+    Ok(text)
+}
+
+/// Field names that are integers in every account and type that declares
+/// them, so `a.balance` is known to be an integer whatever `a` is.
+fn integer_fields(ir: &ProgramIr) -> BTreeSet<String> {
+    let mut declared: Vec<&StateField> = ir.accounts.iter().flat_map(|a| &a.fields).collect();
+    for decl in &ir.types {
+        match &decl.kind {
+            TypeDeclKind::Struct(fields) => declared.extend(fields),
+            TypeDeclKind::Enum(variants) => declared.extend(variants.iter().flatten()),
+        }
+    }
+    let mut integer: BTreeMap<&str, bool> = BTreeMap::new();
+    for field in declared {
+        let is_integer = matches!(field.ty, FieldType::Int { .. });
+        *integer.entry(&field.name).or_insert(true) &= is_integer;
+    }
+    integer.into_iter().filter(|(_, is_integer)| *is_integer).map(|(name, _)| name.to_string()).collect()
+}
+
+fn returns_result(sig: &Signature) -> bool {
+    match &sig.output {
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Path(path) => path.path.segments.last().is_some_and(|s| s.ident == "Result"),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    }
+}
+
+fn is_integer_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path.path.get_ident().is_some_and(|ident| INTEGER_TYPES.iter().any(|t| ident == t)),
+        _ => false,
+    }
+}
+
+fn integer_params(sig: &Signature) -> BTreeSet<String> {
+    sig.inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(arg) if is_integer_type(&arg.ty) => match arg.pat.as_ref() {
+                Pat::Ident(pat) => Some(pat.ident.to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_integer_literal(expr: &Expr) -> bool {
+    matches!(expr, Expr::Lit(lit) if matches!(lit.lit, Lit::Int(_)))
+}
+
+/// Collects rewrites for the outermost fixable arithmetic expressions; nested
+/// ones are picked up by the next pass over the rewritten text.
+///
+/// Only arithmetic whose operands are known to be integers is rewritten:
+/// integer literals, casts to an integer type, integer parameters and
+/// `let` bindings in scope, fields in `fields`, and arithmetic or integer
+/// methods over those. Closures and nested items are left alone, since a
+/// `?` inside them would not return from the handler.
+struct ArithmeticVisitor<'a> {
+    source: &'a str,
+    error: &'a str,
+    fields: &'a BTreeSet<String>,
+    /// Integer bindings in scope.
+    locals: BTreeSet<String>,
+    edits: Vec<Edit>,
+}
+
+impl ArithmeticVisitor<'_> {
+    fn text(&self, expr: &Expr) -> String {
+        let span = expr.span();
+        self.source[offset(self.source, span.start())..offset(self.source, span.end())].to_string()
+    }
+
+    /// `expr` as a method call receiver.
+    fn receiver(&self, expr: &Expr) -> String {
+        match expr {
+            Expr::Path(_)
+            | Expr::Field(_)
+            | Expr::MethodCall(_)
+            | Expr::Call(_)
+            | Expr::Paren(_)
+            | Expr::Index(_)
+            | Expr::Try(_)
+            | Expr::Macro(_) => self.text(expr),
+            _ => format!("({})", self.text(expr)),
+        }
+    }
+
+    /// Whether `expr` is known to be an integer. An integer literal only
+    /// counts next to another operand that is, which fixes its type.
+    fn is_integer(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Path(path) => match (path.path.get_ident(), path.path.segments.first()) {
+                (Some(ident), _) => self.locals.contains(&ident.to_string()),
+                // `u64::MAX` and the like.
+                (None, Some(first)) => {
+                    path.path.segments.len() == 2 && INTEGER_TYPES.iter().any(|t| first.ident == t)
+                }
+                (None, None) => false,
+            },
+            Expr::Field(field) => match &field.member {
+                syn::Member::Named(name) => self.fields.contains(&name.to_string()),
+                syn::Member::Unnamed(_) => false,
+            },
+            Expr::Paren(paren) => self.is_integer(&paren.expr),
+            Expr::Cast(cast) => is_integer_type(&cast.ty),
+            Expr::Binary(binary) => match binary.op {
+                BinOp::Add(_) | BinOp::Sub(_) | BinOp::Mul(_) | BinOp::Div(_) | BinOp::Rem(_) => {
+                    self.operands_are_integers(&binary.left, &binary.right)
+                }
+                _ => false,
+            },
+            Expr::MethodCall(call) if call.method == "len" && call.args.is_empty() => true,
+            Expr::MethodCall(call) if INTEGER_METHODS.iter().any(|m| call.method == m) => {
+                self.is_integer(&call.receiver)
+            }
+            // `x.checked_add(y).ok_or(..)?`, as an earlier pass wrote it.
+            Expr::Try(try_expr) => match try_expr.expr.as_ref() {
+                Expr::MethodCall(ok_or) if ok_or.method == "ok_or" => self.is_integer(&ok_or.receiver),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn operands_are_integers(&self, left: &Expr, right: &Expr) -> bool {
+        match (is_integer_literal(left), is_integer_literal(right)) {
+            (true, true) => false,
+            (true, false) => self.is_integer(right),
+            (false, true) => self.is_integer(left),
+            (false, false) => self.is_integer(left) && self.is_integer(right),
+        }
+    }
+
+    fn rewrite(&self, binary: &syn::ExprBinary) -> Option<String> {
+        let (method, commutative, assign) = match binary.op {
+            BinOp::Add(_) => ("checked_add", true, false),
+            BinOp::Sub(_) => ("checked_sub", false, false),
+            BinOp::Mul(_) => ("checked_mul", true, false),
+            BinOp::AddAssign(_) => ("checked_add", true, true),
+            BinOp::SubAssign(_) => ("checked_sub", false, true),
+            BinOp::MulAssign(_) => ("checked_mul", true, true),
+            _ => return None,
+        };
+        let (left, right) = (binary.left.as_ref(), binary.right.as_ref());
+        if !self.operands_are_integers(left, right) {
+            return None;
+        }
+        if assign {
+            // Lamport arithmetic is reported separately and goes through
+            // `add_lamports`/`sub_lamports` instead.
+            if self.text(left).contains("lamports") {
+                return None;
+            }
+            return Some(format!(
+                "{} = {}.{}({}).ok_or({})?",
+                self.text(left),
+                self.receiver(left),
+                method,
+                self.text(right),
+                self.error
+            ));
+        }
+        // An untyped integer literal cannot be a method receiver.
+        let (receiver, argument) = match (is_integer_literal(left), commutative) {
+            (false, _) => (left, right),
+            (true, true) => (right, left),
+            (true, false) => return None,
+        };
+        Some(format!(
+            "{}.{}({}).ok_or({})?",
+            self.receiver(receiver),
+            method,
+            self.text(argument),
+            self.error
+        ))
+    }
+}
+
+impl<'ast> Visit<'ast> for ArithmeticVisitor<'_> {
+    fn visit_expr_binary(&mut self, binary: &'ast syn::ExprBinary) {
+        match self.rewrite(binary) {
+            Some(text) => self.edits.push(Edit::replace(self.source, binary.span(), text)),
+            None => visit::visit_expr_binary(self, binary),
+        }
+    }
+
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        let outer = self.locals.clone();
+        visit::visit_block(self, block);
+        self.locals = outer;
+    }
+
+    fn visit_local(&mut self, local: &'ast Local) {
+        if let Some(init) = &local.init {
+            self.visit_expr(&init.expr);
+            if let Some((_, diverge)) = &init.diverge {
+                self.visit_expr(diverge);
+            }
+        }
+        // Every name the pattern binds shadows what came before.
+        self.visit_pat(&local.pat);
+        let binding = match &local.pat {
+            Pat::Ident(pat) => local.init.as_ref().filter(|init| self.is_integer(&init.expr)).map(|_| &pat.ident),
+            Pat::Type(typed) if is_integer_type(&typed.ty) => match typed.pat.as_ref() {
+                Pat::Ident(pat) => Some(&pat.ident),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(ident) = binding {
+            self.locals.insert(ident.to_string());
+        }
+    }
+
+    fn visit_pat_ident(&mut self, pat: &'ast syn::PatIdent) {
+        self.locals.remove(&pat.ident.to_string());
+        visit::visit_pat_ident(self, pat);
+    }
+
+    fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+
+    fn visit_expr_async(&mut self, _: &'ast syn::ExprAsync) {}
+
+    fn visit_item(&mut self, _: &'ast Item) {}
+}
@@ -0,0 +1,238 @@
+//! Cross-skill correlation for `deep-audit`.
+//!
+//! Findings from the individual skills are grouped by the instruction handler
+//! they affect. A compound rule fires when all of its constituent rules hit
+//! the same handler, producing a new finding whose severity is escalated
+//! above its parts. The result is a deduplicated graph ordered by risk.
+
+use std::collections::BTreeMap;
+
+use parity::state::Severity;
+
+use crate::finding::{escalate, severity_rank, Finding};
+use crate::ir::{Handler, ProgramIr};
+use crate::skills::DEEP_AUDIT;
+
+/// Extra risk a finding carries for every compound finding it takes part in.
+pub const PARTICIPATION_RISK: u32 = 10;
+
+pub struct CompoundRule {
+    pub id: &'static str,
+    pub title: &'static str,
+    /// Rule ids that must all be reported against the same handler.
+    pub requires: &'static [&'static str],
+    /// Additional condition on the handler itself.
+    pub condition: Option<fn(&Handler) -> bool>,
+    pub message: &'static str,
+}
+
+pub const COMPOUND_RULES: &[CompoundRule] = &[
+    CompoundRule {
+        id: "cpi-reentrancy",
+        title: "State mutated after an unvalidated CPI",
+        requires: &["insecure-cpi"],
+        condition: Some(|h| !h.writes_after_cpi().is_empty()),
+        message: "The handler calls an unvalidated program and then writes account state; a malicious callee can re-enter before the write lands",
+    },
+    CompoundRule {
+        id: "unauthorized-close",
+        title: "Account can be closed by anyone",
+        requires: &["missing-signer-check", "close-account-drain"],
+        condition: None,
+        message: "Lamports are drained from an account without requiring the authority to sign",
+    },
+    CompoundRule {
+        id: "unauthorized-balance-manipulation",
+        title: "Unsigned caller controls unchecked arithmetic",
+        requires: &["missing-signer-check", "unchecked-arithmetic"],
+        condition: None,
+        message: "Any caller can drive arithmetic that is not overflow-checked",
+    },
+    CompoundRule {
+        id: "spoofed-program-cpi",
+        title: "CPI into an account with unchecked owner",
+        requires: &["owner-check", "insecure-cpi"],
+        condition: None,
+        message: "Both the invoked program and the accounts passed to it can be substituted by the caller",
+    },
+    CompoundRule {
+        id: "remaining-accounts-dos",
+        title: "Compute cost scales with caller-supplied accounts",
+        requires: &["cu-budget"],
+        condition: Some(|h| h.uses_remaining_accounts),
+        message: "The handler iterates remaining_accounts and is already expensive; callers can push it past the compute budget",
+    },
+];
+
+#[derive(Debug, Clone)]
+pub struct FindingNode {
+    pub id: usize,
+    pub finding: Finding,
+    /// Produced by a compound rule rather than a single skill.
+    pub compound: bool,
+    pub risk: u32,
+}
+
+/// Deduplicated findings plus `compound -> constituent` edges, ordered by
+/// descending risk. Node ids are indices into `nodes`.
+#[derive(Debug, Clone, Default)]
+pub struct FindingGraph {
+    pub nodes: Vec<FindingNode>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl FindingGraph {
+    pub fn constituents(&self, id: usize) -> impl Iterator<Item = &FindingNode> + '_ {
+        self.edges
+            .iter()
+            .filter(move |(from, _)| *from == id)
+            .map(move |(_, to)| &self.nodes[*to])
+    }
+
+    pub fn findings(&self) -> impl Iterator<Item = &Finding> + '_ {
+        self.nodes.iter().map(|n| &n.finding)
+    }
+}
+
+pub fn severity_weight(severity: Severity) -> u32 {
+    match severity {
+        Severity::Critical => 100,
+        Severity::High => 50,
+        Severity::Medium => 20,
+        Severity::Info => 5,
+        Severity::Pass => 0,
+    }
+}
+
+/// Merges findings that describe the same issue, keeping the highest severity
+/// and the union of reporting skills.
+pub fn dedup(findings: Vec<Finding>) -> Vec<Finding> {
+    let mut merged: Vec<Finding> = Vec::new();
+    let mut index: BTreeMap<(String, String, String), usize> = BTreeMap::new();
+    for finding in findings {
+        let (rule, subject, location) = finding.dedup_key();
+        let key = (rule.to_string(), subject.to_string(), location.to_string());
+        match index.get(&key) {
+            Some(&i) => {
+                let existing = &mut merged[i];
+                if severity_rank(finding.severity) < severity_rank(existing.severity) {
+                    existing.severity = finding.severity;
+                }
+                for skill in finding.skills {
+                    if !existing.skills.contains(&skill) {
+                        existing.skills.push(skill);
+                    }
+                }
+            }
+            None => {
+                index.insert(key, merged.len());
+                merged.push(finding);
+            }
+        }
+    }
+    merged
+}
+
+/// Correlates the output of the chained skills into a finding graph.
+pub fn correlate(ir: &ProgramIr, findings: Vec<Finding>) -> FindingGraph {
+    let base = dedup(findings);
+    let mut findings = base.clone();
+    let mut edges = Vec::new();
+
+    for handler in &ir.handlers {
+        let in_scope: Vec<usize> = base
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.subject == handler.name || f.context() == Some(handler.context.as_str()))
+            .map(|(i, _)| i)
+            .collect();
+
+        for rule in COMPOUND_RULES {
+            if !rule.condition.map(|c| c(handler)).unwrap_or(true) {
+                continue;
+            }
+            let constituents: Vec<usize> = in_scope
+                .iter()
+                .copied()
+                .filter(|&i| rule.requires.contains(&base[i].rule_id.as_str()))
+                .collect();
+            let satisfied = rule
+                .requires
+                .iter()
+                .all(|id| constituents.iter().any(|&i| base[i].rule_id == *id));
+            if !satisfied {
+                continue;
+            }
+
+            let worst = constituents
+                .iter()
+                .map(|&i| base[i].severity)
+                .min_by_key(|s| severity_rank(*s))
+                .unwrap_or(Severity::Info);
+            let mut compound = Finding::new(
+                DEEP_AUDIT,
+                rule.id,
+                escalate(worst),
+                rule.title,
+                &handler.name,
+                handler.location.clone(),
+            )
+            .with_message(rule.message);
+            for &i in &constituents {
+                for skill in &base[i].skills {
+                    if !compound.skills.contains(skill) {
+                        compound.skills.push(skill.clone());
+                    }
+                }
+            }
+
+            let id = findings.len();
+            findings.push(compound);
+            edges.extend(constituents.into_iter().map(|i| (id, i)));
+        }
+    }
+
+    // A single finding gains risk per compound it takes part in; a compound
+    // finding carries the weight of its whole chain so it ranks above its parts.
+    let compound_start = base.len();
+    let mut nodes: Vec<FindingNode> = findings
+        .iter()
+        .enumerate()
+        .map(|(id, finding)| {
+            let own = severity_weight(finding.severity);
+            let risk = if id >= compound_start {
+                own + edges
+                    .iter()
+                    .filter(|(from, _)| *from == id)
+                    .map(|(_, to)| severity_weight(findings[*to].severity))
+                    .sum::<u32>()
+            } else {
+                own + edges.iter().filter(|(_, to)| *to == id).count() as u32 * PARTICIPATION_RISK
+            };
+            FindingNode {
+                id,
+                finding: finding.clone(),
+                compound: id >= compound_start,
+                risk,
+            }
+        })
+        .collect();
+
+    nodes.sort_by(|a, b| {
+        b.risk
+            .cmp(&a.risk)
+            .then(b.compound.cmp(&a.compound))
+            .then_with(|| a.finding.location.to_string().cmp(&b.finding.location.to_string()))
+    });
+
+    // Renumber so ids stay indices into `nodes` after sorting.
+    let mut remap = vec![0; nodes.len()];
+    for (position, node) in nodes.iter_mut().enumerate() {
+        remap[node.id] = position;
+        node.id = position;
+    }
+    let mut edges: Vec<(usize, usize)> = edges.into_iter().map(|(from, to)| (remap[from], remap[to])).collect();
+    edges.sort_unstable();
+
+    FindingGraph { nodes, edges }
+}
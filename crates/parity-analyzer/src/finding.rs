@@ -0,0 +1,223 @@
+//! The finding type every skill produces.
+
+use parity::findings::{self as canonical, FindingLocation};
+use parity::scoring::{Confidence, ScoredFinding};
+use parity::state::{PatternType, Severity};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::ir::Location;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    /// Stable rule identifier, e.g. `insecure-cpi` or `layout-zero-copy`.
+    pub rule_id: String,
+    /// Skills that reported the finding; more than one after deduplication.
+    pub skills: Vec<String>,
+    #[serde(with = "severity_name")]
+    pub severity: Severity,
+    #[serde(with = "pattern_type_name", default, skip_serializing_if = "Option::is_none")]
+    pub pattern_type: Option<PatternType>,
+    #[serde(with = "confidence_name")]
+    pub confidence: Confidence,
+    pub title: String,
+    pub message: String,
+    /// The IR entity the finding is attached to, e.g. `Withdraw.vault`.
+    pub subject: String,
+    pub location: Location,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixes: Vec<FixSuggestion>,
+}
+
+/// A suggested remediation, usually derived from a `codemod` fix.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixSuggestion {
+    pub description: String,
+    /// The `ANCHOR_PATTERNS` entry the fix follows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example: Option<String>,
+}
+
+impl Finding {
+    pub fn new(
+        skill: &str,
+        rule_id: impl Into<String>,
+        severity: Severity,
+        title: impl Into<String>,
+        subject: impl Into<String>,
+        location: Location,
+    ) -> Self {
+        Finding {
+            rule_id: rule_id.into(),
+            skills: vec![skill.to_string()],
+            severity,
+            pattern_type: None,
+            confidence: Confidence::High,
+            title: title.into(),
+            message: String::new(),
+            subject: subject.into(),
+            location,
+            fixes: Vec::new(),
+        }
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    pub fn with_pattern_type(mut self, pattern_type: PatternType) -> Self {
+        self.pattern_type = Some(pattern_type);
+        self
+    }
+
+    pub fn with_confidence(mut self, confidence: Confidence) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    /// The finding as input to `parity::scoring`.
+    pub fn scored(&self) -> ScoredFinding {
+        ScoredFinding {
+            severity: self.severity,
+            pattern_type: self.pattern_type,
+            confidence: self.confidence,
+        }
+    }
+
+    /// The on-chain form committed to by `findings_hash`. The description hash
+    /// covers the title and message.
+    pub fn canonical(&self) -> canonical::Finding {
+        let mut hasher = Sha256::new();
+        hasher.update(self.title.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(self.message.as_bytes());
+        canonical::Finding {
+            rule_id: self.rule_id.clone(),
+            severity: self.severity,
+            location: FindingLocation {
+                file: self.location.file.clone(),
+                line: self.location.line as u32,
+                column: self.location.column as u32,
+            },
+            description_hash: hasher.finalize().into(),
+        }
+    }
+
+    /// The `Accounts` struct a field-level subject (`Context.field`) belongs to.
+    pub fn context(&self) -> Option<&str> {
+        self.subject.split_once('.').map(|(context, _)| context)
+    }
+
+    /// Two findings describe the same issue when rule, subject and location agree.
+    pub fn dedup_key(&self) -> (&str, &str, &Location) {
+        (&self.rule_id, &self.subject, &self.location)
+    }
+}
+
+/// `AnalysisReport.findings_hash` for a set of findings.
+pub fn findings_root(findings: &[Finding]) -> [u8; 32] {
+    let canonical: Vec<canonical::Finding> = findings.iter().map(Finding::canonical).collect();
+    canonical::findings_root(&canonical)
+}
+
+macro_rules! named_enum {
+    ($module:ident, $ty:ty, { $($variant:path => $name:literal),* $(,)? }) => {
+        /// String names used in reports, and their serde adapter.
+        pub mod $module {
+            use super::*;
+
+            pub fn name(value: $ty) -> &'static str {
+                match value {
+                    $($variant => $name,)*
+                }
+            }
+
+            pub fn parse(name: &str) -> Option<$ty> {
+                match name {
+                    $($name => Some($variant),)*
+                    _ => None,
+                }
+            }
+
+            pub fn serialize<S: serde::Serializer>(value: &$ty, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(name(*value))
+            }
+
+            pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<$ty, D::Error> {
+                let text = String::deserialize(deserializer)?;
+                parse(&text).ok_or_else(|| serde::de::Error::custom(format!("unknown value \"{}\"", text)))
+            }
+        }
+    };
+}
+
+named_enum!(severity_name, Severity, {
+    Severity::Critical => "critical",
+    Severity::High => "high",
+    Severity::Medium => "medium",
+    Severity::Info => "info",
+    Severity::Pass => "pass",
+});
+
+named_enum!(confidence_name, Confidence, {
+    Confidence::High => "high",
+    Confidence::Medium => "medium",
+    Confidence::Low => "low",
+});
+
+mod pattern_type_name {
+    use super::*;
+
+    pub(crate) fn serialize<S: serde::Serializer>(value: &Option<PatternType>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(p) => serializer.serialize_some(pattern_type::name(*p)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<PatternType>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(text) => pattern_type::parse(&text)
+                .map(Some)
+                .ok_or_else(|| serde::de::Error::custom(format!("unknown pattern type \"{}\"", text))),
+            None => Ok(None),
+        }
+    }
+}
+
+named_enum!(pattern_type, PatternType, {
+    PatternType::MissingSignerCheck => "missing-signer-check",
+    PatternType::UncheckedArithmetic => "unchecked-arithmetic",
+    PatternType::UnvalidatedPda => "unvalidated-pda",
+    PatternType::InsecureCpi => "insecure-cpi",
+    PatternType::AccountDeserialization => "account-deserialization",
+    PatternType::RentExemption => "rent-exemption",
+    PatternType::CloseAccount => "close-account",
+    PatternType::TypeCosplay => "type-cosplay",
+    PatternType::ReinitiallizationAttack => "reinitialization-attack",
+    PatternType::OwnerCheck => "owner-check",
+    PatternType::Custom => "custom",
+});
+
+/// Ordering key for severities, most severe first.
+pub fn severity_rank(severity: Severity) -> u8 {
+    match severity {
+        Severity::Critical => 0,
+        Severity::High => 1,
+        Severity::Medium => 2,
+        Severity::Info => 3,
+        Severity::Pass => 4,
+    }
+}
+
+/// Raises a severity by one level, saturating at `Critical`.
+pub fn escalate(severity: Severity) -> Severity {
+    match severity {
+        Severity::Pass | Severity::Info => Severity::Medium,
+        Severity::Medium => Severity::High,
+        Severity::High | Severity::Critical => Severity::Critical,
+    }
+}
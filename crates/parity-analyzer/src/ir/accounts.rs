@@ -0,0 +1,187 @@
+//! `#[derive(Accounts)]` structs and their constraints.
+
+use proc_macro2::{TokenStream, TokenTree};
+use syn::spanned::Spanned;
+use syn::{Attribute, GenericArgument, ItemStruct, Meta, PathArguments, Type};
+
+use super::{location, Location};
+
+/// One `name = value` (or bare `name`) entry of an `#[account(..)]` attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    /// Constraint name with any namespace kept, e.g. `has_one` or `token::mint`.
+    pub name: String,
+    pub value: Option<String>,
+}
+
+impl Constraint {
+    /// The name without a namespace suffix, so `token::mint` reports `token`.
+    pub fn base_name(&self) -> &str {
+        self.name.split("::").next().unwrap_or(&self.name).trim()
+    }
+}
+
+/// An account slot in an instruction's `Accounts` struct.
+#[derive(Debug, Clone)]
+pub struct ContextField {
+    pub name: String,
+    /// The `Accounts` struct the field belongs to.
+    pub context: String,
+    /// Outer account type such as `Signer`, `Account` or `AccountInfo`,
+    /// looking through `Box` and `Option`.
+    pub wrapper: String,
+    /// The account type argument, e.g. `Vault` in `Account<'info, Vault>`.
+    pub inner: Option<String>,
+    pub constraints: Vec<Constraint>,
+    /// Carries the `/// CHECK:` doc comment Anchor requires on unchecked accounts.
+    pub check_doc: bool,
+    pub location: Location,
+}
+
+impl ContextField {
+    pub fn has(&self, constraint: &str) -> bool {
+        self.constraints.iter().any(|c| c.base_name() == constraint)
+    }
+
+    pub fn constraint(&self, name: &str) -> Option<&Constraint> {
+        self.constraints.iter().find(|c| c.base_name() == name)
+    }
+
+    pub fn is_mut(&self) -> bool {
+        self.has("mut") || self.has("init") || self.has("init_if_needed")
+    }
+
+    pub fn is_init(&self) -> bool {
+        self.has("init") || self.has("init_if_needed")
+    }
+
+    pub fn is_signer(&self) -> bool {
+        self.wrapper == "Signer" || self.has("signer")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountsContext {
+    pub name: String,
+    pub fields: Vec<ContextField>,
+    pub location: Location,
+}
+
+impl AccountsContext {
+    pub fn field(&self, name: &str) -> Option<&ContextField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+}
+
+pub(super) fn accounts_context(file: &str, item: &ItemStruct) -> AccountsContext {
+    let context = item.ident.to_string();
+    let fields = item
+        .fields
+        .iter()
+        .filter_map(|field| {
+            let name = field.ident.as_ref()?.to_string();
+            let (wrapper, inner) = account_type(&field.ty);
+            Some(ContextField {
+                name,
+                context: context.clone(),
+                wrapper,
+                inner,
+                constraints: constraints(&field.attrs),
+                check_doc: has_check_doc(&field.attrs),
+                location: location(file, field.span()),
+            })
+        })
+        .collect();
+
+    AccountsContext {
+        name: context,
+        fields,
+        location: location(file, item.ident.span()),
+    }
+}
+
+fn account_type(ty: &Type) -> (String, Option<String>) {
+    let Type::Path(path) = ty else {
+        return (String::new(), None);
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return (String::new(), None);
+    };
+    let wrapper = segment.ident.to_string();
+
+    let types: Vec<&Type> = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|a| match a {
+                GenericArgument::Type(t) => Some(t),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    if matches!(wrapper.as_str(), "Box" | "Option") {
+        if let Some(inner) = types.first() {
+            return account_type(inner);
+        }
+    }
+
+    let inner = types.last().and_then(|t| match t {
+        Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    });
+    (wrapper, inner)
+}
+
+fn constraints(attrs: &[Attribute]) -> Vec<Constraint> {
+    let mut out = Vec::new();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("account")) {
+        let Meta::List(list) = &attr.meta else {
+            continue;
+        };
+        for segment in split_top_level_commas(list.tokens.clone()) {
+            let text = segment.to_string();
+            if text.is_empty() {
+                continue;
+            }
+            out.push(match assignment_index(&text) {
+                Some(i) => Constraint {
+                    name: text[..i].replace(' ', ""),
+                    value: Some(text[i + 1..].trim().to_string()),
+                },
+                None => Constraint { name: text.replace(' ', ""), value: None },
+            });
+        }
+    }
+    out
+}
+
+fn split_top_level_commas(tokens: TokenStream) -> Vec<TokenStream> {
+    let mut segments = vec![TokenStream::new()];
+    for tree in tokens {
+        match &tree {
+            TokenTree::Punct(p) if p.as_char() == ',' => segments.push(TokenStream::new()),
+            _ => segments.last_mut().unwrap().extend(std::iter::once(tree)),
+        }
+    }
+    segments
+}
+
+/// Index of the `=` separating a constraint name from its value, skipping
+/// comparison operators such as `==`, `!=`, `<=` and `>=`.
+fn assignment_index(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    (0..bytes.len()).find(|&i| {
+        bytes[i] == b'='
+            && bytes.get(i + 1) != Some(&b'=')
+            && !matches!(i.checked_sub(1).map(|p| bytes[p]), Some(b'=' | b'!' | b'<' | b'>'))
+    })
+}
+
+fn has_check_doc(attrs: &[Attribute]) -> bool {
+    attrs.iter().filter(|a| a.path().is_ident("doc")).any(|attr| match &attr.meta {
+        Meta::NameValue(nv) => quote::quote!(#nv).to_string().contains("CHECK"),
+        _ => false,
+    })
+}
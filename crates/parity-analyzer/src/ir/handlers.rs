@@ -0,0 +1,189 @@
+//! Instruction handlers: functions taking a `Context<T>`.
+//!
+//! Bodies are summarized into ordered side effects (CPIs, state writes,
+//! lamport manipulation, raw arithmetic). The detection is syntactic and
+//! deliberately conservative about what counts as a CPI.
+
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{BinOp, Expr, FnArg, GenericArgument, ItemFn, Macro, PathArguments, Signature, Type};
+
+use super::{location, Location};
+
+/// Modules whose functions perform a CPI when called as `module::function(..)`.
+const CPI_MODULES: &[&str] = &["token", "token_interface", "token_2022", "system_program", "associated_token"];
+
+/// Functions that perform a CPI wherever they are imported from.
+const CPI_FUNCTIONS: &[&str] = &["invoke", "invoke_signed", "invoke_unchecked", "invoke_signed_unchecked"];
+
+const LAMPORT_METHODS: &[&str] = &["sub_lamports", "add_lamports", "try_borrow_mut_lamports"];
+
+#[derive(Debug, Clone)]
+pub struct StateWrite {
+    /// The assigned place expression, e.g. `vault.balance`.
+    pub target: String,
+    pub location: Location,
+}
+
+#[derive(Debug, Clone)]
+pub struct Handler {
+    pub name: String,
+    /// The `Accounts` struct named in `Context<T>`.
+    pub context: String,
+    pub location: Location,
+    pub cpi_calls: Vec<Location>,
+    pub state_writes: Vec<StateWrite>,
+    pub unchecked_arithmetic: Vec<Location>,
+    pub lamport_writes: Vec<Location>,
+    pub emits_event: bool,
+    pub uses_remaining_accounts: bool,
+}
+
+impl Handler {
+    /// State writes that happen after the first CPI in source order.
+    pub fn writes_after_cpi(&self) -> Vec<&StateWrite> {
+        let Some(first_cpi) = self.cpi_calls.iter().min() else {
+            return Vec::new();
+        };
+        self.state_writes
+            .iter()
+            .filter(|w| (w.location.line, w.location.column) > (first_cpi.line, first_cpi.column))
+            .collect()
+    }
+}
+
+/// Returns the handler summary if `item` takes a `Context<T>` parameter.
+pub(super) fn handler(file: &str, item: &ItemFn) -> Option<Handler> {
+    let context = context_type(&item.sig)?;
+    let mut visitor = BodyVisitor {
+        file,
+        handler: Handler {
+            name: item.sig.ident.to_string(),
+            context,
+            location: location(file, item.sig.ident.span()),
+            cpi_calls: Vec::new(),
+            state_writes: Vec::new(),
+            unchecked_arithmetic: Vec::new(),
+            lamport_writes: Vec::new(),
+            emits_event: false,
+            uses_remaining_accounts: false,
+        },
+    };
+    visitor.visit_block(&item.block);
+    Some(visitor.handler)
+}
+
+fn context_type(sig: &Signature) -> Option<String> {
+    sig.inputs.iter().find_map(|input| {
+        let FnArg::Typed(arg) = input else { return None };
+        let Type::Path(path) = arg.ty.as_ref() else { return None };
+        let segment = path.path.segments.last()?;
+        if segment.ident != "Context" {
+            return None;
+        }
+        let PathArguments::AngleBracketed(args) = &segment.arguments else {
+            return None;
+        };
+        args.args.iter().find_map(|a| match a {
+            GenericArgument::Type(Type::Path(p)) => p.path.segments.last().map(|s| s.ident.to_string()),
+            _ => None,
+        })
+    })
+}
+
+struct BodyVisitor<'a> {
+    file: &'a str,
+    handler: Handler,
+}
+
+impl BodyVisitor<'_> {
+    fn at(&self, span: proc_macro2::Span) -> Location {
+        location(self.file, span)
+    }
+
+    fn record_write(&mut self, place: &Expr) {
+        if mentions_lamports(place) {
+            self.handler.lamport_writes.push(self.at(place.span()));
+        } else if matches!(place, Expr::Field(_)) {
+            self.handler.state_writes.push(StateWrite {
+                target: expr_string(place),
+                location: self.at(place.span()),
+            });
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for BodyVisitor<'_> {
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let Expr::Path(path) = call.func.as_ref() {
+            let segments: Vec<String> = path.path.segments.iter().map(|s| s.ident.to_string()).collect();
+            let is_cpi = segments
+                .last()
+                .map(|last| CPI_FUNCTIONS.contains(&last.as_str()))
+                .unwrap_or(false)
+                || (segments.len() >= 2 && CPI_MODULES.contains(&segments[segments.len() - 2].as_str()));
+            if is_cpi {
+                self.handler.cpi_calls.push(self.at(call.span()));
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        let method = call.method.to_string();
+        if CPI_FUNCTIONS.contains(&method.as_str()) {
+            self.handler.cpi_calls.push(self.at(call.method.span()));
+        } else if LAMPORT_METHODS.contains(&method.as_str()) {
+            self.handler.lamport_writes.push(self.at(call.method.span()));
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_assign(&mut self, assign: &'ast syn::ExprAssign) {
+        self.record_write(&assign.left);
+        visit::visit_expr_assign(self, assign);
+    }
+
+    fn visit_expr_binary(&mut self, binary: &'ast syn::ExprBinary) {
+        let is_literal = |e: &Expr| matches!(e, Expr::Lit(_));
+        match binary.op {
+            BinOp::AddAssign(_) | BinOp::SubAssign(_) | BinOp::MulAssign(_) => {
+                self.record_write(&binary.left);
+                if !mentions_lamports(&binary.left) {
+                    self.handler.unchecked_arithmetic.push(self.at(binary.op.span()));
+                }
+            }
+            BinOp::Add(_) | BinOp::Sub(_) | BinOp::Mul(_)
+                if !(is_literal(&binary.left) && is_literal(&binary.right)) =>
+            {
+                self.handler.unchecked_arithmetic.push(self.at(binary.op.span()));
+            }
+            _ => {}
+        }
+        visit::visit_expr_binary(self, binary);
+    }
+
+    fn visit_expr_field(&mut self, field: &'ast syn::ExprField) {
+        if let syn::Member::Named(ident) = &field.member {
+            if ident == "remaining_accounts" {
+                self.handler.uses_remaining_accounts = true;
+            }
+        }
+        visit::visit_expr_field(self, field);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        if mac.path.is_ident("emit") || mac.path.is_ident("emit_cpi") {
+            self.handler.emits_event = true;
+        }
+        visit::visit_macro(self, mac);
+    }
+}
+
+fn mentions_lamports(expr: &Expr) -> bool {
+    expr_string(expr).contains("lamports")
+}
+
+fn expr_string(expr: &Expr) -> String {
+    quote::quote!(#expr).to_string().replace(' ', "")
+}
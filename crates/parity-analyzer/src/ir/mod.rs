@@ -0,0 +1,436 @@
+//! Source-level intermediate representation of an Anchor program.
+//!
+//! Built with `syn` from the program's Rust sources; the analyzers never look
+//! at tokens directly.
+
+mod accounts;
+mod handlers;
+
+pub use accounts::*;
+pub use handlers::*;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use syn::spanned::Spanned;
+use syn::{Attribute, Expr, Fields, GenericArgument, Item, Lit, PathArguments, Type};
+
+/// A position in the analyzed sources.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// Field types as they appear in account state, resolved enough to size them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldType {
+    Bool,
+    Int { bits: u16, signed: bool },
+    Float { bits: u16 },
+    Pubkey,
+    String,
+    Vec(Box<FieldType>),
+    Option(Box<FieldType>),
+    Array(Box<FieldType>, usize),
+    /// A struct or enum declared elsewhere in the program.
+    Named(String),
+    /// Anything the IR could not interpret, kept verbatim.
+    Unknown(String),
+}
+
+impl std::fmt::Display for FieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldType::Bool => write!(f, "bool"),
+            FieldType::Int { bits, signed } => write!(f, "{}{}", if *signed { "i" } else { "u" }, bits),
+            FieldType::Float { bits } => write!(f, "f{}", bits),
+            FieldType::Pubkey => write!(f, "Pubkey"),
+            FieldType::String => write!(f, "String"),
+            FieldType::Vec(inner) => write!(f, "Vec<{}>", inner),
+            FieldType::Option(inner) => write!(f, "Option<{}>", inner),
+            FieldType::Array(inner, len) => write!(f, "[{}; {}]", inner, len),
+            FieldType::Named(name) | FieldType::Unknown(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StateField {
+    pub name: String,
+    pub ty: FieldType,
+    /// Arguments of `#[max_len(..)]`, outermost container first.
+    pub max_len: Vec<usize>,
+    pub location: Location,
+}
+
+/// A struct annotated with `#[account]` or `#[account(zero_copy)]`.
+#[derive(Debug, Clone)]
+pub struct StateAccount {
+    pub name: String,
+    pub fields: Vec<StateField>,
+    pub zero_copy: bool,
+    pub derives_init_space: bool,
+    pub location: Location,
+}
+
+#[derive(Debug, Clone)]
+pub enum TypeDeclKind {
+    Struct(Vec<StateField>),
+    /// One entry per variant, holding that variant's fields.
+    Enum(Vec<Vec<StateField>>),
+}
+
+/// Any other struct or enum, used to size `FieldType::Named` fields.
+#[derive(Debug, Clone)]
+pub struct TypeDecl {
+    pub name: String,
+    pub kind: TypeDeclKind,
+    pub location: Location,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ProgramIr {
+    pub accounts: Vec<StateAccount>,
+    pub types: Vec<TypeDecl>,
+    pub contexts: Vec<AccountsContext>,
+    pub handlers: Vec<Handler>,
+    pub consts: HashMap<String, usize>,
+    /// The `#[program]` module, if the sources contain one.
+    pub program_module: Option<Location>,
+    /// The first `security_txt!` invocation.
+    pub security_txt: Option<Location>,
+}
+
+#[derive(Debug)]
+pub enum IrError {
+    Io(PathBuf, std::io::Error),
+    Parse(String, syn::Error),
+}
+
+impl std::fmt::Display for IrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IrError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            IrError::Parse(file, err) => {
+                let start = err.span().start();
+                write!(f, "{}:{}:{}: {}", file, start.line, start.column + 1, err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for IrError {}
+
+impl ProgramIr {
+    /// Parses every `.rs` file under `root` (or `root` itself if it is a file).
+    pub fn from_path(root: &Path) -> Result<Self, IrError> {
+        let mut files = Vec::new();
+        collect_rust_files(root, &mut files).map_err(|e| IrError::Io(root.to_path_buf(), e))?;
+        files.sort();
+
+        let mut sources = Vec::new();
+        for file in files {
+            let source = fs::read_to_string(&file).map_err(|e| IrError::Io(file.clone(), e))?;
+            sources.push((file.display().to_string(), source));
+        }
+        Self::from_sources(&sources)
+    }
+
+    /// Parses `(file name, source)` pairs. Constants are collected from every
+    /// file before any type is resolved, so declaration order does not matter.
+    pub fn from_sources(sources: &[(String, String)]) -> Result<Self, IrError> {
+        let mut parsed = Vec::new();
+        for (file, source) in sources {
+            let ast = syn::parse_file(source).map_err(|e| IrError::Parse(file.clone(), e))?;
+            parsed.push((file.as_str(), ast));
+        }
+
+        let mut ir = ProgramIr::default();
+        for (_, ast) in &parsed {
+            collect_consts(&ast.items, &mut ir.consts);
+        }
+        for (file, ast) in &parsed {
+            ir.collect_items(file, &ast.items);
+        }
+        Ok(ir)
+    }
+
+    pub fn account(&self, name: &str) -> Option<&StateAccount> {
+        self.accounts.iter().find(|a| a.name == name)
+    }
+
+    pub fn type_decl(&self, name: &str) -> Option<&TypeDecl> {
+        self.types.iter().find(|t| t.name == name)
+    }
+
+    pub fn context(&self, name: &str) -> Option<&AccountsContext> {
+        self.contexts.iter().find(|c| c.name == name)
+    }
+
+    /// Every account field across all `Accounts` structs.
+    pub fn context_fields(&self) -> impl Iterator<Item = &ContextField> {
+        self.contexts.iter().flat_map(|c| c.fields.iter())
+    }
+
+    fn collect_items(&mut self, file: &str, items: &[Item]) {
+        for item in items {
+            match item {
+                Item::Struct(item) if derives(&item.attrs, "Accounts") => {
+                    self.contexts.push(accounts::accounts_context(file, item));
+                }
+                Item::Struct(item) => {
+                    let location = location(file, item.ident.span());
+                    let fields = self.state_fields(file, &item.fields);
+                    match account_attr(&item.attrs) {
+                        Some(zero_copy) => self.accounts.push(StateAccount {
+                            name: item.ident.to_string(),
+                            fields,
+                            zero_copy,
+                            derives_init_space: derives(&item.attrs, "InitSpace"),
+                            location,
+                        }),
+                        None => self.types.push(TypeDecl {
+                            name: item.ident.to_string(),
+                            kind: TypeDeclKind::Struct(fields),
+                            location,
+                        }),
+                    }
+                }
+                Item::Enum(item) => {
+                    let variants = item
+                        .variants
+                        .iter()
+                        .map(|v| self.state_fields(file, &v.fields))
+                        .collect();
+                    self.types.push(TypeDecl {
+                        name: item.ident.to_string(),
+                        kind: TypeDeclKind::Enum(variants),
+                        location: location(file, item.ident.span()),
+                    });
+                }
+                Item::Fn(item) => {
+                    if let Some(handler) = handlers::handler(file, item) {
+                        self.handlers.push(handler);
+                    }
+                }
+                Item::Mod(module) => {
+                    if self.program_module.is_none() && module.attrs.iter().any(|a| a.path().is_ident("program")) {
+                        self.program_module = Some(location(file, module.ident.span()));
+                    }
+                    if let Some((_, items)) = &module.content {
+                        self.collect_items(file, items);
+                    }
+                }
+                Item::Macro(item) => {
+                    let name = item.mac.path.segments.last().map(|s| s.ident.to_string());
+                    if self.security_txt.is_none() && name.as_deref() == Some("security_txt") {
+                        self.security_txt = Some(location(file, item.mac.path.span()));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn state_fields(&self, file: &str, fields: &Fields) -> Vec<StateField> {
+        fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| StateField {
+                name: field
+                    .ident
+                    .as_ref()
+                    .map(|id| id.to_string())
+                    .unwrap_or_else(|| i.to_string()),
+                ty: self.field_type(&field.ty),
+                max_len: self.max_len(&field.attrs),
+                location: location(file, field.span()),
+            })
+            .collect()
+    }
+
+    fn field_type(&self, ty: &Type) -> FieldType {
+        match ty {
+            Type::Array(array) => match self.eval_usize(&array.len) {
+                Some(len) => FieldType::Array(Box::new(self.field_type(&array.elem)), len),
+                None => FieldType::Unknown(type_string(ty)),
+            },
+            Type::Path(path) => {
+                let Some(segment) = path.path.segments.last() else {
+                    return FieldType::Unknown(type_string(ty));
+                };
+                let name = segment.ident.to_string();
+                let generic = match &segment.arguments {
+                    PathArguments::AngleBracketed(args) => args.args.iter().find_map(|a| match a {
+                        GenericArgument::Type(t) => Some(t),
+                        _ => None,
+                    }),
+                    _ => None,
+                };
+                match (name.as_str(), generic) {
+                    ("bool", _) => FieldType::Bool,
+                    ("u8", _) => FieldType::Int { bits: 8, signed: false },
+                    ("u16", _) => FieldType::Int { bits: 16, signed: false },
+                    ("u32", _) => FieldType::Int { bits: 32, signed: false },
+                    ("u64", _) => FieldType::Int { bits: 64, signed: false },
+                    ("u128", _) => FieldType::Int { bits: 128, signed: false },
+                    ("i8", _) => FieldType::Int { bits: 8, signed: true },
+                    ("i16", _) => FieldType::Int { bits: 16, signed: true },
+                    ("i32", _) => FieldType::Int { bits: 32, signed: true },
+                    ("i64", _) => FieldType::Int { bits: 64, signed: true },
+                    ("i128", _) => FieldType::Int { bits: 128, signed: true },
+                    ("f32", _) => FieldType::Float { bits: 32 },
+                    ("f64", _) => FieldType::Float { bits: 64 },
+                    ("Pubkey", _) => FieldType::Pubkey,
+                    ("String", _) => FieldType::String,
+                    ("Vec", Some(inner)) => FieldType::Vec(Box::new(self.field_type(inner))),
+                    ("Option", Some(inner)) => FieldType::Option(Box::new(self.field_type(inner))),
+                    (_, None) => FieldType::Named(name),
+                    _ => FieldType::Unknown(type_string(ty)),
+                }
+            }
+            _ => FieldType::Unknown(type_string(ty)),
+        }
+    }
+
+    fn max_len(&self, attrs: &[Attribute]) -> Vec<usize> {
+        let mut lens = Vec::new();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("max_len")) {
+            if let Ok(args) = attr.parse_args_with(
+                syn::punctuated::Punctuated::<Expr, syn::Token![,]>::parse_terminated,
+            ) {
+                lens = args.iter().filter_map(|e| self.eval_usize(e)).collect();
+            }
+        }
+        lens
+    }
+
+    /// Evaluates integer literals, known constants and simple arithmetic.
+    pub fn eval_usize(&self, expr: &Expr) -> Option<usize> {
+        eval_usize(expr, &self.consts)
+    }
+}
+
+fn eval_usize(expr: &Expr, consts: &HashMap<String, usize>) -> Option<usize> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Int(int) => int.base10_parse().ok(),
+            _ => None,
+        },
+        Expr::Path(path) => path
+            .path
+            .segments
+            .last()
+            .and_then(|s| consts.get(&s.ident.to_string()).copied()),
+        Expr::Paren(inner) => eval_usize(&inner.expr, consts),
+        Expr::Binary(bin) => {
+            let l = eval_usize(&bin.left, consts)?;
+            let r = eval_usize(&bin.right, consts)?;
+            match bin.op {
+                syn::BinOp::Add(_) => l.checked_add(r),
+                syn::BinOp::Sub(_) => l.checked_sub(r),
+                syn::BinOp::Mul(_) => l.checked_mul(r),
+                syn::BinOp::Div(_) => l.checked_div(r),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn collect_consts(items: &[Item], consts: &mut HashMap<String, usize>) {
+    // Constants may reference each other, so evaluate until nothing changes.
+    loop {
+        let before = consts.len();
+        for item in items {
+            match item {
+                Item::Const(item) if !consts.contains_key(&item.ident.to_string()) => {
+                    if let Some(value) = eval_usize(&item.expr, consts) {
+                        consts.insert(item.ident.to_string(), value);
+                    }
+                }
+                Item::Mod(module) => {
+                    if let Some((_, items)) = &module.content {
+                        collect_consts(items, consts);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if consts.len() == before {
+            break;
+        }
+    }
+}
+
+/// `Some(zero_copy)` if the attributes include `#[account]`.
+fn account_attr(attrs: &[Attribute]) -> Option<bool> {
+    attrs.iter().find(|a| a.path().is_ident("account")).map(|attr| {
+        let mut zero_copy = false;
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("zero_copy") {
+                zero_copy = true;
+            }
+            Ok(())
+        });
+        zero_copy
+    })
+}
+
+pub(crate) fn derives(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter().filter(|a| a.path().is_ident("derive")).any(|attr| {
+        let mut found = false;
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.segments.last().map(|s| s.ident == name).unwrap_or(false) {
+                found = true;
+            }
+            Ok(())
+        });
+        found
+    })
+}
+
+pub(crate) fn location(file: &str, span: proc_macro2::Span) -> Location {
+    let start = span.start();
+    Location {
+        file: file.to_string(),
+        line: start.line,
+        column: start.column + 1,
+    }
+}
+
+fn type_string(ty: &Type) -> String {
+    quote::quote!(#ty).to_string().replace(' ', "")
+}
+
+fn collect_rust_files(path: &Path, out: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if path.is_file() {
+        if path.extension().map(|e| e == "rs").unwrap_or(false) {
+            out.push(path.to_path_buf());
+        }
+        return Ok(());
+    }
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let child = entry.path();
+        if child.is_dir() {
+            if child.file_name().map(|n| n == "target").unwrap_or(false) {
+                continue;
+            }
+            collect_rust_files(&child, out)?;
+        } else {
+            collect_rust_files(&child, out)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,326 @@
+//! Account layout and rent analysis for `#[account]` structs.
+//!
+//! Sizes follow Anchor's `InitSpace` rules (borsh encoding, worst case for
+//! every `max_len`), with the 8-byte discriminator included. Alignment and
+//! padding are computed for the `repr(C)` layout used by zero-copy accounts
+//! on SBF, where 128-bit integers are 8-byte aligned. A type the IR cannot
+//! size, such as a tuple or a struct declared outside the analyzed sources,
+//! counts as zero bytes and marks the size as a lower bound.
+
+use solana_program::rent::Rent;
+
+use crate::ir::{FieldType, Location, ProgramIr, StateAccount, StateField, TypeDeclKind};
+
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+/// Fixed-size accounts at least this large are suggested for zero-copy.
+pub const ZERO_COPY_MIN_SPACE: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Size {
+    /// Worst-case serialized bytes.
+    pub bytes: usize,
+    /// Contains a `String`, `Vec` or `Option`, so the encoded length varies.
+    pub dynamic: bool,
+    /// Contains a `String` or `Vec` without a `max_len` bound.
+    pub unbounded: bool,
+    /// Contains a type the IR cannot size, so `bytes` is a lower bound.
+    pub unknown: bool,
+}
+
+impl Size {
+    fn fixed(bytes: usize) -> Self {
+        Size { bytes, ..Size::default() }
+    }
+
+    fn unknown() -> Self {
+        Size { unknown: true, ..Size::default() }
+    }
+
+    fn add(self, other: Size) -> Self {
+        Size {
+            bytes: self.bytes + other.bytes,
+            dynamic: self.dynamic || other.dynamic,
+            unbounded: self.unbounded || other.unbounded,
+            unknown: self.unknown || other.unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldLayout {
+    pub name: String,
+    pub ty: String,
+    pub size: Size,
+    /// `repr(C)` alignment, `None` for variable-length fields.
+    pub align: Option<usize>,
+    pub location: Location,
+}
+
+/// `repr(C)` padding for a fully fixed-size account, as declared and with
+/// fields sorted by descending alignment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Padding {
+    pub declared: usize,
+    pub optimal: usize,
+    pub optimal_order: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutSuggestion {
+    /// Reordering fields removes `saved` bytes of `repr(C)` padding.
+    Reorder { order: Vec<String>, saved: usize },
+    /// An integer field is wider than its value domain requires.
+    NarrowInteger { field: String, from: String, to: String, saved: usize, reason: &'static str },
+    /// A large fixed-size account that would avoid borsh (de)serialization.
+    ZeroCopy { space: usize },
+    /// A `String`/`Vec` field has no `max_len`, so `InitSpace` cannot size it.
+    MissingMaxLen { field: String },
+    /// The account sizes itself by hand instead of deriving `InitSpace`.
+    DeriveInitSpace,
+}
+
+impl std::fmt::Display for LayoutSuggestion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayoutSuggestion::Reorder { order, saved } => write!(
+                f,
+                "reorder fields as [{}] to save {} bytes of padding",
+                order.join(", "),
+                saved
+            ),
+            LayoutSuggestion::NarrowInteger { field, from, to, saved, reason } => write!(
+                f,
+                "`{}` can be {} instead of {} ({}), saving {} bytes",
+                field, to, from, reason, saved
+            ),
+            LayoutSuggestion::ZeroCopy { space } => write!(
+                f,
+                "{} fixed bytes: consider #[account(zero_copy)] with AccountLoader to skip borsh deserialization",
+                space
+            ),
+            LayoutSuggestion::MissingMaxLen { field } => {
+                write!(f, "`{}` is variable-length but has no #[max_len(..)]", field)
+            }
+            LayoutSuggestion::DeriveInitSpace => {
+                write!(f, "derive InitSpace instead of computing account space by hand")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountLayout {
+    pub name: String,
+    pub location: Location,
+    pub fields: Vec<FieldLayout>,
+    /// Total account space including the discriminator.
+    pub space: usize,
+    /// Some field has a type the analyzer cannot size, so `space` and the
+    /// rent are lower bounds.
+    pub lower_bound: bool,
+    /// Worst-case bytes taken by `max_len`-bounded fields (length prefixes included).
+    pub dynamic_bytes: usize,
+    pub rent_exempt_lamports: u64,
+    pub padding: Option<Padding>,
+    pub suggestions: Vec<LayoutSuggestion>,
+}
+
+impl AccountLayout {
+    /// Share of the account reserved for worst-case variable-length data.
+    pub fn dynamic_ratio(&self) -> f64 {
+        self.dynamic_bytes as f64 / self.space as f64
+    }
+}
+
+pub fn analyze_layouts(ir: &ProgramIr) -> Vec<AccountLayout> {
+    ir.accounts.iter().map(|a| analyze_account(ir, a)).collect()
+}
+
+pub fn analyze_account(ir: &ProgramIr, account: &StateAccount) -> AccountLayout {
+    let fields: Vec<FieldLayout> = account
+        .fields
+        .iter()
+        .map(|field| FieldLayout {
+            name: field.name.clone(),
+            ty: field.ty.to_string(),
+            size: field_size(ir, field),
+            align: align_of(ir, &field.ty),
+            location: field.location.clone(),
+        })
+        .collect();
+
+    let total = fields.iter().fold(Size::default(), |acc, f| acc.add(f.size));
+    let space = DISCRIMINATOR_LEN + total.bytes;
+    let dynamic_bytes = fields.iter().filter(|f| f.size.dynamic).map(|f| f.size.bytes).sum();
+    let padding = if total.dynamic { None } else { padding(&fields) };
+
+    let mut suggestions = Vec::new();
+
+    if !account.derives_init_space && !account.zero_copy {
+        suggestions.push(LayoutSuggestion::DeriveInitSpace);
+    }
+    for field in &fields {
+        if field.size.unbounded {
+            suggestions.push(LayoutSuggestion::MissingMaxLen { field: field.name.clone() });
+        }
+    }
+    // Borsh has no padding, so field order only matters once the account is
+    // (or should become) zero-copy.
+    let zero_copy_candidate =
+        !account.zero_copy && !total.dynamic && !total.unknown && space >= ZERO_COPY_MIN_SPACE;
+    if zero_copy_candidate {
+        suggestions.push(LayoutSuggestion::ZeroCopy { space });
+    }
+    if let Some(p) = &padding {
+        if (account.zero_copy || zero_copy_candidate) && p.optimal < p.declared {
+            suggestions.push(LayoutSuggestion::Reorder {
+                order: p.optimal_order.clone(),
+                saved: p.declared - p.optimal,
+            });
+        }
+    }
+    for field in &account.fields {
+        if let Some(suggestion) = narrow_integer(field) {
+            suggestions.push(suggestion);
+        }
+    }
+
+    AccountLayout {
+        name: account.name.clone(),
+        location: account.location.clone(),
+        fields,
+        space,
+        lower_bound: total.unknown,
+        dynamic_bytes,
+        rent_exempt_lamports: Rent::default().minimum_balance(space),
+        padding,
+        suggestions,
+    }
+}
+
+fn field_size(ir: &ProgramIr, field: &StateField) -> Size {
+    type_size(ir, &field.ty, &field.max_len)
+}
+
+/// Worst-case size of `ty`; `max_len` is consumed outermost container first,
+/// matching `#[max_len(8, 64)]` on a `Vec<String>`.
+pub fn type_size(ir: &ProgramIr, ty: &FieldType, max_len: &[usize]) -> Size {
+    match ty {
+        FieldType::Bool => Size::fixed(1),
+        FieldType::Int { bits, .. } | FieldType::Float { bits } => Size::fixed(*bits as usize / 8),
+        FieldType::Pubkey => Size::fixed(32),
+        FieldType::String => match max_len.first() {
+            Some(len) => Size { bytes: 4 + len, dynamic: true, ..Size::default() },
+            None => Size { bytes: 4, dynamic: true, unbounded: true, ..Size::default() },
+        },
+        FieldType::Vec(inner) => match max_len.split_first() {
+            Some((len, rest)) => {
+                let elem = type_size(ir, inner, rest);
+                Size { bytes: 4 + len * elem.bytes, dynamic: true, ..elem }
+            }
+            None => Size { bytes: 4, dynamic: true, unbounded: true, ..Size::default() },
+        },
+        FieldType::Option(inner) => {
+            let inner = type_size(ir, inner, max_len);
+            Size { bytes: 1 + inner.bytes, dynamic: true, ..inner }
+        }
+        FieldType::Array(inner, len) => {
+            let elem = type_size(ir, inner, max_len);
+            Size { bytes: len * elem.bytes, ..elem }
+        }
+        FieldType::Named(name) => match ir.type_decl(name).map(|t| &t.kind) {
+            Some(TypeDeclKind::Struct(fields)) => fields
+                .iter()
+                .fold(Size::default(), |acc, f| acc.add(field_size(ir, f))),
+            Some(TypeDeclKind::Enum(variants)) => {
+                let sizes: Vec<Size> = variants
+                    .iter()
+                    .map(|fields| {
+                        fields
+                            .iter()
+                            .fold(Size::default(), |acc, f| acc.add(field_size(ir, f)))
+                    })
+                    .collect();
+                let largest = sizes.iter().copied().max_by_key(|s| s.bytes).unwrap_or_default();
+                let dynamic = largest.dynamic || variants.iter().any(|v| !v.is_empty());
+                let unknown = sizes.iter().any(|s| s.unknown);
+                Size { bytes: 1 + largest.bytes, dynamic, unknown, ..largest }
+            }
+            None => Size::unknown(),
+        },
+        FieldType::Unknown(_) => Size::unknown(),
+    }
+}
+
+fn align_of(ir: &ProgramIr, ty: &FieldType) -> Option<usize> {
+    match ty {
+        FieldType::Bool | FieldType::Pubkey => Some(1),
+        FieldType::Int { bits, .. } | FieldType::Float { bits } => Some((*bits as usize / 8).min(8)),
+        FieldType::Array(inner, _) => align_of(ir, inner),
+        FieldType::Named(name) => match ir.type_decl(name).map(|t| &t.kind) {
+            Some(TypeDeclKind::Struct(fields)) => fields
+                .iter()
+                .map(|f| align_of(ir, &f.ty))
+                .try_fold(1, |acc, a| a.map(|a| acc.max(a))),
+            Some(TypeDeclKind::Enum(variants)) if variants.iter().all(|v| v.is_empty()) => Some(1),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn padding(fields: &[FieldLayout]) -> Option<Padding> {
+    let items: Vec<(&str, usize, usize)> = fields
+        .iter()
+        .map(|f| f.align.map(|a| (f.name.as_str(), f.size.bytes, a)))
+        .collect::<Option<_>>()?;
+
+    let mut sorted = items.clone();
+    sorted.sort_by_key(|item| std::cmp::Reverse(item.2));
+
+    Some(Padding {
+        declared: repr_c_padding(&items),
+        optimal: repr_c_padding(&sorted),
+        optimal_order: sorted.iter().map(|(name, _, _)| name.to_string()).collect(),
+    })
+}
+
+fn repr_c_padding(items: &[(&str, usize, usize)]) -> usize {
+    let mut offset: usize = 0;
+    let mut data = 0;
+    let mut max_align = 1;
+    for (_, size, align) in items {
+        offset = offset.next_multiple_of(*align);
+        offset += size;
+        data += size;
+        max_align = max_align.max(*align);
+    }
+    offset.next_multiple_of(max_align) - data
+}
+
+fn narrow_integer(field: &StateField) -> Option<LayoutSuggestion> {
+    let FieldType::Int { bits, signed } = field.ty else {
+        return None;
+    };
+    let name = field.name.as_str();
+
+    let (to_bits, reason) = if name.contains("score") && bits > 8 {
+        (8, "scores are bounded to 0-100")
+    } else if name == "bump" && bits > 8 {
+        (8, "PDA bumps are a single byte")
+    } else if (name.ends_with("_count") || name.ends_with("_index")) && bits > 32 {
+        (32, "u32 already allows ~4.3 billion entries")
+    } else {
+        return None;
+    };
+
+    let prefix = if signed { "i" } else { "u" };
+    Some(LayoutSuggestion::NarrowInteger {
+        field: field.name.clone(),
+        from: format!("{}{}", prefix, bits),
+        to: format!("{}{}", prefix, to_bits),
+        saved: (bits - to_bits) as usize / 8,
+        reason,
+    })
+}
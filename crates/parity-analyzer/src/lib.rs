@@ -0,0 +1,15 @@
+//! Off-chain analysis engine for the Parity skills.
+//!
+//! The on-chain program only records results; everything that actually
+//! inspects a target program lives here.
+
+pub mod codemod;
+pub mod correlation;
+pub mod finding;
+pub mod ir;
+pub mod layout;
+pub mod profiler;
+pub mod report;
+pub mod rules;
+pub mod security_txt;
+pub mod skills;
@@ -0,0 +1,335 @@
+use serde::Deserialize;
+use serde_json::Value;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+
+/// Subset of the Anchor 0.30 IDL needed to synthesize instruction fixtures.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Idl {
+    /// The program's address, as `anchor build` records it.
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub instructions: Vec<IdlInstruction>,
+    #[serde(default)]
+    pub types: Vec<IdlTypeDef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdlInstruction {
+    pub name: String,
+    pub discriminator: Vec<u8>,
+    #[serde(default)]
+    pub accounts: Vec<IdlAccountItem>,
+    #[serde(default)]
+    pub args: Vec<IdlField>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdlAccountItem {
+    pub name: String,
+    #[serde(default)]
+    pub writable: bool,
+    #[serde(default)]
+    pub signer: bool,
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub pda: Option<IdlPda>,
+    /// Present when the item is a nested account group.
+    #[serde(default)]
+    pub accounts: Vec<IdlAccountItem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdlPda {
+    pub seeds: Vec<IdlSeed>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum IdlSeed {
+    Const { value: Vec<u8> },
+    Arg { path: String },
+    Account { path: String },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdlField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdlTypeDef {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: Value,
+}
+
+impl Idl {
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    fn type_def(&self, name: &str) -> Option<&IdlTypeDef> {
+        self.types.iter().find(|t| t.name == name)
+    }
+}
+
+/// How instruction arguments are filled in when generating fixtures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgStrategy {
+    /// Zero integers, `false`, empty strings and vectors, `None`, first enum variant.
+    Zero,
+    /// Maximum integers, `true`, populated strings and vectors, `Some`, last enum variant.
+    Max,
+}
+
+impl ArgStrategy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ArgStrategy::Zero => "zero-args",
+            ArgStrategy::Max => "max-args",
+        }
+    }
+}
+
+const MAX_STRATEGY_STRING_LEN: usize = 32;
+
+/// A single instruction invocation to be measured.
+#[derive(Debug, Clone)]
+pub struct ProfileCase {
+    pub instruction: String,
+    pub label: String,
+    pub ix: Instruction,
+}
+
+/// Builds one case per instruction and argument strategy.
+///
+/// Every signer in the IDL resolves to `signer`, so PDAs seeded by an
+/// authority line up across instructions and state created by one case is
+/// visible to the next when cases run in sequence.
+pub fn cases_from_idl(
+    idl: &Idl,
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    strategies: &[ArgStrategy],
+) -> Vec<ProfileCase> {
+    let mut cases = Vec::new();
+
+    for instruction in &idl.instructions {
+        for strategy in strategies {
+            let args: Vec<(String, Value, Vec<u8>)> = instruction
+                .args
+                .iter()
+                .map(|arg| {
+                    let mut encoded = Vec::new();
+                    encode_value(idl, &arg.ty, *strategy, &mut encoded);
+                    (arg.name.clone(), arg.ty.clone(), encoded)
+                })
+                .collect();
+
+            let mut data = instruction.discriminator.clone();
+            for (_, _, encoded) in &args {
+                data.extend_from_slice(encoded);
+            }
+
+            let mut resolved: Vec<(String, Pubkey)> = Vec::new();
+            let mut metas = Vec::new();
+            for item in flatten_accounts(&instruction.accounts) {
+                let key = resolve_account(item, program_id, signer, &args, &resolved);
+                resolved.push((item.name.clone(), key));
+                metas.push(if item.writable {
+                    AccountMeta::new(key, item.signer)
+                } else {
+                    AccountMeta::new_readonly(key, item.signer)
+                });
+            }
+
+            cases.push(ProfileCase {
+                instruction: instruction.name.clone(),
+                label: strategy.label().to_string(),
+                ix: Instruction {
+                    program_id: *program_id,
+                    accounts: metas,
+                    data,
+                },
+            });
+        }
+    }
+
+    cases
+}
+
+fn flatten_accounts(items: &[IdlAccountItem]) -> Vec<&IdlAccountItem> {
+    let mut flat = Vec::new();
+    for item in items {
+        if item.accounts.is_empty() {
+            flat.push(item);
+        } else {
+            flat.extend(flatten_accounts(&item.accounts));
+        }
+    }
+    flat
+}
+
+fn resolve_account(
+    item: &IdlAccountItem,
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    args: &[(String, Value, Vec<u8>)],
+    resolved: &[(String, Pubkey)],
+) -> Pubkey {
+    if item.signer {
+        return *signer;
+    }
+    if let Some(address) = item.address.as_deref().and_then(|a| a.parse().ok()) {
+        return address;
+    }
+
+    let Some(pda) = &item.pda else {
+        return Pubkey::new_unique();
+    };
+
+    let mut seeds: Vec<Vec<u8>> = Vec::new();
+    for seed in &pda.seeds {
+        let bytes = match seed {
+            IdlSeed::Const { value } => Some(value.clone()),
+            IdlSeed::Arg { path } => args
+                .iter()
+                .find(|(name, _, _)| name == path)
+                .map(|(_, ty, encoded)| seed_bytes(ty, encoded)),
+            // Seeds reading a field of another account (`a.b`) need its data;
+            // those accounts are left unresolved.
+            IdlSeed::Account { path } if !path.contains('.') => resolved
+                .iter()
+                .find(|(name, _)| name == path)
+                .map(|(_, key)| key.to_bytes().to_vec()),
+            IdlSeed::Account { .. } => None,
+        };
+        match bytes {
+            Some(bytes) => seeds.push(bytes),
+            None => return Pubkey::new_unique(),
+        }
+    }
+
+    let seed_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    Pubkey::find_program_address(&seed_refs, program_id).0
+}
+
+/// Seeds take the raw bytes of strings and byte vectors, not their borsh form.
+fn seed_bytes(ty: &Value, encoded: &[u8]) -> Vec<u8> {
+    match ty.as_str() {
+        Some("string") | Some("bytes") => encoded[4..].to_vec(),
+        _ => encoded.to_vec(),
+    }
+}
+
+fn encode_value(idl: &Idl, ty: &Value, strategy: ArgStrategy, out: &mut Vec<u8>) {
+    let max = strategy == ArgStrategy::Max;
+
+    if let Some(name) = ty.as_str() {
+        let width = match name {
+            "bool" | "u8" | "i8" => 1,
+            "u16" | "i16" => 2,
+            "u32" | "i32" | "f32" => 4,
+            "u64" | "i64" | "f64" => 8,
+            "u128" | "i128" => 16,
+            "pubkey" => 32,
+            "string" | "bytes" => {
+                let len = if max { MAX_STRATEGY_STRING_LEN } else { 0 };
+                out.extend_from_slice(&(len as u32).to_le_bytes());
+                out.resize(out.len() + len, b'a');
+                return;
+            }
+            _ => 0,
+        };
+        let fill = match (name, max) {
+            (_, false) => 0x00,
+            ("bool", true) => 0x01,
+            ("pubkey", true) => 0x01,
+            ("f32", true) => {
+                out.extend_from_slice(&f32::MAX.to_le_bytes());
+                return;
+            }
+            ("f64", true) => {
+                out.extend_from_slice(&f64::MAX.to_le_bytes());
+                return;
+            }
+            // Signed maxima have a clear top bit in little-endian order.
+            (n, true) if n.starts_with('i') => {
+                out.resize(out.len() + width - 1, 0xff);
+                out.push(0x7f);
+                return;
+            }
+            (_, true) => 0xff,
+        };
+        out.resize(out.len() + width, fill);
+        return;
+    }
+
+    if let Some(inner) = ty.get("vec") {
+        let len: u32 = if max { 1 } else { 0 };
+        out.extend_from_slice(&len.to_le_bytes());
+        if max {
+            encode_value(idl, inner, strategy, out);
+        }
+    } else if let Some(inner) = ty.get("option") {
+        out.push(max as u8);
+        if max {
+            encode_value(idl, inner, strategy, out);
+        }
+    } else if let Some(array) = ty.get("array").and_then(|a| a.as_array()) {
+        let len = array.get(1).and_then(|n| n.as_u64()).unwrap_or(0);
+        for _ in 0..len {
+            encode_value(idl, &array[0], strategy, out);
+        }
+    } else if let Some(name) = ty
+        .get("defined")
+        .and_then(|d| d.get("name").or(Some(d)))
+        .and_then(|n| n.as_str())
+    {
+        if let Some(def) = idl.type_def(name) {
+            encode_defined(idl, &def.ty, strategy, out);
+        }
+    }
+}
+
+fn encode_defined(idl: &Idl, def: &Value, strategy: ArgStrategy, out: &mut Vec<u8>) {
+    match def.get("kind").and_then(|k| k.as_str()) {
+        Some("struct") => {
+            for field in def.get("fields").and_then(|f| f.as_array()).into_iter().flatten() {
+                // Tuple structs list bare types, named structs list `{ name, type }`.
+                let ty = field.get("type").unwrap_or(field);
+                encode_value(idl, ty, strategy, out);
+            }
+        }
+        Some("enum") => {
+            let variants = def
+                .get("variants")
+                .and_then(|v| v.as_array())
+                .cloned()
+                .unwrap_or_default();
+            if variants.is_empty() {
+                return;
+            }
+            let index = match strategy {
+                ArgStrategy::Zero => 0,
+                ArgStrategy::Max => variants.len() - 1,
+            };
+            out.push(index as u8);
+            for field in variants[index]
+                .get("fields")
+                .and_then(|f| f.as_array())
+                .into_iter()
+                .flatten()
+            {
+                let ty = field.get("type").unwrap_or(field);
+                encode_value(idl, ty, strategy, out);
+            }
+        }
+        _ => {}
+    }
+}
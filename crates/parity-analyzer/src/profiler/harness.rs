@@ -0,0 +1,127 @@
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{BanksClientError, ProgramTest};
+use solana_sdk::account::Account;
+use solana_sdk::bpf_loader;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::Signer;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+use super::{cases_from_idl, ArgStrategy, CuProfile, CuSample, Idl, ProfileCase};
+
+/// Compute budget granted to each profiled transaction, so expensive paths
+/// are measured instead of aborting at the default per-instruction limit.
+pub const PROFILER_CU_LIMIT: u64 = 1_400_000;
+
+/// Executes a program build in-process and records compute unit usage.
+pub struct Profiler {
+    program_id: Pubkey,
+    passes: usize,
+}
+
+impl Profiler {
+    pub fn new(program_id: Pubkey) -> Self {
+        Self { program_id, passes: 2 }
+    }
+
+    /// Number of times the full case list is replayed against the same bank.
+    ///
+    /// The first pass typically creates accounts and the second exercises the
+    /// paths that depend on them (and the "already initialized" failures).
+    pub fn passes(mut self, passes: usize) -> Self {
+        self.passes = passes.max(1);
+        self
+    }
+
+    /// Profiles every instruction in `idl` with both argument strategies.
+    pub async fn profile_idl(&self, elf: &[u8], idl: &Idl) -> Result<CuProfile, BanksClientError> {
+        let program_id = self.program_id;
+        self.profile(elf, |signer| {
+            cases_from_idl(idl, &program_id, signer, &[ArgStrategy::Zero, ArgStrategy::Max])
+        })
+        .await
+    }
+
+    /// Profiles caller-supplied cases. `build_cases` receives the fee payer,
+    /// which is the only signer available to the cases.
+    pub async fn profile(
+        &self,
+        elf: &[u8],
+        build_cases: impl FnOnce(&Pubkey) -> Vec<ProfileCase>,
+    ) -> Result<CuProfile, BanksClientError> {
+        let mut program_test = ProgramTest::default();
+        program_test.prefer_bpf(true);
+        program_test.set_compute_max_units(PROFILER_CU_LIMIT);
+        program_test.add_account(
+            self.program_id,
+            Account {
+                lamports: Rent::default().minimum_balance(elf.len()).max(1),
+                data: elf.to_vec(),
+                owner: bpf_loader::id(),
+                executable: true,
+                rent_epoch: 0,
+            },
+        );
+
+        let mut ctx = program_test.start_with_context().await;
+        let payer = ctx.payer.pubkey();
+        let cases = build_cases(&payer);
+        let mut profile = CuProfile::default();
+
+        for _ in 0..self.passes {
+            for case in &cases {
+                // A fresh blockhash per transaction keeps replays of identical
+                // instructions from being rejected as already processed.
+                let blockhash = ctx.get_new_latest_blockhash().await?;
+                let tx = Transaction::new_signed_with_payer(
+                    std::slice::from_ref(&case.ix),
+                    Some(&payer),
+                    &[&ctx.payer],
+                    blockhash,
+                );
+
+                let outcome = ctx.banks_client.process_transaction_with_metadata(tx).await?;
+                let (units, logs) = outcome
+                    .metadata
+                    .map(|m| (m.compute_units_consumed, m.log_messages))
+                    .unwrap_or_default();
+
+                profile.record(CuSample {
+                    instruction: case.instruction.clone(),
+                    case: case.label.clone(),
+                    path: code_path(&outcome.result, &logs),
+                    units,
+                });
+            }
+        }
+
+        Ok(profile)
+    }
+}
+
+/// Names the path a transaction took through the program: its outcome,
+/// followed by the programs it invoked, in order. An instruction that creates
+/// an account through the system program is a different path from one that
+/// finds it already there, even though both succeed.
+pub fn code_path(result: &Result<(), TransactionError>, logs: &[String]) -> String {
+    let mut path = match result {
+        Ok(()) => "ok".to_string(),
+        Err(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+            format!("error:{}", code)
+        }
+        Err(err) => format!("error:{:?}", err),
+    };
+
+    let invoked: Vec<&str> = logs
+        .iter()
+        .filter_map(|line| {
+            let (program, depth) = line.strip_prefix("Program ")?.split_once(" invoke [")?;
+            (depth != "1]").then_some(program)
+        })
+        .collect();
+    if !invoked.is_empty() {
+        path.push_str(" via ");
+        path.push_str(&invoked.join(","));
+    }
+    path
+}
@@ -0,0 +1,16 @@
+//! Compute unit profiler backing the `gas-optimization` skill.
+//!
+//! Fixtures are synthesized from the target's Anchor IDL, executed against an
+//! in-process bank (behind the `profiler` feature), and aggregated per
+//! instruction and per code path. Two profiles can be diffed to catch
+//! regressions between builds.
+
+mod fixtures;
+#[cfg(feature = "profiler")]
+mod harness;
+mod report;
+
+pub use fixtures::*;
+#[cfg(feature = "profiler")]
+pub use harness::*;
+pub use report::*;
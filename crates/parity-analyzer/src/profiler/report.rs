@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+
+use parity::state::Severity;
+
+use crate::finding::{escalate, severity_rank};
+
+/// Default per-instruction compute budget enforced by the runtime.
+pub const DEFAULT_INSTRUCTION_CU_LIMIT: u64 = 200_000;
+
+/// Relative increase between two builds reported as a regression.
+pub const REGRESSION_THRESHOLD_PERCENT: f64 = 10.0;
+
+/// Compute units observed for one execution of one instruction.
+#[derive(Debug, Clone)]
+pub struct CuSample {
+    pub instruction: String,
+    pub case: String,
+    /// `ok` or `error:<code>`, derived from the transaction result, then
+    /// ` via ` and the programs invoked through CPI, if any.
+    pub path: String,
+    pub units: u64,
+}
+
+impl CuSample {
+    pub fn succeeded(&self) -> bool {
+        path_succeeded(&self.path)
+    }
+}
+
+fn path_succeeded(path: &str) -> bool {
+    path == "ok" || path.starts_with("ok via ")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CuStats {
+    pub samples: usize,
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+}
+
+impl CuStats {
+    fn from_units(units: impl Iterator<Item = u64>) -> Option<Self> {
+        let mut stats: Option<CuStats> = None;
+        let mut total: u128 = 0;
+        for u in units {
+            total += u as u128;
+            stats = Some(match stats {
+                None => CuStats { samples: 1, min: u, max: u, mean: 0.0 },
+                Some(s) => CuStats {
+                    samples: s.samples + 1,
+                    min: s.min.min(u),
+                    max: s.max.max(u),
+                    mean: 0.0,
+                },
+            });
+        }
+        stats.map(|s| CuStats { mean: total as f64 / s.samples as f64, ..s })
+    }
+}
+
+/// All samples collected for one build of a program.
+#[derive(Debug, Clone, Default)]
+pub struct CuProfile {
+    pub samples: Vec<CuSample>,
+}
+
+impl CuProfile {
+    pub fn record(&mut self, sample: CuSample) {
+        self.samples.push(sample);
+    }
+
+    /// Statistics keyed by instruction name, across every code path.
+    pub fn by_instruction(&self) -> BTreeMap<String, CuStats> {
+        self.group(|s| s.instruction.clone())
+    }
+
+    /// Statistics keyed by `(instruction, path)`.
+    pub fn by_path(&self) -> BTreeMap<(String, String), CuStats> {
+        self.group(|s| (s.instruction.clone(), s.path.clone()))
+    }
+
+    /// The `compute_units` output of the `gas-optimization` skill: the highest
+    /// consumption of any instruction that completed successfully, i.e. the
+    /// budget a client has to request to call every instruction safely.
+    ///
+    /// Falls back to failing paths when nothing succeeded so the number is
+    /// never silently zero.
+    pub fn compute_units(&self) -> u64 {
+        let ok = self.samples.iter().filter(|s| s.succeeded()).map(|s| s.units).max();
+        ok.or_else(|| self.samples.iter().map(|s| s.units).max())
+            .unwrap_or(0)
+    }
+
+    /// Compares `self` (baseline) against `candidate`, one row per
+    /// `(instruction, path)` seen in either build.
+    pub fn diff(&self, candidate: &CuProfile) -> Vec<CuDelta> {
+        let before = self.by_path();
+        let after = candidate.by_path();
+
+        let mut keys: Vec<&(String, String)> = before.keys().chain(after.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        keys.into_iter()
+            .map(|key| CuDelta {
+                instruction: key.0.clone(),
+                path: key.1.clone(),
+                before: before.get(key).map(|s| s.max),
+                after: after.get(key).map(|s| s.max),
+            })
+            .collect()
+    }
+
+    fn group<K: Ord>(&self, key: impl Fn(&CuSample) -> K) -> BTreeMap<K, CuStats> {
+        let mut buckets: BTreeMap<K, Vec<u64>> = BTreeMap::new();
+        for sample in &self.samples {
+            buckets.entry(key(sample)).or_default().push(sample.units);
+        }
+        buckets
+            .into_iter()
+            .filter_map(|(k, units)| CuStats::from_units(units.into_iter()).map(|s| (k, s)))
+            .collect()
+    }
+}
+
+/// Worst-case consumption of one code path in two builds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CuDelta {
+    pub instruction: String,
+    pub path: String,
+    pub before: Option<u64>,
+    pub after: Option<u64>,
+}
+
+impl CuDelta {
+    pub fn change(&self) -> i64 {
+        self.after.unwrap_or(0) as i64 - self.before.unwrap_or(0) as i64
+    }
+
+    /// `None` when the path only exists in one build.
+    pub fn change_percent(&self) -> Option<f64> {
+        match (self.before, self.after) {
+            (Some(before), Some(after)) if before > 0 => {
+                Some((after as f64 - before as f64) * 100.0 / before as f64)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_regression(&self) -> bool {
+        self.change_percent()
+            .map(|p| p > REGRESSION_THRESHOLD_PERCENT)
+            .unwrap_or(false)
+    }
+}
+
+/// A ranked compute unit finding for the `gas-optimization` skill.
+#[derive(Debug, Clone)]
+pub struct CuFinding {
+    pub severity: Severity,
+    pub instruction: String,
+    pub path: String,
+    pub units: u64,
+    pub message: String,
+}
+
+fn severity_for_units(units: u64) -> Severity {
+    match units {
+        u if u >= DEFAULT_INSTRUCTION_CU_LIMIT => Severity::Critical,
+        u if u >= DEFAULT_INSTRUCTION_CU_LIMIT / 2 => Severity::High,
+        u if u >= DEFAULT_INSTRUCTION_CU_LIMIT / 4 => Severity::Medium,
+        _ => Severity::Info,
+    }
+}
+
+/// Ranks every successful code path by cost, most expensive first. When a
+/// baseline is given, regressions beyond [`REGRESSION_THRESHOLD_PERCENT`] are
+/// raised one severity level and called out in the message.
+pub fn optimization_findings(profile: &CuProfile, baseline: Option<&CuProfile>) -> Vec<CuFinding> {
+    let deltas: BTreeMap<(String, String), CuDelta> = baseline
+        .map(|b| {
+            b.diff(profile)
+                .into_iter()
+                .map(|d| ((d.instruction.clone(), d.path.clone()), d))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut findings: Vec<CuFinding> = profile
+        .by_path()
+        .into_iter()
+        .filter(|((_, path), _)| path_succeeded(path))
+        .map(|((instruction, path), stats)| {
+            let mut severity = severity_for_units(stats.max);
+            let subject = if path == "ok" { instruction.clone() } else { format!("{} ({})", instruction, path) };
+            let mut message = format!(
+                "{} consumes up to {} CU ({:.0}% of the default {} CU instruction budget)",
+                subject,
+                stats.max,
+                stats.max as f64 * 100.0 / DEFAULT_INSTRUCTION_CU_LIMIT as f64,
+                DEFAULT_INSTRUCTION_CU_LIMIT
+            );
+
+            if let Some(delta) = deltas.get(&(instruction.clone(), path.clone())) {
+                if delta.is_regression() {
+                    severity = escalate(severity);
+                    message.push_str(&format!(
+                        "; regressed {:+.1}% against the baseline build",
+                        delta.change_percent().unwrap_or_default()
+                    ));
+                }
+            }
+
+            CuFinding {
+                severity,
+                instruction,
+                path,
+                units: stats.max,
+                message,
+            }
+        })
+        .collect();
+
+    findings.sort_by(|a, b| {
+        severity_rank(a.severity)
+            .cmp(&severity_rank(b.severity))
+            .then(b.units.cmp(&a.units))
+    });
+    findings
+}
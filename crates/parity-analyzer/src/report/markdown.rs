@@ -0,0 +1,69 @@
+//! Human-readable audit report.
+
+use std::fmt::Write;
+
+use parity::state::Severity;
+
+use super::Report;
+
+fn heading(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "CRITICAL",
+        Severity::High => "HIGH",
+        Severity::Medium => "MEDIUM",
+        Severity::Info => "INFO",
+        Severity::Pass => "PASS",
+    }
+}
+
+pub(super) fn to_markdown(report: &Report) -> String {
+    let mut out = String::new();
+    let c = &report.findings_count;
+
+    // Writing to a String cannot fail.
+    let _ = writeln!(out, "# Parity Audit Report: {}\n", report.program);
+    let _ = writeln!(out, "**Score:** {}/100  ", report.score);
+    let _ = writeln!(out, "**Skills:** {}  ", report.skills.join(", "));
+    let _ = writeln!(out, "**Findings hash:** `{}`\n", report.findings_hash);
+
+    let _ = writeln!(out, "| Critical | High | Medium | Info | Pass | Total |");
+    let _ = writeln!(out, "|---|---|---|---|---|---|");
+    let _ = writeln!(
+        out,
+        "| {} | {} | {} | {} | {} | {} |\n",
+        c.critical, c.high, c.medium, c.info, c.pass, c.total
+    );
+
+    if report.findings.is_empty() {
+        let _ = writeln!(out, "No findings.");
+        return out;
+    }
+
+    let _ = writeln!(out, "## Findings\n");
+    for (i, finding) in report.findings.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "### {}. [{}] {}\n",
+            i + 1,
+            heading(finding.severity),
+            finding.title
+        );
+        let _ = writeln!(out, "- **Rule:** `{}`", finding.rule_id);
+        let _ = writeln!(out, "- **Location:** `{}` (`{}`)", finding.location, finding.subject);
+        let _ = writeln!(out, "- **Reported by:** {}", finding.skills.join(", "));
+        if !finding.message.is_empty() {
+            let _ = writeln!(out, "\n{}", finding.message);
+        }
+        if !finding.fixes.is_empty() {
+            let _ = writeln!(out, "\n**Suggested fix:**\n");
+            for fix in &finding.fixes {
+                let _ = writeln!(out, "- {}", fix.description);
+                if let Some(example) = &fix.example {
+                    let _ = writeln!(out, "\n  ```rust\n  {}\n  ```", example);
+                }
+            }
+        }
+        let _ = writeln!(out);
+    }
+    out
+}
@@ -0,0 +1,180 @@
+//! The `Finding[]` output of every skill as a serializable audit report.
+//!
+//! A report carries the findings in a canonical order along with the score,
+//! severity counts and `findings_hash` that are submitted on-chain. JSON is
+//! the interchange format; SARIF and Markdown are export-only.
+
+mod markdown;
+mod sarif;
+
+use std::collections::HashSet;
+
+use parity::scoring::ScoringModel;
+use parity::state::{AnalysisFindingsCount, Severity};
+use serde::{Deserialize, Serialize};
+
+use crate::codemod;
+use crate::finding::{findings_root, severity_rank, Finding, FixSuggestion};
+use crate::ir::ProgramIr;
+
+/// Bumped on incompatible changes to the JSON layout.
+pub const REPORT_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FindingsCount {
+    pub critical: u16,
+    pub high: u16,
+    pub medium: u16,
+    pub info: u16,
+    pub pass: u16,
+    pub total: u16,
+}
+
+impl From<FindingsCount> for AnalysisFindingsCount {
+    fn from(c: FindingsCount) -> Self {
+        AnalysisFindingsCount {
+            critical: c.critical,
+            high: c.high,
+            medium: c.medium,
+            info: c.info,
+            pass: c.pass,
+            total: c.total,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub format_version: u32,
+    /// Name or path of the analyzed program.
+    pub program: String,
+    pub skills: Vec<String>,
+    pub score: u8,
+    pub findings_count: FindingsCount,
+    /// Hex-encoded `AnalysisReport.findings_hash`.
+    pub findings_hash: String,
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    /// Orders the findings canonically, drops those that duplicate an earlier
+    /// finding's leaf, and derives score, counts and hash.
+    pub fn new(program: impl Into<String>, skills: Vec<String>, mut findings: Vec<Finding>, model: &ScoringModel) -> Self {
+        findings.sort_by(|a, b| {
+            severity_rank(a.severity)
+                .cmp(&severity_rank(b.severity))
+                .then_with(|| a.location.cmp(&b.location))
+                .then_with(|| a.rule_id.cmp(&b.rule_id))
+                .then_with(|| a.subject.cmp(&b.subject))
+        });
+        // Findings that commit to the same leaf are one finding on-chain, so
+        // they are counted and scored once too.
+        let mut leaves = HashSet::new();
+        findings.retain(|finding| leaves.insert(finding.canonical().leaf()));
+
+        let scored: Vec<_> = findings.iter().map(Finding::scored).collect();
+        let score = model.score(&scored).score;
+
+        let mut count = FindingsCount::default();
+        for finding in &findings {
+            let slot = match finding.severity {
+                Severity::Critical => &mut count.critical,
+                Severity::High => &mut count.high,
+                Severity::Medium => &mut count.medium,
+                Severity::Info => &mut count.info,
+                Severity::Pass => &mut count.pass,
+            };
+            *slot = slot.saturating_add(1);
+            count.total = count.total.saturating_add(1);
+        }
+
+        Report {
+            format_version: REPORT_FORMAT_VERSION,
+            program: program.into(),
+            skills,
+            score,
+            findings_count: count,
+            findings_hash: hex(&findings_root(&findings)),
+            findings,
+        }
+    }
+
+    /// Adds a fix suggestion for every finding `codemod` knows how to patch.
+    pub fn attach_fixes(&mut self, ir: &ProgramIr) {
+        for finding in &mut self.findings {
+            for fix in codemod::plan(finding, ir) {
+                let pattern = fix.framework_pattern();
+                let suggestion = FixSuggestion {
+                    description: fix.to_string(),
+                    pattern: pattern.map(|p| p.pattern_name.to_string()),
+                    example: pattern.map(|p| p.example_code.to_string()),
+                };
+                if !finding.fixes.contains(&suggestion) {
+                    finding.fixes.push(suggestion);
+                }
+            }
+        }
+    }
+
+    /// The raw `findings_hash`, recomputed from the findings.
+    pub fn findings_hash(&self) -> [u8; 32] {
+        findings_root(&self.findings)
+    }
+
+    pub fn findings_count(&self) -> AnalysisFindingsCount {
+        self.findings_count.into()
+    }
+
+    pub fn to_json(&self) -> String {
+        // Every field serializes infallibly.
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Parses a report and checks that its stored hash matches its findings.
+    pub fn from_json(json: &str) -> Result<Self, ReportError> {
+        let report: Report = serde_json::from_str(json).map_err(ReportError::Json)?;
+        let mut leaves = HashSet::new();
+        if !report.findings.iter().all(|finding| leaves.insert(finding.canonical().leaf())) {
+            return Err(ReportError::DuplicateFinding);
+        }
+        if report.findings_hash != hex(&report.findings_hash()) {
+            return Err(ReportError::HashMismatch);
+        }
+        Ok(report)
+    }
+
+    /// A SARIF 2.1.0 log with a single run.
+    pub fn to_sarif(&self) -> serde_json::Value {
+        sarif::to_sarif(self)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        markdown::to_markdown(self)
+    }
+}
+
+#[derive(Debug)]
+pub enum ReportError {
+    Json(serde_json::Error),
+    /// `findings_hash` does not match the findings it is stored with.
+    HashMismatch,
+    /// Two findings commit to the same leaf, so the counts would disagree
+    /// with `findings_hash`.
+    DuplicateFinding,
+}
+
+impl std::fmt::Display for ReportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportError::Json(err) => write!(f, "invalid report: {}", err),
+            ReportError::HashMismatch => write!(f, "findings_hash does not match the report's findings"),
+            ReportError::DuplicateFinding => write!(f, "report lists the same finding more than once"),
+        }
+    }
+}
+
+impl std::error::Error for ReportError {}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,130 @@
+//! SARIF 2.1.0 export for code-scanning UIs.
+
+use parity::context_engine::VULNERABILITY_RULES;
+use parity::state::Severity;
+use serde_json::{json, Value};
+
+use super::{hex, Report};
+use crate::finding::severity_name;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Info => "note",
+        Severity::Pass => "none",
+    }
+}
+
+/// The `security-severity` property GitHub code scanning ranks alerts by.
+fn security_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "9.5",
+        Severity::High => "7.5",
+        Severity::Medium => "5.0",
+        Severity::Info => "2.0",
+        Severity::Pass => "0.0",
+    }
+}
+
+pub(super) fn to_sarif(report: &Report) -> Value {
+    // One descriptor per distinct rule, in order of first appearance.
+    let mut rule_ids: Vec<&str> = Vec::new();
+    for finding in &report.findings {
+        if !rule_ids.contains(&finding.rule_id.as_str()) {
+            rule_ids.push(&finding.rule_id);
+        }
+    }
+
+    let rules: Vec<Value> = rule_ids
+        .iter()
+        .map(|id| {
+            let first = report.findings.iter().find(|f| f.rule_id == *id);
+            let builtin = VULNERABILITY_RULES.iter().find(|r| r.id == *id);
+            let severity = builtin
+                .map(|r| r.severity)
+                .or(first.map(|f| f.severity))
+                .unwrap_or(Severity::Info);
+            let short = builtin
+                .map(|r| r.description.to_string())
+                .or(first.map(|f| f.title.clone()))
+                .unwrap_or_default();
+            let mut rule = json!({
+                "id": id,
+                "name": id,
+                "shortDescription": { "text": short },
+                "defaultConfiguration": { "level": level(severity) },
+                "properties": {
+                    "security-severity": security_severity(severity),
+                    "tags": ["security", "solana"],
+                },
+            });
+            if let Some(builtin) = builtin {
+                rule["fullDescription"] = json!({ "text": builtin.detection_hint });
+            }
+            rule
+        })
+        .collect();
+
+    let results: Vec<Value> = report
+        .findings
+        .iter()
+        .map(|finding| {
+            let message = if finding.message.is_empty() {
+                finding.title.clone()
+            } else {
+                format!("{}: {}", finding.title, finding.message)
+            };
+            let mut result = json!({
+                "ruleId": finding.rule_id,
+                "ruleIndex": rule_ids.iter().position(|id| *id == finding.rule_id),
+                "level": level(finding.severity),
+                "message": { "text": message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": finding.location.file },
+                        "region": {
+                            "startLine": finding.location.line.max(1),
+                            "startColumn": finding.location.column.max(1),
+                        },
+                    },
+                    "logicalLocations": [{ "fullyQualifiedName": finding.subject }],
+                }],
+                "partialFingerprints": { "parityFinding/v1": hex(&finding.canonical().leaf()) },
+                "properties": {
+                    "severity": severity_name::name(finding.severity),
+                    "skills": finding.skills,
+                },
+            });
+            if !finding.fixes.is_empty() {
+                let fixes: Vec<&str> = finding.fixes.iter().map(|f| f.description.as_str()).collect();
+                result["properties"]["fixes"] = json!(fixes);
+            }
+            result
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "parity",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/parity-cx/parity",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+            "properties": {
+                "program": report.program,
+                "skills": report.skills,
+                "score": report.score,
+                "findingsHash": report.findings_hash,
+            },
+        }],
+    })
+}
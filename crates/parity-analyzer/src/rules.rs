@@ -0,0 +1,182 @@
+//! Evaluates `detection_rule` programs against the program IR.
+
+use parity::detection_rule::{self, CmpOp, DetectionRule, Expr, Literal, RuleError, Target};
+use parity::state::Severity;
+
+use crate::ir::{ContextField, Handler, Location, ProgramIr, StateAccount};
+use crate::layout;
+
+/// An IR entity selected by a rule.
+#[derive(Debug, Clone)]
+pub struct RuleMatch {
+    /// Human-readable handle such as `Withdraw.authority` or `withdraw`.
+    pub subject: String,
+    pub location: Location,
+    /// The rule's `severity` clause, if it has one.
+    pub severity: Option<Severity>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Str(String),
+    Num(u64),
+    Bool(bool),
+}
+
+trait Subject {
+    fn attribute(&self, ir: &ProgramIr, name: &str) -> Option<Value>;
+    fn has(&self, _constraint: &str) -> bool {
+        false
+    }
+    fn subject(&self) -> String;
+    fn location(&self) -> &Location;
+}
+
+impl Subject for ContextField {
+    fn attribute(&self, _ir: &ProgramIr, name: &str) -> Option<Value> {
+        Some(match name {
+            "name" => Value::Str(self.name.clone()),
+            "context" => Value::Str(self.context.clone()),
+            "wrapper" => Value::Str(self.wrapper.clone()),
+            "inner" => Value::Str(self.inner.clone().unwrap_or_default()),
+            "mut" => Value::Bool(self.is_mut()),
+            "init" => Value::Bool(self.is_init()),
+            "signer" => Value::Bool(self.is_signer()),
+            "check_doc" => Value::Bool(self.check_doc),
+            _ => return None,
+        })
+    }
+
+    fn has(&self, constraint: &str) -> bool {
+        ContextField::has(self, constraint)
+    }
+
+    fn subject(&self) -> String {
+        format!("{}.{}", self.context, self.name)
+    }
+
+    fn location(&self) -> &Location {
+        &self.location
+    }
+}
+
+impl Subject for Handler {
+    fn attribute(&self, _ir: &ProgramIr, name: &str) -> Option<Value> {
+        Some(match name {
+            "name" => Value::Str(self.name.clone()),
+            "context" => Value::Str(self.context.clone()),
+            "cpi_calls" => Value::Num(self.cpi_calls.len() as u64),
+            "state_writes" => Value::Num(self.state_writes.len() as u64),
+            "writes_after_cpi" => Value::Bool(!self.writes_after_cpi().is_empty()),
+            "unchecked_arithmetic" => Value::Num(self.unchecked_arithmetic.len() as u64),
+            "lamport_writes" => Value::Num(self.lamport_writes.len() as u64),
+            "emits_event" => Value::Bool(self.emits_event),
+            "uses_remaining_accounts" => Value::Bool(self.uses_remaining_accounts),
+            _ => return None,
+        })
+    }
+
+    fn subject(&self) -> String {
+        self.name.clone()
+    }
+
+    fn location(&self) -> &Location {
+        &self.location
+    }
+}
+
+impl Subject for StateAccount {
+    fn attribute(&self, ir: &ProgramIr, name: &str) -> Option<Value> {
+        Some(match name {
+            "name" => Value::Str(self.name.clone()),
+            "space" => Value::Num(layout::analyze_account(ir, self).space as u64),
+            "init_space" => Value::Bool(self.derives_init_space),
+            "zero_copy" => Value::Bool(self.zero_copy),
+            _ => return None,
+        })
+    }
+
+    fn subject(&self) -> String {
+        self.name.clone()
+    }
+
+    fn location(&self) -> &Location {
+        &self.location
+    }
+}
+
+/// Parses `source` and evaluates it; parse errors are returned unchanged.
+pub fn evaluate_source(source: &str, ir: &ProgramIr) -> Result<Vec<RuleMatch>, RuleError> {
+    detection_rule::parse(source).map(|rule| evaluate(&rule, ir))
+}
+
+pub fn evaluate(rule: &DetectionRule, ir: &ProgramIr) -> Vec<RuleMatch> {
+    match rule.target {
+        Target::Field => select(rule, ir, ir.context_fields()),
+        Target::Handler => select(rule, ir, ir.handlers.iter()),
+        Target::Account => select(rule, ir, ir.accounts.iter()),
+    }
+}
+
+fn select<'a, S: Subject + 'a>(
+    rule: &DetectionRule,
+    ir: &ProgramIr,
+    subjects: impl Iterator<Item = &'a S>,
+) -> Vec<RuleMatch> {
+    subjects
+        .filter(|s| rule.condition.as_ref().map(|c| eval(c, *s, ir)).unwrap_or(true))
+        .map(|s| RuleMatch {
+            subject: s.subject(),
+            location: s.location().clone(),
+            severity: rule.severity,
+        })
+        .collect()
+}
+
+fn eval<S: Subject>(expr: &Expr, subject: &S, ir: &ProgramIr) -> bool {
+    match expr {
+        Expr::And(operands) => operands.iter().all(|e| eval(e, subject, ir)),
+        Expr::Or(operands) => operands.iter().any(|e| eval(e, subject, ir)),
+        Expr::Not(inner) => !eval(inner, subject, ir),
+        Expr::Has(constraint) => subject.has(constraint),
+        Expr::Flag(attribute) => subject.attribute(ir, attribute) == Some(Value::Bool(true)),
+        Expr::In { attribute, values } => match subject.attribute(ir, attribute) {
+            Some(Value::Str(s)) => values.contains(&s),
+            _ => false,
+        },
+        Expr::Compare { attribute, op, value } => {
+            let Some(actual) = subject.attribute(ir, attribute) else {
+                return false;
+            };
+            compare(&actual, *op, value)
+        }
+    }
+}
+
+fn compare(actual: &Value, op: CmpOp, expected: &Literal) -> bool {
+    match (actual, expected) {
+        (Value::Str(a), Literal::Str(b)) => match op {
+            CmpOp::Eq => a == b,
+            CmpOp::Ne => a != b,
+            CmpOp::Contains => a.contains(b.as_str()),
+            CmpOp::StartsWith => a.starts_with(b.as_str()),
+            CmpOp::EndsWith => a.ends_with(b.as_str()),
+            _ => false,
+        },
+        (Value::Num(a), Literal::Num(b)) => match op {
+            CmpOp::Eq => a == b,
+            CmpOp::Ne => a != b,
+            CmpOp::Lt => a < b,
+            CmpOp::Le => a <= b,
+            CmpOp::Gt => a > b,
+            CmpOp::Ge => a >= b,
+            _ => false,
+        },
+        (Value::Bool(a), Literal::Bool(b)) => match op {
+            CmpOp::Eq => a == b,
+            CmpOp::Ne => a != b,
+            _ => false,
+        },
+        _ => false,
+    }
+}
@@ -0,0 +1,234 @@
+//! Extraction of the `security.txt` section embedded by the
+//! `solana-security-txt` crate's `security_txt!` macro.
+//!
+//! The macro emits a single `.security.txt` section holding NUL-terminated
+//! `key, value` pairs between a begin and an end marker. The markers are
+//! unique within a program binary, so the section is located by scanning for
+//! them rather than by walking the ELF section headers.
+
+use std::path::Path;
+
+pub const BEGIN_MARKER: &[u8] = b"=======BEGIN SECURITY.TXT V1=======\0";
+pub const END_MARKER: &[u8] = b"=======END SECURITY.TXT V1=======\0";
+
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+
+/// The fields `security_txt!` accepts; the first four are required.
+const FIELDS: &[&str] = &[
+    "name",
+    "project_url",
+    "contacts",
+    "policy",
+    "preferred_languages",
+    "encryption",
+    "source_code",
+    "source_release",
+    "source_revision",
+    "auditors",
+    "acknowledgements",
+    "expiry",
+];
+const REQUIRED_FIELDS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactKind {
+    Email,
+    Discord,
+    Telegram,
+    Twitter,
+    Link,
+    Other,
+}
+
+impl ContactKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContactKind::Email => "email",
+            ContactKind::Discord => "discord",
+            ContactKind::Telegram => "telegram",
+            ContactKind::Twitter => "twitter",
+            ContactKind::Link => "link",
+            ContactKind::Other => "other",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "email" => Some(ContactKind::Email),
+            "discord" => Some(ContactKind::Discord),
+            "telegram" => Some(ContactKind::Telegram),
+            "twitter" => Some(ContactKind::Twitter),
+            "link" => Some(ContactKind::Link),
+            "other" => Some(ContactKind::Other),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contact {
+    pub kind: ContactKind,
+    pub value: String,
+}
+
+impl std::fmt::Display for Contact {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.kind.as_str(), self.value)
+    }
+}
+
+/// A validated `security.txt`. List fields are split on commas; contacts keep
+/// the order of preference they were declared in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SecurityTxt {
+    pub name: String,
+    pub project_url: String,
+    pub contacts: Vec<Contact>,
+    pub policy: String,
+    pub preferred_languages: Vec<String>,
+    pub encryption: Option<String>,
+    pub source_code: Option<String>,
+    pub source_release: Option<String>,
+    pub source_revision: Option<String>,
+    pub auditors: Vec<String>,
+    pub acknowledgements: Option<String>,
+    pub expiry: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum SecurityTxtError {
+    Io(std::io::Error),
+    NotElf,
+    /// The binary has no `security.txt` section.
+    Missing,
+    /// The begin marker is not followed by an end marker.
+    Unterminated,
+    /// A key or value is not NUL-terminated UTF-8.
+    Malformed,
+    UnknownField(String),
+    DuplicateField(String),
+    MissingField(&'static str),
+    EmptyField(&'static str),
+    InvalidContact(String),
+}
+
+impl std::fmt::Display for SecurityTxtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecurityTxtError::Io(err) => write!(f, "{}", err),
+            SecurityTxtError::NotElf => write!(f, "not an ELF binary"),
+            SecurityTxtError::Missing => write!(f, "no security.txt section"),
+            SecurityTxtError::Unterminated => write!(f, "security.txt section has no end marker"),
+            SecurityTxtError::Malformed => write!(f, "security.txt section is not NUL-separated UTF-8"),
+            SecurityTxtError::UnknownField(key) => write!(f, "unknown security.txt field `{}`", key),
+            SecurityTxtError::DuplicateField(key) => write!(f, "security.txt field `{}` is repeated", key),
+            SecurityTxtError::MissingField(key) => write!(f, "security.txt is missing `{}`", key),
+            SecurityTxtError::EmptyField(key) => write!(f, "security.txt field `{}` is empty", key),
+            SecurityTxtError::InvalidContact(contact) => {
+                write!(f, "contact `{}` is not of the form <email|discord|telegram|twitter|link|other>:<value>", contact)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SecurityTxtError {}
+
+impl SecurityTxt {
+    /// Reads and parses the `security.txt` of the `.so` at `path`.
+    pub fn from_path(path: &Path) -> Result<Self, SecurityTxtError> {
+        let elf = std::fs::read(path).map_err(SecurityTxtError::Io)?;
+        Self::from_elf(&elf)
+    }
+
+    pub fn from_elf(elf: &[u8]) -> Result<Self, SecurityTxtError> {
+        if !elf.starts_with(ELF_MAGIC) {
+            return Err(SecurityTxtError::NotElf);
+        }
+        Self::parse(section(elf)?)
+    }
+
+    /// Parses the bytes between the markers.
+    pub fn parse(section: &[u8]) -> Result<Self, SecurityTxtError> {
+        let mut values: Vec<Option<String>> = vec![None; FIELDS.len()];
+        let mut parts = section.split_inclusive(|b| *b == 0).map(nul_terminated);
+        while let Some(key) = parts.next() {
+            let key = key?;
+            let value = parts.next().ok_or(SecurityTxtError::Malformed)??;
+            let Some(index) = FIELDS.iter().position(|f| *f == key) else {
+                return Err(SecurityTxtError::UnknownField(key.to_string()));
+            };
+            if values[index].replace(value.to_string()).is_some() {
+                return Err(SecurityTxtError::DuplicateField(key.to_string()));
+            }
+        }
+
+        for (index, field) in FIELDS.iter().enumerate() {
+            match &values[index] {
+                Some(value) if value.trim().is_empty() => return Err(SecurityTxtError::EmptyField(field)),
+                None if index < REQUIRED_FIELDS => return Err(SecurityTxtError::MissingField(field)),
+                _ => {}
+            }
+        }
+
+        let mut take = |key: &str| values[FIELDS.iter().position(|f| *f == key).unwrap()].take();
+        let contacts = split_list(&take("contacts").unwrap_or_default())
+            .into_iter()
+            .map(|contact| parse_contact(&contact))
+            .collect::<Result<Vec<_>, _>>()?;
+        if contacts.is_empty() {
+            return Err(SecurityTxtError::EmptyField("contacts"));
+        }
+        Ok(SecurityTxt {
+            name: take("name").unwrap_or_default(),
+            project_url: take("project_url").unwrap_or_default(),
+            contacts,
+            policy: take("policy").unwrap_or_default(),
+            preferred_languages: take("preferred_languages").map(|v| split_list(&v)).unwrap_or_default(),
+            encryption: take("encryption"),
+            source_code: take("source_code"),
+            source_release: take("source_release"),
+            source_revision: take("source_revision"),
+            auditors: take("auditors").map(|v| split_list(&v)).unwrap_or_default(),
+            acknowledgements: take("acknowledgements"),
+            expiry: take("expiry"),
+        })
+    }
+
+    /// The preferred contact, i.e. the first one declared.
+    pub fn primary_contact(&self) -> Option<&Contact> {
+        self.contacts.first()
+    }
+}
+
+/// The bytes between the begin and end markers.
+fn section(elf: &[u8]) -> Result<&[u8], SecurityTxtError> {
+    let start = find(elf, BEGIN_MARKER).ok_or(SecurityTxtError::Missing)? + BEGIN_MARKER.len();
+    let len = find(&elf[start..], END_MARKER).ok_or(SecurityTxtError::Unterminated)?;
+    Ok(&elf[start..start + len])
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn nul_terminated(part: &[u8]) -> Result<&str, SecurityTxtError> {
+    let Some((0, text)) = part.split_last() else {
+        return Err(SecurityTxtError::Malformed);
+    };
+    std::str::from_utf8(text).map_err(|_| SecurityTxtError::Malformed)
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value.split(',').map(str::trim).filter(|item| !item.is_empty()).map(str::to_string).collect()
+}
+
+fn parse_contact(contact: &str) -> Result<Contact, SecurityTxtError> {
+    let invalid = || SecurityTxtError::InvalidContact(contact.to_string());
+    let (kind, value) = contact.split_once(':').ok_or_else(invalid)?;
+    let kind = ContactKind::parse(kind.trim()).ok_or_else(invalid)?;
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(invalid());
+    }
+    Ok(Contact { kind, value: value.to_string() })
+}
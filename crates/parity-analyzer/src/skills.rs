@@ -0,0 +1,185 @@
+//! Executable counterparts of the built-in skills in `parity::skills`.
+
+use parity::context_engine::VULNERABILITY_RULES;
+use parity::detection_rule;
+use parity::state::Severity;
+
+use crate::finding::Finding;
+use crate::ir::{Location, ProgramIr};
+use crate::layout::{analyze_layouts, LayoutSuggestion};
+use crate::profiler::{optimization_findings, CuProfile};
+use crate::rules::evaluate;
+
+pub const SECURITY_AUDIT: &str = "security-audit";
+pub const BEST_PRACTICES: &str = "best-practices";
+pub const GAS_OPTIMIZATION: &str = "gas-optimization";
+pub const DEEP_AUDIT: &str = "deep-audit";
+
+/// Skills `deep-audit` chains before correlating.
+pub const DEEP_AUDIT_CHAIN: &[&str] = &[SECURITY_AUDIT, BEST_PRACTICES, GAS_OPTIMIZATION];
+
+/// Runs one of the single-pass skills; `None` for unknown names and for
+/// `deep-audit`, which is assembled by the caller from [`DEEP_AUDIT_CHAIN`].
+pub fn run_skill(name: &str, ir: &ProgramIr, profile: Option<&CuProfile>) -> Option<Vec<Finding>> {
+    match name {
+        SECURITY_AUDIT => Some(security_audit(ir)),
+        BEST_PRACTICES => Some(best_practices(ir)),
+        GAS_OPTIMIZATION => Some(gas_optimization(ir, profile)),
+        _ => None,
+    }
+}
+
+/// Evaluates every `VULNERABILITY_RULES` detection rule.
+pub fn security_audit(ir: &ProgramIr) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for rule in VULNERABILITY_RULES {
+        // Built-in rules are compiled in, so one that does not parse is a bug
+        // in this build, not something to skip; `builtin_rules_are_valid`
+        // catches it before release.
+        let parsed = detection_rule::parse(rule.detection_rule)
+            .unwrap_or_else(|err| panic!("built-in rule {} does not parse: {}", rule.id, err));
+        for m in evaluate(&parsed, ir) {
+            findings.push(
+                Finding::new(
+                    SECURITY_AUDIT,
+                    rule.id,
+                    m.severity.unwrap_or(rule.severity),
+                    rule.description,
+                    m.subject,
+                    m.location,
+                )
+                .with_pattern_type(rule.pattern_type)
+                .with_message(rule.detection_hint),
+            );
+        }
+    }
+    findings
+}
+
+pub fn best_practices(ir: &ProgramIr) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for layout in analyze_layouts(ir) {
+        if layout.suggestions.contains(&LayoutSuggestion::DeriveInitSpace) {
+            findings.push(
+                Finding::new(
+                    BEST_PRACTICES,
+                    "missing-init-space",
+                    Severity::Info,
+                    "Account does not derive InitSpace",
+                    &layout.name,
+                    layout.location.clone(),
+                )
+                .with_message("Derive InitSpace and allocate 8 + T::INIT_SPACE instead of hand-computed space"),
+            );
+        }
+    }
+
+    for field in ir.context_fields() {
+        if matches!(field.wrapper.as_str(), "AccountInfo" | "UncheckedAccount") && !field.check_doc {
+            findings.push(
+                Finding::new(
+                    BEST_PRACTICES,
+                    "raw-account-info",
+                    Severity::Medium,
+                    "Untyped account without a CHECK justification",
+                    format!("{}.{}", field.context, field.name),
+                    field.location.clone(),
+                )
+                .with_message("Use a typed wrapper such as Account<'info, T> or document the manual checks with /// CHECK:"),
+            );
+        }
+    }
+
+    for handler in &ir.handlers {
+        if !handler.state_writes.is_empty() && !handler.emits_event {
+            findings.push(
+                Finding::new(
+                    BEST_PRACTICES,
+                    "missing-event",
+                    Severity::Info,
+                    "State-changing instruction emits no event",
+                    &handler.name,
+                    handler.location.clone(),
+                )
+                .with_message("emit! an event for critical state changes so indexers can follow them"),
+            );
+        }
+    }
+
+    if let (Some(program), None) = (&ir.program_module, &ir.security_txt) {
+        findings.push(
+            Finding::new(
+                BEST_PRACTICES,
+                "missing-security-txt",
+                Severity::Info,
+                "Program does not embed a security.txt",
+                "security_txt",
+                program.clone(),
+            )
+            .with_message("Embed contacts and a disclosure policy with solana_security_txt::security_txt! so researchers can reach the maintainers"),
+        );
+    }
+
+    findings
+}
+
+/// Layout findings, plus compute unit findings when a profile is available.
+pub fn gas_optimization(ir: &ProgramIr, profile: Option<&CuProfile>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for layout in analyze_layouts(ir) {
+        for suggestion in &layout.suggestions {
+            let (rule_id, severity, field) = match suggestion {
+                LayoutSuggestion::Reorder { .. } => ("layout-reorder", Severity::Info, None),
+                LayoutSuggestion::NarrowInteger { field, .. } => {
+                    ("layout-narrow-integer", Severity::Info, Some(field))
+                }
+                LayoutSuggestion::ZeroCopy { .. } => ("layout-zero-copy", Severity::Medium, None),
+                LayoutSuggestion::MissingMaxLen { field } => {
+                    ("layout-missing-max-len", Severity::Medium, Some(field))
+                }
+                LayoutSuggestion::DeriveInitSpace => continue,
+            };
+            let (subject, location) = match field.and_then(|f| layout.fields.iter().find(|l| &l.name == f)) {
+                Some(f) => (format!("{}.{}", layout.name, f.name), f.location.clone()),
+                None => (layout.name.clone(), layout.location.clone()),
+            };
+            findings.push(
+                Finding::new(
+                    GAS_OPTIMIZATION,
+                    rule_id,
+                    severity,
+                    format!("{} account layout can be tightened", layout.name),
+                    subject,
+                    location,
+                )
+                .with_message(suggestion.to_string()),
+            );
+        }
+    }
+
+    if let Some(profile) = profile {
+        for cu in optimization_findings(profile, None) {
+            let location = ir
+                .handlers
+                .iter()
+                .find(|h| h.name == cu.instruction)
+                .map(|h| h.location.clone())
+                .unwrap_or_else(|| Location { file: String::new(), line: 0, column: 0 });
+            findings.push(
+                Finding::new(
+                    GAS_OPTIMIZATION,
+                    "cu-budget",
+                    cu.severity,
+                    "Instruction compute unit consumption",
+                    cu.instruction,
+                    location,
+                )
+                .with_message(cu.message),
+            );
+        }
+    }
+
+    findings
+}
@@ -0,0 +1,161 @@
+use std::process::Command;
+
+use parity_analyzer::codemod::{apply, generate_patches, CodemodOptions, Fix};
+use parity_analyzer::ir::ProgramIr;
+use parity_analyzer::skills::security_audit;
+
+const VAULT: &str = r#"use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    /// CHECK: compared against vault.authority
+    pub authority: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+}
+
+pub fn withdraw(ctx: Context<Withdraw>, amount: u64, fee: u64) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    vault.withdrawn += amount;
+    vault.balance = vault.balance - (amount + fee) * 2;
+    Ok(())
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub balance: u64,
+    pub withdrawn: u64,
+}
+"#;
+
+/// Plain Rust, so the rewritten file compiles without Anchor.
+const MIXED: &str = r#"use std::time::Duration;
+
+#[derive(Debug)]
+pub enum ProgramError {
+    ArithmeticOverflow,
+}
+
+pub type Result<T> = std::result::Result<T, ProgramError>;
+
+pub struct Ledger {
+    pub balance: u64,
+    pub entries: u32,
+    pub rate: f64,
+    pub memo: String,
+    pub window: Duration,
+}
+
+pub fn settle(ledger: &mut Ledger, amount: u64, bonus: u64, suffix: &str) -> Result<u64> {
+    let total = amount + bonus;
+    ledger.balance -= total * 2;
+    ledger.entries += 1;
+    ledger.rate = ledger.rate * 1.5 + 0.25;
+    ledger.memo = ledger.memo.clone() + suffix;
+    ledger.window = ledger.window + Duration::from_secs(60);
+    let triple = |x: u64| x * 3;
+    let headroom = u64::MAX - triple(ledger.balance);
+    let total = String::from("settled ") + suffix;
+    ledger.memo = total;
+    Ok(headroom)
+}
+
+pub fn preview(ledger: &Ledger, amount: u64) -> u64 {
+    ledger.balance + amount
+}
+"#;
+
+fn sources() -> Vec<(String, String)> {
+    vec![("vault.rs".to_string(), VAULT.to_string())]
+}
+
+/// Type-checks `source` as a library crate with the toolchain running the tests.
+fn compile(name: &str, source: &str) -> Result<(), String> {
+    let dir = std::env::temp_dir().join(format!("parity-codemod-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let lib = dir.join("lib.rs");
+    std::fs::write(&lib, source).unwrap();
+    let output = Command::new(std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string()))
+        .args(["--edition", "2021", "--crate-type", "lib", "--emit", "metadata", "--out-dir"])
+        .arg(&dir)
+        .arg(&lib)
+        .output()
+        .unwrap();
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(String::from_utf8_lossy(&output.stderr).into_owned()),
+    }
+}
+
+#[test]
+fn findings_become_parseable_patches() {
+    let ir = ProgramIr::from_sources(&sources()).unwrap();
+    let findings = security_audit(&ir);
+    let patches = generate_patches(&ir, &findings, &sources(), &CodemodOptions::default()).unwrap();
+    assert_eq!(patches.len(), 1);
+
+    let patch = &patches[0];
+    let patched = &patch.patched;
+    assert!(patched.contains("#[account(mut, has_one = authority)]"));
+    assert!(patched.contains("pub authority: Signer<'info>,"));
+    assert!(patched.contains("pub token_program: Program<'info, Token>,"));
+    assert!(patched.contains(
+        "vault.withdrawn = vault.withdrawn.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;"
+    ));
+    assert!(!patched.contains(" + "));
+    assert!(!patched.contains(" * "));
+
+    let rewritten = ProgramIr::from_sources(&[("vault.rs".to_string(), patched.clone())]).unwrap();
+    assert!(security_audit(&rewritten).is_empty());
+
+    let diff = patch.unified_diff();
+    assert!(diff.starts_with("--- a/vault.rs\n+++ b/vault.rs\n"));
+    assert!(diff.contains("-    pub token_program: AccountInfo<'info>,"));
+}
+
+#[test]
+fn constraints_are_not_duplicated() {
+    let fix = Fix::AddConstraint {
+        context: "Withdraw".to_string(),
+        field: "vault".to_string(),
+        constraint: "has_one = authority".to_string(),
+    };
+    let ir = ProgramIr::from_sources(&sources()).unwrap();
+    let options = CodemodOptions::default();
+    let once = apply(&ir, "vault.rs", VAULT, std::slice::from_ref(&fix), &options).unwrap();
+    let twice = apply(&ir, "vault.rs", &once, &[fix], &options).unwrap();
+    assert_eq!(once, twice);
+}
+
+#[test]
+fn only_integer_arithmetic_is_made_checked() {
+    let ir = ProgramIr::from_sources(&[("ledger.rs".to_string(), MIXED.to_string())]).unwrap();
+    let options = CodemodOptions::default();
+    let fixes = ["settle", "preview"].map(|handler| Fix::CheckedArithmetic { handler: handler.to_string() });
+    let patched = apply(&ir, "ledger.rs", MIXED, &fixes, &options).unwrap();
+    compile("mixed", MIXED).unwrap();
+    compile("patched", &patched).unwrap();
+
+    let overflow = ".ok_or(ProgramError::ArithmeticOverflow)?";
+    for rewritten in [
+        format!("let total = amount.checked_add(bonus){};", overflow),
+        format!("ledger.balance = ledger.balance.checked_sub(total.checked_mul(2){}){};", overflow, overflow),
+        format!("ledger.entries = ledger.entries.checked_add(1){};", overflow),
+    ] {
+        assert!(patched.contains(&rewritten), "missing `{}` in\n{}", rewritten, patched);
+    }
+    for kept in [
+        "ledger.rate = ledger.rate * 1.5 + 0.25;",
+        "ledger.memo = ledger.memo.clone() + suffix;",
+        "ledger.window = ledger.window + Duration::from_secs(60);",
+        "let triple = |x: u64| x * 3;",
+        "let headroom = u64::MAX - triple(ledger.balance);",
+        "let total = String::from(\"settled \") + suffix;",
+        "    ledger.balance + amount\n",
+    ] {
+        assert!(patched.contains(kept), "rewrote `{}` in\n{}", kept, patched);
+    }
+}
@@ -0,0 +1,79 @@
+use parity::state::Severity;
+use parity_analyzer::correlation::correlate;
+use parity_analyzer::ir::ProgramIr;
+use parity_analyzer::skills::{best_practices, security_audit, BEST_PRACTICES, SECURITY_AUDIT};
+
+const VAULT: &str = r#"
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub token_program: AccountInfo<'info>,
+}
+
+pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    token::transfer(ctx.accounts.transfer_ctx(), amount)?;
+    ctx.accounts.vault.balance = 0;
+    Ok(())
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Vault {
+    pub balance: u64,
+}
+"#;
+
+fn vault_ir() -> ProgramIr {
+    ProgramIr::from_sources(&[("vault.rs".to_string(), VAULT.to_string())]).unwrap()
+}
+
+#[test]
+fn cpi_followed_by_write_becomes_reentrancy() {
+    let ir = vault_ir();
+    let mut findings = security_audit(&ir);
+    findings.extend(best_practices(&ir));
+
+    let graph = correlate(&ir, findings);
+    assert!(graph.nodes[0].compound);
+    let top = graph
+        .nodes
+        .iter()
+        .find(|n| n.finding.rule_id == "cpi-reentrancy")
+        .expect("reentrancy finding");
+    let cpi = graph.nodes.iter().find(|n| n.finding.rule_id == "insecure-cpi").unwrap();
+    assert!(top.risk > cpi.risk);
+    assert_eq!(top.finding.subject, "withdraw");
+    assert_eq!(top.finding.severity, Severity::Critical);
+
+    let parts: Vec<&str> = graph.constituents(top.id).map(|n| n.finding.rule_id.as_str()).collect();
+    assert_eq!(parts, ["insecure-cpi"]);
+    assert!(top.finding.skills.iter().any(|s| s == SECURITY_AUDIT));
+}
+
+#[test]
+fn duplicate_findings_merge_provenance() {
+    let ir = vault_ir();
+    let mut findings = security_audit(&ir);
+    let mut again = security_audit(&ir);
+    for f in &mut again {
+        f.skills = vec![BEST_PRACTICES.to_string()];
+    }
+    let count = findings.len();
+    findings.extend(again);
+
+    let graph = correlate(&ir, findings);
+    let singles = graph.nodes.iter().filter(|n| !n.compound).count();
+    assert_eq!(singles, count);
+    assert!(graph
+        .nodes
+        .iter()
+        .filter(|n| !n.compound)
+        .all(|n| n.finding.skills.len() == 2));
+
+    for pair in graph.nodes.windows(2) {
+        assert!(pair[0].risk >= pair[1].risk);
+    }
+}
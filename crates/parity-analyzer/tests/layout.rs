@@ -0,0 +1,96 @@
+use std::path::Path;
+
+use anchor_lang::Space;
+use parity::state::{
+    AnalysisCommitment, AnalysisReport, AuditorAccount, ContextPattern, ProgramEntry, ProgramMetadata, Registry,
+    SkillEntry, VerificationBadge,
+};
+use parity_analyzer::ir::ProgramIr;
+use parity_analyzer::layout::{analyze_layouts, AccountLayout, LayoutSuggestion};
+
+fn parity_layouts() -> Vec<AccountLayout> {
+    let state = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../programs/parity/src/state.rs");
+    let ir = ProgramIr::from_path(&state).expect("state.rs parses");
+    analyze_layouts(&ir)
+}
+
+fn layout<'a>(layouts: &'a [AccountLayout], name: &str) -> &'a AccountLayout {
+    layouts.iter().find(|l| l.name == name).expect(name)
+}
+
+#[test]
+fn context_pattern_space_matches_init_space() {
+    let layouts = parity_layouts();
+    let pattern = layout(&layouts, "ContextPattern");
+
+    assert_eq!(pattern.space, 8 + ContextPattern::INIT_SPACE);
+    assert_eq!(pattern.dynamic_bytes, 4 + 64 + 4 + 256 + 4 + 512);
+    assert!(pattern.padding.is_none());
+}
+
+#[test]
+fn every_parity_account_space_matches_init_space() {
+    let layouts = parity_layouts();
+    let spaces = [
+        ("Registry", Registry::INIT_SPACE),
+        ("ProgramEntry", ProgramEntry::INIT_SPACE),
+        ("AnalysisReport", AnalysisReport::INIT_SPACE),
+        ("ProgramMetadata", ProgramMetadata::INIT_SPACE),
+        ("AnalysisCommitment", AnalysisCommitment::INIT_SPACE),
+        ("SkillEntry", SkillEntry::INIT_SPACE),
+        ("AuditorAccount", AuditorAccount::INIT_SPACE),
+        ("VerificationBadge", VerificationBadge::INIT_SPACE),
+        ("ContextPattern", ContextPattern::INIT_SPACE),
+    ];
+    for (name, init_space) in spaces {
+        let account = layout(&layouts, name);
+        assert_eq!(account.space, 8 + init_space, "{}", name);
+        assert!(!account.lower_bound, "{}", name);
+    }
+}
+
+#[test]
+fn types_it_cannot_size_make_the_space_a_lower_bound() {
+    let source = r#"
+        #[account]
+        #[derive(InitSpace)]
+        pub struct Vault {
+            pub authority: Pubkey,
+            pub limits: (u64, u64),
+            pub mint: spl::Mint,
+            pub bump: u8,
+        }
+    "#;
+    let ir = ProgramIr::from_sources(&[("lib.rs".to_string(), source.to_string())]).unwrap();
+    let layouts = analyze_layouts(&ir);
+    let vault = layout(&layouts, "Vault");
+
+    assert_eq!(vault.space, 8 + 32 + 1);
+    assert!(vault.lower_bound);
+    let unknown: Vec<&str> = vault.fields.iter().filter(|f| f.size.unknown).map(|f| f.name.as_str()).collect();
+    assert_eq!(unknown, ["limits", "mint"]);
+}
+
+#[test]
+fn analysis_report_sizes_nested_max_len() {
+    let layouts = parity_layouts();
+    let report = layout(&layouts, "AnalysisReport");
+
+    let skills = report.fields.iter().find(|f| f.name == "skills_used").unwrap();
+    assert_eq!(skills.size.bytes, 4 + 8 * (4 + 64));
+
+    let counts = report.fields.iter().find(|f| f.name == "findings_count").unwrap();
+    assert_eq!(counts.size.bytes, 12);
+    assert!(report.rent_exempt_lamports > 0);
+}
+
+#[test]
+fn auditor_average_score_is_narrowed() {
+    let layouts = parity_layouts();
+    let auditor = layout(&layouts, "AuditorAccount");
+
+    assert!(auditor.suggestions.iter().any(|s| matches!(
+        s,
+        LayoutSuggestion::NarrowInteger { field, saved: 7, .. } if field == "average_score"
+    )));
+}
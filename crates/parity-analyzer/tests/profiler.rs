@@ -0,0 +1,293 @@
+use parity::state::Severity;
+use parity_analyzer::profiler::{
+    cases_from_idl, optimization_findings, ArgStrategy, CuProfile, CuSample, Idl, DEFAULT_INSTRUCTION_CU_LIMIT,
+};
+use solana_program::pubkey::Pubkey;
+
+const IDL: &str = r#"{
+  "instructions": [
+    {
+      "name": "open_vault",
+      "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+      "accounts": [
+        { "name": "authority", "writable": true, "signer": true },
+        {
+          "name": "vault",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              { "kind": "const", "value": [118, 97, 117, 108, 116] },
+              { "kind": "account", "path": "authority" },
+              { "kind": "arg", "path": "label" }
+            ]
+          }
+        },
+        {
+          "name": "system",
+          "accounts": [
+            { "name": "system_program", "address": "11111111111111111111111111111111" }
+          ]
+        }
+      ],
+      "args": [
+        { "name": "amount", "type": "u64" },
+        { "name": "label", "type": "string" },
+        { "name": "limit", "type": { "option": "i16" } },
+        { "name": "kind", "type": { "defined": { "name": "VaultKind" } } }
+      ]
+    }
+  ],
+  "types": [
+    {
+      "name": "VaultKind",
+      "type": {
+        "kind": "enum",
+        "variants": [{ "name": "Basic" }, { "name": "Timelocked", "fields": ["u8"] }]
+      }
+    }
+  ]
+}"#;
+
+fn sample(instruction: &str, path: &str, units: u64) -> CuSample {
+    CuSample {
+        instruction: instruction.to_string(),
+        case: "case".to_string(),
+        path: path.to_string(),
+        units,
+    }
+}
+
+fn profile(samples: &[(&str, &str, u64)]) -> CuProfile {
+    let mut profile = CuProfile::default();
+    for (instruction, path, units) in samples {
+        profile.record(sample(instruction, path, *units));
+    }
+    profile
+}
+
+#[test]
+fn cases_from_idl_encodes_each_argument_strategy() {
+    let idl = Idl::from_json(IDL).unwrap();
+    let program_id = Pubkey::new_unique();
+    let signer = Pubkey::new_unique();
+    let cases = cases_from_idl(&idl, &program_id, &signer, &[ArgStrategy::Zero, ArgStrategy::Max]);
+
+    assert_eq!(cases.len(), 2);
+    assert_eq!((cases[0].instruction.as_str(), cases[0].label.as_str()), ("open_vault", "zero-args"));
+    assert_eq!(cases[1].label, "max-args");
+
+    let mut zero = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    zero.extend([0; 8]); // amount
+    zero.extend([0; 4]); // empty label
+    zero.push(0); // None
+    zero.push(0); // Basic
+    assert_eq!(cases[0].ix.data, zero);
+
+    let mut max = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    max.extend([0xff; 8]);
+    max.extend(32u32.to_le_bytes());
+    max.extend([b'a'; 32]);
+    max.extend([1, 0xff, 0x7f]); // Some(i16::MAX)
+    max.extend([1, 0xff]); // Timelocked(u8::MAX)
+    assert_eq!(cases[1].ix.data, max);
+}
+
+#[test]
+fn cases_from_idl_resolves_signers_pdas_and_fixed_addresses() {
+    let idl = Idl::from_json(IDL).unwrap();
+    let program_id = Pubkey::new_unique();
+    let signer = Pubkey::new_unique();
+    let cases = cases_from_idl(&idl, &program_id, &signer, &[ArgStrategy::Max]);
+    let accounts = &cases[0].ix.accounts;
+
+    assert_eq!(accounts.len(), 3, "nested account groups are flattened");
+    assert_eq!(accounts[0].pubkey, signer);
+    assert!(accounts[0].is_signer && accounts[0].is_writable);
+
+    // String seeds use the raw bytes, not the length-prefixed encoding.
+    let label = [b'a'; 32];
+    let vault = Pubkey::find_program_address(&[b"vault", signer.as_ref(), &label], &program_id).0;
+    assert_eq!(accounts[1].pubkey, vault);
+    assert!(!accounts[1].is_signer && accounts[1].is_writable);
+
+    assert_eq!(accounts[2].pubkey, solana_program::system_program::ID);
+    assert!(!accounts[2].is_writable);
+}
+
+#[test]
+fn profile_statistics_group_samples_by_instruction_and_path() {
+    let profile = profile(&[
+        ("deposit", "ok", 1_000),
+        ("deposit", "ok", 3_000),
+        ("deposit", "ok via 11111111111111111111111111111111", 9_000),
+        ("deposit", "error:6000", 500),
+    ]);
+
+    let deposit = profile.by_instruction()["deposit"];
+    assert_eq!((deposit.samples, deposit.min, deposit.max), (4, 500, 9_000));
+    assert_eq!(deposit.mean, 3_375.0);
+
+    let paths = profile.by_path();
+    assert_eq!(paths.len(), 3);
+    let ok = paths[&("deposit".to_string(), "ok".to_string())];
+    assert_eq!((ok.samples, ok.min, ok.max, ok.mean), (2, 1_000, 3_000, 2_000.0));
+
+    assert!(profile.samples[2].succeeded());
+    assert!(!profile.samples[3].succeeded());
+}
+
+#[test]
+fn compute_units_is_the_costliest_successful_path() {
+    let profile = profile(&[("deposit", "ok", 4_000), ("withdraw", "error:6001", 90_000)]);
+    assert_eq!(profile.compute_units(), 4_000);
+
+    let failing = self::profile(&[("withdraw", "error:6001", 90_000), ("withdraw", "error:6002", 1_000)]);
+    assert_eq!(failing.compute_units(), 90_000, "falls back to failing paths");
+    assert_eq!(CuProfile::default().compute_units(), 0);
+}
+
+#[test]
+fn diff_compares_worst_cases_across_builds() {
+    let baseline = profile(&[("deposit", "ok", 10_000), ("deposit", "ok", 8_000), ("close", "ok", 2_000)]);
+    let candidate = profile(&[("deposit", "ok", 10_500), ("withdraw", "ok", 5_000)]);
+
+    let diff = baseline.diff(&candidate);
+    let rows: Vec<(&str, Option<u64>, Option<u64>)> =
+        diff.iter().map(|d| (d.instruction.as_str(), d.before, d.after)).collect();
+    assert_eq!(
+        rows,
+        vec![("close", Some(2_000), None), ("deposit", Some(10_000), Some(10_500)), ("withdraw", None, Some(5_000))]
+    );
+
+    assert_eq!(diff[1].change(), 500);
+    assert_eq!(diff[1].change_percent(), Some(5.0));
+    assert!(!diff[1].is_regression());
+    assert_eq!(diff[0].change(), -2_000);
+    assert_eq!(diff[0].change_percent(), None, "paths missing from a build have no percentage");
+    assert!(!diff[2].is_regression());
+
+    let slower = profile(&[("deposit", "ok", 11_500)]);
+    assert!(baseline.diff(&slower)[1].is_regression());
+}
+
+#[test]
+fn optimization_findings_rank_successful_paths_by_cost() {
+    let profile = profile(&[
+        ("cheap", "ok", 1_000),
+        ("heavy", "ok", DEFAULT_INSTRUCTION_CU_LIMIT),
+        ("mid", "ok via 11111111111111111111111111111111", DEFAULT_INSTRUCTION_CU_LIMIT / 2),
+        ("small", "ok", 30_000),
+        ("failing", "error:6000", 190_000),
+    ]);
+
+    let findings = optimization_findings(&profile, None);
+    let ranked: Vec<(&str, Severity)> = findings.iter().map(|f| (f.instruction.as_str(), f.severity)).collect();
+    assert_eq!(
+        ranked,
+        vec![("heavy", Severity::Critical), ("mid", Severity::High), ("small", Severity::Info), ("cheap", Severity::Info)]
+    );
+    assert_eq!(findings[0].message, "heavy consumes up to 200000 CU (100% of the default 200000 CU instruction budget)");
+    assert!(findings[1].message.starts_with("mid (ok via 11111111111111111111111111111111) consumes up to 100000 CU"));
+}
+
+#[test]
+fn optimization_findings_escalate_regressions_against_a_baseline() {
+    let baseline = profile(&[("deposit", "ok", 40_000), ("withdraw", "ok", 40_000)]);
+    let candidate = profile(&[("deposit", "ok", 60_000), ("withdraw", "ok", 41_000)]);
+
+    let findings = optimization_findings(&candidate, Some(&baseline));
+    let deposit = findings.iter().find(|f| f.instruction == "deposit").unwrap();
+    assert_eq!(deposit.severity, Severity::High, "Medium raised one level");
+    assert!(deposit.message.ends_with("; regressed +50.0% against the baseline build"));
+
+    let withdraw = findings.iter().find(|f| f.instruction == "withdraw").unwrap();
+    assert_eq!(withdraw.severity, Severity::Info);
+    assert!(!withdraw.message.contains("regressed"));
+}
+
+#[cfg(feature = "profiler")]
+mod harness {
+    use super::*;
+    use parity_analyzer::profiler::{code_path, ProfileCase, Profiler};
+    use solana_program::instruction::{Instruction, InstructionError};
+    use solana_program_test::programs::spl_programs;
+    use solana_sdk::account::ReadableAccount;
+    use solana_sdk::rent::Rent;
+    use solana_sdk::transaction::TransactionError;
+
+    const MEMO_PROGRAM: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+
+    /// The SPL memo program bundled with solana-program-test, as a real SBF
+    /// build to profile.
+    fn memo_elf() -> Vec<u8> {
+        let memo: Pubkey = MEMO_PROGRAM.parse().unwrap();
+        let (_, account) = spl_programs(&Rent::default()).into_iter().find(|(id, _)| *id == memo).unwrap();
+        account.data().to_vec()
+    }
+
+    fn memo_case(program_id: &Pubkey, label: &str, memo: &[u8]) -> ProfileCase {
+        ProfileCase {
+            instruction: "memo".to_string(),
+            label: label.to_string(),
+            ix: Instruction { program_id: *program_id, accounts: vec![], data: memo.to_vec() },
+        }
+    }
+
+    #[test]
+    fn code_path_names_the_outcome_and_invoked_programs() {
+        let logs: Vec<String> = [
+            "Program Vau1t11111111111111111111111111111111111 invoke [1]",
+            "Program log: Instruction: Deposit",
+            "Program 11111111111111111111111111111111 invoke [2]",
+            "Program 11111111111111111111111111111111 success",
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [2]",
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA success",
+            "Program Vau1t11111111111111111111111111111111111 success",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        assert_eq!(code_path(&Ok(()), &logs[..2]), "ok");
+        assert_eq!(
+            code_path(&Ok(()), &logs),
+            "ok via 11111111111111111111111111111111,TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        );
+
+        let custom = Err(TransactionError::InstructionError(0, InstructionError::Custom(6003)));
+        assert_eq!(code_path(&custom, &logs[..4]), "error:6003 via 11111111111111111111111111111111");
+        let builtin = Err(TransactionError::InstructionError(0, InstructionError::InvalidInstructionData));
+        assert_eq!(code_path(&builtin, &[]), "error:InstructionError(0, InvalidInstructionData)");
+    }
+
+    #[tokio::test]
+    async fn profiler_measures_each_path_of_a_deployed_program() {
+        let program_id = Pubkey::new_unique();
+        let profile = Profiler::new(program_id)
+            .passes(2)
+            .profile(&memo_elf(), |_| {
+                vec![
+                    memo_case(&program_id, "short", b"hi"),
+                    memo_case(&program_id, "long", &[b'a'; 512]),
+                    memo_case(&program_id, "not-utf8", &[0xff, 0xfe]),
+                ]
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(profile.samples.len(), 6, "every case runs once per pass");
+        let paths = profile.by_path();
+        let ok = paths[&("memo".to_string(), "ok".to_string())];
+        let failed = paths[&("memo".to_string(), "error:InstructionError(0, InvalidInstructionData)".to_string())];
+        assert_eq!((ok.samples, failed.samples), (4, 2));
+
+        let units = |label: &str| profile.samples.iter().find(|s| s.case == label).unwrap().units;
+        assert!(units("short") > 0);
+        assert!(units("long") > units("short"), "logging a longer memo costs more");
+        assert_eq!(profile.compute_units(), units("long"));
+
+        let findings = optimization_findings(&profile, None);
+        assert_eq!(findings.len(), 1, "failing paths are not reported");
+        assert_eq!(findings[0].units, units("long"));
+    }
+}
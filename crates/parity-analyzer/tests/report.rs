@@ -0,0 +1,104 @@
+use parity::scoring::ScoringModel;
+use parity_analyzer::finding::findings_root;
+use parity_analyzer::ir::ProgramIr;
+use parity_analyzer::report::{hex, Report, ReportError};
+use parity_analyzer::skills::security_audit;
+
+const VAULT: &str = r#"use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    /// CHECK: compared against vault.authority
+    pub authority: AccountInfo<'info>,
+}
+
+pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    ctx.accounts.vault.balance -= amount;
+    Ok(())
+}
+
+#[account]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub balance: u64,
+}
+"#;
+
+fn report() -> (ProgramIr, Report) {
+    let ir = ProgramIr::from_sources(&[("vault.rs".to_string(), VAULT.to_string())]).unwrap();
+    let findings = security_audit(&ir);
+    assert!(!findings.is_empty());
+    let report = Report::new("vault", vec!["security-audit".to_string()], findings, &ScoringModel::DEFAULT);
+    (ir, report)
+}
+
+#[test]
+fn json_round_trips_and_matches_the_onchain_hash() {
+    let (ir, mut report) = report();
+    report.attach_fixes(&ir);
+    assert!(report.findings.iter().any(|f| !f.fixes.is_empty()));
+
+    let parsed = Report::from_json(&report.to_json()).unwrap();
+    assert_eq!(parsed.findings.len(), report.findings.len());
+    assert_eq!(parsed.findings_hash(), findings_root(&report.findings));
+    assert_eq!(parsed.findings_count().total as usize, report.findings.len());
+    assert_eq!(parsed.score, report.score);
+
+    let mut reversed = report.findings.clone();
+    reversed.reverse();
+    let reordered = Report::new("vault", report.skills.clone(), reversed, &ScoringModel::DEFAULT);
+    assert_eq!(reordered.to_json(), Report::new("vault", report.skills.clone(), report.findings.clone(), &ScoringModel::DEFAULT).to_json());
+}
+
+#[test]
+fn tampered_json_is_rejected() {
+    let (_, mut report) = report();
+    report.findings[0].message.push_str(" (edited)");
+    assert!(matches!(Report::from_json(&report.to_json()), Err(ReportError::HashMismatch)));
+
+    report.findings_hash = hex(&report.findings_hash());
+    assert!(Report::from_json(&report.to_json()).is_ok());
+}
+
+#[test]
+fn duplicate_findings_are_counted_once() {
+    let (_, report) = report();
+    let mut doubled = report.findings.clone();
+    doubled.extend(report.findings.iter().cloned());
+    let deduped = Report::new("vault", report.skills.clone(), doubled, &ScoringModel::DEFAULT);
+    assert_eq!(deduped.to_json(), report.to_json());
+    assert_eq!(deduped.findings_count().total as usize, deduped.findings.len());
+
+    let mut tampered = report.clone();
+    tampered.findings.push(report.findings[0].clone());
+    tampered.findings_hash = hex(&tampered.findings_hash());
+    assert!(matches!(Report::from_json(&tampered.to_json()), Err(ReportError::DuplicateFinding)));
+}
+
+#[test]
+fn sarif_has_one_result_per_finding() {
+    let (_, report) = report();
+    let sarif = report.to_sarif();
+    assert_eq!(sarif["version"], "2.1.0");
+    let run = &sarif["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "parity");
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), report.findings.len());
+    for result in results {
+        let index = result["ruleIndex"].as_u64().unwrap() as usize;
+        assert_eq!(run["tool"]["driver"]["rules"][index]["id"], result["ruleId"]);
+        assert_eq!(result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], "vault.rs");
+    }
+}
+
+#[test]
+fn markdown_lists_every_finding() {
+    let (_, report) = report();
+    let markdown = report.to_markdown();
+    assert!(markdown.contains(&report.findings_hash));
+    for finding in &report.findings {
+        assert!(markdown.contains(&format!("`{}`", finding.rule_id)));
+    }
+}
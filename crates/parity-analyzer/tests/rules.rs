@@ -0,0 +1,123 @@
+use std::path::Path;
+
+use parity::context_engine::VULNERABILITY_RULES;
+use parity::detection_rule::{parse, Expr, RuleErrorKind, Target, MAX_RULE_DEPTH};
+use parity_analyzer::ir::ProgramIr;
+use parity_analyzer::rules::evaluate_source;
+
+fn parity_ir() -> ProgramIr {
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../programs/parity/src");
+    ProgramIr::from_path(&src).expect("parity sources parse")
+}
+
+#[test]
+fn builtin_rules_are_valid() {
+    for rule in VULNERABILITY_RULES {
+        let parsed = parse(rule.detection_rule);
+        assert!(parsed.is_ok(), "{}: {}", rule.id, parsed.unwrap_err());
+    }
+}
+
+#[test]
+fn errors_point_at_the_offending_token() {
+    let err = parse("rule v1\nmatch field\nwhere wrapper > 3").unwrap_err();
+    assert_eq!((err.line, err.column), (3, 15));
+    assert!(matches!(err.kind, RuleErrorKind::InvalidOperator { .. }));
+
+    let err = parse("rule v2 match field").unwrap_err();
+    assert_eq!((err.line, err.column), (1, 6));
+
+    let err = parse("rule v1 match handler where has(seeds)").unwrap_err();
+    assert_eq!(err.kind, RuleErrorKind::HasOnlyOnFields);
+
+    let err = parse("rule v1 match field where nme == \"x\"").unwrap_err();
+    assert_eq!(err.to_string(), "line 1, column 27: field has no attribute \"nme\"");
+}
+
+#[test]
+fn conditions_nested_past_the_limit_are_rejected() {
+    let nots = |n: usize| format!("rule v1 match field where {}signer", "not ".repeat(n));
+    assert!(parse(&nots(MAX_RULE_DEPTH)).is_ok());
+    let err = parse(&nots(MAX_RULE_DEPTH + 1)).unwrap_err();
+    assert_eq!(err.kind, RuleErrorKind::TooDeep);
+    assert_eq!(err.column as usize, 27 + 4 * MAX_RULE_DEPTH, "points at the first not past the limit");
+
+    let parens = |n: usize| format!("rule v1 match field where {}signer{}", "(".repeat(n), ")".repeat(n));
+    assert!(parse(&parens(MAX_RULE_DEPTH)).is_ok());
+    assert_eq!(parse(&parens(MAX_RULE_DEPTH + 1)).unwrap_err().kind, RuleErrorKind::TooDeep);
+
+    // A chain is one level above its deepest operand.
+    let chained = |n: usize| format!("rule v1 match field where {}signer and mut", "not ".repeat(n));
+    assert!(parse(&chained(MAX_RULE_DEPTH - 1)).is_ok());
+    assert_eq!(parse(&chained(MAX_RULE_DEPTH)).unwrap_err().kind, RuleErrorKind::TooDeep);
+    let mixed = format!("rule v1 match field where signer or {}mut", "not ".repeat(MAX_RULE_DEPTH));
+    assert_eq!(parse(&mixed).unwrap_err().kind, RuleErrorKind::TooDeep);
+
+    // A rule of nothing but openers fails cleanly instead of exhausting the stack.
+    let err = parse(&format!("rule v1 match field where {}", "(".repeat(10_000))).unwrap_err();
+    assert_eq!(err.to_string(), format!("line 1, column {}: condition nests more than 12 levels deep", 27 + 12));
+}
+
+#[test]
+fn long_flat_chains_are_one_level() {
+    let chain = |op: &str, n: usize| format!("rule v1 match field where signer{}", format!(" {} mut", op).repeat(n));
+    for op in ["and", "or"] {
+        let rule = parse(&chain(op, 100)).unwrap();
+        let operands = match rule.condition {
+            Some(Expr::And(operands) | Expr::Or(operands)) => operands,
+            other => panic!("expected one chain, got {:?}", other),
+        };
+        assert_eq!(operands.len(), 101);
+    }
+
+    let ir = parity_ir();
+    let flat = format!("rule v1 match field where name == \"auditor\"{}", " and signer".repeat(MAX_RULE_DEPTH + 1));
+    let matches = evaluate_source(&flat, &ir).unwrap();
+    assert!(matches.iter().any(|m| m.subject == "SubmitAnalysis.auditor"));
+}
+
+#[test]
+fn not_binds_tightest_and_or_loosest() {
+    let flag = |name: &str| Expr::Flag(name.to_string());
+    let not = |expr: Expr| Expr::Not(Box::new(expr));
+
+    let rule = parse("rule v1 match field where signer or not mut and init or (signer and mut)").unwrap();
+    let expected = Expr::Or(vec![
+        flag("signer"),
+        Expr::And(vec![not(flag("mut")), flag("init")]),
+        Expr::And(vec![flag("signer"), flag("mut")]),
+    ]);
+    assert_eq!(rule.condition, Some(expected));
+
+    let rule = parse("rule v1 match field where not (signer or mut) and not not init").unwrap();
+    let expected = Expr::And(vec![not(Expr::Or(vec![flag("signer"), flag("mut")])), not(not(flag("init")))]);
+    assert_eq!(rule.condition, Some(expected));
+
+    let err = parse("rule v1 match field where (signer and mut").unwrap_err();
+    assert_eq!(err.to_string(), "line 1, column 42: expected ), found end of rule");
+}
+
+#[test]
+fn severity_override_is_parsed() {
+    let rule = parse("rule v1 match account where space > 900 severity info").unwrap();
+    assert_eq!(rule.target, Target::Account);
+    assert!(rule.severity.is_some());
+}
+
+#[test]
+fn rules_evaluate_against_parity_sources() {
+    let ir = parity_ir();
+
+    let matches = evaluate_source(
+        "rule v1 match field where name == \"auditor\" and signer",
+        &ir,
+    )
+    .unwrap();
+    assert!(matches.iter().any(|m| m.subject == "SubmitAnalysis.auditor"));
+
+    let matches = evaluate_source("rule v1 match account where space > 900", &ir).unwrap();
+    assert!(matches.iter().any(|m| m.subject == "ContextPattern"));
+
+    let matches = evaluate_source("rule v1 match handler where cpi_calls > 0", &ir).unwrap();
+    assert!(matches.is_empty());
+}
@@ -0,0 +1,124 @@
+use parity_analyzer::ir::ProgramIr;
+use parity_analyzer::security_txt::{ContactKind, SecurityTxt, SecurityTxtError, BEGIN_MARKER, END_MARKER};
+use parity_analyzer::skills::best_practices;
+
+const REQUIRED: &[(&str, &str)] = &[
+    ("name", "Vault"),
+    ("project_url", "https://vault.example"),
+    ("contacts", "email:security@vault.example, discord:vault#1234"),
+    ("policy", "https://vault.example/SECURITY.md"),
+];
+
+/// An ELF-looking blob with the section between unrelated bytes, the way the
+/// linker places it.
+fn elf(fields: &[(&str, &str)]) -> Vec<u8> {
+    let mut bytes = b"\x7fELF\x02\x01\x01".to_vec();
+    bytes.extend_from_slice(&[0xAA; 64]);
+    bytes.extend_from_slice(BEGIN_MARKER);
+    for (key, value) in fields {
+        bytes.extend_from_slice(key.as_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(value.as_bytes());
+        bytes.push(0);
+    }
+    bytes.extend_from_slice(END_MARKER);
+    bytes.extend_from_slice(&[0x55; 32]);
+    bytes
+}
+
+fn with(extra: &[(&'static str, &'static str)]) -> Vec<(&'static str, &'static str)> {
+    REQUIRED.iter().chain(extra).copied().collect()
+}
+
+#[test]
+fn parses_every_field() {
+    let fields = with(&[
+        ("preferred_languages", "en,de"),
+        ("source_code", "https://github.com/vault/vault"),
+        ("source_release", "v1.2.0"),
+        ("source_revision", "0123456789abcdef0123456789abcdef01234567"),
+        ("auditors", "Neodyme, OtterSec"),
+        ("expiry", "2030-01-01"),
+    ]);
+    let txt = SecurityTxt::from_elf(&elf(&fields)).unwrap();
+
+    assert_eq!(txt.name, "Vault");
+    assert_eq!(txt.policy, "https://vault.example/SECURITY.md");
+    assert_eq!(txt.contacts.len(), 2);
+    assert_eq!(txt.contacts[1].kind, ContactKind::Discord);
+    assert_eq!(txt.primary_contact().unwrap().to_string(), "email:security@vault.example");
+    assert_eq!(txt.preferred_languages, ["en", "de"]);
+    assert_eq!(txt.source_code.as_deref(), Some("https://github.com/vault/vault"));
+    assert_eq!(txt.source_release.as_deref(), Some("v1.2.0"));
+    assert_eq!(txt.source_revision.as_deref(), Some("0123456789abcdef0123456789abcdef01234567"));
+    assert_eq!(txt.auditors, ["Neodyme", "OtterSec"]);
+    assert_eq!(txt.encryption, None);
+}
+
+#[test]
+fn rejects_missing_and_invalid_sections() {
+    assert!(matches!(SecurityTxt::from_elf(b"not an elf"), Err(SecurityTxtError::NotElf)));
+    assert!(matches!(SecurityTxt::from_elf(&[b"\x7fELF".as_slice(), &[0; 64]].concat()), Err(SecurityTxtError::Missing)));
+
+    let mut truncated = elf(REQUIRED);
+    truncated.truncate(truncated.len() - 40);
+    assert!(matches!(SecurityTxt::from_elf(&truncated), Err(SecurityTxtError::Unterminated)));
+
+    let missing = SecurityTxt::from_elf(&elf(&REQUIRED[..3]));
+    assert!(matches!(missing, Err(SecurityTxtError::MissingField("policy"))));
+
+    let unknown = SecurityTxt::from_elf(&elf(&with(&[("bounty", "yes")])));
+    assert!(matches!(unknown, Err(SecurityTxtError::UnknownField(key)) if key == "bounty"));
+
+    let repeated = SecurityTxt::from_elf(&elf(&with(&[("name", "Other")])));
+    assert!(matches!(repeated, Err(SecurityTxtError::DuplicateField(key)) if key == "name"));
+
+    let empty = SecurityTxt::from_elf(&elf(&with(&[("auditors", " ")])));
+    assert!(matches!(empty, Err(SecurityTxtError::EmptyField("auditors"))));
+}
+
+#[test]
+fn contacts_need_a_known_kind_and_a_value() {
+    for contacts in ["security@vault.example", "pager:123", "email:", " , "] {
+        let mut fields = REQUIRED.to_vec();
+        fields[2].1 = contacts;
+        let result = SecurityTxt::from_elf(&elf(&fields));
+        assert!(
+            matches!(result, Err(SecurityTxtError::InvalidContact(_) | SecurityTxtError::EmptyField("contacts"))),
+            "{contacts}: {result:?}"
+        );
+    }
+}
+
+#[test]
+fn best_practices_flags_programs_without_security_txt() {
+    const PROGRAM: &str = r#"
+use anchor_lang::prelude::*;
+
+#[program]
+pub mod vault {
+    use super::*;
+}
+"#;
+    const SECURITY_TXT: &str = r#"
+#[cfg(not(feature = "no-entrypoint"))]
+solana_security_txt::security_txt! {
+    name: "Vault",
+    project_url: "https://vault.example",
+    contacts: "email:security@vault.example",
+    policy: "https://vault.example/SECURITY.md"
+}
+"#;
+    let flagged = |sources: &[(&str, &str)]| {
+        let sources: Vec<(String, String)> =
+            sources.iter().map(|(file, source)| (file.to_string(), source.to_string())).collect();
+        let ir = ProgramIr::from_sources(&sources).unwrap();
+        best_practices(&ir).into_iter().find(|f| f.rule_id == "missing-security-txt")
+    };
+
+    let finding = flagged(&[("lib.rs", PROGRAM)]).expect("missing security.txt");
+    assert_eq!(finding.location.line, 5);
+    assert!(flagged(&[("lib.rs", PROGRAM), ("security.rs", SECURITY_TXT)]).is_none());
+    // Without a #[program] module there is nothing to attach the finding to.
+    assert!(flagged(&[("state.rs", "pub struct Vault;")]).is_none());
+}
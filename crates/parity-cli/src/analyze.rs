@@ -0,0 +1,164 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, ValueEnum};
+use parity::scoring::ScoringModel;
+use parity::skills::{get_skill_by_name, list_skill_names};
+use parity_analyzer::correlation::{correlate, dedup};
+use parity_analyzer::finding::{severity_name, severity_rank};
+use parity_analyzer::ir::ProgramIr;
+use parity_analyzer::profiler::CuProfile;
+use parity_analyzer::report::Report;
+use parity_analyzer::skills::{run_skill, DEEP_AUDIT, DEEP_AUDIT_CHAIN, SECURITY_AUDIT};
+
+#[derive(Args)]
+pub struct AnalyzeArgs {
+    /// Program source: a `.rs` file or a directory searched recursively.
+    path: PathBuf,
+
+    /// Skill to run; may be repeated.
+    #[arg(long = "skill", short, default_value = SECURITY_AUDIT)]
+    skills: Vec<String>,
+
+    #[arg(long, short, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Write the report here instead of stdout.
+    #[arg(long, short)]
+    out: Option<PathBuf>,
+
+    /// Exit with status 1 if any finding is at least this severe.
+    #[arg(long, value_parser = parse_severity)]
+    fail_on: Option<parity::state::Severity>,
+
+    /// Compiled program to profile, so `gas-optimization` reports the
+    /// compute units each instruction consumes.
+    #[cfg(feature = "profiler")]
+    #[arg(long, requires = "idl")]
+    so: Option<PathBuf>,
+
+    /// Anchor IDL of the `--so` build, from which its instructions are
+    /// invoked.
+    #[cfg(feature = "profiler")]
+    #[arg(long, requires = "so")]
+    idl: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+    Sarif,
+    Markdown,
+}
+
+fn parse_severity(name: &str) -> Result<parity::state::Severity, String> {
+    severity_name::parse(name).ok_or_else(|| format!("unknown severity `{}`", name))
+}
+
+pub fn run(args: &AnalyzeArgs) -> Result<ExitCode> {
+    for skill in &args.skills {
+        if get_skill_by_name(skill).is_none() {
+            bail!("unknown skill `{}`; built-in skills are {}", skill, list_skill_names().join(", "));
+        }
+    }
+
+    let ir = ProgramIr::from_path(&args.path)?;
+    let profile = cu_profile(args)?;
+    let mut findings = Vec::new();
+    for skill in &args.skills {
+        if skill == DEEP_AUDIT {
+            let chained = DEEP_AUDIT_CHAIN
+                .iter()
+                .filter_map(|name| run_skill(name, &ir, profile.as_ref()))
+                .flatten()
+                .collect();
+            findings.extend(correlate(&ir, chained).findings().cloned());
+        } else if let Some(output) = run_skill(skill, &ir, profile.as_ref()) {
+            findings.extend(output);
+        } else {
+            bail!("skill `{}` has no local runner", skill);
+        }
+    }
+
+    let program = args
+        .path
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| args.path.display().to_string());
+    let mut report = Report::new(program, args.skills.clone(), dedup(findings), &ScoringModel::DEFAULT);
+    report.attach_fixes(&ir);
+
+    let rendered = match args.format {
+        Format::Text => text(&report),
+        Format::Json => report.to_json(),
+        Format::Sarif => serde_json::to_string_pretty(&report.to_sarif())?,
+        Format::Markdown => report.to_markdown(),
+    };
+    match &args.out {
+        Some(path) => std::fs::write(path, rendered).with_context(|| format!("writing {}", path.display()))?,
+        None => println!("{}", rendered),
+    }
+
+    let failed = args.fail_on.is_some_and(|threshold| {
+        report
+            .findings
+            .iter()
+            .any(|f| severity_rank(f.severity) <= severity_rank(threshold))
+    });
+    Ok(if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
+
+/// Profiles the `--so` build by invoking every instruction in its IDL.
+#[cfg(feature = "profiler")]
+fn cu_profile(args: &AnalyzeArgs) -> Result<Option<CuProfile>> {
+    use parity_analyzer::profiler::{Idl, Profiler};
+
+    let (Some(so), Some(idl_path)) = (&args.so, &args.idl) else {
+        return Ok(None);
+    };
+    let elf = std::fs::read(so).with_context(|| format!("reading {}", so.display()))?;
+    let json = std::fs::read_to_string(idl_path).with_context(|| format!("reading {}", idl_path.display()))?;
+    let idl = Idl::from_json(&json).with_context(|| format!("parsing {}", idl_path.display()))?;
+    let Some(address) = &idl.address else {
+        bail!("{} records no program address; rebuild it with anchor 0.30 or later", idl_path.display());
+    };
+    let program_id = address.parse().with_context(|| format!("invalid program address `{}`", address))?;
+
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    let profile = runtime
+        .block_on(Profiler::new(program_id).profile_idl(&elf, &idl))
+        .with_context(|| format!("profiling {}", so.display()))?;
+    Ok(Some(profile))
+}
+
+/// Without the `profiler` feature, `gas-optimization` reports layout
+/// findings only.
+#[cfg(not(feature = "profiler"))]
+fn cu_profile(_: &AnalyzeArgs) -> Result<Option<CuProfile>> {
+    Ok(None)
+}
+
+fn text(report: &Report) -> String {
+    let c = &report.findings_count;
+    let mut out = format!(
+        "{}: score {}/100, {} findings ({} critical, {} high, {} medium, {} info)\nfindings_hash {}\n",
+        report.program, report.score, c.total, c.critical, c.high, c.medium, c.info, report.findings_hash
+    );
+    for finding in &report.findings {
+        out.push_str(&format!(
+            "\n{:<8} {} {}\n         {}: {}\n",
+            severity_name::name(finding.severity).to_uppercase(),
+            finding.rule_id,
+            finding.location,
+            finding.subject,
+            finding.title
+        ));
+        for fix in &finding.fixes {
+            out.push_str(&format!("         fix: {}\n", fix.description));
+        }
+    }
+    out
+}
@@ -0,0 +1,153 @@
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use parity::context_engine::{get_framework_patterns, VULNERABILITY_RULES};
+use parity::skills::{get_skill_by_name, list_skill_names, SkillDefinition, BUILTIN_SKILLS};
+use parity_analyzer::finding::{pattern_type, severity_name};
+use serde_json::json;
+
+use crate::session::Session;
+use crate::Output;
+
+#[derive(Subcommand)]
+pub enum SkillsCommand {
+    /// List the built-in skills.
+    List,
+    /// Show a skill's steps, inputs and outputs.
+    Show { name: String },
+}
+
+#[derive(Subcommand)]
+pub enum PatternsCommand {
+    /// List vulnerability rules and framework patterns.
+    List {
+        /// Only framework patterns for this framework.
+        #[arg(long, default_value = "anchor")]
+        framework: String,
+        /// Also list community patterns submitted on-chain.
+        #[arg(long)]
+        onchain: bool,
+    },
+}
+
+fn skill_json(skill: &SkillDefinition) -> serde_json::Value {
+    json!({
+        "name": skill.name,
+        "version": skill.version,
+        "description": skill.description,
+        "type": format!("{:?}", skill.skill_type),
+        "steps": skill.steps,
+        "inputs": skill.inputs.iter().map(|i| json!({
+            "name": i.name,
+            "type": i.input_type,
+            "required": i.required,
+            "default": i.default_value,
+        })).collect::<Vec<_>>(),
+        "outputs": skill.outputs.iter().map(|o| json!({ "name": o.name, "type": o.output_type })).collect::<Vec<_>>(),
+    })
+}
+
+pub fn skills(command: &SkillsCommand, output: Output) -> Result<()> {
+    match command {
+        SkillsCommand::List => match output {
+            Output::Json => println!("{}", serde_json::to_string_pretty(&BUILTIN_SKILLS.iter().map(skill_json).collect::<Vec<_>>())?),
+            Output::Text => {
+                for skill in BUILTIN_SKILLS {
+                    println!("{:<18} {:<7} {}", skill.name, skill.version, skill.description);
+                }
+            }
+        },
+        SkillsCommand::Show { name } => {
+            let Some(skill) = get_skill_by_name(name) else {
+                bail!("unknown skill `{}`; built-in skills are {}", name, list_skill_names().join(", "));
+            };
+            match output {
+                Output::Json => println!("{}", serde_json::to_string_pretty(&skill_json(skill))?),
+                Output::Text => {
+                    println!("{} {} ({:?})\n\n{}\n\nSteps:", skill.name, skill.version, skill.skill_type, skill.description);
+                    for (i, step) in skill.steps.iter().enumerate() {
+                        println!("  {}. {}", i + 1, step);
+                    }
+                    println!("\nInputs:");
+                    for input in skill.inputs {
+                        let default = input.default_value.map(|d| format!(" (default: {})", d)).unwrap_or_default();
+                        let required = if input.required { "required" } else { "optional" };
+                        println!("  {:<12} {:<8} {}{}", input.name, input.input_type, required, default);
+                    }
+                    println!("\nOutputs:");
+                    for out in skill.outputs {
+                        println!("  {:<12} {}", out.name, out.output_type);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn patterns(command: &PatternsCommand, output: Output, connect: impl FnOnce() -> Result<Session>) -> Result<()> {
+    let PatternsCommand::List { framework, onchain } = command;
+    let community = if *onchain {
+        let session = connect()?;
+        // Built-in rules are also on-chain, in the registry's namespace.
+        let registry = session.registry();
+        let mut patterns = session.client.patterns()?;
+        patterns.retain(|(_, pattern)| pattern.namespace != registry);
+        patterns
+    } else {
+        Vec::new()
+    };
+    let framework_patterns = get_framework_patterns(framework);
+
+    if output == Output::Json {
+        let value = json!({
+            "rules": VULNERABILITY_RULES.iter().map(|r| json!({
+                "id": r.id,
+                "severity": severity_name::name(r.severity),
+                "pattern_type": pattern_type::name(r.pattern_type),
+                "description": r.description,
+                "detection_rule": r.detection_rule,
+            })).collect::<Vec<_>>(),
+            "framework_patterns": framework_patterns.iter().map(|p| json!({
+                "framework": p.framework,
+                "name": p.pattern_name,
+                "description": p.description,
+                "example": p.example_code,
+            })).collect::<Vec<_>>(),
+            "community_patterns": community.iter().map(|(address, p)| json!({
+                "address": address.to_string(),
+                "id": p.pattern_id,
+                "severity": severity_name::name(p.severity),
+                "pattern_type": pattern_type::name(p.pattern_type),
+                "description": p.description,
+                "detection_rule": p.detection_rule,
+                "active": p.is_active,
+                "submitter": p.submitter.to_string(),
+            })).collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    println!("Vulnerability rules:");
+    for rule in VULNERABILITY_RULES {
+        println!("  {:<28} {:<8} {}", rule.id, severity_name::name(rule.severity), rule.description);
+    }
+    println!("\nFramework patterns ({}):", framework);
+    for pattern in &framework_patterns {
+        println!("  {:<28} {}", pattern.pattern_name, pattern.description);
+    }
+    if *onchain {
+        println!("\nCommunity patterns:");
+        for (_, pattern) in &community {
+            let status = if pattern.is_active { "" } else { " (inactive)" };
+            println!(
+                "  {:<28} {:<8} {}{}",
+                pattern.pattern_id,
+                severity_name::name(pattern.severity),
+                pattern.description,
+                status
+            );
+        }
+    }
+    Ok(())
+}
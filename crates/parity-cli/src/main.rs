@@ -0,0 +1,171 @@
+//! `parity`: run the skills locally and record the results on-chain.
+
+mod analyze;
+mod catalog;
+mod registry;
+mod session;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use parity_client::{pda, program_hash, ParityClient};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::read_keypair_file;
+
+use crate::session::Session;
+
+#[derive(Parser)]
+#[command(name = "parity", version, about = "Solana program analysis and on-chain verification")]
+struct Cli {
+    /// RPC URL or moniker: localnet, devnet, mainnet-beta.
+    #[arg(long, short = 'u', global = true, default_value = "localnet")]
+    url: String,
+
+    /// Keypair that signs and pays for transactions.
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<PathBuf>,
+
+    /// Parity program ID, if not the one this CLI was built against.
+    #[arg(long, global = true)]
+    program_id: Option<Pubkey>,
+
+    /// Namespace of the registry to use on the deployment.
+    #[arg(long, global = true, default_value = pda::DEFAULT_NAMESPACE)]
+    registry: String,
+
+    /// Output format for status and listing commands.
+    #[arg(long, global = true, value_enum, default_value_t = Output::Text)]
+    output: Output,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Output {
+    Text,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run skills against a program's source and write a report.
+    Analyze(analyze::AnalyzeArgs),
+    /// Register a program in the on-chain registry.
+    Register(registry::RegisterArgs),
+    /// Check the bytecode deployed on-chain against a registered program hash.
+    Verify(registry::VerifyArgs),
+    /// Submit an analysis report for a registered program.
+    Submit(registry::SubmitArgs),
+    /// Sign a report offline for someone else to submit.
+    Attest(registry::AttestArgs),
+    /// Submit a report signed with `parity attest`, paying for it.
+    Relay(registry::RelayArgs),
+    /// Issue or revoke verification badges (registry authority only).
+    #[command(subcommand)]
+    Badge(registry::BadgeCommand),
+    /// Register the signing keypair as an auditor.
+    #[command(subcommand)]
+    Auditor(registry::AuditorCommand),
+    /// Inspect the built-in skills.
+    #[command(subcommand)]
+    Skills(catalog::SkillsCommand),
+    /// Inspect vulnerability rules and framework patterns.
+    #[command(subcommand)]
+    Patterns(catalog::PatternsCommand),
+    /// Inspect or initialize the registry.
+    #[command(subcommand)]
+    Registry(registry::RegistryCommand),
+}
+
+/// Identifies a registered program by its build artifact or its hash.
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+pub struct ProgramTarget {
+    /// Path to the program's `.so`, or a dump of its `ProgramData` account.
+    #[arg(long)]
+    so: Option<PathBuf>,
+    /// Hex-encoded program hash.
+    #[arg(long)]
+    hash: Option<String>,
+}
+
+impl ProgramTarget {
+    pub fn program_hash(&self) -> Result<[u8; 32]> {
+        match (&self.so, &self.hash) {
+            (Some(path), _) => {
+                program_hash::from_file(path).with_context(|| format!("hashing {}", path.display()))
+            }
+            (None, Some(hex)) => parse_hash(hex),
+            (None, None) => bail!("either --so or --hash is required"),
+        }
+    }
+}
+
+pub fn parse_hash(hex: &str) -> Result<[u8; 32]> {
+    let hex = hex.trim_start_matches("0x");
+    if hex.len() != 64 || !hex.is_ascii() {
+        bail!("expected 64 hex characters, got `{}`", hex);
+    }
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).with_context(|| format!("invalid hex `{}`", hex))?;
+    }
+    Ok(out)
+}
+
+fn cluster_url(url: &str) -> &str {
+    match url {
+        "localnet" | "l" => "http://127.0.0.1:8899",
+        "devnet" | "d" => "https://api.devnet.solana.com",
+        "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+        other => other,
+    }
+}
+
+impl Cli {
+    fn connect(&self) -> Result<Session> {
+        let path = match &self.keypair {
+            Some(path) => path.clone(),
+            None => {
+                let home = std::env::var_os("HOME").context("HOME is not set; pass --keypair")?;
+                PathBuf::from(home).join(".config/solana/id.json")
+            }
+        };
+        let payer = read_keypair_file(&path).map_err(|e| anyhow::anyhow!("reading keypair {}: {}", path.display(), e))?;
+        let rpc = RpcClient::new_with_commitment(cluster_url(&self.url).to_string(), CommitmentConfig::confirmed());
+        let client =
+            ParityClient::with_program_id(rpc, self.program_id.unwrap_or(parity_client::ID)).with_namespace(&self.registry);
+        Ok(Session { client, payer, namespace: self.registry.clone() })
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
+    match &cli.command {
+        Command::Analyze(args) => analyze::run(args),
+        Command::Skills(command) => catalog::skills(command, cli.output).map(|_| ExitCode::SUCCESS),
+        Command::Patterns(command) => catalog::patterns(command, cli.output, || cli.connect()).map(|_| ExitCode::SUCCESS),
+        Command::Register(args) => registry::register(&cli.connect()?, args).map(|_| ExitCode::SUCCESS),
+        Command::Verify(args) => registry::verify(&cli.connect()?, args),
+        Command::Submit(args) => registry::submit(&cli.connect()?, args).map(|_| ExitCode::SUCCESS),
+        Command::Attest(args) => registry::attest(&cli.connect()?, args).map(|_| ExitCode::SUCCESS),
+        Command::Relay(args) => registry::relay(&cli.connect()?, args).map(|_| ExitCode::SUCCESS),
+        Command::Badge(command) => registry::badge(&cli.connect()?, command).map(|_| ExitCode::SUCCESS),
+        Command::Auditor(command) => registry::auditor(&cli.connect()?, command).map(|_| ExitCode::SUCCESS),
+        Command::Registry(command) => registry::registry(&cli.connect()?, command, cli.output).map(|_| ExitCode::SUCCESS),
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            ExitCode::from(2)
+        }
+    }
+}
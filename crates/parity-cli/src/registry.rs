@@ -0,0 +1,586 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use parity::state::{
+    AnalysisReport, AuditorAccount, Framework, ProgramDetails, ProgramEntry, Registry, RegistryConfig, SourceRevision,
+    VerificationBadge, VerificationTier, MAX_PROGRAM_NAME_LEN, MAX_RELEASE_TAG_LEN, MAX_REPOSITORY_URL_LEN,
+    MAX_SECURITY_CONTACT_LEN,
+};
+use parity_analyzer::report::{hex, Report};
+use parity_analyzer::security_txt::{SecurityTxt, SecurityTxtError};
+use parity_client::instructions::{self, AnalysisResult};
+use parity_client::{attestation, pda};
+use sha2::{Digest, Sha256};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Signature, Signer};
+
+use crate::session::Session;
+use crate::{Output, ProgramTarget};
+
+#[derive(Args)]
+pub struct RegisterArgs {
+    #[command(flatten)]
+    target: ProgramTarget,
+    /// anchor, native, seahorse or steel.
+    #[arg(long, default_value = "anchor", value_parser = parse_framework)]
+    framework: Framework,
+    #[arg(long, default_value = "")]
+    metadata_uri: String,
+    /// Address of the deployed program.
+    #[arg(long)]
+    program: Pubkey,
+    /// Keypair of the program's upgrade authority, if it is not the signing
+    /// keypair.
+    #[arg(long)]
+    upgrade_authority: Option<PathBuf>,
+    #[command(flatten)]
+    details: DetailsArgs,
+}
+
+/// Facts recorded in the program's metadata account. Unset fields are
+/// pre-filled from the `security.txt` embedded in `--so`; nothing is written
+/// unless at least one is known.
+#[derive(Args)]
+pub struct DetailsArgs {
+    /// Human-readable program name.
+    #[arg(long)]
+    name: Option<String>,
+    /// Source repository URL.
+    #[arg(long)]
+    repository: Option<String>,
+    #[command(flatten)]
+    source: SourceArgs,
+    /// Release tag the build was cut from.
+    #[arg(long)]
+    release_tag: Option<String>,
+    /// Where to report vulnerabilities.
+    #[arg(long)]
+    security_contact: Option<String>,
+}
+
+impl DetailsArgs {
+    fn details(&self, embedded: Option<&SecurityTxt>) -> Option<ProgramDetails> {
+        // Embedded values the metadata account would reject are left out
+        // rather than failing the registration.
+        let fits = |value: Option<&String>, max: usize| value.filter(|v| v.len() <= max).cloned();
+        let name = self.name.clone().or_else(|| fits(embedded.map(|t| &t.name), MAX_PROGRAM_NAME_LEN));
+        let repository = self.repository.clone().or_else(|| {
+            let url = embedded.and_then(|t| t.source_code.as_ref().or(Some(&t.project_url)));
+            fits(url, MAX_REPOSITORY_URL_LEN)
+        });
+        let commit = self.source.commit.clone().or_else(|| {
+            let revision = embedded.and_then(|t| t.source_revision.as_ref()).map(|r| r.to_ascii_lowercase());
+            revision.filter(|r| matches!(r.len(), 40 | 64) && r.bytes().all(|b| b.is_ascii_hexdigit()))
+        });
+        let release_tag = self
+            .release_tag
+            .clone()
+            .or_else(|| fits(embedded.and_then(|t| t.source_release.as_ref()), MAX_RELEASE_TAG_LEN));
+        let security_contact = self.security_contact.clone().or_else(|| {
+            let contact = embedded.and_then(|t| t.primary_contact()).map(|c| c.to_string());
+            fits(contact.as_ref(), MAX_SECURITY_CONTACT_LEN)
+        });
+
+        let known = [&name, &repository, &commit, &self.source.toolchain, &release_tag, &security_contact];
+        if known.iter().all(|field| field.is_none()) {
+            return None;
+        }
+        Some(ProgramDetails {
+            name: name.unwrap_or_default(),
+            repository_url: repository.unwrap_or_default(),
+            source: SourceRevision {
+                commit: commit.unwrap_or_default(),
+                toolchain: self.source.toolchain.clone().unwrap_or_default(),
+            },
+            release_tag: release_tag.unwrap_or_default(),
+            security_contact: security_contact.unwrap_or_default(),
+        })
+    }
+}
+
+/// The source revision a build or an analysis corresponds to.
+#[derive(Args)]
+pub struct SourceArgs {
+    /// Full git commit hash.
+    #[arg(long)]
+    commit: Option<String>,
+    /// Toolchain that built the program, e.g. `anchor 0.30.1`.
+    #[arg(long)]
+    toolchain: Option<String>,
+}
+
+impl SourceArgs {
+    fn revision(&self) -> SourceRevision {
+        SourceRevision {
+            commit: self.commit.clone().unwrap_or_default(),
+            toolchain: self.toolchain.clone().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    #[command(flatten)]
+    target: ProgramTarget,
+}
+
+#[derive(Args)]
+pub struct SubmitArgs {
+    /// Report written by `parity analyze --format json`.
+    report: PathBuf,
+    #[command(flatten)]
+    target: ProgramTarget,
+    /// Justification for a score outside the range the findings imply; its
+    /// hash is recorded as the score override.
+    #[arg(long)]
+    override_justification: Option<PathBuf>,
+    #[command(flatten)]
+    source: SourceArgs,
+}
+
+/// Signs a submission offline for someone else to relay.
+#[derive(Args)]
+pub struct AttestArgs {
+    #[command(flatten)]
+    submission: SubmitArgs,
+    /// Must exceed every nonce the auditor used before; defaults to the
+    /// current unix time.
+    #[arg(long)]
+    nonce: Option<u64>,
+    #[arg(long, default_value_t = 7)]
+    expires_in_days: i64,
+    /// Where to write the signed attestation.
+    #[arg(long)]
+    out: PathBuf,
+}
+
+#[derive(Args)]
+pub struct RelayArgs {
+    /// Signed attestation written by `parity attest`.
+    attestation: PathBuf,
+}
+
+#[derive(Subcommand)]
+pub enum BadgeCommand {
+    /// Issue a badge for a verified program.
+    Issue {
+        #[command(flatten)]
+        target: ProgramTarget,
+        /// bronze, silver, gold or platinum.
+        #[arg(long, value_parser = parse_tier)]
+        tier: VerificationTier,
+        #[arg(long, default_value_t = 365)]
+        expires_in_days: i64,
+    },
+    /// Revoke a program's badge.
+    Revoke {
+        #[command(flatten)]
+        target: ProgramTarget,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AuditorCommand {
+    /// Register the signing keypair as an auditor.
+    Register {
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value = "")]
+        credentials_uri: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RegistryCommand {
+    /// Show the registry's counters and configuration.
+    Status,
+    /// Create the registry with the signing keypair as authority.
+    Init {
+        #[arg(long, default_value_t = 70)]
+        min_score_for_badge: u8,
+        #[arg(long, default_value_t = 0)]
+        analysis_fee_lamports: u64,
+        #[arg(long, default_value_t = 0)]
+        reveal_delay_seconds: i64,
+        #[arg(long, default_value_t = 0)]
+        reveal_quorum: u8,
+        #[arg(long, default_value_t = 7 * 24 * 60 * 60)]
+        commitment_ttl_seconds: i64,
+    },
+    /// Register the built-in skills and vulnerability rules under the
+    /// registry authority, reserving their names.
+    Bootstrap,
+}
+
+fn parse_framework(name: &str) -> Result<Framework, String> {
+    match name {
+        "anchor" => Ok(Framework::Anchor),
+        "native" => Ok(Framework::Native),
+        "seahorse" => Ok(Framework::Seahorse),
+        "steel" => Ok(Framework::Steel),
+        _ => Err(format!("unknown framework `{}`", name)),
+    }
+}
+
+fn parse_tier(name: &str) -> Result<VerificationTier, String> {
+    match name {
+        "bronze" => Ok(VerificationTier::Bronze),
+        "silver" => Ok(VerificationTier::Silver),
+        "gold" => Ok(VerificationTier::Gold),
+        "platinum" => Ok(VerificationTier::Platinum),
+        _ => Err(format!("unknown tier `{}`", name)),
+    }
+}
+
+/// The `security.txt` embedded in `--so`. A binary without one is not an
+/// error; an invalid one is reported and otherwise ignored.
+fn embedded_security_txt(target: &ProgramTarget) -> Result<Option<SecurityTxt>> {
+    let Some(path) = &target.so else {
+        return Ok(None);
+    };
+    match SecurityTxt::from_path(path) {
+        Ok(txt) => Ok(Some(txt)),
+        Err(SecurityTxtError::Missing) => Ok(None),
+        Err(SecurityTxtError::Io(err)) => Err(err).with_context(|| format!("reading {}", path.display())),
+        Err(err) => {
+            eprintln!("warning: ignoring security.txt in {}: {}", path.display(), err);
+            Ok(None)
+        }
+    }
+}
+
+fn fetch_program_entry(session: &Session, program_hash: &[u8; 32]) -> Result<(Pubkey, ProgramEntry)> {
+    let address = pda::program_entry(&session.program_id(), &session.registry(), program_hash).0;
+    match session.client.try_account::<ProgramEntry>(&address)? {
+        Some(entry) => Ok((address, entry)),
+        None => bail!("program {} is not registered", hex(program_hash)),
+    }
+}
+
+pub fn register(session: &Session, args: &RegisterArgs) -> Result<()> {
+    let program_hash = args.target.program_hash()?;
+    let program_id = session.program_id();
+    let registry = session.registry();
+    let upgrade_authority = match &args.upgrade_authority {
+        Some(path) => Some(
+            read_keypair_file(path).map_err(|e| anyhow::anyhow!("reading keypair {}: {}", path.display(), e))?,
+        ),
+        None => None,
+    };
+    let authority = upgrade_authority.as_ref().map_or(session.payer(), |k| k.pubkey());
+    let instruction = instructions::register_program(
+        &program_id,
+        &registry,
+        &session.payer(),
+        &authority,
+        &args.program,
+        program_hash,
+        args.framework,
+        args.metadata_uri.clone(),
+    );
+    let signature = match &upgrade_authority {
+        Some(keypair) => session.send_with(instruction, &[keypair])?,
+        None => session.send(instruction)?,
+    };
+    let program_entry = pda::program_entry(&program_id, &registry, &program_hash).0;
+    println!("registered {} as {}\n{}", hex(&program_hash), program_entry, signature);
+
+    let embedded = embedded_security_txt(&args.target)?;
+    if let Some(details) = args.details.details(embedded.as_ref()) {
+        let signature = session.send(instructions::set_program_metadata(
+            &program_id,
+            &registry,
+            &session.payer(),
+            &program_hash,
+            details,
+        ))?;
+        let metadata = pda::program_metadata(&program_id, &program_entry).0;
+        println!("wrote metadata {}\n{}", metadata, signature);
+    }
+    Ok(())
+}
+
+/// Compares the registered hash with the one recomputed from the deployed
+/// `ProgramData`, the same check `verify_program_hash` makes on-chain.
+pub fn verify(session: &Session, args: &VerifyArgs) -> Result<ExitCode> {
+    let program_hash = args.target.program_hash()?;
+    let (_, entry) = fetch_program_entry(session, &program_hash)?;
+    let deployed = session.client.deployed_program_hash(&entry.program_id)?;
+    if deployed == program_hash {
+        println!("{} matches {}", entry.program_id, hex(&program_hash));
+        Ok(ExitCode::SUCCESS)
+    } else {
+        println!("{} is deployed as {}, registered as {}", entry.program_id, hex(&deployed), hex(&program_hash));
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+fn unix_now() -> Result<i64> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .context("system clock before 1970")?
+        .as_secs() as i64)
+}
+
+/// The result recorded for `args.report`.
+fn analysis_result(args: &SubmitArgs) -> Result<AnalysisResult> {
+    let json = std::fs::read_to_string(&args.report).with_context(|| format!("reading {}", args.report.display()))?;
+    let report = Report::from_json(&json)?;
+    let score_override = match &args.override_justification {
+        Some(path) => {
+            let text = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+            Some(Sha256::digest(&text).into())
+        }
+        None => None,
+    };
+    Ok(AnalysisResult {
+        score: report.score,
+        findings_hash: report.findings_hash(),
+        skills_used: report.skills.clone(),
+        findings_count: report.findings_count(),
+        score_override,
+        source: args.source.revision(),
+    })
+}
+
+pub fn submit(session: &Session, args: &SubmitArgs) -> Result<()> {
+    let result = analysis_result(args)?;
+    let score = result.score;
+    let program_id = session.program_id();
+    let auditor = session.payer();
+    let program_hash = args.target.program_hash()?;
+    let (program_entry, _) = fetch_program_entry(session, &program_hash)?;
+    let analysis_report = pda::analysis(&program_id, &program_entry, &auditor).0;
+
+    // An auditor has one report per program; later runs update it.
+    let instruction = if session.client.try_account::<AnalysisReport>(&analysis_report)?.is_some() {
+        instructions::update_analysis(&program_id, &session.registry(), &auditor, &program_hash, result)
+    } else {
+        instructions::submit_analysis(&program_id, &session.registry(), &auditor, &program_hash, result)
+    };
+    let signature = session.send(instruction)?;
+    println!("submitted score {} to {}\n{}", score, analysis_report, signature);
+    Ok(())
+}
+
+/// Signs the submission with the auditor keypair without touching the
+/// network. The file holds the signed message, so it can be inspected
+/// before it is relayed.
+pub fn attest(session: &Session, args: &AttestArgs) -> Result<()> {
+    let result = analysis_result(&args.submission)?;
+    let program_hash = args.submission.target.program_hash()?;
+    let now = unix_now()?;
+    let nonce = args.nonce.unwrap_or(now as u64);
+    let signed = result.attestation(&session.registry(), program_hash, nonce, now + args.expires_in_days * 24 * 60 * 60);
+    let signature = attestation::sign(&session.payer, &signed);
+
+    let value = serde_json::json!({
+        "auditor": session.payer().to_string(),
+        "signature": signature.to_string(),
+        "message": hex(&attestation::attestation_message(&signed)),
+    });
+    std::fs::write(&args.out, serde_json::to_string_pretty(&value)?)
+        .with_context(|| format!("writing {}", args.out.display()))?;
+    println!("signed score {} for {} with nonce {}", signed.score, hex(&program_hash), nonce);
+    Ok(())
+}
+
+/// Submits an attestation signed by someone else; the signing keypair only
+/// pays.
+pub fn relay(session: &Session, args: &RelayArgs) -> Result<()> {
+    let path = &args.attestation;
+    let json = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let value: serde_json::Value = serde_json::from_str(&json).with_context(|| format!("parsing {}", path.display()))?;
+    let field = |name: &str| value[name].as_str().with_context(|| format!("{} has no `{}`", path.display(), name));
+    let auditor: Pubkey = field("auditor")?.parse().context("invalid auditor")?;
+    let signature: Signature = field("signature")?.parse().context("invalid signature")?;
+    let signed = parse_hex(field("message")?)
+        .and_then(|message| attestation::parse_message(&message))
+        .context("invalid attestation message")?;
+    if !attestation::verify(&auditor, &signature, &signed) {
+        bail!("signature does not match the attestation");
+    }
+
+    let registry = session.registry();
+    if signed.registry != registry {
+        bail!("the attestation is for registry {}, not {}", signed.registry, registry);
+    }
+
+    let program_id = session.program_id();
+    let program_entry = pda::program_entry(&program_id, &registry, &signed.program_hash).0;
+    let analysis_report = pda::analysis(&program_id, &program_entry, &auditor).0;
+    let score = signed.score;
+    let instructions = attestation::instructions(&program_id, &registry, &session.payer(), &auditor, &signature, signed);
+    let signature = session.client.send(&instructions, &[&session.payer])?;
+    println!("relayed score {} to {}\n{}", score, analysis_report, signature);
+    Ok(())
+}
+
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    let digits = hex.as_bytes().chunks_exact(2);
+    if !digits.remainder().is_empty() {
+        return None;
+    }
+    digits.map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()).collect()
+}
+
+pub fn badge(session: &Session, command: &BadgeCommand) -> Result<()> {
+    let program_id = session.program_id();
+    match command {
+        BadgeCommand::Issue { target, tier, expires_in_days } => {
+            let program_hash = target.program_hash()?;
+            let (program_entry, _) = fetch_program_entry(session, &program_hash)?;
+            let now = unix_now()?;
+            let signature = session.send(instructions::create_verification_badge(
+                &program_id,
+                &session.registry(),
+                &session.payer(),
+                &program_hash,
+                *tier,
+                now + expires_in_days * 24 * 60 * 60,
+            ))?;
+            let badge = pda::badge(&program_id, &program_entry).0;
+            println!("issued {:?} badge {}\n{}", tier, badge, signature);
+        }
+        BadgeCommand::Revoke { target } => {
+            let program_hash = target.program_hash()?;
+            let (program_entry, _) = fetch_program_entry(session, &program_hash)?;
+            let badge = pda::badge(&program_id, &program_entry).0;
+            if session.client.try_account::<VerificationBadge>(&badge)?.is_none() {
+                bail!("program {} has no badge", program_entry);
+            }
+            let signature = session.send(instructions::revoke_verification_badge(
+                &program_id,
+                &session.registry(),
+                &session.payer(),
+                &program_hash,
+            ))?;
+            println!("revoked badge {}\n{}", badge, signature);
+        }
+    }
+    Ok(())
+}
+
+pub fn auditor(session: &Session, command: &AuditorCommand) -> Result<()> {
+    let AuditorCommand::Register { name, credentials_uri } = command;
+    let program_id = session.program_id();
+    let signature = session.send(instructions::register_auditor(
+        &program_id,
+        &session.registry(),
+        &session.payer(),
+        name.clone(),
+        credentials_uri.clone(),
+    ))?;
+    let auditor_account = pda::auditor(&program_id, &session.registry(), &session.payer()).0;
+    println!("registered auditor {}\n{}", auditor_account, signature);
+    Ok(())
+}
+
+pub fn registry(session: &Session, command: &RegistryCommand, output: Output) -> Result<()> {
+    let program_id = session.program_id();
+    let address = session.registry();
+    match command {
+        RegistryCommand::Status => {
+            let Some(registry) = session.client.try_account::<Registry>(&address)? else {
+                bail!("no registry at {}; create one with `parity registry init`", address);
+            };
+            let auditor = session
+                .client
+                .try_account::<AuditorAccount>(&pda::auditor(&program_id, &address, &session.payer()).0)?;
+            print_status(&address, &registry, auditor.as_ref(), output)?;
+        }
+        RegistryCommand::Init {
+            min_score_for_badge,
+            analysis_fee_lamports,
+            reveal_delay_seconds,
+            reveal_quorum,
+            commitment_ttl_seconds,
+        } => {
+            let config = RegistryConfig {
+                min_score_for_badge: *min_score_for_badge,
+                analysis_fee_lamports: *analysis_fee_lamports,
+                is_paused: false,
+                reveal_delay_seconds: *reveal_delay_seconds,
+                reveal_quorum: *reveal_quorum,
+                commitment_ttl_seconds: *commitment_ttl_seconds,
+            };
+            let signature = session.send(instructions::initialize_registry(
+                &program_id,
+                &session.payer(),
+                session.namespace.clone(),
+                config,
+            ))?;
+            println!("initialized registry {}\n{}", address, signature);
+        }
+        RegistryCommand::Bootstrap => {
+            let signature = session.send(instructions::bootstrap_builtins(&program_id, &address, &session.payer()))?;
+            println!("bootstrapped built-ins in registry {}\n{}", address, signature);
+        }
+    }
+    Ok(())
+}
+
+fn print_status(
+    address: &Pubkey,
+    registry: &Registry,
+    auditor: Option<&AuditorAccount>,
+    output: Output,
+) -> Result<()> {
+    if output == Output::Json {
+        let value = serde_json::json!({
+            "address": address.to_string(),
+            "namespace": registry.namespace,
+            "authority": registry.authority.to_string(),
+            "total_programs": registry.total_programs,
+            "total_analyses": registry.total_analyses,
+            "total_skills": registry.total_skills,
+            "total_auditors": registry.total_auditors,
+            "total_patterns": registry.total_patterns,
+            "min_score_for_badge": registry.min_score_for_badge,
+            "analysis_fee_lamports": registry.analysis_fee_lamports,
+            "is_paused": registry.is_paused,
+            "reveal_delay_seconds": registry.reveal_delay_seconds,
+            "reveal_quorum": registry.reveal_quorum,
+            "commitment_ttl_seconds": registry.commitment_ttl_seconds,
+            "auditor": auditor.map(|a| serde_json::json!({
+                "name": a.name,
+                "is_active": a.is_active,
+                "total_analyses": a.total_analyses,
+                "average_score": a.average_score,
+            })),
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    println!("registry   {} ({}){}", address, registry.namespace, if registry.is_paused { " (paused)" } else { "" });
+    println!("authority  {}", registry.authority);
+    println!(
+        "totals     {} programs, {} analyses, {} skills, {} auditors, {} patterns",
+        registry.total_programs,
+        registry.total_analyses,
+        registry.total_skills,
+        registry.total_auditors,
+        registry.total_patterns
+    );
+    println!(
+        "config     badge score >= {}, fee {} lamports",
+        registry.min_score_for_badge, registry.analysis_fee_lamports
+    );
+    println!(
+        "reveal     delay {}s, quorum {}, ttl {}s",
+        registry.reveal_delay_seconds, registry.reveal_quorum, registry.commitment_ttl_seconds
+    );
+    match auditor {
+        Some(a) => println!(
+            "auditor    {} ({}), {} analyses",
+            a.name,
+            if a.is_active { "active" } else { "inactive" },
+            a.total_analyses
+        ),
+        None => println!("auditor    not registered"),
+    }
+    Ok(())
+}
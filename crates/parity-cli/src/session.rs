@@ -0,0 +1,37 @@
+use anyhow::Result;
+use parity_client::ParityClient;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+
+/// A client plus the keypair that signs and pays for every transaction.
+pub struct Session {
+    pub client: ParityClient,
+    pub payer: Keypair,
+    /// Namespace of the registry the client is scoped to.
+    pub namespace: String,
+}
+
+impl Session {
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        self.client.program_id()
+    }
+
+    pub fn registry(&self) -> Pubkey {
+        self.client.registry_address()
+    }
+
+    pub fn send(&self, instruction: Instruction) -> Result<Signature> {
+        Ok(self.client.send(&[instruction], &[&self.payer])?)
+    }
+
+    /// Sends `instruction` signed by the payer and `signers`.
+    pub fn send_with(&self, instruction: Instruction, signers: &[&Keypair]) -> Result<Signature> {
+        let all: Vec<&dyn Signer> = std::iter::once(&self.payer).chain(signers.iter().copied()).map(|k| k as &dyn Signer).collect();
+        Ok(self.client.send(&[instruction], &all)?)
+    }
+}
@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use parity_analyzer::report::Report;
+
+const VAULT: &str = r#"use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    /// CHECK: compared against vault.authority
+    pub authority: AccountInfo<'info>,
+}
+
+pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    ctx.accounts.vault.balance -= amount;
+    Ok(())
+}
+
+#[account]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub balance: u64,
+}
+"#;
+
+fn fixture(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("parity-cli-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("lib.rs"), VAULT).unwrap();
+    dir
+}
+
+fn parity() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_parity"))
+}
+
+#[test]
+fn analyze_writes_a_submittable_report() {
+    let dir = fixture("analyze");
+    let out = dir.join("report.json");
+    let status = parity()
+        .args(["analyze", dir.to_str().unwrap(), "--skill", "deep-audit", "--format", "json", "--out"])
+        .arg(&out)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let report = Report::from_json(&std::fs::read_to_string(&out).unwrap()).unwrap();
+    assert_eq!(report.skills, ["deep-audit"]);
+    assert!(report.findings.iter().any(|f| f.rule_id == "missing-signer-check"));
+    assert!(report.score < 100);
+}
+
+#[test]
+fn fail_on_sets_the_exit_status() {
+    let dir = fixture("fail-on");
+    let run = |threshold: &str| {
+        parity()
+            .args(["analyze", dir.to_str().unwrap(), "--fail-on", threshold])
+            .output()
+            .unwrap()
+            .status
+            .code()
+    };
+    assert_eq!(run("critical"), Some(1));
+
+    let unknown = parity().args(["analyze", dir.to_str().unwrap(), "--skill", "nope"]).output().unwrap();
+    assert_eq!(unknown.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&unknown.stderr).contains("unknown skill"));
+}
+
+#[cfg(feature = "profiler")]
+#[test]
+fn analyze_reports_compute_units_of_a_profiled_build() {
+    use solana_program_test::programs::spl_programs;
+    use solana_sdk::account::ReadableAccount;
+    use solana_sdk::rent::Rent;
+
+    // The SPL memo program logs whatever instruction data it is given, so
+    // an IDL instruction without accounts or arguments invokes it.
+    const MEMO_PROGRAM: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+    let memo = MEMO_PROGRAM.parse().unwrap();
+    let (_, account) = spl_programs(&Rent::default()).into_iter().find(|(id, _)| *id == memo).unwrap();
+
+    let dir = fixture("profile");
+    let (so, idl) = (dir.join("memo.so"), dir.join("memo.json"));
+    std::fs::write(&so, account.data()).unwrap();
+    let json = format!(r#"{{"address": "{}", "instructions": [{{"name": "memo", "discriminator": [104, 105]}}]}}"#, MEMO_PROGRAM);
+    std::fs::write(&idl, json).unwrap();
+
+    let output = parity()
+        .args(["analyze", dir.to_str().unwrap(), "--skill", "gas-optimization", "--format", "json", "--so"])
+        .arg(&so)
+        .arg("--idl")
+        .arg(&idl)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let report = Report::from_json(&String::from_utf8(output.stdout).unwrap()).unwrap();
+    let cu = report.findings.iter().find(|f| f.rule_id == "cu-budget").expect("a compute unit finding");
+    assert_eq!(cu.subject, "memo");
+}
+
+#[test]
+fn skills_list_covers_the_builtins() {
+    let output = parity().args(["skills", "list", "--output", "json"]).output().unwrap();
+    assert!(output.status.success());
+    let skills: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let names: Vec<&str> = skills.as_array().unwrap().iter().map(|s| s["name"].as_str().unwrap()).collect();
+    let builtin: Vec<&str> = parity::skills::BUILTIN_SKILLS.iter().map(|s| s.name).collect();
+    assert_eq!(names, builtin);
+}
@@ -0,0 +1,14 @@
+#![no_main]
+
+//! Decodes arbitrary bytes into instruction sequences and runs them against
+//! the in-process runtime used by the integration tests, checking the same
+//! invariants after every step.
+
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    common::model::World::run(&common::model::decode(data));
+});
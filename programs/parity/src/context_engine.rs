@@ -1,4 +1,4 @@
-use anchor_lang::prelude::*;
+use crate::scoring::{ScoredFinding, ScoringModel};
 use crate::state::{Severity, PatternType};
 
 /// A static rule derived from curated audit databases and framework intelligence.
@@ -8,6 +8,8 @@ pub struct VulnerabilityRule {
     pub pattern_type: PatternType,
     pub description: &'static str,
     pub detection_hint: &'static str,
+    /// Executable form of the rule, in the `detection_rule` language.
+    pub detection_rule: &'static str,
 }
 
 pub const VULNERABILITY_RULES: &[VulnerabilityRule] = &[
@@ -17,6 +19,7 @@ pub const VULNERABILITY_RULES: &[VulnerabilityRule] = &[
         pattern_type: PatternType::MissingSignerCheck,
         description: "Instruction does not verify that the authority account has signed the transaction",
         detection_hint: "Check for Signer<'info> constraint on authority accounts in #[derive(Accounts)]",
+        detection_rule: r#"rule v1 match field where name in ["authority", "admin", "owner"] and not signer"#,
     },
     VulnerabilityRule {
         id: "unchecked-arithmetic",
@@ -24,6 +27,7 @@ pub const VULNERABILITY_RULES: &[VulnerabilityRule] = &[
         pattern_type: PatternType::UncheckedArithmetic,
         description: "Arithmetic operation may overflow or underflow without checked math",
         detection_hint: "Look for +, -, * operators without checked_add, checked_sub, checked_mul",
+        detection_rule: r#"rule v1 match handler where unchecked_arithmetic > 0"#,
     },
     VulnerabilityRule {
         id: "unvalidated-pda",
@@ -31,6 +35,7 @@ pub const VULNERABILITY_RULES: &[VulnerabilityRule] = &[
         pattern_type: PatternType::UnvalidatedPda,
         description: "PDA derivation uses attacker-controlled seeds without validation",
         detection_hint: "Verify seeds constraints in #[account] and check for bump validation",
+        detection_rule: r#"rule v1 match field where has(seeds) and not has(bump)"#,
     },
     VulnerabilityRule {
         id: "insecure-cpi",
@@ -38,6 +43,7 @@ pub const VULNERABILITY_RULES: &[VulnerabilityRule] = &[
         pattern_type: PatternType::InsecureCpi,
         description: "Cross-program invocation does not verify the target program ID",
         detection_hint: "Ensure CPI calls use Program<'info, T> typed accounts",
+        detection_rule: r#"rule v1 match field where name ends_with "_program" and wrapper in ["AccountInfo", "UncheckedAccount"] and not has(address)"#,
     },
     VulnerabilityRule {
         id: "account-deserialization",
@@ -45,6 +51,7 @@ pub const VULNERABILITY_RULES: &[VulnerabilityRule] = &[
         pattern_type: PatternType::AccountDeserialization,
         description: "Account data deserialization does not verify discriminator or owner",
         detection_hint: "Use Account<'info, T> instead of AccountInfo for typed deserialization",
+        detection_rule: r#"rule v1 match field where wrapper == "AccountInfo" and not check_doc and not has(owner) and not has(address)"#,
     },
     VulnerabilityRule {
         id: "rent-exemption",
@@ -52,6 +59,7 @@ pub const VULNERABILITY_RULES: &[VulnerabilityRule] = &[
         pattern_type: PatternType::RentExemption,
         description: "Account may not be rent-exempt after initialization",
         detection_hint: "Verify init constraint includes correct space calculation",
+        detection_rule: r#"rule v1 match field where init and not has(space)"#,
     },
     VulnerabilityRule {
         id: "close-account-drain",
@@ -59,6 +67,7 @@ pub const VULNERABILITY_RULES: &[VulnerabilityRule] = &[
         pattern_type: PatternType::CloseAccount,
         description: "Close account instruction does not properly drain lamports and zero data",
         detection_hint: "Check close = target constraint or manual lamport transfer and data zeroing",
+        detection_rule: r#"rule v1 match handler where lamport_writes > 0"#,
     },
     VulnerabilityRule {
         id: "type-cosplay",
@@ -66,6 +75,7 @@ pub const VULNERABILITY_RULES: &[VulnerabilityRule] = &[
         pattern_type: PatternType::TypeCosplay,
         description: "Account can be substituted with a different account type due to missing discriminator check",
         detection_hint: "Ensure all accounts use Anchor discriminators via Account<> wrapper",
+        detection_rule: r#"rule v1 match field where wrapper in ["AccountInfo", "UncheckedAccount"] and mut and not has(owner) and not has(address) and not has(seeds)"#,
     },
     VulnerabilityRule {
         id: "reinitialization-attack",
@@ -73,6 +83,7 @@ pub const VULNERABILITY_RULES: &[VulnerabilityRule] = &[
         pattern_type: PatternType::ReinitiallizationAttack,
         description: "Account can be re-initialized by calling init instruction multiple times",
         detection_hint: "Use init_if_needed with care or add is_initialized flag checks",
+        detection_rule: r#"rule v1 match field where has(init_if_needed)"#,
     },
     VulnerabilityRule {
         id: "owner-check",
@@ -80,6 +91,7 @@ pub const VULNERABILITY_RULES: &[VulnerabilityRule] = &[
         pattern_type: PatternType::OwnerCheck,
         description: "Account owner is not validated, allowing cross-program account injection",
         detection_hint: "Verify owner field matches expected program ID in constraints",
+        detection_rule: r#"rule v1 match field where wrapper in ["AccountInfo", "UncheckedAccount"] and not has(owner) and not has(address) and not has(seeds) and not check_doc"#,
     },
 ];
 
@@ -229,23 +241,13 @@ pub fn get_framework_patterns(framework: &str) -> Vec<&'static FrameworkPattern>
         .collect()
 }
 
+/// Scores curated findings with the default [`ScoringModel`]. Use
+/// [`ScoringModel::score`] directly for pattern types, confidence and a
+/// per-deduction breakdown.
 pub fn calculate_risk_score(findings: &[&AuditFinding]) -> u8 {
-    if findings.is_empty() {
-        return 100;
-    }
-
-    let mut score: i32 = 100;
-
-    for finding in findings {
-        let penalty = match finding.severity {
-            Severity::Critical => 25,
-            Severity::High => 15,
-            Severity::Medium => 8,
-            Severity::Info => 3,
-            Severity::Pass => 0,
-        };
-        score = score.saturating_sub(penalty);
-    }
-
-    score.max(0) as u8
+    let scored: Vec<ScoredFinding> = findings
+        .iter()
+        .map(|finding| ScoredFinding::new(finding.severity))
+        .collect();
+    ScoringModel::DEFAULT.score(&scored).score
 }
@@ -0,0 +1,729 @@
+//! The `detection_rule` language stored in `ContextPattern`s.
+//!
+//! A rule selects entities from the analyzer's program IR and filters them
+//! with a boolean condition:
+//!
+//! ```text
+//! rule v1
+//! match field
+//! where wrapper in ["AccountInfo", "UncheckedAccount"] and not has(owner)
+//! severity high
+//! ```
+//!
+//! This module only parses and type-checks; evaluation happens off-chain in
+//! the analyzer. The same parser runs in `submit_context_pattern`, so every
+//! pattern stored on-chain is guaranteed to be executable.
+
+use crate::state::Severity;
+
+/// Highest rule language version this build understands.
+pub const DETECTION_RULE_VERSION: u8 = 1;
+
+/// Deepest a condition may nest. `not` and parentheses each add a level, as
+/// does a chain of `and`s or `or`s however long it is. The parser keeps its
+/// own stack, so on-chain the limit bounds the rule's complexity, not the
+/// call depth; the analyzer's evaluator recurses once per level. A chain's
+/// length is bounded only by the rule's length.
+pub const MAX_RULE_DEPTH: usize = 12;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DetectionRule {
+    pub version: u8,
+    pub target: Target,
+    pub condition: Option<Expr>,
+    /// Overrides the pattern's severity for every match when present.
+    pub severity: Option<Severity>,
+}
+
+/// The kind of IR entity a rule iterates over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// An account in a `#[derive(Accounts)]` struct.
+    Field,
+    /// A function taking a `Context<T>`.
+    Handler,
+    /// An `#[account]` state struct.
+    Account,
+}
+
+impl Target {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Target::Field => "field",
+            Target::Handler => "handler",
+            Target::Account => "account",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    /// Two or more operands joined by `and`.
+    And(Vec<Expr>),
+    /// Two or more operands joined by `or`.
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    /// `has(constraint)`: the field carries the named Anchor constraint.
+    Has(String),
+    /// A boolean attribute used on its own.
+    Flag(String),
+    Compare { attribute: String, op: CmpOp, value: Literal },
+    In { attribute: String, values: Vec<String> },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Literal {
+    Str(String),
+    Num(u64),
+    Bool(bool),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttrType {
+    Str,
+    Num,
+    Bool,
+}
+
+impl AttrType {
+    fn name(&self) -> &'static str {
+        match self {
+            AttrType::Str => "string",
+            AttrType::Num => "number",
+            AttrType::Bool => "boolean",
+        }
+    }
+}
+
+const FIELD_ATTRIBUTES: &[(&str, AttrType)] = &[
+    ("name", AttrType::Str),
+    ("context", AttrType::Str),
+    ("wrapper", AttrType::Str),
+    ("inner", AttrType::Str),
+    ("mut", AttrType::Bool),
+    ("init", AttrType::Bool),
+    ("signer", AttrType::Bool),
+    ("check_doc", AttrType::Bool),
+];
+
+const HANDLER_ATTRIBUTES: &[(&str, AttrType)] = &[
+    ("name", AttrType::Str),
+    ("context", AttrType::Str),
+    ("cpi_calls", AttrType::Num),
+    ("state_writes", AttrType::Num),
+    ("writes_after_cpi", AttrType::Bool),
+    ("unchecked_arithmetic", AttrType::Num),
+    ("lamport_writes", AttrType::Num),
+    ("emits_event", AttrType::Bool),
+    ("uses_remaining_accounts", AttrType::Bool),
+];
+
+const ACCOUNT_ATTRIBUTES: &[(&str, AttrType)] = &[
+    ("name", AttrType::Str),
+    ("space", AttrType::Num),
+    ("init_space", AttrType::Bool),
+    ("zero_copy", AttrType::Bool),
+];
+
+/// Anchor constraints accepted by `has(..)` on `field` rules.
+pub const FIELD_CONSTRAINTS: &[&str] = &[
+    "address",
+    "associated_token",
+    "bump",
+    "close",
+    "constraint",
+    "executable",
+    "has_one",
+    "init",
+    "init_if_needed",
+    "mut",
+    "owner",
+    "payer",
+    "realloc",
+    "rent_exempt",
+    "seeds",
+    "signer",
+    "space",
+    "token",
+    "zero",
+];
+
+pub fn attribute_type(target: Target, attribute: &str) -> Option<AttrType> {
+    let table = match target {
+        Target::Field => FIELD_ATTRIBUTES,
+        Target::Handler => HANDLER_ATTRIBUTES,
+        Target::Account => ACCOUNT_ATTRIBUTES,
+    };
+    table.iter().find(|(name, _)| *name == attribute).map(|(_, ty)| *ty)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RuleErrorKind {
+    Empty,
+    UnexpectedChar(char),
+    UnterminatedString,
+    NumberTooLarge,
+    Expected { expected: &'static str, found: String },
+    UnsupportedVersion(String),
+    UnknownTarget(String),
+    UnknownSeverity(String),
+    UnknownAttribute { target: &'static str, attribute: String },
+    UnknownConstraint(String),
+    HasOnlyOnFields,
+    TypeMismatch { attribute: String, expected: &'static str },
+    InvalidOperator { attribute: String, op: &'static str },
+    MatchMustComeFirst,
+    DuplicateClause(&'static str),
+    TooDeep,
+}
+
+/// A parse or validation failure, positioned at the offending token.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleError {
+    pub line: u32,
+    pub column: u32,
+    pub kind: RuleErrorKind,
+}
+
+impl std::fmt::Display for RuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            RuleErrorKind::Empty => write!(f, "rule is empty"),
+            RuleErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {:?}", c),
+            RuleErrorKind::UnterminatedString => write!(f, "unterminated string literal"),
+            RuleErrorKind::NumberTooLarge => write!(f, "number does not fit in 64 bits"),
+            RuleErrorKind::Expected { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            RuleErrorKind::UnsupportedVersion(v) => write!(
+                f,
+                "unsupported rule version {:?} (this program supports up to v{})",
+                v, DETECTION_RULE_VERSION
+            ),
+            RuleErrorKind::UnknownTarget(t) => {
+                write!(f, "unknown match target {:?} (expected field, handler or account)", t)
+            }
+            RuleErrorKind::UnknownSeverity(s) => write!(
+                f,
+                "unknown severity {:?} (expected critical, high, medium, info or pass)",
+                s
+            ),
+            RuleErrorKind::UnknownAttribute { target, attribute } => {
+                write!(f, "{} has no attribute {:?}", target, attribute)
+            }
+            RuleErrorKind::UnknownConstraint(c) => write!(f, "unknown constraint {:?}", c),
+            RuleErrorKind::HasOnlyOnFields => write!(f, "has(..) is only valid in field rules"),
+            RuleErrorKind::TypeMismatch { attribute, expected } => {
+                write!(f, "{:?} must be compared with a {}", attribute, expected)
+            }
+            RuleErrorKind::InvalidOperator { attribute, op } => {
+                write!(f, "operator {} cannot be applied to {:?}", op, attribute)
+            }
+            RuleErrorKind::MatchMustComeFirst => {
+                write!(f, "the match clause must come before where and severity")
+            }
+            RuleErrorKind::DuplicateClause(c) => write!(f, "duplicate {} clause", c),
+            RuleErrorKind::TooDeep => write!(f, "condition nests more than {} levels deep", MAX_RULE_DEPTH),
+        }
+    }
+}
+
+/// Parses and type-checks a rule.
+pub fn parse(source: &str) -> Result<DetectionRule, RuleError> {
+    let tokens = tokenize(source)?;
+    Parser { tokens, pos: 0, target: Target::Field }.rule()
+}
+
+/// Checks that `source` parses, as `submit_context_pattern` does. The parsed
+/// rule is leaked rather than dropped: dropping it recurses once per level,
+/// and the program's bump allocator never reclaims the memory anyway.
+pub fn validate(source: &str) -> Result<(), RuleError> {
+    parse(source).map(std::mem::forget)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tok {
+    Ident(String),
+    Str(String),
+    Num(u64),
+    Punct(&'static str),
+    End,
+}
+
+impl Tok {
+    fn describe(&self) -> String {
+        match self {
+            Tok::Ident(s) => format!("{:?}", s),
+            Tok::Str(s) => format!("string {:?}", s),
+            Tok::Num(n) => format!("number {}", n),
+            Tok::Punct(p) => format!("{:?}", p),
+            Tok::End => "end of rule".to_string(),
+        }
+    }
+}
+
+struct Token {
+    tok: Tok,
+    line: u32,
+    column: u32,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, RuleError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    let (mut line, mut column) = (1u32, 1u32);
+
+    while let Some(&c) = chars.peek() {
+        let (start_line, start_column) = (line, column);
+        let err = |kind| RuleError { line: start_line, column: start_column, kind };
+
+        if c == '\n' {
+            chars.next();
+            line += 1;
+            column = 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            chars.next();
+            column += 1;
+            continue;
+        }
+        if c == '#' {
+            while chars.peek().map(|c| *c != '\n').unwrap_or(false) {
+                chars.next();
+            }
+            continue;
+        }
+
+        let tok = if c.is_ascii_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&c) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                ident.push(c);
+                chars.next();
+                column += 1;
+            }
+            Tok::Ident(ident)
+        } else if c.is_ascii_digit() {
+            let mut value: u64 = 0;
+            while let Some(&c) = chars.peek() {
+                let Some(digit) = c.to_digit(10) else { break };
+                value = value
+                    .checked_mul(10)
+                    .and_then(|v| v.checked_add(digit as u64))
+                    .ok_or_else(|| err(RuleErrorKind::NumberTooLarge))?;
+                chars.next();
+                column += 1;
+            }
+            Tok::Num(value)
+        } else if c == '"' {
+            chars.next();
+            column += 1;
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => {
+                        column += 1;
+                        break;
+                    }
+                    Some('\n') | None => return Err(err(RuleErrorKind::UnterminatedString)),
+                    Some(c) => {
+                        value.push(c);
+                        column += 1;
+                    }
+                }
+            }
+            Tok::Str(value)
+        } else {
+            chars.next();
+            column += 1;
+            let two = chars.peek().map(|next| (c, *next));
+            let punct = match two {
+                Some(('=', '=')) => Some("=="),
+                Some(('!', '=')) => Some("!="),
+                Some(('<', '=')) => Some("<="),
+                Some(('>', '=')) => Some(">="),
+                _ => None,
+            };
+            match punct {
+                Some(p) => {
+                    chars.next();
+                    column += 1;
+                    Tok::Punct(p)
+                }
+                None => match c {
+                    '<' => Tok::Punct("<"),
+                    '>' => Tok::Punct(">"),
+                    '(' => Tok::Punct("("),
+                    ')' => Tok::Punct(")"),
+                    '[' => Tok::Punct("["),
+                    ']' => Tok::Punct("]"),
+                    ',' => Tok::Punct(","),
+                    other => return Err(err(RuleErrorKind::UnexpectedChar(other))),
+                },
+            }
+        };
+
+        tokens.push(Token { tok, line: start_line, column: start_column });
+    }
+
+    tokens.push(Token { tok: Tok::End, line, column });
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    target: Target,
+}
+
+/// Operands joined by one keyword, with the token of its first occurrence.
+#[derive(Default)]
+struct Chain {
+    operands: Vec<Expr>,
+    depth: usize,
+    at: Option<usize>,
+}
+
+impl Chain {
+    fn push(&mut self, (operand, depth): (Expr, usize)) {
+        self.operands.push(operand);
+        self.depth = self.depth.max(depth);
+    }
+}
+
+/// A level of the condition still waiting for operands: a `not`, or a
+/// parenthesis or the whole condition collecting `or`s of `and` chains.
+enum Open {
+    Not(usize),
+    Group { paren: Option<usize>, ors: Chain, ands: Chain },
+}
+
+impl Open {
+    fn group(paren: Option<usize>) -> Self {
+        Open::Group { paren, ors: Chain::default(), ands: Chain::default() }
+    }
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> &Token {
+        let pos = self.pos;
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        &self.tokens[pos]
+    }
+
+    fn error_at(token: &Token, kind: RuleErrorKind) -> RuleError {
+        RuleError { line: token.line, column: token.column, kind }
+    }
+
+    fn expected(&self, expected: &'static str) -> RuleError {
+        let token = self.peek();
+        Self::error_at(token, RuleErrorKind::Expected { expected, found: token.tok.describe() })
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.peek().tok, Tok::Ident(s) if s == keyword)
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(&self.peek().tok, Tok::Punct(p) if *p == punct)
+    }
+
+    fn expect_keyword(&mut self, keyword: &'static str) -> Result<(), RuleError> {
+        if !self.is_keyword(keyword) {
+            return Err(self.expected(keyword));
+        }
+        self.next();
+        Ok(())
+    }
+
+    fn expect_punct(&mut self, punct: &'static str) -> Result<(), RuleError> {
+        if !self.is_punct(punct) {
+            return Err(self.expected(punct));
+        }
+        self.next();
+        Ok(())
+    }
+
+    fn ident(&mut self, expected: &'static str) -> Result<(String, usize), RuleError> {
+        match &self.peek().tok {
+            Tok::Ident(s) => {
+                let s = s.clone();
+                let at = self.pos;
+                self.next();
+                Ok((s, at))
+            }
+            _ => Err(self.expected(expected)),
+        }
+    }
+
+    fn rule(mut self) -> Result<DetectionRule, RuleError> {
+        if self.peek().tok == Tok::End {
+            return Err(Self::error_at(self.peek(), RuleErrorKind::Empty));
+        }
+
+        self.expect_keyword("rule")?;
+        let (version, at) = self.ident("a version such as v1")?;
+        let supported = version
+            .strip_prefix('v')
+            .and_then(|v| v.parse::<u8>().ok())
+            .filter(|v| (1..=DETECTION_RULE_VERSION).contains(v));
+        let Some(version) = supported else {
+            return Err(Self::error_at(&self.tokens[at], RuleErrorKind::UnsupportedVersion(version)));
+        };
+
+        if !self.is_keyword("match") {
+            if self.is_keyword("where") || self.is_keyword("severity") {
+                return Err(Self::error_at(self.peek(), RuleErrorKind::MatchMustComeFirst));
+            }
+            return Err(self.expected("match"));
+        }
+        self.next();
+        let (target, at) = self.ident("a match target")?;
+        self.target = match target.as_str() {
+            "field" => Target::Field,
+            "handler" => Target::Handler,
+            "account" => Target::Account,
+            _ => return Err(Self::error_at(&self.tokens[at], RuleErrorKind::UnknownTarget(target))),
+        };
+
+        let mut condition = None;
+        let mut severity = None;
+        loop {
+            if self.is_keyword("where") {
+                if condition.is_some() {
+                    return Err(Self::error_at(self.peek(), RuleErrorKind::DuplicateClause("where")));
+                }
+                self.next();
+                condition = Some(self.condition()?);
+            } else if self.is_keyword("severity") {
+                if severity.is_some() {
+                    return Err(Self::error_at(self.peek(), RuleErrorKind::DuplicateClause("severity")));
+                }
+                self.next();
+                let (name, at) = self.ident("a severity")?;
+                severity = Some(match name.as_str() {
+                    "critical" => Severity::Critical,
+                    "high" => Severity::High,
+                    "medium" => Severity::Medium,
+                    "info" => Severity::Info,
+                    "pass" => Severity::Pass,
+                    _ => return Err(Self::error_at(&self.tokens[at], RuleErrorKind::UnknownSeverity(name))),
+                });
+            } else if self.is_keyword("match") {
+                return Err(Self::error_at(self.peek(), RuleErrorKind::DuplicateClause("match")));
+            } else if self.peek().tok == Tok::End {
+                break;
+            } else {
+                return Err(self.expected("where, severity or end of rule"));
+            }
+        }
+
+        Ok(DetectionRule { version, target: self.target, condition, severity })
+    }
+
+    /// The depth of a level wrapping a subexpression `depth` deep, where the
+    /// token at `at` opened it.
+    fn wrap(&self, at: usize, depth: usize) -> Result<usize, RuleError> {
+        if depth >= MAX_RULE_DEPTH {
+            return Err(Self::error_at(&self.tokens[at], RuleErrorKind::TooDeep));
+        }
+        Ok(depth + 1)
+    }
+
+    /// A chain's single operand as is, or two or more as one `node` a level
+    /// above the deepest.
+    fn close(&self, chain: Chain, node: fn(Vec<Expr>) -> Expr) -> Result<(Expr, usize), RuleError> {
+        match chain.at {
+            Some(at) => Ok((node(chain.operands), self.wrap(at, chain.depth)?)),
+            None => Ok((chain.operands.into_iter().next().expect("a chain has an operand"), chain.depth)),
+        }
+    }
+
+    /// Parses a condition with an explicit stack of the levels still open,
+    /// so nesting costs no call depth on-chain. Each operand found is pushed
+    /// onto the innermost `and` chain once the `not`s waiting for it apply;
+    /// a keyword other than `and` closes that chain, one other than `or` the
+    /// group, so `and` binds tighter than `or`.
+    fn condition(&mut self) -> Result<Expr, RuleError> {
+        let mut stack = vec![Open::group(None)];
+        loop {
+            while self.is_keyword("not") || self.is_punct("(") {
+                let at = self.pos;
+                self.wrap(at, stack.len() - 1)?;
+                stack.push(if self.is_punct("(") { Open::group(Some(at)) } else { Open::Not(at) });
+                self.next();
+            }
+            let mut operand = if self.is_keyword("has") { (self.has()?, 0) } else { (self.predicate()?, 0) };
+
+            loop {
+                while let Some(&Open::Not(at)) = stack.last() {
+                    stack.pop();
+                    operand = (Expr::Not(Box::new(operand.0)), self.wrap(at, operand.1)?);
+                }
+                let Some(Open::Group { paren, ors, ands }) = stack.last_mut() else {
+                    unreachable!("the whole condition stays at the bottom of the stack");
+                };
+                ands.push(operand);
+                if self.is_keyword("and") {
+                    ands.at.get_or_insert(self.pos);
+                    self.next();
+                    break;
+                }
+                ors.push(self.close(std::mem::take(ands), Expr::And)?);
+                if self.is_keyword("or") {
+                    ors.at.get_or_insert(self.pos);
+                    self.next();
+                    break;
+                }
+                let (group, depth) = self.close(std::mem::take(ors), Expr::Or)?;
+                let Some(at) = *paren else {
+                    return Ok(group);
+                };
+                self.expect_punct(")")?;
+                stack.pop();
+                operand = (group, self.wrap(at, depth)?);
+            }
+        }
+    }
+
+    fn has(&mut self) -> Result<Expr, RuleError> {
+        let at = self.pos;
+        self.next();
+        if self.target != Target::Field {
+            return Err(Self::error_at(&self.tokens[at], RuleErrorKind::HasOnlyOnFields));
+        }
+        self.expect_punct("(")?;
+        let (constraint, at) = self.ident("a constraint name")?;
+        if !FIELD_CONSTRAINTS.contains(&constraint.as_str()) {
+            return Err(Self::error_at(&self.tokens[at], RuleErrorKind::UnknownConstraint(constraint)));
+        }
+        self.expect_punct(")")?;
+        Ok(Expr::Has(constraint))
+    }
+
+    fn predicate(&mut self) -> Result<Expr, RuleError> {
+        let (attribute, at) = self.ident("an attribute, not, has or (")?;
+        let Some(ty) = attribute_type(self.target, &attribute) else {
+            return Err(Self::error_at(
+                &self.tokens[at],
+                RuleErrorKind::UnknownAttribute { target: self.target.name(), attribute },
+            ));
+        };
+
+        if self.is_keyword("in") {
+            let op_at = self.pos;
+            self.next();
+            if ty != AttrType::Str {
+                return Err(Self::error_at(
+                    &self.tokens[op_at],
+                    RuleErrorKind::InvalidOperator { attribute, op: "in" },
+                ));
+            }
+            self.expect_punct("[")?;
+            let mut values = Vec::new();
+            loop {
+                match &self.peek().tok {
+                    Tok::Str(s) => {
+                        values.push(s.clone());
+                        self.next();
+                    }
+                    _ => return Err(self.expected("a string")),
+                }
+                if self.is_punct(",") {
+                    self.next();
+                } else {
+                    break;
+                }
+            }
+            self.expect_punct("]")?;
+            return Ok(Expr::In { attribute, values });
+        }
+
+        let op_token = self.pos;
+        let op = match &self.peek().tok {
+            Tok::Punct("==") => CmpOp::Eq,
+            Tok::Punct("!=") => CmpOp::Ne,
+            Tok::Punct("<") => CmpOp::Lt,
+            Tok::Punct("<=") => CmpOp::Le,
+            Tok::Punct(">") => CmpOp::Gt,
+            Tok::Punct(">=") => CmpOp::Ge,
+            Tok::Ident(s) if s == "contains" => CmpOp::Contains,
+            Tok::Ident(s) if s == "starts_with" => CmpOp::StartsWith,
+            Tok::Ident(s) if s == "ends_with" => CmpOp::EndsWith,
+            _ if ty == AttrType::Bool => return Ok(Expr::Flag(attribute)),
+            _ => return Err(self.expected("a comparison operator")),
+        };
+        self.next();
+
+        let allowed = match op {
+            CmpOp::Eq | CmpOp::Ne => true,
+            CmpOp::Lt | CmpOp::Le | CmpOp::Gt | CmpOp::Ge => ty == AttrType::Num,
+            CmpOp::Contains | CmpOp::StartsWith | CmpOp::EndsWith => ty == AttrType::Str,
+        };
+        if !allowed {
+            return Err(Self::error_at(
+                &self.tokens[op_token],
+                RuleErrorKind::InvalidOperator { attribute, op: op_symbol(op) },
+            ));
+        }
+
+        let value_token = self.pos;
+        let value = match &self.peek().tok {
+            Tok::Str(s) => Literal::Str(s.clone()),
+            Tok::Num(n) => Literal::Num(*n),
+            Tok::Ident(s) if s == "true" => Literal::Bool(true),
+            Tok::Ident(s) if s == "false" => Literal::Bool(false),
+            _ => return Err(self.expected("a string, number, true or false")),
+        };
+        let matches_type = matches!(
+            (&value, ty),
+            (Literal::Str(_), AttrType::Str) | (Literal::Num(_), AttrType::Num) | (Literal::Bool(_), AttrType::Bool)
+        );
+        if !matches_type {
+            return Err(Self::error_at(
+                &self.tokens[value_token],
+                RuleErrorKind::TypeMismatch { attribute, expected: ty.name() },
+            ));
+        }
+        self.next();
+
+        Ok(Expr::Compare { attribute, op, value })
+    }
+}
+
+fn op_symbol(op: CmpOp) -> &'static str {
+    match op {
+        CmpOp::Eq => "==",
+        CmpOp::Ne => "!=",
+        CmpOp::Lt => "<",
+        CmpOp::Le => "<=",
+        CmpOp::Gt => ">",
+        CmpOp::Ge => ">=",
+        CmpOp::Contains => "contains",
+        CmpOp::StartsWith => "starts_with",
+        CmpOp::EndsWith => "ends_with",
+    }
+}
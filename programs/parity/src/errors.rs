@@ -77,4 +77,121 @@ pub enum ParityError {
 
     #[msg("Cannot update a deprecated skill")]
     CannotUpdateDeprecatedSkill,
+
+    #[msg("Detection rule is not a valid rule program")]
+    InvalidDetectionRule,
+
+    #[msg("Score is outside the range implied by the findings count")]
+    ScoreInconsistentWithFindings,
+
+    #[msg("Commitment TTL must exceed a non-negative reveal delay")]
+    InvalidCommitRevealWindow,
+
+    #[msg("Revealed values do not match the commitment")]
+    CommitmentMismatch,
+
+    #[msg("Commitment cannot be revealed yet")]
+    RevealNotOpen,
+
+    #[msg("Commitment has expired")]
+    CommitmentExpired,
+
+    #[msg("Commitment has not expired yet")]
+    CommitmentNotExpired,
+
+    #[msg("Finding is not included in the report's findings root")]
+    InvalidInclusionProof,
+
+    #[msg("Finding exclusion proof does not match the report's findings root")]
+    InvalidExclusionProof,
+
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+
+    #[msg("Analysis report has reached the maximum number of updates")]
+    TooManyUpdates,
+
+    #[msg("Accounts do not match the built-in skill and pattern addresses")]
+    BuiltinAccountMismatch,
+
+    #[msg("Signer is neither the program's upgrade authority nor the registry authority")]
+    ProgramOwnershipUnproven,
+
+    #[msg("Signer is not the owner of the program entry")]
+    UnauthorizedProgramOwner,
+
+    #[msg("Signer is not the pending owner of the program entry")]
+    NotPendingOwner,
+
+    #[msg("Program is being deregistered")]
+    ProgramRetired,
+
+    #[msg("Accounts do not match the program's reports and badge")]
+    DeregistrationAccountMismatch,
+
+    #[msg("Program name exceeds maximum length of 64 characters")]
+    ProgramNameTooLong,
+
+    #[msg("Repository URL exceeds maximum length of 200 characters")]
+    RepositoryUrlTooLong,
+
+    #[msg("Source commit must be a 40 or 64 character hex git hash")]
+    InvalidSourceCommit,
+
+    #[msg("Toolchain exceeds maximum length of 64 characters")]
+    ToolchainTooLong,
+
+    #[msg("Release tag exceeds maximum length of 32 characters")]
+    ReleaseTagTooLong,
+
+    #[msg("Security contact exceeds maximum length of 128 characters")]
+    SecurityContactTooLong,
+
+    #[msg("Deployed program does not match the registered program hash")]
+    ProgramHashMismatch,
+
+    #[msg("Attestation must be signed by the auditor in the preceding Ed25519 instruction")]
+    AttestationSignatureMissing,
+
+    #[msg("Attestation has expired")]
+    AttestationExpired,
+
+    #[msg("Attestation nonce must exceed the auditor's last used nonce")]
+    AttestationNonceReused,
+
+    #[msg("Attestation was issued for another registry")]
+    AttestationRegistryMismatch,
+
+    #[msg("Remaining accounts do not match the batch items")]
+    BatchAccountMismatch,
+
+    #[msg("Registry namespace must be 1 to 32 characters")]
+    InvalidRegistryNamespace,
+
+    #[msg("Account is already at the current schema version")]
+    AccountAlreadyMigrated,
+
+    #[msg("Account was written with a newer schema version than this program supports")]
+    UnsupportedSchemaVersion,
+
+    #[msg("Account predates schema versioning and must be migrated first")]
+    UnversionedAccount,
+
+    #[msg("Unversioned accounts belong to the registry without a namespace")]
+    LegacyRegistryRequired,
+
+    #[msg("Accounts do not match the old and new addresses of the legacy account")]
+    MigrationAccountMismatch,
+
+    #[msg("An account already exists at the legacy account's new address")]
+    MigrationDestinationInUse,
+
+    #[msg("Registry requires results to be committed and revealed")]
+    CommitRevealRequired,
+
+    #[msg("Signer is not the registry authority")]
+    UnauthorizedRegistryAuthority,
+
+    #[msg("Program entry already records its deployed program")]
+    ProgramIdAlreadyBound,
 }
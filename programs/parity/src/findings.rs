@@ -0,0 +1,226 @@
+//! Canonical findings and the Merkle construction behind `findings_hash`.
+//!
+//! A finding is committed to as its Borsh serialization. Leaves are
+//! `sha256(0x00 || borsh(finding))`, sorted and deduplicated, so the root does
+//! not depend on the order an analyzer emitted findings in. Interior nodes are
+//! `sha256(0x01 || left || right)`; a node without a sibling is carried up
+//! unchanged. The published root binds the leaf count as well:
+//! `sha256(0x02 || leaf_count as u32 LE || tree_root)`, with an all-zero tree
+//! root for an empty report.
+//!
+//! Because leaves are sorted, absence can be proven too: two adjacent leaves
+//! that bracket the finding's leaf hash, or a single boundary leaf.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::state::Severity;
+
+const LEAF_PREFIX: &[u8] = &[0x00];
+const NODE_PREFIX: &[u8] = &[0x01];
+const ROOT_PREFIX: &[u8] = &[0x02];
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FindingLocation {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct Finding {
+    /// Stable rule identifier, e.g. an id from `VULNERABILITY_RULES`.
+    pub rule_id: String,
+    pub severity: Severity,
+    pub location: FindingLocation,
+    /// sha256 of the human-readable description, kept off-chain.
+    pub description_hash: [u8; 32],
+}
+
+impl Finding {
+    pub fn leaf(&self) -> [u8; 32] {
+        // Serializing into a Vec cannot fail.
+        let bytes = self.try_to_vec().unwrap_or_default();
+        hashv(&[LEAF_PREFIX, &bytes]).to_bytes()
+    }
+}
+
+/// Path from a leaf to the tree root.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// Position of the leaf among the sorted leaves.
+    pub index: u32,
+    /// Number of leaves in the tree; bound into the root.
+    pub leaf_count: u32,
+    /// Sibling hashes from the leaf level upwards; levels where the node is
+    /// carried up have no entry.
+    pub siblings: Vec<[u8; 32]>,
+}
+
+/// A leaf hash together with its inclusion proof.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LeafProof {
+    pub leaf: [u8; 32],
+    pub proof: MerkleProof,
+}
+
+/// Proof that a finding is not in a report: the neighbours its leaf would sit
+/// between. `lower` is absent when the leaf would sort first, `upper` when it
+/// would sort last, and both are absent for an empty report.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExclusionProof {
+    pub leaf_count: u32,
+    pub lower: Option<LeafProof>,
+    pub upper: Option<LeafProof>,
+}
+
+fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+fn bind_count(leaf_count: u32, tree_root: &[u8; 32]) -> [u8; 32] {
+    hashv(&[ROOT_PREFIX, &leaf_count.to_le_bytes(), tree_root]).to_bytes()
+}
+
+/// The published root a proof for `leaf` leads to, `None` if the proof is
+/// malformed for the tree shape it claims.
+fn proof_root(leaf: [u8; 32], proof: &MerkleProof) -> Option<[u8; 32]> {
+    if proof.index >= proof.leaf_count {
+        return None;
+    }
+    let mut hash = leaf;
+    let mut index = proof.index;
+    let mut width = proof.leaf_count;
+    let mut siblings = proof.siblings.iter();
+    while width > 1 {
+        if index % 2 == 1 {
+            hash = node(siblings.next()?, &hash);
+        } else if index + 1 < width {
+            hash = node(&hash, siblings.next()?);
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+    if siblings.next().is_some() {
+        return None;
+    }
+    Some(bind_count(proof.leaf_count, &hash))
+}
+
+/// Checks that `finding` is committed to by `root`.
+pub fn verify_inclusion(root: &[u8; 32], finding: &Finding, proof: &MerkleProof) -> bool {
+    proof_root(finding.leaf(), proof) == Some(*root)
+}
+
+/// Checks that `finding` is absent from the report committed to by `root`.
+pub fn verify_exclusion(root: &[u8; 32], finding: &Finding, proof: &ExclusionProof) -> bool {
+    let target = finding.leaf();
+    let count = proof.leaf_count;
+    let included = |p: &LeafProof| p.proof.leaf_count == count && proof_root(p.leaf, &p.proof) == Some(*root);
+
+    match (&proof.lower, &proof.upper) {
+        (None, None) => count == 0 && bind_count(0, &[0; 32]) == *root,
+        (Some(lower), None) => {
+            included(lower) && lower.proof.index + 1 == count && lower.leaf < target
+        }
+        (None, Some(upper)) => included(upper) && upper.proof.index == 0 && target < upper.leaf,
+        (Some(lower), Some(upper)) => {
+            included(lower)
+                && included(upper)
+                && lower.proof.index + 1 == upper.proof.index
+                && lower.leaf < target
+                && target < upper.leaf
+        }
+    }
+}
+
+/// All levels of a findings tree, built off-chain to produce the root and proofs.
+#[derive(Clone, Debug)]
+pub struct FindingsTree {
+    /// `levels[0]` holds the sorted leaves, the last level the tree root.
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl FindingsTree {
+    pub fn new(findings: &[Finding]) -> Self {
+        let mut leaves: Vec<[u8; 32]> = findings.iter().map(Finding::leaf).collect();
+        leaves.sort_unstable();
+        leaves.dedup();
+
+        let mut levels = vec![leaves];
+        while levels.last().map(|l| l.len() > 1).unwrap_or(false) {
+            let next = levels
+                .last()
+                .map(|level| {
+                    level
+                        .chunks(2)
+                        .map(|pair| match pair {
+                            [left, right] => node(left, right),
+                            [single] => *single,
+                            _ => unreachable!(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            levels.push(next);
+        }
+        FindingsTree { levels }
+    }
+
+    pub fn leaf_count(&self) -> u32 {
+        self.levels[0].len() as u32
+    }
+
+    /// The value stored as `AnalysisReport.findings_hash`.
+    pub fn root(&self) -> [u8; 32] {
+        let tree_root = match self.levels[0].len() {
+            0 => [0; 32],
+            _ => self.levels.last().map(|l| l[0]).unwrap_or([0; 32]),
+        };
+        bind_count(self.leaf_count(), &tree_root)
+    }
+
+    pub fn prove(&self, finding: &Finding) -> Option<MerkleProof> {
+        let index = self.levels[0].binary_search(&finding.leaf()).ok()?;
+        Some(self.proof_at(index))
+    }
+
+    pub fn prove_absent(&self, finding: &Finding) -> Option<ExclusionProof> {
+        let leaves = &self.levels[0];
+        let position = match leaves.binary_search(&finding.leaf()) {
+            Ok(_) => return None,
+            Err(position) => position,
+        };
+        let leaf_proof = |index: usize| LeafProof {
+            leaf: leaves[index],
+            proof: self.proof_at(index),
+        };
+        Some(ExclusionProof {
+            leaf_count: self.leaf_count(),
+            lower: position.checked_sub(1).map(leaf_proof),
+            upper: (position < leaves.len()).then(|| leaf_proof(position)),
+        })
+    }
+
+    fn proof_at(&self, index: usize) -> MerkleProof {
+        let mut siblings = Vec::new();
+        let mut i = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = if i % 2 == 1 { Some(i - 1) } else { Some(i + 1).filter(|s| *s < level.len()) };
+            if let Some(s) = sibling {
+                siblings.push(level[s]);
+            }
+            i /= 2;
+        }
+        MerkleProof {
+            index: index as u32,
+            leaf_count: self.leaf_count(),
+            siblings,
+        }
+    }
+}
+
+/// `findings_hash` for a set of findings.
+pub fn findings_root(findings: &[Finding]) -> [u8; 32] {
+    FindingsTree::new(findings).root()
+}
@@ -1,6 +1,10 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
+use crate::schema::CURRENT_SCHEMA_VERSION;
 use crate::errors::ParityError;
+use crate::findings::{self, ExclusionProof, Finding, MerkleProof};
+use crate::instructions::metadata::check_source_revision;
+use crate::scoring::ScoringModel;
 
 pub fn submit_analysis(
     ctx: Context<SubmitAnalysis>,
@@ -8,55 +12,84 @@ pub fn submit_analysis(
     findings_hash: [u8; 32],
     skills_used: Vec<String>,
     findings_count: AnalysisFindingsCount,
+    score_override: Option<[u8; 32]>,
+    source: SourceRevision,
 ) -> Result<()> {
-    require!(score <= 100, ParityError::InvalidScore);
-    require!(
-        skills_used.len() <= MAX_SKILLS_PER_ANALYSIS,
-        ParityError::TooManySkills
-    );
-
-    for skill_name in &skills_used {
-        require!(
-            skill_name.len() <= MAX_SKILL_NAME_LEN,
-            ParityError::SkillNameTooLong
-        );
-    }
+    let accounts = ctx.accounts;
+    check_direct_submission(&accounts.registry)?;
+    record_analysis(
+        &mut accounts.auditor_account,
+        &mut accounts.analysis_report,
+        &mut accounts.program_entry,
+        &mut accounts.registry,
+        ctx.bumps.analysis_report,
+        score,
+        findings_hash,
+        skills_used,
+        findings_count,
+        score_override,
+        source,
+    )?;
 
-    let expected_total = findings_count
-        .critical
-        .checked_add(findings_count.high)
-        .and_then(|v| v.checked_add(findings_count.medium))
-        .and_then(|v| v.checked_add(findings_count.info))
-        .and_then(|v| v.checked_add(findings_count.pass))
-        .unwrap();
-    require!(
-        findings_count.total == expected_total,
-        ParityError::FindingsCountMismatch
+    msg!(
+        "Analysis submitted: score={}, findings={}, skills={:?}",
+        score,
+        findings_count.total,
+        accounts.analysis_report.skills_used.len()
     );
+    Ok(())
+}
+
+/// Validates a submission and writes it to a freshly initialized report.
+/// Shared by the direct and the commit-reveal submission paths.
+///
+/// The auditor's `total_analyses` and `average_score` are lifetime
+/// statistics: they count every report filed and average, rounding down, the
+/// scores those reports were filed with. Neither changes when a report is
+/// later updated or closed.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn record_analysis(
+    auditor_account: &mut AuditorAccount,
+    analysis: &mut Account<AnalysisReport>,
+    program_entry: &mut Account<ProgramEntry>,
+    registry: &mut Account<Registry>,
+    bump: u8,
+    score: u8,
+    findings_hash: [u8; 32],
+    skills_used: Vec<String>,
+    findings_count: AnalysisFindingsCount,
+    score_override: Option<[u8; 32]>,
+    source: SourceRevision,
+) -> Result<()> {
+    require!(!program_entry.is_retired, ParityError::ProgramRetired);
+    require!(score <= 100, ParityError::InvalidScore);
+    check_skills(&skills_used)?;
+    check_findings_total(&findings_count)?;
+    check_score_bounds(score, &findings_count, &score_override)?;
+    check_source_revision(&source)?;
 
-    let auditor_account = &ctx.accounts.auditor_account;
     require!(auditor_account.is_active, ParityError::AuditorInactive);
 
-    let analysis = &mut ctx.accounts.analysis_report;
-    let program_entry = &mut ctx.accounts.program_entry;
-    let registry = &mut ctx.accounts.registry;
     let clock = Clock::get()?;
 
     analysis.program_entry = program_entry.key();
-    analysis.auditor = ctx.accounts.auditor.key();
+    analysis.auditor = auditor_account.authority;
     analysis.score = score;
     analysis.findings_hash = findings_hash;
     analysis.skills_used = skills_used;
     analysis.findings_count = findings_count;
+    analysis.score_override = score_override;
+    analysis.source = source;
     analysis.submitted_at = clock.unix_timestamp;
     analysis.updated_at = clock.unix_timestamp;
     analysis.version = 1;
-    analysis.bump = ctx.bumps.analysis_report;
+    analysis.bump = bump;
+    analysis.schema_version = CURRENT_SCHEMA_VERSION;
 
     program_entry.analysis_count = program_entry
         .analysis_count
         .checked_add(1)
-        .unwrap();
+        .ok_or(ParityError::ArithmeticOverflow)?;
     program_entry.latest_score = score;
     program_entry.updated_at = clock.unix_timestamp;
 
@@ -64,15 +97,21 @@ pub fn submit_analysis(
         program_entry.is_verified = true;
     }
 
-    registry.total_analyses = registry.total_analyses.checked_add(1).unwrap();
+    registry.total_analyses = registry
+        .total_analyses
+        .checked_add(1)
+        .ok_or(ParityError::ArithmeticOverflow)?;
     registry.updated_at = clock.unix_timestamp;
 
-    msg!(
-        "Analysis submitted: score={}, findings={}, skills={:?}",
-        score,
-        findings_count.total,
-        analysis.skills_used.len()
-    );
+    let filed = auditor_account.total_analyses;
+    let total = filed.checked_add(1).ok_or(ParityError::ArithmeticOverflow)?;
+    auditor_account.average_score = auditor_account
+        .average_score
+        .checked_mul(filed)
+        .and_then(|sum| sum.checked_add(score as u64))
+        .ok_or(ParityError::ArithmeticOverflow)?
+        / total;
+    auditor_account.total_analyses = total;
     Ok(())
 }
 
@@ -81,45 +120,169 @@ pub fn update_analysis(
     new_score: u8,
     new_findings_hash: [u8; 32],
     new_findings_count: AnalysisFindingsCount,
+    score_override: Option<[u8; 32]>,
+    source: SourceRevision,
 ) -> Result<()> {
-    require!(new_score <= 100, ParityError::InvalidScore);
+    let accounts = ctx.accounts;
+    check_direct_submission(&accounts.registry)?;
+    amend_analysis(
+        &mut accounts.analysis_report,
+        &mut accounts.program_entry,
+        &accounts.registry,
+        new_score,
+        new_findings_hash,
+        new_findings_count,
+        score_override,
+        source,
+    )?;
 
-    let expected_total = new_findings_count
-        .critical
-        .checked_add(new_findings_count.high)
-        .and_then(|v| v.checked_add(new_findings_count.medium))
-        .and_then(|v| v.checked_add(new_findings_count.info))
-        .and_then(|v| v.checked_add(new_findings_count.pass))
-        .unwrap();
-    require!(
-        new_findings_count.total == expected_total,
-        ParityError::FindingsCountMismatch
+    msg!(
+        "Analysis updated: new_score={}, version={}",
+        new_score,
+        accounts.analysis_report.version
     );
+    Ok(())
+}
+
+/// Validates a new result for an existing report and overwrites it. Shared
+/// by the direct and the commit-reveal update paths.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn amend_analysis(
+    analysis: &mut Account<AnalysisReport>,
+    program_entry: &mut Account<ProgramEntry>,
+    registry: &Registry,
+    new_score: u8,
+    new_findings_hash: [u8; 32],
+    new_findings_count: AnalysisFindingsCount,
+    score_override: Option<[u8; 32]>,
+    source: SourceRevision,
+) -> Result<()> {
+    require!(!program_entry.is_retired, ParityError::ProgramRetired);
+    require!(new_score <= 100, ParityError::InvalidScore);
+
+    check_findings_total(&new_findings_count)?;
+    check_score_bounds(new_score, &new_findings_count, &score_override)?;
+    check_source_revision(&source)?;
 
-    let analysis = &mut ctx.accounts.analysis_report;
-    let program_entry = &mut ctx.accounts.program_entry;
-    let registry = &ctx.accounts.registry;
     let clock = Clock::get()?;
 
     analysis.score = new_score;
     analysis.findings_hash = new_findings_hash;
     analysis.findings_count = new_findings_count;
+    analysis.score_override = score_override;
+    analysis.source = source;
     analysis.updated_at = clock.unix_timestamp;
-    analysis.version = analysis.version.checked_add(1).unwrap();
+    analysis.version = analysis
+        .version
+        .checked_add(1)
+        .ok_or(ParityError::TooManyUpdates)?;
 
     program_entry.latest_score = new_score;
     program_entry.updated_at = clock.unix_timestamp;
 
-    if new_score >= registry.min_score_for_badge {
-        program_entry.is_verified = true;
-    } else {
-        program_entry.is_verified = false;
+    program_entry.is_verified = new_score >= registry.min_score_for_badge;
+    Ok(())
+}
+
+/// Rejects a plaintext result in a registry that sets a reveal delay or
+/// quorum. There, results are only recorded by `reveal_analysis`, so none
+/// can be copied from another auditor's pending or revealed result.
+pub(crate) fn check_direct_submission(registry: &Registry) -> Result<()> {
+    require!(
+        registry.reveal_delay_seconds == 0 && registry.reveal_quorum == 0,
+        ParityError::CommitRevealRequired
+    );
+    Ok(())
+}
+
+pub(crate) fn check_skills(skills_used: &[String]) -> Result<()> {
+    require!(
+        skills_used.len() <= MAX_SKILLS_PER_ANALYSIS,
+        ParityError::TooManySkills
+    );
+
+    for skill_name in skills_used {
+        require!(
+            skill_name.len() <= MAX_SKILL_NAME_LEN,
+            ParityError::SkillNameTooLong
+        );
     }
+    Ok(())
+}
+
+/// Proves that `finding` is part of the report's `findings_hash`.
+pub fn verify_finding_inclusion(
+    ctx: Context<VerifyFinding>,
+    finding: Finding,
+    proof: MerkleProof,
+) -> Result<()> {
+    let analysis = &ctx.accounts.analysis_report;
+    require!(
+        findings::verify_inclusion(&analysis.findings_hash, &finding, &proof),
+        ParityError::InvalidInclusionProof
+    );
 
     msg!(
-        "Analysis updated: new_score={}, version={}",
-        new_score,
-        analysis.version
+        "Finding {} included in report {}",
+        finding.rule_id,
+        analysis.key()
+    );
+    Ok(())
+}
+
+/// Proves that `finding` is not part of the report's `findings_hash`.
+pub fn verify_finding_exclusion(
+    ctx: Context<VerifyFinding>,
+    finding: Finding,
+    proof: ExclusionProof,
+) -> Result<()> {
+    let analysis = &ctx.accounts.analysis_report;
+    require!(
+        findings::verify_exclusion(&analysis.findings_hash, &finding, &proof),
+        ParityError::InvalidExclusionProof
+    );
+
+    msg!(
+        "Finding {} absent from report {}",
+        finding.rule_id,
+        analysis.key()
+    );
+    Ok(())
+}
+
+/// Rejects a findings count whose total is not the sum of its severities.
+fn check_findings_total(findings_count: &AnalysisFindingsCount) -> Result<()> {
+    let expected_total = findings_count
+        .critical
+        .checked_add(findings_count.high)
+        .and_then(|v| v.checked_add(findings_count.medium))
+        .and_then(|v| v.checked_add(findings_count.info))
+        .and_then(|v| v.checked_add(findings_count.pass))
+        .ok_or(ParityError::ArithmeticOverflow)?;
+    require!(
+        findings_count.total == expected_total,
+        ParityError::FindingsCountMismatch
+    );
+    Ok(())
+}
+
+/// Rejects a score the default scoring model cannot produce from the
+/// findings counts, unless the auditor attached a justification hash.
+fn check_score_bounds(
+    score: u8,
+    findings_count: &AnalysisFindingsCount,
+    score_override: &Option<[u8; 32]>,
+) -> Result<()> {
+    let (min_score, max_score) = ScoringModel::DEFAULT.score_bounds(findings_count);
+    if score >= min_score && score <= max_score {
+        return Ok(());
+    }
+    require!(score_override.is_some(), ParityError::ScoreInconsistentWithFindings);
+    msg!(
+        "Score {} outside expected range {}..={}, accepted with override",
+        score,
+        min_score,
+        max_score
     );
     Ok(())
 }
@@ -130,7 +293,8 @@ pub struct SubmitAnalysis<'info> {
     pub auditor: Signer<'info>,
 
     #[account(
-        seeds = [b"auditor", auditor.key().as_ref()],
+        mut,
+        seeds = [b"auditor", registry.key().as_ref(), auditor.key().as_ref()],
         bump = auditor_account.bump,
         constraint = auditor_account.authority == auditor.key() @ ParityError::UnauthorizedAuditor
     )]
@@ -138,14 +302,14 @@ pub struct SubmitAnalysis<'info> {
 
     #[account(
         mut,
-        seeds = [b"registry"],
+        seeds = [b"registry", registry.namespace.as_bytes()],
         bump = registry.bump
     )]
     pub registry: Account<'info, Registry>,
 
     #[account(
         mut,
-        seeds = [b"program", program_entry.program_hash.as_ref()],
+        seeds = [b"program", registry.key().as_ref(), program_entry.program_hash.as_ref()],
         bump = program_entry.bump
     )]
     pub program_entry: Account<'info, ProgramEntry>,
@@ -174,14 +338,14 @@ pub struct UpdateAnalysis<'info> {
     pub auditor: Signer<'info>,
 
     #[account(
-        seeds = [b"registry"],
+        seeds = [b"registry", registry.namespace.as_bytes()],
         bump = registry.bump
     )]
     pub registry: Account<'info, Registry>,
 
     #[account(
         mut,
-        seeds = [b"program", program_entry.program_hash.as_ref()],
+        seeds = [b"program", registry.key().as_ref(), program_entry.program_hash.as_ref()],
         bump = program_entry.bump
     )]
     pub program_entry: Account<'info, ProgramEntry>,
@@ -197,3 +361,8 @@ pub struct UpdateAnalysis<'info> {
     )]
     pub analysis_report: Account<'info, AnalysisReport>,
 }
+
+#[derive(Accounts)]
+pub struct VerifyFinding<'info> {
+    pub analysis_report: Account<'info, AnalysisReport>,
+}
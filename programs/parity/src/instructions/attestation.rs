@@ -0,0 +1,160 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use crate::state::*;
+use crate::errors::ParityError;
+use crate::instructions::analysis::{check_direct_submission, record_analysis};
+
+/// Prefix of every attestation message, so an auditor key cannot be tricked
+/// into signing an attestation that parses as something else.
+pub const ATTESTATION_DOMAIN: &[u8] = b"parity:analysis-attestation:v1";
+
+/// Layout of the Ed25519 program's instruction data: a signature count and
+/// a padding byte, then one 14-byte offsets record per signature.
+const ED25519_OFFSETS_START: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+/// An offsets record's instruction index meaning "this instruction".
+const ED25519_SELF: u16 = u16::MAX;
+
+/// The bytes the auditor signs for `attestation`.
+pub fn attestation_message(attestation: &AnalysisAttestation) -> Vec<u8> {
+    let mut message = ATTESTATION_DOMAIN.to_vec();
+    // Serializing into a Vec cannot fail.
+    attestation.serialize(&mut message).unwrap();
+    message
+}
+
+/// Records an analysis signed off-chain by the auditor. The transaction must
+/// verify the signature with an Ed25519 program instruction placed right
+/// before this one; whoever submits it pays for the report.
+pub fn submit_signed_analysis(ctx: Context<SubmitSignedAnalysis>, attestation: AnalysisAttestation) -> Result<()> {
+    let accounts = ctx.accounts;
+    check_direct_submission(&accounts.registry)?;
+    require_keys_eq!(
+        attestation.registry,
+        accounts.registry.key(),
+        ParityError::AttestationRegistryMismatch
+    );
+    require!(
+        Clock::get()?.unix_timestamp < attestation.expires_at,
+        ParityError::AttestationExpired
+    );
+    require!(
+        attestation.nonce > accounts.auditor_account.attestation_nonce,
+        ParityError::AttestationNonceReused
+    );
+
+    let (signer, message) = preceding_ed25519_signature(&accounts.instructions)?;
+    require!(
+        signer == accounts.auditor_account.authority && message == attestation_message(&attestation),
+        ParityError::AttestationSignatureMissing
+    );
+
+    accounts.auditor_account.attestation_nonce = attestation.nonce;
+    let AnalysisAttestation { score, findings_hash, skills_used, findings_count, score_override, source, nonce, .. } =
+        attestation;
+    record_analysis(
+        &mut accounts.auditor_account,
+        &mut accounts.analysis_report,
+        &mut accounts.program_entry,
+        &mut accounts.registry,
+        ctx.bumps.analysis_report,
+        score,
+        findings_hash,
+        skills_used,
+        findings_count,
+        score_override,
+        source,
+    )?;
+
+    msg!(
+        "Signed analysis submitted: auditor={}, score={}, nonce={}",
+        accounts.auditor_account.authority,
+        score,
+        nonce
+    );
+    Ok(())
+}
+
+/// The key and message of the single signature checked by the Ed25519
+/// instruction preceding the current one. Offsets must point into that
+/// instruction's own data, so the bytes returned are the bytes verified.
+fn preceding_ed25519_signature(instructions: &AccountInfo) -> Result<(Pubkey, Vec<u8>)> {
+    let current = load_current_index_checked(instructions)?;
+    require!(current > 0, ParityError::AttestationSignatureMissing);
+    let ix = load_instruction_at_checked(usize::from(current - 1), instructions)?;
+    require_keys_eq!(ix.program_id, ed25519_program::ID, ParityError::AttestationSignatureMissing);
+
+    let data = &ix.data;
+    require!(
+        data.len() >= ED25519_OFFSETS_START + ED25519_OFFSETS_LEN && data[0] == 1,
+        ParityError::AttestationSignatureMissing
+    );
+    let field = |i: usize| {
+        let at = ED25519_OFFSETS_START + 2 * i;
+        u16::from_le_bytes([data[at], data[at + 1]])
+    };
+    let (public_key_offset, public_key_ix) = (usize::from(field(2)), field(3));
+    let (message_offset, message_len, message_ix) = (usize::from(field(4)), usize::from(field(5)), field(6));
+    require!(
+        field(1) == ED25519_SELF && public_key_ix == ED25519_SELF && message_ix == ED25519_SELF,
+        ParityError::AttestationSignatureMissing
+    );
+
+    let public_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(ParityError::AttestationSignatureMissing)?;
+    let message = data
+        .get(message_offset..message_offset + message_len)
+        .ok_or(ParityError::AttestationSignatureMissing)?;
+    Ok((Pubkey::try_from(public_key).unwrap(), message.to_vec()))
+}
+
+#[derive(Accounts)]
+#[instruction(attestation: AnalysisAttestation)]
+pub struct SubmitSignedAnalysis<'info> {
+    /// Relays the attestation and pays for the report; need not be the
+    /// auditor.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auditor", registry.key().as_ref(), auditor_account.authority.as_ref()],
+        bump = auditor_account.bump
+    )]
+    pub auditor_account: Account<'info, AuditorAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", registry.namespace.as_bytes()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"program", registry.key().as_ref(), attestation.program_hash.as_ref()],
+        bump = program_entry.bump
+    )]
+    pub program_entry: Account<'info, ProgramEntry>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + AnalysisReport::INIT_SPACE,
+        seeds = [
+            b"analysis",
+            program_entry.key().as_ref(),
+            auditor_account.authority.as_ref()
+        ],
+        bump
+    )]
+    pub analysis_report: Account<'info, AnalysisReport>,
+
+    /// CHECK: the instructions sysvar, checked by address.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -1,7 +1,10 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
+use crate::schema::CURRENT_SCHEMA_VERSION;
 use crate::errors::ParityError;
 
+/// Makes the signer an auditor in this registry. An auditor taking part in
+/// several registries registers once in each.
 pub fn register_auditor(
     ctx: Context<RegisterAuditor>,
     name: String,
@@ -20,6 +23,7 @@ pub fn register_auditor(
     let registry = &mut ctx.accounts.registry;
     let clock = Clock::get()?;
 
+    auditor.registry = registry.key();
     auditor.authority = ctx.accounts.authority.key();
     auditor.name = name.clone();
     auditor.credentials_uri = credentials_uri;
@@ -28,9 +32,14 @@ pub fn register_auditor(
     auditor.is_active = true;
     auditor.registered_at = clock.unix_timestamp;
     auditor.updated_at = clock.unix_timestamp;
+    auditor.attestation_nonce = 0;
     auditor.bump = ctx.bumps.auditor_account;
+    auditor.schema_version = CURRENT_SCHEMA_VERSION;
 
-    registry.total_auditors = registry.total_auditors.checked_add(1).unwrap();
+    registry.total_auditors = registry
+        .total_auditors
+        .checked_add(1)
+        .ok_or(ParityError::ArithmeticOverflow)?;
     registry.updated_at = clock.unix_timestamp;
 
     msg!("Auditor registered: {}", name);
@@ -62,7 +71,7 @@ pub struct RegisterAuditor<'info> {
 
     #[account(
         mut,
-        seeds = [b"registry"],
+        seeds = [b"registry", registry.namespace.as_bytes()],
         bump = registry.bump
     )]
     pub registry: Account<'info, Registry>,
@@ -71,7 +80,7 @@ pub struct RegisterAuditor<'info> {
         init,
         payer = authority,
         space = 8 + AuditorAccount::INIT_SPACE,
-        seeds = [b"auditor", authority.key().as_ref()],
+        seeds = [b"auditor", registry.key().as_ref(), authority.key().as_ref()],
         bump
     )]
     pub auditor_account: Account<'info, AuditorAccount>,
@@ -82,19 +91,19 @@ pub struct RegisterAuditor<'info> {
 #[derive(Accounts)]
 pub struct UpdateAuditorStatus<'info> {
     #[account(
-        constraint = authority.key() == registry.authority @ ParityError::UnauthorizedAuditor
+        constraint = authority.key() == registry.authority @ ParityError::UnauthorizedRegistryAuthority
     )]
     pub authority: Signer<'info>,
 
     #[account(
-        seeds = [b"registry"],
+        seeds = [b"registry", registry.namespace.as_bytes()],
         bump = registry.bump
     )]
     pub registry: Account<'info, Registry>,
 
     #[account(
         mut,
-        seeds = [b"auditor", auditor_account.authority.as_ref()],
+        seeds = [b"auditor", registry.key().as_ref(), auditor_account.authority.as_ref()],
         bump = auditor_account.bump
     )]
     pub auditor_account: Account<'info, AuditorAccount>,
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
+use crate::schema::CURRENT_SCHEMA_VERSION;
 use crate::errors::ParityError;
 
 /// Issues a tiered badge if the latest analysis meets the score threshold.
@@ -38,6 +39,7 @@ pub fn create_verification_badge(
     badge.expires_at = expires_at;
     badge.is_revoked = false;
     badge.bump = ctx.bumps.verification_badge;
+    badge.schema_version = CURRENT_SCHEMA_VERSION;
 
     msg!(
         "Verification badge created: tier={:?}, score={}, expires={}",
@@ -65,15 +67,16 @@ pub struct CreateVerificationBadge<'info> {
     pub authority: Signer<'info>,
 
     #[account(
-        seeds = [b"registry"],
+        seeds = [b"registry", registry.namespace.as_bytes()],
         bump = registry.bump,
-        constraint = authority.key() == registry.authority @ ParityError::UnauthorizedAuditor
+        constraint = authority.key() == registry.authority @ ParityError::UnauthorizedRegistryAuthority
     )]
     pub registry: Account<'info, Registry>,
 
     #[account(
-        seeds = [b"program", program_entry.program_hash.as_ref()],
+        seeds = [b"program", registry.key().as_ref(), program_entry.program_hash.as_ref()],
         bump = program_entry.bump,
+        constraint = !program_entry.is_retired @ ParityError::ProgramRetired,
         constraint = program_entry.is_verified @ ParityError::ProgramNotRegistered
     )]
     pub program_entry: Account<'info, ProgramEntry>,
@@ -93,12 +96,12 @@ pub struct CreateVerificationBadge<'info> {
 #[derive(Accounts)]
 pub struct RevokeVerificationBadge<'info> {
     #[account(
-        constraint = authority.key() == registry.authority @ ParityError::UnauthorizedAuditor
+        constraint = authority.key() == registry.authority @ ParityError::UnauthorizedRegistryAuthority
     )]
     pub authority: Signer<'info>,
 
     #[account(
-        seeds = [b"registry"],
+        seeds = [b"registry", registry.namespace.as_bytes()],
         bump = registry.bump
     )]
     pub registry: Account<'info, Registry>,
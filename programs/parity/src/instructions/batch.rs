@@ -0,0 +1,316 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use crate::state::*;
+use crate::errors::ParityError;
+use crate::instructions::analysis::{check_direct_submission, record_analysis};
+use crate::instructions::builtins::create_program_account;
+use crate::instructions::registry::{init_program_entry, require_deployed_hash};
+use crate::instructions::skill_registry::init_skill_entry;
+
+/// Remaining accounts per `register_programs` item: the program entry, the
+/// program and its `ProgramData`.
+pub const REGISTER_PROGRAMS_ACCOUNTS: usize = 3;
+/// Remaining accounts per `register_skills` item: the skill entry.
+pub const REGISTER_SKILLS_ACCOUNTS: usize = 1;
+/// Remaining accounts per `submit_analyses` item: the program entry and the
+/// analysis report.
+pub const SUBMIT_ANALYSES_ACCOUNTS: usize = 2;
+
+/// Batched `register_program`: registers every item with `owner` as the
+/// entry's owner and `authority` proving control of each program.
+///
+/// Items are checked exactly like single registrations. One whose entry
+/// already exists is skipped and logged with its address, so a batch can be
+/// re-sent after a partial run; any other invalid item fails the whole
+/// batch, with its index logged.
+pub fn register_programs<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RegisterPrograms<'info>>,
+    items: Vec<ProgramRegistration>,
+) -> Result<()> {
+    let chunks = batch_accounts(ctx.remaining_accounts, items.len(), REGISTER_PROGRAMS_ACCOUNTS)?;
+    let accounts = ctx.accounts;
+
+    let (mut created, mut skipped) = (0u64, 0u64);
+    for (index, (item, chunk)) in items.into_iter().zip(chunks).enumerate() {
+        let [entry, program, program_data] = chunk else {
+            unreachable!()
+        };
+        if batch_item(index, register_program_item(accounts, item, entry, program, program_data))? {
+            created += 1;
+        } else {
+            batch_item_skipped(index, entry);
+            skipped += 1;
+        }
+    }
+
+    msg!("Programs registered: created={}, skipped={}", created, skipped);
+    Ok(())
+}
+
+fn register_program_item<'info>(
+    accounts: &mut RegisterPrograms<'info>,
+    item: ProgramRegistration,
+    entry: &'info AccountInfo<'info>,
+    program: &'info AccountInfo<'info>,
+    program_data: &'info AccountInfo<'info>,
+) -> Result<bool> {
+    // The same ownership proof and hash check as `register_program`.
+    let program_data_account = Account::<ProgramData>::try_from(program_data)?;
+    let (expected, _) = Pubkey::find_program_address(&[program.key.as_ref()], &bpf_loader_upgradeable::ID);
+    require_keys_eq!(program_data.key(), expected, ErrorCode::ConstraintSeeds);
+    let authority = accounts.authority.key();
+    require!(
+        program_data_account.upgrade_authority_address == Some(authority)
+            || (program_data_account.upgrade_authority_address.is_none() && authority == accounts.registry.authority),
+        ParityError::ProgramOwnershipUnproven
+    );
+    require_deployed_hash(program_data, item.program_hash)?;
+
+    let registry = accounts.registry.key();
+    let seeds: &[&[u8]] = &[b"program", registry.as_ref(), item.program_hash.as_ref()];
+    let Some(bump) = create_program_account(
+        &accounts.owner,
+        &accounts.system_program,
+        entry,
+        seeds,
+        8 + ProgramEntry::INIT_SPACE,
+        ParityError::BatchAccountMismatch,
+    )?
+    else {
+        return Ok(false);
+    };
+
+    let mut program_entry = Account::<ProgramEntry>::try_from_unchecked(entry)?;
+    init_program_entry(
+        &mut program_entry,
+        &mut accounts.registry,
+        accounts.owner.key(),
+        item.program_hash,
+        program.key(),
+        item.framework,
+        item.metadata_uri,
+        bump,
+    )?;
+    program_entry.exit(&crate::ID)?;
+    Ok(true)
+}
+
+/// Batched `register_skill`: registers every item in `authority`'s
+/// namespace, with the same partial-failure rules as `register_programs`.
+pub fn register_skills<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RegisterSkills<'info>>,
+    items: Vec<SkillRegistration>,
+) -> Result<()> {
+    let chunks = batch_accounts(ctx.remaining_accounts, items.len(), REGISTER_SKILLS_ACCOUNTS)?;
+    let accounts = ctx.accounts;
+
+    let (mut created, mut skipped) = (0u64, 0u64);
+    for (index, (item, chunk)) in items.into_iter().zip(chunks).enumerate() {
+        let [skill_entry] = chunk else {
+            unreachable!()
+        };
+        if batch_item(index, register_skill_item(accounts, item, skill_entry))? {
+            created += 1;
+        } else {
+            batch_item_skipped(index, skill_entry);
+            skipped += 1;
+        }
+    }
+
+    msg!("Skills registered: created={}, skipped={}", created, skipped);
+    Ok(())
+}
+
+fn register_skill_item<'info>(
+    accounts: &mut RegisterSkills<'info>,
+    item: SkillRegistration,
+    skill_entry: &'info AccountInfo<'info>,
+) -> Result<bool> {
+    let registry = accounts.registry.key();
+    let authority = accounts.authority.key();
+    let seeds: &[&[u8]] = &[b"skill", registry.as_ref(), authority.as_ref(), item.name.as_bytes()];
+    let Some(bump) = create_program_account(
+        &accounts.authority,
+        &accounts.system_program,
+        skill_entry,
+        seeds,
+        8 + SkillEntry::INIT_SPACE,
+        ParityError::BatchAccountMismatch,
+    )?
+    else {
+        return Ok(false);
+    };
+
+    let mut skill = Account::<SkillEntry>::try_from_unchecked(skill_entry)?;
+    init_skill_entry(
+        &mut skill,
+        &mut accounts.registry,
+        authority,
+        item.name,
+        item.version,
+        item.description,
+        item.skill_type,
+        bump,
+    )?;
+    skill.exit(&crate::ID)?;
+    Ok(true)
+}
+
+/// Batched `submit_analysis`: records one report per item, with the same
+/// partial-failure rules as `register_programs`. A report that already
+/// exists is left as is; changing it still takes `update_analysis`.
+pub fn submit_analyses<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SubmitAnalyses<'info>>,
+    items: Vec<AnalysisSubmission>,
+) -> Result<()> {
+    check_direct_submission(&ctx.accounts.registry)?;
+    let chunks = batch_accounts(ctx.remaining_accounts, items.len(), SUBMIT_ANALYSES_ACCOUNTS)?;
+    let accounts = ctx.accounts;
+
+    let (mut created, mut skipped) = (0u64, 0u64);
+    for (index, (item, chunk)) in items.into_iter().zip(chunks).enumerate() {
+        let [program_entry, analysis_report] = chunk else {
+            unreachable!()
+        };
+        if batch_item(index, submit_analysis_item(accounts, item, program_entry, analysis_report))? {
+            created += 1;
+        } else {
+            batch_item_skipped(index, analysis_report);
+            skipped += 1;
+        }
+    }
+
+    msg!("Analyses submitted: created={}, skipped={}", created, skipped);
+    Ok(())
+}
+
+fn submit_analysis_item<'info>(
+    accounts: &mut SubmitAnalyses<'info>,
+    item: AnalysisSubmission,
+    program_entry: &'info AccountInfo<'info>,
+    analysis_report: &'info AccountInfo<'info>,
+) -> Result<bool> {
+    let mut entry = Account::<ProgramEntry>::try_from(program_entry)?;
+    let expected = Pubkey::create_program_address(
+        &[b"program", accounts.registry.key().as_ref(), item.program_hash.as_ref(), &[entry.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| ParityError::BatchAccountMismatch)?;
+    require_keys_eq!(program_entry.key(), expected, ParityError::BatchAccountMismatch);
+
+    let auditor = accounts.auditor.key();
+    let seeds: &[&[u8]] = &[b"analysis", program_entry.key.as_ref(), auditor.as_ref()];
+    let Some(bump) = create_program_account(
+        &accounts.auditor,
+        &accounts.system_program,
+        analysis_report,
+        seeds,
+        8 + AnalysisReport::INIT_SPACE,
+        ParityError::BatchAccountMismatch,
+    )?
+    else {
+        return Ok(false);
+    };
+
+    let mut report = Account::<AnalysisReport>::try_from_unchecked(analysis_report)?;
+    record_analysis(
+        &mut accounts.auditor_account,
+        &mut report,
+        &mut entry,
+        &mut accounts.registry,
+        bump,
+        item.score,
+        item.findings_hash,
+        item.skills_used,
+        item.findings_count,
+        item.score_override,
+        item.source,
+    )?;
+    report.exit(&crate::ID)?;
+    entry.exit(&crate::ID)?;
+    Ok(true)
+}
+
+/// Splits the remaining accounts into one group of `per_item` per item.
+pub(crate) fn batch_accounts<'a, 'info>(
+    remaining: &'a [AccountInfo<'info>],
+    items: usize,
+    per_item: usize,
+) -> Result<std::slice::ChunksExact<'a, AccountInfo<'info>>> {
+    require!(
+        items > 0 && remaining.len() == items * per_item,
+        ParityError::BatchAccountMismatch
+    );
+    Ok(remaining.chunks_exact(per_item))
+}
+
+/// Logs an item left as is because its account already exists, for clients
+/// to report; see `parity_client::batch::skipped`.
+pub(crate) fn batch_item_skipped(index: usize, account: &AccountInfo) {
+    msg!("Batch item {} skipped: {} already exists", index, account.key);
+}
+
+/// Logs which item failed before the error aborts the batch.
+pub(crate) fn batch_item<T>(index: usize, result: Result<T>) -> Result<T> {
+    if result.is_err() {
+        msg!("Batch item {} failed", index);
+    }
+    result
+}
+
+#[derive(Accounts)]
+pub struct RegisterPrograms<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", registry.namespace.as_bytes()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    /// The upgrade authority of every program in the batch, or the registry
+    /// authority. May be the same key as `owner`.
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterSkills<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", registry.namespace.as_bytes()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitAnalyses<'info> {
+    #[account(mut)]
+    pub auditor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auditor", registry.key().as_ref(), auditor.key().as_ref()],
+        bump = auditor_account.bump,
+        constraint = auditor_account.authority == auditor.key() @ ParityError::UnauthorizedAuditor
+    )]
+    pub auditor_account: Account<'info, AuditorAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", registry.namespace.as_bytes()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    pub system_program: Program<'info, System>,
+}
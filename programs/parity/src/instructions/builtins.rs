@@ -0,0 +1,182 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Allocate, Assign, CreateAccount, Transfer};
+use crate::context_engine::VULNERABILITY_RULES;
+use crate::errors::ParityError;
+use crate::skills::BUILTIN_SKILLS;
+use crate::state::*;
+use crate::schema::CURRENT_SCHEMA_VERSION;
+
+/// Registers every built-in skill and vulnerability rule in the registry's
+/// namespace, owned by the registry authority.
+///
+/// Takes the skill entries in `BUILTIN_SKILLS` order followed by the patterns
+/// in `VULNERABILITY_RULES` order as remaining accounts. Entries that already
+/// exist are left untouched, so the instruction can be re-run after a release
+/// adds built-ins.
+pub fn bootstrap_builtins<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BootstrapBuiltins<'info>>,
+) -> Result<()> {
+    require!(
+        ctx.remaining_accounts.len() == BUILTIN_SKILLS.len() + VULNERABILITY_RULES.len(),
+        ParityError::BuiltinAccountMismatch
+    );
+    let (skill_accounts, pattern_accounts) = ctx.remaining_accounts.split_at(BUILTIN_SKILLS.len());
+    // Built-ins live in a namespace of their own: the registry's address.
+    let registry = ctx.accounts.registry.key();
+    let namespace = registry;
+    let authority = ctx.accounts.authority.key();
+    let clock = Clock::get()?;
+
+    let mut skills_created = 0u64;
+    for (definition, account) in BUILTIN_SKILLS.iter().zip(skill_accounts) {
+        let seeds: &[&[u8]] = &[b"skill", registry.as_ref(), namespace.as_ref(), definition.name.as_bytes()];
+        let Some(bump) = create_builtin(ctx.accounts, account, seeds, 8 + SkillEntry::INIT_SPACE)? else {
+            continue;
+        };
+        let skill = SkillEntry {
+            registry,
+            authority,
+            namespace,
+            name: definition.name.to_string(),
+            version: definition.version.to_string(),
+            description: definition.description.to_string(),
+            skill_type: definition.skill_type,
+            usage_count: 0,
+            is_deprecated: false,
+            registered_at: clock.unix_timestamp,
+            updated_at: clock.unix_timestamp,
+            bump,
+            schema_version: CURRENT_SCHEMA_VERSION,
+            reserved: [0; SCHEMA_RESERVED_LEN],
+        };
+        skill.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+        skills_created += 1;
+    }
+
+    let mut patterns_created = 0u64;
+    for (rule, account) in VULNERABILITY_RULES.iter().zip(pattern_accounts) {
+        let seeds: &[&[u8]] = &[b"pattern", registry.as_ref(), namespace.as_ref(), rule.id.as_bytes()];
+        let Some(bump) = create_builtin(ctx.accounts, account, seeds, 8 + ContextPattern::INIT_SPACE)? else {
+            continue;
+        };
+        let pattern = ContextPattern {
+            registry,
+            submitter: authority,
+            namespace,
+            pattern_id: rule.id.to_string(),
+            severity: rule.severity,
+            pattern_type: rule.pattern_type,
+            description: rule.description.to_string(),
+            detection_rule: rule.detection_rule.to_string(),
+            usage_count: 0,
+            submitted_at: clock.unix_timestamp,
+            updated_at: clock.unix_timestamp,
+            is_active: true,
+            bump,
+            schema_version: CURRENT_SCHEMA_VERSION,
+            reserved: [0; SCHEMA_RESERVED_LEN],
+        };
+        pattern.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+        patterns_created += 1;
+    }
+
+    let registry = &mut ctx.accounts.registry;
+    registry.total_skills = registry
+        .total_skills
+        .checked_add(skills_created)
+        .ok_or(ParityError::ArithmeticOverflow)?;
+    registry.total_patterns = registry
+        .total_patterns
+        .checked_add(patterns_created)
+        .ok_or(ParityError::ArithmeticOverflow)?;
+    registry.updated_at = clock.unix_timestamp;
+
+    msg!(
+        "Built-ins bootstrapped: skills={}, patterns={}",
+        skills_created,
+        patterns_created
+    );
+    Ok(())
+}
+
+fn create_builtin<'info>(
+    accounts: &BootstrapBuiltins<'info>,
+    account: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    space: usize,
+) -> Result<Option<u8>> {
+    create_program_account(
+        &accounts.authority,
+        &accounts.system_program,
+        account,
+        seeds,
+        space,
+        ParityError::BuiltinAccountMismatch,
+    )
+}
+
+/// Creates the program account at `seeds` with `space` bytes, paid by
+/// `payer`. Returns its bump, or `None` if the account already exists.
+/// `mismatch` is raised when `account` is not at that address.
+pub(crate) fn create_program_account<'info>(
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    space: usize,
+    mismatch: ParityError,
+) -> Result<Option<u8>> {
+    let (address, bump) = Pubkey::try_find_program_address(seeds, &crate::ID).ok_or(mismatch)?;
+    require_keys_eq!(account.key(), address, mismatch);
+    if account.owner == &crate::ID {
+        return Ok(None);
+    }
+
+    let bump_seed = [bump];
+    let signer_seeds = [seeds, &[&bump_seed[..]]].concat();
+    let signer = &[&signer_seeds[..]];
+    let payer = payer.clone();
+    let system = system_program.clone();
+    let rent = Rent::get()?.minimum_balance(space);
+
+    if account.lamports() == 0 {
+        let cpi = CreateAccount { from: payer, to: account.clone() };
+        system_program::create_account(
+            CpiContext::new_with_signer(system, cpi, signer),
+            rent,
+            space as u64,
+            &crate::ID,
+        )?;
+    } else {
+        // Lamports sent to the address ahead of time would make
+        // `create_account` fail; top up and claim the account instead.
+        let top_up = rent.saturating_sub(account.lamports());
+        if top_up > 0 {
+            let cpi = Transfer { from: payer, to: account.clone() };
+            system_program::transfer(CpiContext::new(system.clone(), cpi), top_up)?;
+        }
+        let cpi = Allocate { account_to_allocate: account.clone() };
+        system_program::allocate(CpiContext::new_with_signer(system.clone(), cpi, signer), space as u64)?;
+        let cpi = Assign { account_to_assign: account.clone() };
+        system_program::assign(CpiContext::new_with_signer(system, cpi, signer), &crate::ID)?;
+    }
+    Ok(Some(bump))
+}
+
+#[derive(Accounts)]
+pub struct BootstrapBuiltins<'info> {
+    #[account(
+        mut,
+        constraint = authority.key() == registry.authority @ ParityError::UnauthorizedRegistryAuthority
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", registry.namespace.as_bytes()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    pub system_program: Program<'info, System>,
+}
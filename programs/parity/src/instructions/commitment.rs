@@ -0,0 +1,331 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use crate::state::*;
+use crate::schema::CURRENT_SCHEMA_VERSION;
+use crate::errors::ParityError;
+use crate::instructions::analysis::{amend_analysis, check_skills, record_analysis};
+
+/// The value `commit_analysis` expects: the `sha256` of every field
+/// `reveal_analysis` records, Borsh-serialized in argument order, followed by
+/// `salt`. None of them can be chosen after seeing other reveals.
+pub fn analysis_commitment(
+    score: u8,
+    findings_hash: &[u8; 32],
+    skills_used: &[String],
+    findings_count: &AnalysisFindingsCount,
+    score_override: &Option<[u8; 32]>,
+    source: &SourceRevision,
+    salt: &[u8; 32],
+) -> [u8; 32] {
+    let fields = (score, findings_hash, skills_used, findings_count, score_override, source)
+        .try_to_vec()
+        .unwrap_or_default();
+    hashv(&[&fields, salt]).to_bytes()
+}
+
+/// Seals an analysis result without publishing the score or findings.
+pub fn commit_analysis(ctx: Context<CommitAnalysis>, commitment: [u8; 32]) -> Result<()> {
+    require!(
+        ctx.accounts.auditor_account.is_active,
+        ParityError::AuditorInactive
+    );
+    require!(!ctx.accounts.program_entry.is_retired, ParityError::ProgramRetired);
+
+    let program_entry = &mut ctx.accounts.program_entry;
+    let registry = &ctx.accounts.registry;
+    let sealed = &mut ctx.accounts.analysis_commitment;
+    let clock = Clock::get()?;
+
+    if program_entry.open_commitments == 0 {
+        program_entry.round_start = program_entry.commit_count;
+        program_entry.round_revealed_at = None;
+    }
+
+    sealed.program_entry = program_entry.key();
+    sealed.auditor = ctx.accounts.auditor.key();
+    sealed.commitment = commitment;
+    sealed.commit_index = program_entry.commit_count;
+    sealed.committed_at = clock.unix_timestamp;
+    sealed.expires_at = clock
+        .unix_timestamp
+        .checked_add(registry.commitment_ttl_seconds)
+        .ok_or(ParityError::ArithmeticOverflow)?;
+    sealed.bump = ctx.bumps.analysis_commitment;
+    sealed.schema_version = CURRENT_SCHEMA_VERSION;
+
+    program_entry.commit_count = program_entry
+        .commit_count
+        .checked_add(1)
+        .ok_or(ParityError::ArithmeticOverflow)?;
+    program_entry.open_commitments = program_entry
+        .open_commitments
+        .checked_add(1)
+        .ok_or(ParityError::ArithmeticOverflow)?;
+
+    msg!(
+        "Analysis committed: index={}, expires={}",
+        sealed.commit_index,
+        sealed.expires_at
+    );
+    Ok(())
+}
+
+/// Opens a commitment once the reveal delay has passed or enough auditors
+/// have committed on the same program, and records the analysis. An auditor
+/// who already has a report on the program updates it this way, as
+/// `update_analysis` would.
+///
+/// The quorum counts the commitments of the current round: those made since
+/// the program last had none open, up to the round's first reveal. Each
+/// auditor has at most one open commitment per program, so they come from
+/// distinct auditors, and none of them can have seen a revealed result. Only
+/// they open early through it.
+#[allow(clippy::too_many_arguments)]
+pub fn reveal_analysis(
+    ctx: Context<RevealAnalysis>,
+    score: u8,
+    findings_hash: [u8; 32],
+    salt: [u8; 32],
+    skills_used: Vec<String>,
+    findings_count: AnalysisFindingsCount,
+    score_override: Option<[u8; 32]>,
+    source: SourceRevision,
+) -> Result<()> {
+    let sealed = &ctx.accounts.analysis_commitment;
+    let registry = &ctx.accounts.registry;
+    let clock = Clock::get()?;
+
+    require!(
+        clock.unix_timestamp <= sealed.expires_at,
+        ParityError::CommitmentExpired
+    );
+
+    let delay_passed = clock.unix_timestamp
+        >= sealed
+            .committed_at
+            .checked_add(registry.reveal_delay_seconds)
+            .ok_or(ParityError::ArithmeticOverflow)?;
+    let program_entry = &ctx.accounts.program_entry;
+    let round_end = program_entry
+        .round_revealed_at
+        .unwrap_or(program_entry.commit_count);
+    let in_round = (program_entry.round_start..round_end).contains(&sealed.commit_index);
+    let quorum_reached = registry.reveal_quorum > 0
+        && in_round
+        && round_end - program_entry.round_start >= registry.reveal_quorum as u32;
+    require!(delay_passed || quorum_reached, ParityError::RevealNotOpen);
+
+    let expected = analysis_commitment(
+        score,
+        &findings_hash,
+        &skills_used,
+        &findings_count,
+        &score_override,
+        &source,
+        &salt,
+    );
+    require!(expected == sealed.commitment, ParityError::CommitmentMismatch);
+
+    let accounts = ctx.accounts;
+    accounts.program_entry.open_commitments = accounts
+        .program_entry
+        .open_commitments
+        .checked_sub(1)
+        .ok_or(ParityError::ArithmeticOverflow)?;
+    if accounts.program_entry.round_revealed_at.is_none() {
+        accounts.program_entry.round_revealed_at = Some(accounts.program_entry.commit_count);
+    }
+    // A report is created at version 1, so version 0 means `init_if_needed`
+    // has just allocated it.
+    if accounts.analysis_report.version == 0 {
+        record_analysis(
+            &mut accounts.auditor_account,
+            &mut accounts.analysis_report,
+            &mut accounts.program_entry,
+            &mut accounts.registry,
+            ctx.bumps.analysis_report,
+            score,
+            findings_hash,
+            skills_used,
+            findings_count,
+            score_override,
+            source,
+        )?;
+    } else {
+        require!(accounts.auditor_account.is_active, ParityError::AuditorInactive);
+        check_skills(&skills_used)?;
+        accounts.analysis_report.skills_used = skills_used;
+        amend_analysis(
+            &mut accounts.analysis_report,
+            &mut accounts.program_entry,
+            &accounts.registry,
+            score,
+            findings_hash,
+            findings_count,
+            score_override,
+            source,
+        )?;
+    }
+
+    msg!(
+        "Analysis revealed: score={}, findings={}",
+        score,
+        findings_count.total
+    );
+    Ok(())
+}
+
+/// Closes an expired, unrevealed commitment and refunds its rent to the
+/// auditor. Callable by anyone.
+pub fn expire_commitment(ctx: Context<ExpireCommitment>) -> Result<()> {
+    let sealed = &ctx.accounts.analysis_commitment;
+    let clock = Clock::get()?;
+
+    require!(
+        clock.unix_timestamp > sealed.expires_at,
+        ParityError::CommitmentNotExpired
+    );
+
+    let program_entry = &mut ctx.accounts.program_entry;
+    program_entry.open_commitments = program_entry
+        .open_commitments
+        .checked_sub(1)
+        .ok_or(ParityError::ArithmeticOverflow)?;
+    program_entry.expired_commitments = program_entry
+        .expired_commitments
+        .checked_add(1)
+        .ok_or(ParityError::ArithmeticOverflow)?;
+
+    msg!(
+        "Commitment expired: program={}, auditor={}",
+        sealed.program_entry,
+        sealed.auditor
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CommitAnalysis<'info> {
+    #[account(mut)]
+    pub auditor: Signer<'info>,
+
+    #[account(
+        seeds = [b"auditor", registry.key().as_ref(), auditor.key().as_ref()],
+        bump = auditor_account.bump,
+        constraint = auditor_account.authority == auditor.key() @ ParityError::UnauthorizedAuditor
+    )]
+    pub auditor_account: Account<'info, AuditorAccount>,
+
+    #[account(
+        seeds = [b"registry", registry.namespace.as_bytes()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"program", registry.key().as_ref(), program_entry.program_hash.as_ref()],
+        bump = program_entry.bump
+    )]
+    pub program_entry: Account<'info, ProgramEntry>,
+
+    #[account(
+        init,
+        payer = auditor,
+        space = 8 + AnalysisCommitment::INIT_SPACE,
+        seeds = [
+            b"commitment",
+            program_entry.key().as_ref(),
+            auditor.key().as_ref()
+        ],
+        bump
+    )]
+    pub analysis_commitment: Account<'info, AnalysisCommitment>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealAnalysis<'info> {
+    #[account(mut)]
+    pub auditor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auditor", registry.key().as_ref(), auditor.key().as_ref()],
+        bump = auditor_account.bump,
+        constraint = auditor_account.authority == auditor.key() @ ParityError::UnauthorizedAuditor
+    )]
+    pub auditor_account: Account<'info, AuditorAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", registry.namespace.as_bytes()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"program", registry.key().as_ref(), program_entry.program_hash.as_ref()],
+        bump = program_entry.bump
+    )]
+    pub program_entry: Account<'info, ProgramEntry>,
+
+    #[account(
+        mut,
+        close = auditor,
+        seeds = [
+            b"commitment",
+            program_entry.key().as_ref(),
+            auditor.key().as_ref()
+        ],
+        bump = analysis_commitment.bump
+    )]
+    pub analysis_commitment: Account<'info, AnalysisCommitment>,
+
+    #[account(
+        init_if_needed,
+        payer = auditor,
+        space = 8 + AnalysisReport::INIT_SPACE,
+        seeds = [
+            b"analysis",
+            program_entry.key().as_ref(),
+            auditor.key().as_ref()
+        ],
+        bump
+    )]
+    pub analysis_report: Account<'info, AnalysisReport>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireCommitment<'info> {
+    pub caller: Signer<'info>,
+
+    /// CHECK: rent refund destination, pinned to the committing auditor.
+    #[account(
+        mut,
+        address = analysis_commitment.auditor
+    )]
+    pub auditor: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        address = analysis_commitment.program_entry
+    )]
+    pub program_entry: Account<'info, ProgramEntry>,
+
+    #[account(
+        mut,
+        close = auditor,
+        seeds = [
+            b"commitment",
+            analysis_commitment.program_entry.as_ref(),
+            analysis_commitment.auditor.as_ref()
+        ],
+        bump = analysis_commitment.bump
+    )]
+    pub analysis_commitment: Account<'info, AnalysisCommitment>,
+}
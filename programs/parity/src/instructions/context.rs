@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
+use crate::schema::CURRENT_SCHEMA_VERSION;
 use crate::errors::ParityError;
 
 pub fn submit_context_pattern(
@@ -23,11 +24,18 @@ pub fn submit_context_pattern(
         ParityError::DetectionRuleTooLong
     );
 
+    if let Err(err) = crate::detection_rule::validate(&detection_rule) {
+        msg!("Invalid detection rule: {}", err);
+        return err!(ParityError::InvalidDetectionRule);
+    }
+
     let pattern = &mut ctx.accounts.context_pattern;
     let registry = &mut ctx.accounts.registry;
     let clock = Clock::get()?;
 
+    pattern.registry = registry.key();
     pattern.submitter = ctx.accounts.submitter.key();
+    pattern.namespace = ctx.accounts.submitter.key();
     pattern.pattern_id = pattern_id.clone();
     pattern.severity = severity;
     pattern.pattern_type = pattern_type;
@@ -38,8 +46,12 @@ pub fn submit_context_pattern(
     pattern.updated_at = clock.unix_timestamp;
     pattern.is_active = true;
     pattern.bump = ctx.bumps.context_pattern;
+    pattern.schema_version = CURRENT_SCHEMA_VERSION;
 
-    registry.total_patterns = registry.total_patterns.checked_add(1).unwrap();
+    registry.total_patterns = registry
+        .total_patterns
+        .checked_add(1)
+        .ok_or(ParityError::ArithmeticOverflow)?;
     registry.updated_at = clock.unix_timestamp;
 
     msg!("Context pattern submitted: {}", pattern_id);
@@ -54,7 +66,7 @@ pub struct SubmitContextPattern<'info> {
 
     #[account(
         mut,
-        seeds = [b"registry"],
+        seeds = [b"registry", registry.namespace.as_bytes()],
         bump = registry.bump
     )]
     pub registry: Account<'info, Registry>,
@@ -63,7 +75,7 @@ pub struct SubmitContextPattern<'info> {
         init,
         payer = submitter,
         space = 8 + ContextPattern::INIT_SPACE,
-        seeds = [b"pattern", pattern_id.as_bytes()],
+        seeds = [b"pattern", registry.key().as_ref(), submitter.key().as_ref(), pattern_id.as_bytes()],
         bump
     )]
     pub context_pattern: Account<'info, ContextPattern>,
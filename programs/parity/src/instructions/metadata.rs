@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::schema::CURRENT_SCHEMA_VERSION;
+use crate::errors::ParityError;
+
+/// Creates or replaces the program's metadata account. Only the entry's
+/// owner can write it, and not once the entry is retired.
+pub fn set_program_metadata(ctx: Context<SetProgramMetadata>, details: ProgramDetails) -> Result<()> {
+    require!(
+        details.name.len() <= MAX_PROGRAM_NAME_LEN,
+        ParityError::ProgramNameTooLong
+    );
+    require!(
+        details.repository_url.len() <= MAX_REPOSITORY_URL_LEN,
+        ParityError::RepositoryUrlTooLong
+    );
+    require!(
+        details.release_tag.len() <= MAX_RELEASE_TAG_LEN,
+        ParityError::ReleaseTagTooLong
+    );
+    require!(
+        details.security_contact.len() <= MAX_SECURITY_CONTACT_LEN,
+        ParityError::SecurityContactTooLong
+    );
+    check_source_revision(&details.source)?;
+
+    let metadata = &mut ctx.accounts.program_metadata;
+    let clock = Clock::get()?;
+
+    metadata.program_entry = ctx.accounts.program_entry.key();
+    metadata.details = details;
+    metadata.updated_at = clock.unix_timestamp;
+    metadata.bump = ctx.bumps.program_metadata;
+    metadata.schema_version = CURRENT_SCHEMA_VERSION;
+
+    msg!(
+        "Program metadata set: name={}, commit={}",
+        metadata.details.name,
+        metadata.details.source.commit
+    );
+    Ok(())
+}
+
+/// Empty fields are allowed; a commit, when given, must be a full git hash.
+pub(crate) fn check_source_revision(source: &SourceRevision) -> Result<()> {
+    let commit = source.commit.as_bytes();
+    require!(
+        commit.is_empty()
+            || ((commit.len() == 40 || commit.len() == 64)
+                && commit.iter().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(b))),
+        ParityError::InvalidSourceCommit
+    );
+    require!(
+        source.toolchain.len() <= MAX_TOOLCHAIN_LEN,
+        ParityError::ToolchainTooLong
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetProgramMetadata<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"program", program_entry.registry.as_ref(), program_entry.program_hash.as_ref()],
+        bump = program_entry.bump,
+        constraint = program_entry.owner == owner.key() @ ParityError::UnauthorizedProgramOwner,
+        constraint = !program_entry.is_retired @ ParityError::ProgramRetired
+    )]
+    pub program_entry: Account<'info, ProgramEntry>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + ProgramMetadata::INIT_SPACE,
+        seeds = [b"metadata", program_entry.key().as_ref()],
+        bump
+    )]
+    pub program_metadata: Account<'info, ProgramMetadata>,
+
+    pub system_program: Program<'info, System>,
+}
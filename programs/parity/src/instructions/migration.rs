@@ -0,0 +1,381 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_lang::Discriminator;
+use crate::state::*;
+use crate::errors::ParityError;
+use crate::instructions::batch::{batch_accounts, batch_item};
+use crate::instructions::builtins::create_program_account;
+use crate::instructions::registry::close_account;
+use crate::schema::v0::{self, Legacy};
+use crate::schema::{self, Versioned, CURRENT_SCHEMA_VERSION};
+
+/// Remaining accounts per `migrate_legacy_accounts` item: the legacy account,
+/// its new address and the migrated program entry of a report or badge (any
+/// account otherwise).
+pub const MIGRATE_LEGACY_ACCOUNTS_ACCOUNTS: usize = 3;
+
+/// Upgrades one account to the current schema version in place, whatever its
+/// type. Anyone can migrate an account; the payer covers the rent for any
+/// space it grows by. Accounts are migrated lazily, when someone next needs
+/// them.
+///
+/// The registry from before versioning keeps its address and is upgraded
+/// here too. Every other account from then moves to a new address with
+/// `migrate_legacy_account`.
+pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+    let accounts = ctx.accounts;
+    let migrated = migrate(
+        &accounts.account.to_account_info(),
+        &accounts.payer.to_account_info(),
+        &accounts.system_program.to_account_info(),
+    )?;
+    require!(migrated, ParityError::AccountAlreadyMigrated);
+    Ok(())
+}
+
+/// Batched `migrate_account` over the remaining accounts. Accounts already
+/// at the current version are skipped; any other failure fails the whole
+/// batch, with its index logged.
+pub fn migrate_accounts<'info>(ctx: Context<'_, '_, 'info, 'info, MigrateAccounts<'info>>) -> Result<()> {
+    require!(!ctx.remaining_accounts.is_empty(), ParityError::BatchAccountMismatch);
+    let payer = ctx.accounts.payer.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+
+    let (mut migrated, mut skipped) = (0u64, 0u64);
+    for (index, account) in ctx.remaining_accounts.iter().enumerate() {
+        if batch_item(index, migrate(account, &payer, &system_program))? {
+            migrated += 1;
+        } else {
+            skipped += 1;
+        }
+    }
+
+    msg!("Accounts migrated: migrated={}, skipped={}", migrated, skipped);
+    Ok(())
+}
+
+/// Moves an account created before versioning to the address its type is
+/// derived at today, in the registry without a namespace, converting it field
+/// by field from its `schema::v0` layout. The legacy account is closed to the
+/// payer, who so covers only the rent for the space the account grows by.
+///
+/// The registry must be migrated first, and a report or badge needs the
+/// program entry it belongs to migrated before it.
+pub fn migrate_legacy_account(ctx: Context<MigrateLegacyAccount>) -> Result<()> {
+    let accounts = ctx.accounts;
+    let program_entry = accounts.program_entry.as_ref().map(|entry| entry.to_account_info());
+    let legacy = accounts.legacy.to_account_info();
+    let payer = accounts.payer.to_account_info();
+    migrate_legacy(
+        &accounts.registry.key(),
+        &legacy,
+        &accounts.destination.to_account_info(),
+        program_entry.as_ref(),
+        &payer,
+        &accounts.system_program.to_account_info(),
+    )?;
+    close_account(&legacy, &payer)
+}
+
+/// Batched `migrate_legacy_account`. Items whose legacy account is already
+/// closed are skipped, so a sweep can be re-sent after a partial run; any
+/// other failure fails the whole batch, with its index logged.
+pub fn migrate_legacy_accounts<'info>(ctx: Context<'_, '_, 'info, 'info, MigrateLegacyAccounts<'info>>) -> Result<()> {
+    let items = ctx.remaining_accounts.len() / MIGRATE_LEGACY_ACCOUNTS_ACCOUNTS;
+    let chunks = batch_accounts(ctx.remaining_accounts, items, MIGRATE_LEGACY_ACCOUNTS_ACCOUNTS)?;
+    let registry = ctx.accounts.registry.key();
+    let payer = ctx.accounts.payer.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+
+    let (mut migrated, mut skipped) = (Vec::new(), 0u64);
+    for (index, chunk) in chunks.enumerate() {
+        let [legacy, destination, program_entry] = chunk else {
+            unreachable!()
+        };
+        if legacy.owner != &crate::ID {
+            skipped += 1;
+            continue;
+        }
+        batch_item(
+            index,
+            migrate_legacy(&registry, legacy, destination, Some(program_entry), &payer, &system_program),
+        )?;
+        migrated.push(legacy);
+    }
+    for legacy in &migrated {
+        close_account(legacy, &payer)?;
+    }
+
+    let migrated = migrated.len();
+    msg!("Legacy accounts migrated: migrated={}, skipped={}", migrated, skipped);
+    Ok(())
+}
+
+/// Migrates `account` as the type its discriminator names. `false` if it
+/// was already current.
+fn migrate<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<bool> {
+    require_keys_eq!(*account.owner, crate::ID, ErrorCode::ConstraintOwner);
+    require!(account.is_writable, ErrorCode::ConstraintMut);
+
+    match discriminator(account)? {
+        Registry::DISCRIMINATOR if account.data_len() == v0::Registry::SPACE => {
+            let data = account.try_borrow_data()?.to_vec();
+            let registry = v0::Registry::try_from_account_data(&data)?.upgrade();
+            write_upgraded(account, payer, system_program, registry, 0)
+        }
+        Registry::DISCRIMINATOR => migrate_as::<Registry>(account, payer, system_program),
+        ProgramEntry::DISCRIMINATOR => migrate_as::<ProgramEntry>(account, payer, system_program),
+        AnalysisReport::DISCRIMINATOR => migrate_as::<AnalysisReport>(account, payer, system_program),
+        ProgramMetadata::DISCRIMINATOR => migrate_as::<ProgramMetadata>(account, payer, system_program),
+        AnalysisCommitment::DISCRIMINATOR => migrate_as::<AnalysisCommitment>(account, payer, system_program),
+        SkillEntry::DISCRIMINATOR => migrate_as::<SkillEntry>(account, payer, system_program),
+        AuditorAccount::DISCRIMINATOR => migrate_as::<AuditorAccount>(account, payer, system_program),
+        VerificationBadge::DISCRIMINATOR => migrate_as::<VerificationBadge>(account, payer, system_program),
+        ContextPattern::DISCRIMINATOR => migrate_as::<ContextPattern>(account, payer, system_program),
+        _ => err!(ErrorCode::AccountDiscriminatorMismatch),
+    }
+}
+
+fn migrate_as<'info, T: Versioned>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<bool> {
+    let upgraded = schema::deserialize::<T>(&account.try_borrow_data()?)?;
+    let from = upgraded.schema_version();
+    require!(from <= CURRENT_SCHEMA_VERSION, ParityError::UnsupportedSchemaVersion);
+    if from == CURRENT_SCHEMA_VERSION {
+        return Ok(false);
+    }
+    write_upgraded(account, payer, system_program, upgraded, from)
+}
+
+/// Brings `upgraded`, read from `account` at version `from`, to the current
+/// version and writes it back, growing the account if it needs more space.
+fn write_upgraded<'info, T: Versioned>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    mut upgraded: T,
+    from: u8,
+) -> Result<bool> {
+    schema::upgrade(&mut upgraded)?;
+    if account.data_len() < T::SPACE {
+        let rent = Rent::get()?.minimum_balance(T::SPACE).saturating_sub(account.lamports());
+        if rent > 0 {
+            let transfer = Transfer { from: payer.clone(), to: account.clone() };
+            system_program::transfer(CpiContext::new(system_program.clone(), transfer), rent)?;
+        }
+        account.realloc(T::SPACE, true)?;
+    }
+    upgraded.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+
+    msg!("Account {} migrated from schema version {} to {}", account.key, from, CURRENT_SCHEMA_VERSION);
+    Ok(true)
+}
+
+/// Moves the legacy account `legacy` of `registry` to `destination`, as the
+/// type its discriminator names.
+///
+/// Closing `legacy` is left to the caller, after every CPI of the
+/// instruction: lamports moved directly between accounts ahead of a CPI that
+/// does not pass both of them leave the instruction unbalanced.
+fn migrate_legacy<'info>(
+    registry: &Pubkey,
+    legacy: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    program_entry: Option<&AccountInfo<'info>>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    require_keys_eq!(*legacy.owner, crate::ID, ErrorCode::ConstraintOwner);
+    require!(legacy.is_writable && destination.is_writable, ErrorCode::ConstraintMut);
+    let data = legacy.try_borrow_data()?.to_vec();
+    let moving = Moving { legacy, destination, payer, system_program };
+
+    match discriminator(legacy)? {
+        ProgramEntry::DISCRIMINATOR => {
+            let old = read_legacy::<v0::ProgramEntry>(&data)?;
+            let hash = old.program_hash;
+            require_legacy_address(legacy, &[b"program", &hash, &[old.bump]])?;
+            moving.to(&[b"program", registry.as_ref(), &hash], |bump| old.upgrade(*registry, bump))
+        }
+        AnalysisReport::DISCRIMINATOR => {
+            let old = read_legacy::<v0::AnalysisReport>(&data)?;
+            let auditor = old.auditor;
+            require_legacy_address(legacy, &[b"analysis", old.program_entry.as_ref(), auditor.as_ref(), &[old.bump]])?;
+            let entry = migrated_entry(program_entry, registry, &old.program_entry)?;
+            moving.to(&[b"analysis", entry.as_ref(), auditor.as_ref()], |bump| old.upgrade(entry, bump))
+        }
+        SkillEntry::DISCRIMINATOR => {
+            let old = read_legacy::<v0::SkillEntry>(&data)?;
+            let (authority, name) = (old.authority, old.name.clone());
+            require_legacy_address(legacy, &[b"skill", name.as_bytes(), &[old.bump]])?;
+            let seeds: &[&[u8]] = &[b"skill", registry.as_ref(), authority.as_ref(), name.as_bytes()];
+            moving.to(seeds, |bump| old.upgrade(*registry, bump))
+        }
+        AuditorAccount::DISCRIMINATOR => {
+            let old = read_legacy::<v0::AuditorAccount>(&data)?;
+            let authority = old.authority;
+            require_legacy_address(legacy, &[b"auditor", authority.as_ref(), &[old.bump]])?;
+            moving.to(&[b"auditor", registry.as_ref(), authority.as_ref()], |bump| old.upgrade(*registry, bump))
+        }
+        VerificationBadge::DISCRIMINATOR => {
+            let old = read_legacy::<v0::VerificationBadge>(&data)?;
+            require_legacy_address(legacy, &[b"badge", old.program_entry.as_ref(), &[old.bump]])?;
+            let entry = migrated_entry(program_entry, registry, &old.program_entry)?;
+            moving.to(&[b"badge", entry.as_ref()], |bump| old.upgrade(entry, bump))
+        }
+        ContextPattern::DISCRIMINATOR => {
+            let old = read_legacy::<v0::ContextPattern>(&data)?;
+            let (submitter, pattern_id) = (old.submitter, old.pattern_id.clone());
+            require_legacy_address(legacy, &[b"pattern", pattern_id.as_bytes(), &[old.bump]])?;
+            let seeds: &[&[u8]] = &[b"pattern", registry.as_ref(), submitter.as_ref(), pattern_id.as_bytes()];
+            moving.to(seeds, |bump| old.upgrade(*registry, bump))
+        }
+        // The registry migrates in place, and the other types came later.
+        _ => err!(ErrorCode::AccountDiscriminatorMismatch),
+    }
+}
+
+/// A legacy account on its way to its new address.
+struct Moving<'a, 'info> {
+    legacy: &'a AccountInfo<'info>,
+    destination: &'a AccountInfo<'info>,
+    payer: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'info> Moving<'_, 'info> {
+    /// Creates the destination at the address `seeds` derive and writes
+    /// `upgrade(bump)` to it at the current version.
+    fn to<T: Versioned>(self, seeds: &[&[u8]], upgrade: impl FnOnce(u8) -> T) -> Result<()> {
+        let bump = create_program_account(
+            self.payer,
+            self.system_program,
+            self.destination,
+            seeds,
+            T::SPACE,
+            ParityError::MigrationAccountMismatch,
+        )?
+        .ok_or(ParityError::MigrationDestinationInUse)?;
+
+        let mut account = upgrade(bump);
+        schema::upgrade(&mut account)?;
+        account.try_serialize(&mut &mut self.destination.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "Account {} migrated from schema version 0 to {} at {}",
+            self.legacy.key,
+            CURRENT_SCHEMA_VERSION,
+            self.destination.key
+        );
+        Ok(())
+    }
+}
+
+fn discriminator(account: &AccountInfo) -> Result<[u8; 8]> {
+    let discriminator = account
+        .try_borrow_data()?
+        .get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ErrorCode::AccountDiscriminatorNotFound)?;
+    Ok(discriminator)
+}
+
+/// Reads `data` as the legacy layout `L`. Accounts of any other size have
+/// already been migrated.
+fn read_legacy<L: Legacy>(data: &[u8]) -> Result<L> {
+    require!(data.len() == L::SPACE, ParityError::AccountAlreadyMigrated);
+    L::try_from_account_data(data)
+}
+
+/// Checks that `legacy` sits at the address its legacy `seeds`, bump
+/// included, derive.
+fn require_legacy_address(legacy: &AccountInfo, seeds: &[&[u8]]) -> Result<()> {
+    let address =
+        Pubkey::create_program_address(seeds, &crate::ID).map_err(|_| ParityError::MigrationAccountMismatch)?;
+    require_keys_eq!(*legacy.key, address, ParityError::MigrationAccountMismatch);
+    Ok(())
+}
+
+/// The address of the migrated `program_entry`, which must be the one that
+/// was at `legacy_entry` in `registry`.
+fn migrated_entry(program_entry: Option<&AccountInfo>, registry: &Pubkey, legacy_entry: &Pubkey) -> Result<Pubkey> {
+    let info = program_entry.ok_or(ParityError::MigrationAccountMismatch)?;
+    require_keys_eq!(*info.owner, crate::ID, ErrorCode::ConstraintOwner);
+    let entry = schema::deserialize::<ProgramEntry>(&info.try_borrow_data()?)?;
+    let (address, _) = Pubkey::find_program_address(&[b"program", &entry.program_hash], &crate::ID);
+    require!(
+        entry.registry == *registry && address == *legacy_entry,
+        ParityError::MigrationAccountMismatch
+    );
+    Ok(info.key())
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: any parity account; its type is read from its discriminator.
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccounts<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateLegacyAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The registry from before versioning, already migrated.
+    #[account(
+        seeds = [b"registry", registry.namespace.as_bytes()],
+        bump = registry.bump,
+        constraint = registry.namespace.is_empty() @ ParityError::LegacyRegistryRequired
+    )]
+    pub registry: Account<'info, Registry>,
+
+    /// CHECK: a parity account from before versioning; its type is read
+    /// from its discriminator and its address checked against its old seeds.
+    #[account(mut, owner = crate::ID)]
+    pub legacy: UncheckedAccount<'info>,
+
+    /// CHECK: created here, at the address the current seeds derive.
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    /// The migrated entry of the program a legacy report or badge is for.
+    pub program_entry: Option<Account<'info, ProgramEntry>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateLegacyAccounts<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry", registry.namespace.as_bytes()],
+        bump = registry.bump,
+        constraint = registry.namespace.is_empty() @ ParityError::LegacyRegistryRequired
+    )]
+    pub registry: Account<'info, Registry>,
+
+    pub system_program: Program<'info, System>,
+}
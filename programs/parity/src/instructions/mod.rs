@@ -1,13 +1,25 @@
 pub mod registry;
 pub mod analysis;
+pub mod commitment;
 pub mod skill_registry;
 pub mod auditor;
 pub mod badge;
 pub mod context;
+pub mod builtins;
+pub mod metadata;
+pub mod attestation;
+pub mod batch;
+pub mod migration;
 
 pub use registry::*;
 pub use analysis::*;
+pub use commitment::*;
 pub use skill_registry::*;
 pub use auditor::*;
 pub use badge::*;
 pub use context::*;
+pub use builtins::*;
+pub use metadata::*;
+pub use attestation::*;
+pub use batch::*;
+pub use migration::*;
@@ -1,15 +1,29 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
 use crate::state::*;
+use crate::schema::CURRENT_SCHEMA_VERSION;
 use crate::errors::ParityError;
+use crate::program_hash::program_data_hash;
 
+/// Creates the registry under `namespace`. Every program, auditor, skill and
+/// pattern account is derived from the registry's address, so registries on
+/// the same deployment share nothing.
 pub fn initialize_registry(
     ctx: Context<InitializeRegistry>,
+    namespace: String,
     config: RegistryConfig,
 ) -> Result<()> {
+    require!(
+        !namespace.is_empty() && namespace.len() <= MAX_REGISTRY_NAMESPACE_LEN,
+        ParityError::InvalidRegistryNamespace
+    );
+    validate_config(&config)?;
+
     let registry = &mut ctx.accounts.registry;
     let clock = Clock::get()?;
 
     registry.authority = ctx.accounts.authority.key();
+    registry.namespace = namespace;
     registry.total_programs = 0;
     registry.total_analyses = 0;
     registry.total_skills = 0;
@@ -18,31 +32,73 @@ pub fn initialize_registry(
     registry.min_score_for_badge = config.min_score_for_badge;
     registry.analysis_fee_lamports = config.analysis_fee_lamports;
     registry.is_paused = config.is_paused;
+    registry.reveal_delay_seconds = config.reveal_delay_seconds;
+    registry.reveal_quorum = config.reveal_quorum;
+    registry.commitment_ttl_seconds = config.commitment_ttl_seconds;
     registry.bump = ctx.bumps.registry;
+    registry.schema_version = CURRENT_SCHEMA_VERSION;
     registry.created_at = clock.unix_timestamp;
     registry.updated_at = clock.unix_timestamp;
 
-    msg!("Registry initialized by {}", ctx.accounts.authority.key());
+    msg!(
+        "Registry {} initialized by {}",
+        registry.namespace,
+        ctx.accounts.authority.key()
+    );
     Ok(())
 }
 
+/// Registers `program_hash` as a build of the deployed program, with `owner`
+/// as the entry's owner. The upgrade authority recorded in the program's
+/// `ProgramData` has to sign, or the registry authority on its behalf for
+/// programs that can no longer be upgraded.
+///
+/// `program_hash` must be the hash of the bytes deployed right now. Entries
+/// live at an address derived from the hash, so an unchecked hash would let
+/// the authority of any program claim the entry of someone else's.
 pub fn register_program(
     ctx: Context<RegisterProgram>,
     program_hash: [u8; 32],
     framework: Framework,
     metadata_uri: String,
+) -> Result<()> {
+    require_deployed_hash(&ctx.accounts.program_data.to_account_info(), program_hash)?;
+    init_program_entry(
+        &mut ctx.accounts.program_entry,
+        &mut ctx.accounts.registry,
+        ctx.accounts.owner.key(),
+        program_hash,
+        ctx.accounts.program.key(),
+        framework,
+        metadata_uri,
+        ctx.bumps.program_entry,
+    )
+}
+
+/// Validates a registration and writes it to a freshly created entry.
+/// Shared by `register_program` and `register_programs`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn init_program_entry(
+    program_entry: &mut ProgramEntry,
+    registry: &mut Account<Registry>,
+    owner: Pubkey,
+    program_hash: [u8; 32],
+    program_id: Pubkey,
+    framework: Framework,
+    metadata_uri: String,
+    bump: u8,
 ) -> Result<()> {
     require!(
         metadata_uri.len() <= MAX_METADATA_URI_LEN,
         ParityError::MetadataUriTooLong
     );
 
-    let program_entry = &mut ctx.accounts.program_entry;
-    let registry = &mut ctx.accounts.registry;
     let clock = Clock::get()?;
 
-    program_entry.owner = ctx.accounts.owner.key();
+    program_entry.registry = registry.key();
+    program_entry.owner = owner;
     program_entry.program_hash = program_hash;
+    program_entry.program_id = program_id;
     program_entry.framework = framework;
     program_entry.metadata_uri = metadata_uri;
     program_entry.registered_at = clock.unix_timestamp;
@@ -50,9 +106,20 @@ pub fn register_program(
     program_entry.analysis_count = 0;
     program_entry.latest_score = 0;
     program_entry.is_verified = false;
-    program_entry.bump = ctx.bumps.program_entry;
+    program_entry.commit_count = 0;
+    program_entry.open_commitments = 0;
+    program_entry.expired_commitments = 0;
+    program_entry.pending_owner = None;
+    program_entry.is_retired = false;
+    program_entry.bump = bump;
+    program_entry.schema_version = CURRENT_SCHEMA_VERSION;
+    program_entry.round_start = 0;
+    program_entry.round_revealed_at = None;
 
-    registry.total_programs = registry.total_programs.checked_add(1).unwrap();
+    registry.total_programs = registry
+        .total_programs
+        .checked_add(1)
+        .ok_or(ParityError::ArithmeticOverflow)?;
     registry.updated_at = clock.unix_timestamp;
 
     msg!(
@@ -63,23 +130,272 @@ pub fn register_program(
     Ok(())
 }
 
+/// Hands a program entry to `new_owner`. Lets the registry authority recover
+/// an entry registered by someone other than the program's team, such as a
+/// previous upgrade authority.
+pub fn reassign_program_owner(ctx: Context<ReassignProgramOwner>, new_owner: Pubkey) -> Result<()> {
+    let program_entry = &mut ctx.accounts.program_entry;
+    let clock = Clock::get()?;
+
+    let previous_owner = program_entry.owner;
+    program_entry.owner = new_owner;
+    program_entry.pending_owner = None;
+    program_entry.updated_at = clock.unix_timestamp;
+
+    msg!(
+        "Program owner reassigned: {} -> {}",
+        previous_owner,
+        new_owner
+    );
+    Ok(())
+}
+
+pub fn update_program_metadata(
+    ctx: Context<UpdateProgramEntry>,
+    framework: Framework,
+    metadata_uri: String,
+) -> Result<()> {
+    require!(
+        metadata_uri.len() <= MAX_METADATA_URI_LEN,
+        ParityError::MetadataUriTooLong
+    );
+
+    let program_entry = &mut ctx.accounts.program_entry;
+    let clock = Clock::get()?;
+
+    program_entry.framework = framework;
+    program_entry.metadata_uri = metadata_uri;
+    program_entry.updated_at = clock.unix_timestamp;
+
+    msg!("Program metadata updated: framework={:?}", framework);
+    Ok(())
+}
+
+/// Proposes `new_owner` as the entry's owner; nothing changes until it calls
+/// `accept_program_ownership`. `None` withdraws a pending proposal.
+pub fn transfer_program_ownership(
+    ctx: Context<UpdateProgramEntry>,
+    new_owner: Option<Pubkey>,
+) -> Result<()> {
+    let program_entry = &mut ctx.accounts.program_entry;
+    let clock = Clock::get()?;
+
+    program_entry.pending_owner = new_owner;
+    program_entry.updated_at = clock.unix_timestamp;
+
+    msg!("Program ownership transfer proposed: {:?}", new_owner);
+    Ok(())
+}
+
+pub fn accept_program_ownership(ctx: Context<AcceptProgramOwnership>) -> Result<()> {
+    let program_entry = &mut ctx.accounts.program_entry;
+    let clock = Clock::get()?;
+
+    let previous_owner = program_entry.owner;
+    program_entry.owner = ctx.accounts.new_owner.key();
+    program_entry.pending_owner = None;
+    program_entry.updated_at = clock.unix_timestamp;
+
+    msg!(
+        "Program ownership transferred: {} -> {}",
+        previous_owner,
+        program_entry.owner
+    );
+    Ok(())
+}
+
+/// Recomputes the canonical hash of the deployed program from its
+/// `ProgramData` and checks it against the entry. Fails with
+/// `ProgramHashMismatch` once the program has been upgraded to different
+/// bytes; hashing costs roughly one compute unit per two bytes of bytecode.
+pub fn verify_program_hash(ctx: Context<VerifyProgramHash>) -> Result<()> {
+    require_deployed_hash(
+        &ctx.accounts.program_data.to_account_info(),
+        ctx.accounts.program_entry.program_hash,
+    )?;
+
+    msg!(
+        "Program {} matches entry {}",
+        ctx.accounts.program_entry.program_id,
+        ctx.accounts.program_entry.key()
+    );
+    Ok(())
+}
+
+/// Records the deployed program behind an entry migrated from the first
+/// release, which never recorded it, so `verify_program_hash` can find it.
+/// Takes the same proof as `register_program`: the program's upgrade
+/// authority signs, or the registry authority for an immutable program, and
+/// the deployed bytes hash to the entry's `program_hash`.
+pub fn bind_program_id(ctx: Context<BindProgramId>) -> Result<()> {
+    require_deployed_hash(
+        &ctx.accounts.program_data.to_account_info(),
+        ctx.accounts.program_entry.program_hash,
+    )?;
+
+    let program_entry = &mut ctx.accounts.program_entry;
+    program_entry.program_id = ctx.accounts.program.key();
+    program_entry.updated_at = Clock::get()?.unix_timestamp;
+
+    msg!(
+        "Program {} bound to entry {}",
+        program_entry.program_id,
+        program_entry.key()
+    );
+    Ok(())
+}
+
+/// Fails with `ProgramHashMismatch` unless the program held in
+/// `program_data` hashes to `program_hash`.
+pub(crate) fn require_deployed_hash(program_data: &AccountInfo, program_hash: [u8; 32]) -> Result<()> {
+    let deployed = program_data_hash(&program_data.try_borrow_data()?);
+    require!(
+        deployed == Some(program_hash),
+        ParityError::ProgramHashMismatch
+    );
+    Ok(())
+}
+
+/// Retires a program entry and closes it along with everything attached to
+/// it.
+///
+/// The first call retires the entry: it stops taking analyses, commitments
+/// and badges, and loses its verified status. Every call closes the badge,
+/// if there is one, and the reports passed as `(analysis_report, auditor)`
+/// pairs in the remaining accounts, refunding their rent to the issuer and
+/// the auditors. Once no reports and no open commitments remain, the call
+/// also closes the entry and its metadata, refunding the owner, and removes
+/// the entry from the registry's count. Programs
+/// with more reports than fit in one transaction, or with commitments still
+/// to expire, are deregistered over several calls. The auditors' statistics
+/// are lifetime counts and keep the closed reports.
+pub fn deregister_program<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DeregisterProgram<'info>>,
+) -> Result<()> {
+    let pairs = ctx.remaining_accounts.chunks_exact(2);
+    require!(
+        pairs.remainder().is_empty(),
+        ParityError::DeregistrationAccountMismatch
+    );
+    let accounts = ctx.accounts;
+    let entry_key = accounts.program_entry.key();
+    let clock = Clock::get()?;
+
+    let badge = accounts.verification_badge.to_account_info();
+    if badge.owner == &crate::ID {
+        let issuer = VerificationBadge::try_deserialize(&mut &badge.try_borrow_data()?[..])?.issuer;
+        require_keys_eq!(
+            accounts.badge_issuer.key(),
+            issuer,
+            ParityError::DeregistrationAccountMismatch
+        );
+        close_account(&badge, &accounts.badge_issuer.to_account_info())?;
+    }
+
+    let mut reports_closed = 0u32;
+    for pair in pairs {
+        let (report, auditor) = (&pair[0], &pair[1]);
+        require!(
+            report.owner == &crate::ID && report.is_writable && auditor.is_writable,
+            ParityError::DeregistrationAccountMismatch
+        );
+        let filed = AnalysisReport::try_deserialize(&mut &report.try_borrow_data()?[..])?;
+        require!(
+            filed.program_entry == entry_key && filed.auditor == auditor.key(),
+            ParityError::DeregistrationAccountMismatch
+        );
+        close_account(report, auditor)?;
+        reports_closed += 1;
+    }
+
+    let program_entry = &mut accounts.program_entry;
+    let registry = &mut accounts.registry;
+    program_entry.is_retired = true;
+    program_entry.is_verified = false;
+    program_entry.pending_owner = None;
+    program_entry.updated_at = clock.unix_timestamp;
+    program_entry.analysis_count = program_entry
+        .analysis_count
+        .checked_sub(reports_closed)
+        .ok_or(ParityError::ArithmeticOverflow)?;
+    registry.total_analyses = registry
+        .total_analyses
+        .checked_sub(reports_closed as u64)
+        .ok_or(ParityError::ArithmeticOverflow)?;
+    registry.updated_at = clock.unix_timestamp;
+
+    if program_entry.analysis_count > 0 || program_entry.open_commitments > 0 {
+        msg!(
+            "Program deregistering: closed {} reports, {} reports and {} commitments remain",
+            reports_closed,
+            program_entry.analysis_count,
+            program_entry.open_commitments
+        );
+        return Ok(());
+    }
+
+    registry.total_programs = registry
+        .total_programs
+        .checked_sub(1)
+        .ok_or(ParityError::ArithmeticOverflow)?;
+    let owner = accounts.owner.to_account_info();
+    let metadata = accounts.program_metadata.to_account_info();
+    if metadata.owner == &crate::ID {
+        close_account(&metadata, &owner)?;
+    }
+    close_account(&program_entry.to_account_info(), &owner)?;
+
+    msg!("Program deregistered: closed {} reports", reports_closed);
+    Ok(())
+}
+
 pub fn update_registry_config(
     ctx: Context<UpdateRegistryConfig>,
     new_config: RegistryConfig,
 ) -> Result<()> {
+    validate_config(&new_config)?;
+
     let registry = &mut ctx.accounts.registry;
     let clock = Clock::get()?;
 
     registry.min_score_for_badge = new_config.min_score_for_badge;
     registry.analysis_fee_lamports = new_config.analysis_fee_lamports;
     registry.is_paused = new_config.is_paused;
+    registry.reveal_delay_seconds = new_config.reveal_delay_seconds;
+    registry.reveal_quorum = new_config.reveal_quorum;
+    registry.commitment_ttl_seconds = new_config.commitment_ttl_seconds;
     registry.updated_at = clock.unix_timestamp;
 
     msg!("Registry config updated by {}", ctx.accounts.authority.key());
     Ok(())
 }
 
+/// Moves all of `account`'s lamports to `destination` and hands the account
+/// back to the system program.
+pub(crate) fn close_account<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ParityError::ArithmeticOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&System::id());
+    account.realloc(0, false)?;
+    Ok(())
+}
+
+/// A commitment must be able to open by delay before it expires.
+fn validate_config(config: &RegistryConfig) -> Result<()> {
+    require!(
+        config.reveal_delay_seconds >= 0
+            && config.commitment_ttl_seconds > config.reveal_delay_seconds,
+        ParityError::InvalidCommitRevealWindow
+    );
+    Ok(())
+}
+
 #[derive(Accounts)]
+#[instruction(namespace: String)]
 pub struct InitializeRegistry<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -88,7 +404,7 @@ pub struct InitializeRegistry<'info> {
         init,
         payer = authority,
         space = 8 + Registry::INIT_SPACE,
-        seeds = [b"registry"],
+        seeds = [b"registry", namespace.as_bytes()],
         bump
     )]
     pub registry: Account<'info, Registry>,
@@ -104,7 +420,7 @@ pub struct RegisterProgram<'info> {
 
     #[account(
         mut,
-        seeds = [b"registry"],
+        seeds = [b"registry", registry.namespace.as_bytes()],
         bump = registry.bump
     )]
     pub registry: Account<'info, Registry>,
@@ -113,24 +429,188 @@ pub struct RegisterProgram<'info> {
         init,
         payer = owner,
         space = 8 + ProgramEntry::INIT_SPACE,
-        seeds = [b"program", program_hash.as_ref()],
+        seeds = [b"program", registry.key().as_ref(), program_hash.as_ref()],
         bump
     )]
     pub program_entry: Account<'info, ProgramEntry>,
 
+    /// The program's upgrade authority, or the registry authority if there is
+    /// none. May be the same key as `owner`.
+    pub authority: Signer<'info>,
+
+    /// CHECK: only its address is used, to derive `program_data`.
+    pub program: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [program.key().as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            || (program_data.upgrade_authority_address.is_none() && authority.key() == registry.authority)
+            @ ParityError::ProgramOwnershipUnproven
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct ReassignProgramOwner<'info> {
+    #[account(
+        constraint = authority.key() == registry.authority @ ParityError::UnauthorizedRegistryAuthority
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry", registry.namespace.as_bytes()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"program", registry.key().as_ref(), program_entry.program_hash.as_ref()],
+        bump = program_entry.bump
+    )]
+    pub program_entry: Account<'info, ProgramEntry>,
+}
+
+/// The owner changing its own, not yet retired, entry.
+#[derive(Accounts)]
+pub struct UpdateProgramEntry<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program", program_entry.registry.as_ref(), program_entry.program_hash.as_ref()],
+        bump = program_entry.bump,
+        constraint = program_entry.owner == owner.key() @ ParityError::UnauthorizedProgramOwner,
+        constraint = !program_entry.is_retired @ ParityError::ProgramRetired
+    )]
+    pub program_entry: Account<'info, ProgramEntry>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptProgramOwnership<'info> {
+    pub new_owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program", program_entry.registry.as_ref(), program_entry.program_hash.as_ref()],
+        bump = program_entry.bump,
+        constraint = program_entry.pending_owner == Some(new_owner.key()) @ ParityError::NotPendingOwner,
+        constraint = !program_entry.is_retired @ ParityError::ProgramRetired
+    )]
+    pub program_entry: Account<'info, ProgramEntry>,
+}
+
+#[derive(Accounts)]
+pub struct BindProgramId<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry", registry.namespace.as_bytes()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"program", registry.key().as_ref(), program_entry.program_hash.as_ref()],
+        bump = program_entry.bump,
+        constraint = program_entry.owner == owner.key() @ ParityError::UnauthorizedProgramOwner,
+        constraint = !program_entry.is_retired @ ParityError::ProgramRetired,
+        constraint = program_entry.program_id == Pubkey::default() @ ParityError::ProgramIdAlreadyBound
+    )]
+    pub program_entry: Account<'info, ProgramEntry>,
+
+    /// The program's upgrade authority, or the registry authority if there is
+    /// none. May be the same key as `owner`.
+    pub authority: Signer<'info>,
+
+    /// CHECK: only its address is used, to derive `program_data`.
+    pub program: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [program.key().as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            || (program_data.upgrade_authority_address.is_none() && authority.key() == registry.authority)
+            @ ParityError::ProgramOwnershipUnproven
+    )]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyProgramHash<'info> {
+    #[account(
+        seeds = [b"program", program_entry.registry.as_ref(), program_entry.program_hash.as_ref()],
+        bump = program_entry.bump
+    )]
+    pub program_entry: Account<'info, ProgramEntry>,
+
+    #[account(
+        seeds = [program_entry.program_id.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID
+    )]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+#[derive(Accounts)]
+pub struct DeregisterProgram<'info> {
+    /// Receives the entry's rent once it is closed.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", registry.namespace.as_bytes()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"program", registry.key().as_ref(), program_entry.program_hash.as_ref()],
+        bump = program_entry.bump,
+        constraint = program_entry.owner == owner.key() @ ParityError::UnauthorizedProgramOwner
+    )]
+    pub program_entry: Account<'info, ProgramEntry>,
+
+    /// CHECK: the program's badge address, which may hold no account.
+    #[account(
+        mut,
+        seeds = [b"badge", program_entry.key().as_ref()],
+        bump
+    )]
+    pub verification_badge: UncheckedAccount<'info>,
+
+    /// CHECK: receives the badge's rent; checked against its issuer when
+    /// there is a badge.
+    #[account(mut)]
+    pub badge_issuer: UncheckedAccount<'info>,
+
+    /// CHECK: the program's metadata address, which may hold no account.
+    #[account(
+        mut,
+        seeds = [b"metadata", program_entry.key().as_ref()],
+        bump
+    )]
+    pub program_metadata: UncheckedAccount<'info>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateRegistryConfig<'info> {
     #[account(
-        constraint = authority.key() == registry.authority @ ParityError::UnauthorizedAuditor
+        constraint = authority.key() == registry.authority @ ParityError::UnauthorizedRegistryAuthority
     )]
     pub authority: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"registry"],
+        seeds = [b"registry", registry.namespace.as_bytes()],
         bump = registry.bump
     )]
     pub registry: Account<'info, Registry>,
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
+use crate::schema::CURRENT_SCHEMA_VERSION;
 use crate::errors::ParityError;
 
 pub fn register_skill(
@@ -8,6 +9,31 @@ pub fn register_skill(
     version: String,
     description: String,
     skill_type: SkillType,
+) -> Result<()> {
+    init_skill_entry(
+        &mut ctx.accounts.skill_entry,
+        &mut ctx.accounts.registry,
+        ctx.accounts.authority.key(),
+        name,
+        version,
+        description,
+        skill_type,
+        ctx.bumps.skill_entry,
+    )
+}
+
+/// Validates a skill and writes it to a freshly created entry in
+/// `authority`'s namespace. Shared by `register_skill` and `register_skills`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn init_skill_entry(
+    skill: &mut SkillEntry,
+    registry: &mut Account<Registry>,
+    authority: Pubkey,
+    name: String,
+    version: String,
+    description: String,
+    skill_type: SkillType,
+    bump: u8,
 ) -> Result<()> {
     require!(name.len() <= MAX_SKILL_NAME_LEN, ParityError::SkillNameTooLong);
     require!(
@@ -19,11 +45,11 @@ pub fn register_skill(
         ParityError::SkillDescriptionTooLong
     );
 
-    let skill = &mut ctx.accounts.skill_entry;
-    let registry = &mut ctx.accounts.registry;
     let clock = Clock::get()?;
 
-    skill.authority = ctx.accounts.authority.key();
+    skill.registry = registry.key();
+    skill.authority = authority;
+    skill.namespace = authority;
     skill.name = name.clone();
     skill.version = version;
     skill.description = description;
@@ -32,9 +58,13 @@ pub fn register_skill(
     skill.is_deprecated = false;
     skill.registered_at = clock.unix_timestamp;
     skill.updated_at = clock.unix_timestamp;
-    skill.bump = ctx.bumps.skill_entry;
+    skill.bump = bump;
+    skill.schema_version = CURRENT_SCHEMA_VERSION;
 
-    registry.total_skills = registry.total_skills.checked_add(1).unwrap();
+    registry.total_skills = registry
+        .total_skills
+        .checked_add(1)
+        .ok_or(ParityError::ArithmeticOverflow)?;
     registry.updated_at = clock.unix_timestamp;
 
     msg!("Skill registered: {}", name);
@@ -88,7 +118,7 @@ pub struct RegisterSkill<'info> {
 
     #[account(
         mut,
-        seeds = [b"registry"],
+        seeds = [b"registry", registry.namespace.as_bytes()],
         bump = registry.bump
     )]
     pub registry: Account<'info, Registry>,
@@ -97,7 +127,7 @@ pub struct RegisterSkill<'info> {
         init,
         payer = authority,
         space = 8 + SkillEntry::INIT_SPACE,
-        seeds = [b"skill", name.as_bytes()],
+        seeds = [b"skill", registry.key().as_ref(), authority.key().as_ref(), name.as_bytes()],
         bump
     )]
     pub skill_entry: Account<'info, SkillEntry>,
@@ -114,7 +144,7 @@ pub struct UpdateSkill<'info> {
 
     #[account(
         mut,
-        seeds = [b"skill", skill_entry.name.as_bytes()],
+        seeds = [b"skill", skill_entry.registry.as_ref(), skill_entry.namespace.as_ref(), skill_entry.name.as_bytes()],
         bump = skill_entry.bump
     )]
     pub skill_entry: Account<'info, SkillEntry>,
@@ -129,7 +159,7 @@ pub struct DeprecateSkill<'info> {
 
     #[account(
         mut,
-        seeds = [b"skill", skill_entry.name.as_bytes()],
+        seeds = [b"skill", skill_entry.registry.as_ref(), skill_entry.namespace.as_ref(), skill_entry.name.as_bytes()],
         bump = skill_entry.bump
     )]
     pub skill_entry: Account<'info, SkillEntry>,
@@ -4,11 +4,17 @@ pub mod errors;
 pub mod instructions;
 pub mod state;
 pub mod context_engine;
+pub mod detection_rule;
+pub mod findings;
+pub mod program_hash;
+pub mod schema;
+pub mod scoring;
 pub mod skills;
 
 use instructions::*;
+use state::*;
 
-declare_id!("PARTYxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx1");
+declare_id!("Parity1111111111111111111111111111111111111");
 
 #[program]
 pub mod parity {
@@ -16,9 +22,10 @@ pub mod parity {
 
     pub fn initialize_registry(
         ctx: Context<InitializeRegistry>,
+        namespace: String,
         config: RegistryConfig,
     ) -> Result<()> {
-        instructions::registry::initialize_registry(ctx, config)
+        instructions::registry::initialize_registry(ctx, namespace, config)
     }
 
     pub fn register_program(
@@ -30,12 +37,61 @@ pub mod parity {
         instructions::registry::register_program(ctx, program_hash, framework, metadata_uri)
     }
 
+    pub fn reassign_program_owner(
+        ctx: Context<ReassignProgramOwner>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        instructions::registry::reassign_program_owner(ctx, new_owner)
+    }
+
+    pub fn update_program_metadata(
+        ctx: Context<UpdateProgramEntry>,
+        framework: Framework,
+        metadata_uri: String,
+    ) -> Result<()> {
+        instructions::registry::update_program_metadata(ctx, framework, metadata_uri)
+    }
+
+    pub fn transfer_program_ownership(
+        ctx: Context<UpdateProgramEntry>,
+        new_owner: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::registry::transfer_program_ownership(ctx, new_owner)
+    }
+
+    pub fn accept_program_ownership(ctx: Context<AcceptProgramOwnership>) -> Result<()> {
+        instructions::registry::accept_program_ownership(ctx)
+    }
+
+    pub fn set_program_metadata(
+        ctx: Context<SetProgramMetadata>,
+        details: ProgramDetails,
+    ) -> Result<()> {
+        instructions::metadata::set_program_metadata(ctx, details)
+    }
+
+    pub fn verify_program_hash(ctx: Context<VerifyProgramHash>) -> Result<()> {
+        instructions::registry::verify_program_hash(ctx)
+    }
+
+    pub fn bind_program_id(ctx: Context<BindProgramId>) -> Result<()> {
+        instructions::registry::bind_program_id(ctx)
+    }
+
+    pub fn deregister_program<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DeregisterProgram<'info>>,
+    ) -> Result<()> {
+        instructions::registry::deregister_program(ctx)
+    }
+
     pub fn submit_analysis(
         ctx: Context<SubmitAnalysis>,
         score: u8,
         findings_hash: [u8; 32],
         skills_used: Vec<String>,
         findings_count: AnalysisFindingsCount,
+        score_override: Option<[u8; 32]>,
+        source: SourceRevision,
     ) -> Result<()> {
         instructions::analysis::submit_analysis(
             ctx,
@@ -43,23 +99,83 @@ pub mod parity {
             findings_hash,
             skills_used,
             findings_count,
+            score_override,
+            source,
         )
     }
 
+    pub fn submit_signed_analysis(
+        ctx: Context<SubmitSignedAnalysis>,
+        attestation: AnalysisAttestation,
+    ) -> Result<()> {
+        instructions::attestation::submit_signed_analysis(ctx, attestation)
+    }
+
     pub fn update_analysis(
         ctx: Context<UpdateAnalysis>,
         new_score: u8,
         new_findings_hash: [u8; 32],
         new_findings_count: AnalysisFindingsCount,
+        score_override: Option<[u8; 32]>,
+        source: SourceRevision,
     ) -> Result<()> {
         instructions::analysis::update_analysis(
             ctx,
             new_score,
             new_findings_hash,
             new_findings_count,
+            score_override,
+            source,
+        )
+    }
+
+    pub fn verify_finding_inclusion(
+        ctx: Context<VerifyFinding>,
+        finding: findings::Finding,
+        proof: findings::MerkleProof,
+    ) -> Result<()> {
+        instructions::analysis::verify_finding_inclusion(ctx, finding, proof)
+    }
+
+    pub fn verify_finding_exclusion(
+        ctx: Context<VerifyFinding>,
+        finding: findings::Finding,
+        proof: findings::ExclusionProof,
+    ) -> Result<()> {
+        instructions::analysis::verify_finding_exclusion(ctx, finding, proof)
+    }
+
+    pub fn commit_analysis(ctx: Context<CommitAnalysis>, commitment: [u8; 32]) -> Result<()> {
+        instructions::commitment::commit_analysis(ctx, commitment)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn reveal_analysis(
+        ctx: Context<RevealAnalysis>,
+        score: u8,
+        findings_hash: [u8; 32],
+        salt: [u8; 32],
+        skills_used: Vec<String>,
+        findings_count: AnalysisFindingsCount,
+        score_override: Option<[u8; 32]>,
+        source: SourceRevision,
+    ) -> Result<()> {
+        instructions::commitment::reveal_analysis(
+            ctx,
+            score,
+            findings_hash,
+            salt,
+            skills_used,
+            findings_count,
+            score_override,
+            source,
         )
     }
 
+    pub fn expire_commitment(ctx: Context<ExpireCommitment>) -> Result<()> {
+        instructions::commitment::expire_commitment(ctx)
+    }
+
     pub fn register_skill(
         ctx: Context<RegisterSkill>,
         name: String,
@@ -88,6 +204,12 @@ pub mod parity {
         instructions::skill_registry::deprecate_skill(ctx)
     }
 
+    pub fn bootstrap_builtins<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BootstrapBuiltins<'info>>,
+    ) -> Result<()> {
+        instructions::builtins::bootstrap_builtins(ctx)
+    }
+
     pub fn register_auditor(
         ctx: Context<RegisterAuditor>,
         name: String,
@@ -139,4 +261,43 @@ pub mod parity {
     ) -> Result<()> {
         instructions::registry::update_registry_config(ctx, new_config)
     }
+
+    pub fn register_programs<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RegisterPrograms<'info>>,
+        items: Vec<ProgramRegistration>,
+    ) -> Result<()> {
+        instructions::batch::register_programs(ctx, items)
+    }
+
+    pub fn register_skills<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RegisterSkills<'info>>,
+        items: Vec<SkillRegistration>,
+    ) -> Result<()> {
+        instructions::batch::register_skills(ctx, items)
+    }
+
+    pub fn submit_analyses<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SubmitAnalyses<'info>>,
+        items: Vec<AnalysisSubmission>,
+    ) -> Result<()> {
+        instructions::batch::submit_analyses(ctx, items)
+    }
+
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migration::migrate_account(ctx)
+    }
+
+    pub fn migrate_accounts<'info>(ctx: Context<'_, '_, 'info, 'info, MigrateAccounts<'info>>) -> Result<()> {
+        instructions::migration::migrate_accounts(ctx)
+    }
+
+    pub fn migrate_legacy_account(ctx: Context<MigrateLegacyAccount>) -> Result<()> {
+        instructions::migration::migrate_legacy_account(ctx)
+    }
+
+    pub fn migrate_legacy_accounts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateLegacyAccounts<'info>>,
+    ) -> Result<()> {
+        instructions::migration::migrate_legacy_accounts(ctx)
+    }
 }
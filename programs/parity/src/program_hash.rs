@@ -0,0 +1,31 @@
+//! The canonical `ProgramEntry.program_hash`.
+//!
+//! A program is identified by `sha256` of its executable bytes with trailing
+//! zero bytes removed. The loader zero-pads `ProgramData` to the allocated
+//! length, and verifiable-build tooling strips that padding before hashing,
+//! so a local `.so` and the deployed account hash the same. The same
+//! functions run on-chain in `verify_program_hash`.
+
+use anchor_lang::solana_program::bpf_loader_upgradeable::UpgradeableLoaderState;
+use anchor_lang::solana_program::hash::hash;
+
+/// Bytes of loader state preceding the executable in a `ProgramData` account.
+pub const PROGRAM_DATA_METADATA_LEN: usize = UpgradeableLoaderState::size_of_programdata_metadata();
+
+/// `bytes` without its trailing zero padding.
+pub fn executable_bytes(bytes: &[u8]) -> &[u8] {
+    let end = bytes.iter().rposition(|b| *b != 0).map_or(0, |last| last + 1);
+    &bytes[..end]
+}
+
+/// The canonical hash of a program's executable, e.g. the contents of its
+/// `.so`.
+pub fn program_hash(elf: &[u8]) -> [u8; 32] {
+    hash(executable_bytes(elf)).to_bytes()
+}
+
+/// The canonical hash of the program held in a `ProgramData` account's data;
+/// `None` if the data is too short to hold the loader state.
+pub fn program_data_hash(program_data: &[u8]) -> Option<[u8; 32]> {
+    program_data.get(PROGRAM_DATA_METADATA_LEN..).map(program_hash)
+}
@@ -0,0 +1,109 @@
+//! Versioned account layouts.
+//!
+//! Every account ends with a `schema_version` byte followed by
+//! `SCHEMA_RESERVED_LEN` reserved bytes. New fields go between the two, so an
+//! account written under an older version still deserializes while its
+//! reserved bytes cover them: the new fields read as zero until
+//! `migrate_account` upgrades it. Fields the reserved bytes do not cover grow
+//! the account, and the shorter layout each older version wrote is kept as an
+//! explicit struct in that version's module: [`v1`] for version 1, [`v0`] for
+//! accounts created before versioning.
+
+pub mod v0;
+pub mod v1;
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::errors::ParityError;
+use crate::state::*;
+
+/// The layout version new accounts are written with.
+pub const CURRENT_SCHEMA_VERSION: u8 = 2;
+
+/// An account with a versioned layout.
+pub trait Versioned: AccountSerialize + AccountDeserialize + Discriminator + Space {
+    /// The allocated size of an account at the current version.
+    const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    fn schema_version(&self) -> u8;
+
+    /// Marks the account as written under `version`, clearing the reserved
+    /// bytes.
+    fn set_schema_version(&mut self, version: u8);
+
+    /// Fills in fields added after version `from`. Fields taken from the
+    /// reserved bytes already read as zero, so only those needing another
+    /// value have to be set here.
+    fn upgrade(&mut self, _from: u8) -> Result<()> {
+        Ok(())
+    }
+
+    /// Reads an account shorter than the current layout through the layout
+    /// of the version it records. Types that never grew have no shorter
+    /// versioned layout, so their short accounts predate versioning.
+    fn deserialize_shorter(_data: &[u8]) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        err!(ParityError::UnversionedAccount)
+    }
+}
+
+macro_rules! versioned {
+    ($($account:ty $({ $($layouts:tt)* })?),* $(,)?) => {
+        $(
+            impl Versioned for $account {
+                fn schema_version(&self) -> u8 {
+                    self.schema_version
+                }
+
+                fn set_schema_version(&mut self, version: u8) {
+                    self.schema_version = version;
+                    self.reserved = [0; SCHEMA_RESERVED_LEN];
+                }
+
+                $($($layouts)*)?
+            }
+        )*
+    };
+}
+
+versioned!(
+    Registry,
+    ProgramEntry {
+        fn deserialize_shorter(data: &[u8]) -> Result<Self> {
+            Ok(v1::ProgramEntry::try_from_account_data(data)?.upgrade())
+        }
+    },
+    AnalysisReport,
+    ProgramMetadata,
+    AnalysisCommitment,
+    SkillEntry,
+    AuditorAccount,
+    VerificationBadge,
+    ContextPattern,
+);
+
+/// Deserializes an account written under any version. One shorter than the
+/// current layout is read through the layout of the version it records, with
+/// the fields added since at their defaults. Short accounts that record no
+/// version predate versioning and are rejected rather than misread; they
+/// decode with [`v0::Legacy::try_from_account_data`].
+pub fn deserialize<T: Versioned>(data: &[u8]) -> Result<T> {
+    if data.len() < T::SPACE {
+        return T::deserialize_shorter(data);
+    }
+    T::try_deserialize(&mut &data[..])
+}
+
+/// Upgrades `account` from its version to the current one.
+pub fn upgrade<T: Versioned>(account: &mut T) -> Result<()> {
+    let from = account.schema_version();
+    require!(from <= CURRENT_SCHEMA_VERSION, ParityError::UnsupportedSchemaVersion);
+    if from < CURRENT_SCHEMA_VERSION {
+        account.upgrade(from)?;
+        account.set_schema_version(CURRENT_SCHEMA_VERSION);
+    }
+    Ok(())
+}
@@ -0,0 +1,340 @@
+//! The account layouts of the first release, before accounts carried a
+//! schema version.
+//!
+//! Each struct here is the layout its namesake in `state` was written with
+//! then, under the same discriminator and at exactly `8 + INIT_SPACE` bytes.
+//! The first release had a single registry, so none of them records one, and
+//! they lived at addresses derived without it; the seeds are noted on each.
+//! `upgrade` converts them, field by field, to version 1 of the current
+//! layout.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::schema::Versioned;
+use crate::state::{
+    self, AnalysisFindingsCount, Framework, PatternType, Severity, SkillType, SourceRevision, VerificationTier,
+    SCHEMA_RESERVED_LEN,
+};
+
+/// Commitments did not exist in the first release; a migrated registry gets
+/// the expiry `parity registry init` defaults to, and no reveal delay or
+/// quorum, until its authority changes them.
+pub const MIGRATED_COMMITMENT_TTL_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+/// A layout from before versioning, read as version 0 of `Current`.
+pub trait Legacy: AnchorSerialize + AnchorDeserialize + Space {
+    type Current: Versioned;
+
+    /// The size every account of this layout was allocated with.
+    const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    /// Reads an account of this layout, discriminator included.
+    fn try_from_account_data(data: &[u8]) -> Result<Self> {
+        require!(data.len() == Self::SPACE, ErrorCode::AccountDidNotDeserialize);
+        require!(data[..8] == Self::Current::DISCRIMINATOR, ErrorCode::AccountDiscriminatorMismatch);
+        Self::deserialize(&mut &data[8..]).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+
+    /// The account's data as the first release wrote it.
+    fn to_account_data(&self) -> Vec<u8> {
+        let mut data = Self::Current::DISCRIMINATOR.to_vec();
+        self.serialize(&mut data).expect("writing to a Vec cannot fail");
+        data.resize(Self::SPACE, 0);
+        data
+    }
+}
+
+/// Seeds `[b"registry"]`, which derive the same address as the current
+/// `[b"registry", namespace]` with an empty namespace. It is migrated in place
+/// and keeps that empty namespace, which no new registry can take.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Registry {
+    pub authority: Pubkey,
+    pub total_programs: u64,
+    pub total_analyses: u64,
+    pub total_skills: u64,
+    pub total_auditors: u64,
+    pub total_patterns: u64,
+    pub min_score_for_badge: u8,
+    pub analysis_fee_lamports: u64,
+    pub is_paused: bool,
+    pub bump: u8,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl Registry {
+    pub fn upgrade(self) -> state::Registry {
+        state::Registry {
+            authority: self.authority,
+            namespace: String::new(),
+            total_programs: self.total_programs,
+            total_analyses: self.total_analyses,
+            total_skills: self.total_skills,
+            total_auditors: self.total_auditors,
+            total_patterns: self.total_patterns,
+            min_score_for_badge: self.min_score_for_badge,
+            analysis_fee_lamports: self.analysis_fee_lamports,
+            is_paused: self.is_paused,
+            reveal_delay_seconds: 0,
+            reveal_quorum: 0,
+            commitment_ttl_seconds: MIGRATED_COMMITMENT_TTL_SECONDS,
+            bump: self.bump,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            schema_version: 1,
+            reserved: [0; SCHEMA_RESERVED_LEN],
+        }
+    }
+}
+
+/// Seeds `[b"program", program_hash]`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ProgramEntry {
+    pub owner: Pubkey,
+    pub program_hash: [u8; 32],
+    pub framework: Framework,
+    #[max_len(200)]
+    pub metadata_uri: String,
+    pub registered_at: i64,
+    pub updated_at: i64,
+    pub analysis_count: u32,
+    pub latest_score: u8,
+    pub is_verified: bool,
+    pub bump: u8,
+}
+
+impl ProgramEntry {
+    /// The entry in `registry`, at the address `bump` derives. The first
+    /// release never recorded the deployed program, so `program_id` is left
+    /// as the default key.
+    pub fn upgrade(self, registry: Pubkey, bump: u8) -> state::ProgramEntry {
+        state::ProgramEntry {
+            registry,
+            owner: self.owner,
+            program_hash: self.program_hash,
+            program_id: Pubkey::default(),
+            framework: self.framework,
+            metadata_uri: self.metadata_uri,
+            registered_at: self.registered_at,
+            updated_at: self.updated_at,
+            analysis_count: self.analysis_count,
+            latest_score: self.latest_score,
+            is_verified: self.is_verified,
+            commit_count: 0,
+            open_commitments: 0,
+            expired_commitments: 0,
+            pending_owner: None,
+            is_retired: false,
+            bump,
+            schema_version: 1,
+            round_start: 0,
+            round_revealed_at: None,
+            reserved: [0; SCHEMA_RESERVED_LEN],
+        }
+    }
+}
+
+/// Seeds `[b"analysis", program_entry, auditor]`, with the entry's legacy
+/// address.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct AnalysisReport {
+    pub program_entry: Pubkey,
+    pub auditor: Pubkey,
+    pub score: u8,
+    pub findings_hash: [u8; 32],
+    #[max_len(8, 64)]
+    pub skills_used: Vec<String>,
+    pub findings_count: AnalysisFindingsCount,
+    pub submitted_at: i64,
+    pub updated_at: i64,
+    pub version: u8,
+    pub bump: u8,
+}
+
+impl AnalysisReport {
+    /// The report on the migrated `program_entry`, at the address `bump`
+    /// derives. No source revision was recorded then.
+    pub fn upgrade(self, program_entry: Pubkey, bump: u8) -> state::AnalysisReport {
+        state::AnalysisReport {
+            program_entry,
+            auditor: self.auditor,
+            score: self.score,
+            findings_hash: self.findings_hash,
+            skills_used: self.skills_used,
+            findings_count: self.findings_count,
+            score_override: None,
+            source: SourceRevision::default(),
+            submitted_at: self.submitted_at,
+            updated_at: self.updated_at,
+            version: u32::from(self.version),
+            bump,
+            schema_version: 1,
+            reserved: [0; SCHEMA_RESERVED_LEN],
+        }
+    }
+}
+
+/// Seeds `[b"skill", name]`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct SkillEntry {
+    pub authority: Pubkey,
+    #[max_len(64)]
+    pub name: String,
+    #[max_len(16)]
+    pub version: String,
+    #[max_len(256)]
+    pub description: String,
+    pub skill_type: SkillType,
+    pub usage_count: u64,
+    pub is_deprecated: bool,
+    pub registered_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl SkillEntry {
+    /// The skill in `registry`, in its authority's namespace: the first
+    /// release had no built-ins.
+    pub fn upgrade(self, registry: Pubkey, bump: u8) -> state::SkillEntry {
+        state::SkillEntry {
+            registry,
+            authority: self.authority,
+            namespace: self.authority,
+            name: self.name,
+            version: self.version,
+            description: self.description,
+            skill_type: self.skill_type,
+            usage_count: self.usage_count,
+            is_deprecated: self.is_deprecated,
+            registered_at: self.registered_at,
+            updated_at: self.updated_at,
+            bump,
+            schema_version: 1,
+            reserved: [0; SCHEMA_RESERVED_LEN],
+        }
+    }
+}
+
+/// Seeds `[b"auditor", authority]`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct AuditorAccount {
+    pub authority: Pubkey,
+    #[max_len(64)]
+    pub name: String,
+    #[max_len(200)]
+    pub credentials_uri: String,
+    pub total_analyses: u64,
+    pub average_score: u64,
+    pub is_active: bool,
+    pub registered_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl AuditorAccount {
+    /// The membership in `registry`, with no attestation submitted yet.
+    pub fn upgrade(self, registry: Pubkey, bump: u8) -> state::AuditorAccount {
+        state::AuditorAccount {
+            registry,
+            authority: self.authority,
+            name: self.name,
+            credentials_uri: self.credentials_uri,
+            total_analyses: self.total_analyses,
+            average_score: self.average_score,
+            is_active: self.is_active,
+            registered_at: self.registered_at,
+            updated_at: self.updated_at,
+            attestation_nonce: 0,
+            bump,
+            schema_version: 1,
+            reserved: [0; SCHEMA_RESERVED_LEN],
+        }
+    }
+}
+
+/// Seeds `[b"badge", program_entry]`, with the entry's legacy address.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct VerificationBadge {
+    pub program_entry: Pubkey,
+    pub issuer: Pubkey,
+    pub tier: VerificationTier,
+    pub score_at_issuance: u8,
+    pub issued_at: i64,
+    pub expires_at: i64,
+    pub is_revoked: bool,
+    pub bump: u8,
+}
+
+impl VerificationBadge {
+    /// The badge on the migrated `program_entry`.
+    pub fn upgrade(self, program_entry: Pubkey, bump: u8) -> state::VerificationBadge {
+        state::VerificationBadge {
+            program_entry,
+            issuer: self.issuer,
+            tier: self.tier,
+            score_at_issuance: self.score_at_issuance,
+            issued_at: self.issued_at,
+            expires_at: self.expires_at,
+            is_revoked: self.is_revoked,
+            bump,
+            schema_version: 1,
+            reserved: [0; SCHEMA_RESERVED_LEN],
+        }
+    }
+}
+
+/// Seeds `[b"pattern", pattern_id]`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ContextPattern {
+    pub submitter: Pubkey,
+    #[max_len(64)]
+    pub pattern_id: String,
+    pub severity: Severity,
+    pub pattern_type: PatternType,
+    #[max_len(256)]
+    pub description: String,
+    #[max_len(512)]
+    pub detection_rule: String,
+    pub usage_count: u64,
+    pub submitted_at: i64,
+    pub updated_at: i64,
+    pub is_active: bool,
+    pub bump: u8,
+}
+
+impl ContextPattern {
+    /// The pattern in `registry`, in its submitter's namespace.
+    pub fn upgrade(self, registry: Pubkey, bump: u8) -> state::ContextPattern {
+        state::ContextPattern {
+            registry,
+            submitter: self.submitter,
+            namespace: self.submitter,
+            pattern_id: self.pattern_id,
+            severity: self.severity,
+            pattern_type: self.pattern_type,
+            description: self.description,
+            detection_rule: self.detection_rule,
+            usage_count: self.usage_count,
+            submitted_at: self.submitted_at,
+            updated_at: self.updated_at,
+            is_active: self.is_active,
+            bump,
+            schema_version: 1,
+            reserved: [0; SCHEMA_RESERVED_LEN],
+        }
+    }
+}
+
+macro_rules! legacy {
+    ($($account:ident),* $(,)?) => {
+        $(
+            impl Legacy for $account {
+                type Current = state::$account;
+            }
+        )*
+    };
+}
+
+legacy!(Registry, ProgramEntry, AnalysisReport, SkillEntry, AuditorAccount, VerificationBadge, ContextPattern);
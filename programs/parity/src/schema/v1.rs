@@ -0,0 +1,92 @@
+//! The account layouts of version 1 that version 2 grew.
+//!
+//! Version 2 added fields to these accounts beyond what their reserved bytes
+//! absorb, so an account written under version 1 is shorter than the current
+//! layout. Each struct here is the layout its namesake in `state` had then,
+//! under the same discriminator and at exactly `8 + INIT_SPACE` bytes.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::errors::ParityError;
+use crate::state::{self, Framework, SCHEMA_RESERVED_LEN};
+
+/// The version these layouts were written under.
+pub const SCHEMA_VERSION: u8 = 1;
+
+/// Seeds `[b"program", registry, program_hash]`, as now.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ProgramEntry {
+    pub registry: Pubkey,
+    pub owner: Pubkey,
+    pub program_hash: [u8; 32],
+    pub program_id: Pubkey,
+    pub framework: Framework,
+    #[max_len(200)]
+    pub metadata_uri: String,
+    pub registered_at: i64,
+    pub updated_at: i64,
+    pub analysis_count: u32,
+    pub latest_score: u8,
+    pub is_verified: bool,
+    pub commit_count: u32,
+    pub open_commitments: u32,
+    pub expired_commitments: u32,
+    pub pending_owner: Option<Pubkey>,
+    pub is_retired: bool,
+    pub bump: u8,
+    pub schema_version: u8,
+    pub reserved: [u8; SCHEMA_RESERVED_LEN],
+}
+
+impl ProgramEntry {
+    /// The size every entry of this layout was allocated with.
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    /// Reads an entry of this layout, discriminator included. Data of this
+    /// size recording any other version was never written by the program.
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self> {
+        require!(data.len() == Self::SPACE, ParityError::UnversionedAccount);
+        require!(data[..8] == state::ProgramEntry::DISCRIMINATOR, ErrorCode::AccountDiscriminatorMismatch);
+        let entry = Self::deserialize(&mut &data[8..]).map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+        require!(entry.schema_version == SCHEMA_VERSION, ParityError::UnversionedAccount);
+        Ok(entry)
+    }
+
+    /// The entry's data as version 1 wrote it.
+    pub fn to_account_data(&self) -> Vec<u8> {
+        let mut data = state::ProgramEntry::DISCRIMINATOR.to_vec();
+        self.serialize(&mut data).expect("writing to a Vec cannot fail");
+        data.resize(Self::SPACE, 0);
+        data
+    }
+
+    /// The entry at the current layout, still marked as version 1 for
+    /// `schema::upgrade` to finish. The commitments open under version 1
+    /// form one reveal round, so they count towards the quorum as they did.
+    pub fn upgrade(self) -> state::ProgramEntry {
+        state::ProgramEntry {
+            registry: self.registry,
+            owner: self.owner,
+            program_hash: self.program_hash,
+            program_id: self.program_id,
+            framework: self.framework,
+            metadata_uri: self.metadata_uri,
+            registered_at: self.registered_at,
+            updated_at: self.updated_at,
+            analysis_count: self.analysis_count,
+            latest_score: self.latest_score,
+            is_verified: self.is_verified,
+            commit_count: self.commit_count,
+            open_commitments: self.open_commitments,
+            expired_commitments: self.expired_commitments,
+            pending_owner: self.pending_owner,
+            is_retired: self.is_retired,
+            bump: self.bump,
+            schema_version: self.schema_version,
+            round_start: 0,
+            round_revealed_at: None,
+            reserved: [0; SCHEMA_RESERVED_LEN],
+        }
+    }
+}
@@ -0,0 +1,384 @@
+//! Risk scoring for analysis results.
+//!
+//! A score starts at 100 and every finding deducts points. The deduction is
+//! the severity penalty scaled by the finding's pattern weight and confidence;
+//! repeated findings of the same severity in the same category are discounted
+//! geometrically, so ten copies of one issue do not read as ten issues. The
+//! discount bottoms out at a floor, so a thousand still sink the score.
+//! A result with any critical finding scores no higher than a ceiling, so no
+//! weighting of criticals keeps a passing score. Each category also gets its
+//! own sub-score.
+//!
+//! All arithmetic is integer (basis points and milli-points) so the on-chain
+//! program can evaluate the same model, see [`ScoringModel::score_bounds`].
+
+use crate::state::{AnalysisFindingsCount, PatternType, Severity};
+
+pub const MAX_SCORE: u8 = 100;
+
+/// Multipliers are expressed in basis points; `BPS` is 1.0.
+pub const BPS: u64 = 10_000;
+
+/// Deductions are tracked in thousandths of a score point.
+pub const MILLI_POINTS: u64 = 1_000;
+
+const PATTERN_TYPE_COUNT: usize = 11;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    AccessControl,
+    Arithmetic,
+    AccountValidation,
+    Cpi,
+    AccountLifecycle,
+    Other,
+}
+
+impl Category {
+    pub const ALL: [Category; 6] = [
+        Category::AccessControl,
+        Category::Arithmetic,
+        Category::AccountValidation,
+        Category::Cpi,
+        Category::AccountLifecycle,
+        Category::Other,
+    ];
+
+    pub fn of(pattern_type: Option<PatternType>) -> Self {
+        match pattern_type {
+            Some(PatternType::MissingSignerCheck | PatternType::OwnerCheck) => Category::AccessControl,
+            Some(PatternType::UncheckedArithmetic) => Category::Arithmetic,
+            Some(
+                PatternType::UnvalidatedPda | PatternType::AccountDeserialization | PatternType::TypeCosplay,
+            ) => Category::AccountValidation,
+            Some(PatternType::InsecureCpi) => Category::Cpi,
+            Some(
+                PatternType::RentExemption | PatternType::CloseAccount | PatternType::ReinitiallizationAttack,
+            ) => Category::AccountLifecycle,
+            Some(PatternType::Custom) | None => Category::Other,
+        }
+    }
+}
+
+/// How certain the detector is that a finding is real.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Confidence {
+    High,
+    Medium,
+    Low,
+}
+
+/// One finding as seen by the scoring model.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScoredFinding {
+    pub severity: Severity,
+    pub pattern_type: Option<PatternType>,
+    pub confidence: Confidence,
+}
+
+impl ScoredFinding {
+    pub fn new(severity: Severity) -> Self {
+        ScoredFinding { severity, pattern_type: None, confidence: Confidence::High }
+    }
+
+    pub fn with_pattern_type(mut self, pattern_type: PatternType) -> Self {
+        self.pattern_type = Some(pattern_type);
+        self
+    }
+
+    pub fn with_confidence(mut self, confidence: Confidence) -> Self {
+        self.confidence = confidence;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeverityPenalties {
+    pub critical: u64,
+    pub high: u64,
+    pub medium: u64,
+    pub info: u64,
+}
+
+impl SeverityPenalties {
+    /// Points deducted for the first finding of `severity`.
+    pub fn get(&self, severity: Severity) -> u64 {
+        match severity {
+            Severity::Critical => self.critical,
+            Severity::High => self.high,
+            Severity::Medium => self.medium,
+            Severity::Info => self.info,
+            Severity::Pass => 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConfidenceMultipliers {
+    pub high: u64,
+    pub medium: u64,
+    pub low: u64,
+}
+
+impl ConfidenceMultipliers {
+    pub fn get(&self, confidence: Confidence) -> u64 {
+        match confidence {
+            Confidence::High => self.high,
+            Confidence::Medium => self.medium,
+            Confidence::Low => self.low,
+        }
+    }
+
+    fn max(&self) -> u64 {
+        self.high.max(self.medium).max(self.low)
+    }
+
+    fn min(&self) -> u64 {
+        self.high.min(self.medium).min(self.low)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScoringModel {
+    pub penalties: SeverityPenalties,
+    pub confidence: ConfidenceMultipliers,
+    /// Weight per `PatternType`, indexed by discriminant, in basis points.
+    pub pattern_weights: [u64; PATTERN_TYPE_COUNT],
+    /// Weight of findings without a pattern type, in basis points.
+    pub untyped_weight: u64,
+    /// Each repeat of a severity within a category deducts this fraction of
+    /// the previous one, in basis points; `BPS` or more disables the discount.
+    pub repeat_decay: u64,
+    /// No repeat deducts less than this fraction of the first, in basis
+    /// points. Without it the discounts converge, and any number of findings
+    /// costs at most twice the first one.
+    pub repeat_floor: u64,
+    /// Highest score of a result with at least one critical finding.
+    pub critical_ceiling: u8,
+}
+
+impl Default for ScoringModel {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// One line of a score explanation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deduction {
+    /// Position of the finding in the scored slice.
+    pub finding: usize,
+    pub severity: Severity,
+    pub category: Category,
+    /// 1 for the first finding of this severity in this category.
+    pub occurrence: u32,
+    pub base_points: u64,
+    pub pattern_weight: u64,
+    pub confidence_multiplier: u64,
+    /// Combined repeat discount applied, in basis points.
+    pub repeat_multiplier: u64,
+    pub milli_points: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CategoryScore {
+    pub category: Category,
+    pub findings: u32,
+    pub milli_points: u64,
+    pub score: u8,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoreBreakdown {
+    pub score: u8,
+    pub milli_points: u64,
+    pub deductions: Vec<Deduction>,
+    pub categories: Vec<CategoryScore>,
+}
+
+impl ScoringModel {
+    /// Keeps the historical 25/15/8/3 penalties for a first finding and halves
+    /// every repeat, down to a tenth. A critical finding fails the result.
+    pub const DEFAULT: ScoringModel = ScoringModel {
+        penalties: SeverityPenalties { critical: 25, high: 15, medium: 8, info: 3 },
+        confidence: ConfidenceMultipliers { high: 10_000, medium: 7_500, low: 5_000 },
+        pattern_weights: [
+            12_000, // MissingSignerCheck
+            10_000, // UncheckedArithmetic
+            10_000, // UnvalidatedPda
+            12_000, // InsecureCpi
+            10_000, // AccountDeserialization
+            8_000,  // RentExemption
+            10_000, // CloseAccount
+            10_000, // TypeCosplay
+            10_000, // ReinitiallizationAttack
+            10_000, // OwnerCheck
+            10_000, // Custom
+        ],
+        untyped_weight: 10_000,
+        repeat_decay: 5_000,
+        repeat_floor: 1_000,
+        critical_ceiling: 50,
+    };
+
+    pub fn pattern_weight(&self, pattern_type: Option<PatternType>) -> u64 {
+        match pattern_type {
+            Some(p) => self.pattern_weights[p as usize],
+            None => self.untyped_weight,
+        }
+    }
+
+    /// Scores `findings` and explains every deduction.
+    pub fn score(&self, findings: &[ScoredFinding]) -> ScoreBreakdown {
+        let mut deductions = Vec::with_capacity(findings.len());
+        let mut seen: Vec<(Category, u8, u32)> = Vec::new();
+
+        for (index, finding) in findings.iter().enumerate() {
+            if finding.severity == Severity::Pass {
+                continue;
+            }
+            let category = Category::of(finding.pattern_type);
+            let key = (category, finding.severity as u8);
+            let occurrence = match seen.iter_mut().find(|(c, s, _)| (*c, *s) == key) {
+                Some(entry) => {
+                    entry.2 += 1;
+                    entry.2
+                }
+                None => {
+                    seen.push((key.0, key.1, 1));
+                    1
+                }
+            };
+
+            let base_points = self.penalties.get(finding.severity);
+            let pattern_weight = self.pattern_weight(finding.pattern_type);
+            let confidence_multiplier = self.confidence.get(finding.confidence);
+            let repeat_multiplier = self.repeat_multiplier(occurrence);
+            let milli_points = scale(
+                base_points.saturating_mul(MILLI_POINTS),
+                &[pattern_weight, confidence_multiplier, repeat_multiplier],
+            );
+
+            deductions.push(Deduction {
+                finding: index,
+                severity: finding.severity,
+                category,
+                occurrence,
+                base_points,
+                pattern_weight,
+                confidence_multiplier,
+                repeat_multiplier,
+                milli_points,
+            });
+        }
+
+        let categories = Category::ALL
+            .iter()
+            .map(|&category| {
+                let (count, total) = deductions
+                    .iter()
+                    .filter(|d| d.category == category)
+                    .fold((0u32, 0u64), |(n, sum), d| (n + 1, sum.saturating_add(d.milli_points)));
+                CategoryScore { category, findings: count, milli_points: total, score: to_score(total) }
+            })
+            .collect();
+
+        let milli_points = deductions.iter().fold(0u64, |sum, d| sum.saturating_add(d.milli_points));
+        let critical = deductions.iter().any(|d| d.severity == Severity::Critical);
+        let score = self.ceiling(to_score(milli_points), critical);
+        ScoreBreakdown { score, milli_points, deductions, categories }
+    }
+
+    /// The lowest and highest score any set of findings with these counts
+    /// can produce, over every pattern type, confidence and category split.
+    pub fn score_bounds(&self, counts: &AnalysisFindingsCount) -> (u8, u8) {
+        let weights = self.pattern_weights.iter().copied().chain([self.untyped_weight]);
+        let max_weight = weights.clone().max().unwrap_or(BPS);
+        let min_weight = weights.min().unwrap_or(BPS);
+
+        let mut most = 0u64;
+        let mut least = 0u64;
+        for (severity, count) in [
+            (Severity::Critical, counts.critical),
+            (Severity::High, counts.high),
+            (Severity::Medium, counts.medium),
+            (Severity::Info, counts.info),
+        ] {
+            let base = self.penalties.get(severity).saturating_mul(MILLI_POINTS);
+            // Spreading findings over every category discounts them least;
+            // stacking them in one category discounts them most.
+            let heaviest = scale(base, &[max_weight, self.confidence.max()]);
+            let lightest = scale(base, &[min_weight, self.confidence.min()]);
+            most = most.saturating_add(self.repeated(heaviest, count as u64, Category::ALL.len() as u64));
+            least = least.saturating_add(self.repeated(lightest, count as u64, 1));
+        }
+        let critical = counts.critical > 0;
+        (self.ceiling(to_score(most), critical), self.ceiling(to_score(least), critical))
+    }
+
+    /// Whether `score` could have been produced from `counts` under this model.
+    pub fn is_consistent(&self, score: u8, counts: &AnalysisFindingsCount) -> bool {
+        let sum = counts.critical as u32
+            + counts.high as u32
+            + counts.medium as u32
+            + counts.info as u32
+            + counts.pass as u32;
+        let (low, high) = self.score_bounds(counts);
+        sum == counts.total as u32 && score >= low && score <= high
+    }
+
+    fn ceiling(&self, score: u8, critical: bool) -> u8 {
+        if critical {
+            score.min(self.critical_ceiling)
+        } else {
+            score
+        }
+    }
+
+    fn repeat_multiplier(&self, occurrence: u32) -> u64 {
+        if self.repeat_decay >= BPS {
+            return BPS;
+        }
+        let floor = self.repeat_floor.min(BPS);
+        let mut multiplier = BPS;
+        for _ in 1..occurrence {
+            multiplier = (multiplier * self.repeat_decay / BPS).max(floor);
+            if multiplier == floor {
+                break;
+            }
+        }
+        multiplier
+    }
+
+    /// Total deduction of `count` findings of `unit` milli-points when
+    /// `per_level` of them share each repeat level.
+    fn repeated(&self, unit: u64, count: u64, per_level: u64) -> u64 {
+        if self.repeat_decay >= BPS {
+            return unit.saturating_mul(count);
+        }
+        let floor = self.repeat_floor.min(BPS);
+        let mut total = 0u64;
+        let mut remaining = count;
+        let mut multiplier = BPS;
+        while remaining > 0 && multiplier > floor {
+            let n = remaining.min(per_level);
+            total = total.saturating_add(scale(unit, &[multiplier]).saturating_mul(n));
+            remaining -= n;
+            multiplier = (multiplier * self.repeat_decay / BPS).max(floor);
+        }
+        // Every level past this point deducts the floor.
+        total.saturating_add(scale(unit, &[floor]).saturating_mul(remaining))
+    }
+}
+
+fn scale(value: u64, multipliers: &[u64]) -> u64 {
+    multipliers
+        .iter()
+        .fold(value, |v, m| v.saturating_mul(*m) / BPS)
+}
+
+/// Rounds a deduction to whole points and subtracts it from [`MAX_SCORE`].
+fn to_score(milli_points: u64) -> u8 {
+    let points = milli_points.saturating_add(MILLI_POINTS / 2) / MILLI_POINTS;
+    (MAX_SCORE as u64).saturating_sub(points) as u8
+}
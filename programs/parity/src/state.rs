@@ -10,11 +10,22 @@ pub const MAX_PATTERN_ID_LEN: usize = 64;
 pub const MAX_PATTERN_DESC_LEN: usize = 256;
 pub const MAX_DETECTION_RULE_LEN: usize = 512;
 pub const MAX_SKILLS_PER_ANALYSIS: usize = 8;
+pub const MAX_PROGRAM_NAME_LEN: usize = 64;
+pub const MAX_REPOSITORY_URL_LEN: usize = 200;
+pub const MAX_RELEASE_TAG_LEN: usize = 32;
+pub const MAX_TOOLCHAIN_LEN: usize = 64;
+pub const MAX_SECURITY_CONTACT_LEN: usize = 128;
+pub const MAX_REGISTRY_NAMESPACE_LEN: usize = 32;
+pub const SCHEMA_RESERVED_LEN: usize = 64;
 
 #[account]
 #[derive(InitSpace)]
 pub struct Registry {
     pub authority: Pubkey,
+    /// Chosen by the authority at creation and part of the registry's
+    /// address, so one deployment can host several registries.
+    #[max_len(32)]
+    pub namespace: String,
     pub total_programs: u64,
     pub total_analyses: u64,
     pub total_skills: u64,
@@ -23,16 +34,28 @@ pub struct Registry {
     pub min_score_for_badge: u8,
     pub analysis_fee_lamports: u64,
     pub is_paused: bool,
+    /// Seconds after a commitment before it may be revealed.
+    pub reveal_delay_seconds: i64,
+    /// Commitments in one reveal round on the same program that open their
+    /// reveals early; 0 disables.
+    pub reveal_quorum: u8,
+    /// Seconds after which an unrevealed commitment expires.
+    pub commitment_ttl_seconds: i64,
     pub bump: u8,
     pub created_at: i64,
     pub updated_at: i64,
+    pub schema_version: u8,
+    pub reserved: [u8; SCHEMA_RESERVED_LEN],
 }
 
 #[account]
 #[derive(InitSpace)]
 pub struct ProgramEntry {
+    pub registry: Pubkey,
     pub owner: Pubkey,
     pub program_hash: [u8; 32],
+    /// The deployed program whose upgrade authority approved registration.
+    pub program_id: Pubkey,
     pub framework: Framework,
     #[max_len(200)]
     pub metadata_uri: String,
@@ -41,7 +64,29 @@ pub struct ProgramEntry {
     pub analysis_count: u32,
     pub latest_score: u8,
     pub is_verified: bool,
+    /// Analysis commitments ever made against this program.
+    pub commit_count: u32,
+    /// Commitments not yet revealed or expired. The entry cannot be closed
+    /// while any remain.
+    pub open_commitments: u32,
+    /// Commitments closed by `expire_commitment` without being revealed.
+    pub expired_commitments: u32,
+    /// Proposed by `transfer_program_ownership`, becomes the owner once it
+    /// accepts.
+    pub pending_owner: Option<Pubkey>,
+    /// Set by the first `deregister_program` call. A retired entry takes no
+    /// new analyses, commitments, badges or changes.
+    pub is_retired: bool,
     pub bump: u8,
+    pub schema_version: u8,
+    /// Since version 2: `commit_count` when the current reveal round began,
+    /// with the first commitment made while none were open.
+    pub round_start: u32,
+    /// Since version 2: `commit_count` at the first reveal of the current
+    /// round. Commitments made after it may copy a revealed result, so they
+    /// neither count towards the reveal quorum nor open early through it.
+    pub round_revealed_at: Option<u32>,
+    pub reserved: [u8; SCHEMA_RESERVED_LEN],
 }
 
 #[account]
@@ -54,10 +99,18 @@ pub struct AnalysisReport {
     #[max_len(8, 64)]
     pub skills_used: Vec<String>,
     pub findings_count: AnalysisFindingsCount,
+    /// Hash of the auditor's justification for a score outside the range the
+    /// findings count implies.
+    pub score_override: Option<[u8; 32]>,
+    /// The source revision the auditor analyzed.
+    pub source: SourceRevision,
     pub submitted_at: i64,
     pub updated_at: i64,
-    pub version: u8,
+    /// 1 on submission, incremented by every update.
+    pub version: u32,
     pub bump: u8,
+    pub schema_version: u8,
+    pub reserved: [u8; SCHEMA_RESERVED_LEN],
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq)]
@@ -70,10 +123,122 @@ pub struct AnalysisFindingsCount {
     pub total: u16,
 }
 
+/// A git commit and the toolchain that built it. Empty fields are unknown.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace, PartialEq, Debug, Default)]
+pub struct SourceRevision {
+    /// Hex-encoded commit hash, 40 characters for SHA-1 repositories or 64
+    /// for SHA-256 ones.
+    #[max_len(64)]
+    pub commit: String,
+    /// e.g. `anchor 0.30.1, solana 1.18.26`.
+    #[max_len(64)]
+    pub toolchain: String,
+}
+
+/// Facts about a registered program, kept on-chain so they cannot change
+/// without a transaction from its owner.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace, PartialEq, Debug, Default)]
+pub struct ProgramDetails {
+    #[max_len(64)]
+    pub name: String,
+    #[max_len(200)]
+    pub repository_url: String,
+    pub source: SourceRevision,
+    #[max_len(32)]
+    pub release_tag: String,
+    /// Where to report vulnerabilities, as in the program's `security.txt`.
+    #[max_len(128)]
+    pub security_contact: String,
+}
+
+/// An analysis result signed off-chain by an auditor's key, so that anyone
+/// can relay it with `submit_signed_analysis`. The signed message is
+/// `ATTESTATION_DOMAIN || borsh(attestation)`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct AnalysisAttestation {
+    /// The registry the attestation is meant for.
+    pub registry: Pubkey,
+    pub program_hash: [u8; 32],
+    pub score: u8,
+    /// Root of the findings tree, see `findings::findings_root`.
+    pub findings_hash: [u8; 32],
+    pub skills_used: Vec<String>,
+    pub findings_count: AnalysisFindingsCount,
+    pub score_override: Option<[u8; 32]>,
+    pub source: SourceRevision,
+    /// Must exceed the auditor's `attestation_nonce`.
+    pub nonce: u64,
+    /// Unix timestamp after which the attestation can no longer be submitted.
+    pub expires_at: i64,
+}
+
+/// One `register_programs` item; the arguments of `register_program`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct ProgramRegistration {
+    pub program_hash: [u8; 32],
+    pub framework: Framework,
+    pub metadata_uri: String,
+}
+
+/// One `register_skills` item; the arguments of `register_skill`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct SkillRegistration {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub skill_type: SkillType,
+}
+
+/// One `submit_analyses` item; the arguments of `submit_analysis` for the
+/// program registered under `program_hash`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct AnalysisSubmission {
+    pub program_hash: [u8; 32],
+    pub score: u8,
+    pub findings_hash: [u8; 32],
+    pub skills_used: Vec<String>,
+    pub findings_count: AnalysisFindingsCount,
+    pub score_override: Option<[u8; 32]>,
+    pub source: SourceRevision,
+}
+
+/// Optional companion to a `ProgramEntry`, written by its owner.
+#[account]
+#[derive(InitSpace)]
+pub struct ProgramMetadata {
+    pub program_entry: Pubkey,
+    pub details: ProgramDetails,
+    pub updated_at: i64,
+    pub bump: u8,
+    pub schema_version: u8,
+    pub reserved: [u8; SCHEMA_RESERVED_LEN],
+}
+
+/// A sealed analysis result, opened later by `reveal_analysis`.
+#[account]
+#[derive(InitSpace)]
+pub struct AnalysisCommitment {
+    pub program_entry: Pubkey,
+    pub auditor: Pubkey,
+    /// See `analysis_commitment`.
+    pub commitment: [u8; 32],
+    /// `ProgramEntry.commit_count` when this commitment was made.
+    pub commit_index: u32,
+    pub committed_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
+    pub schema_version: u8,
+    pub reserved: [u8; SCHEMA_RESERVED_LEN],
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct SkillEntry {
+    pub registry: Pubkey,
     pub authority: Pubkey,
+    /// The registry for built-in skills, otherwise the submitter. Part of the
+    /// address, so community names can never take a built-in one.
+    pub namespace: Pubkey,
     #[max_len(64)]
     pub name: String,
     #[max_len(16)]
@@ -86,11 +251,16 @@ pub struct SkillEntry {
     pub registered_at: i64,
     pub updated_at: i64,
     pub bump: u8,
+    pub schema_version: u8,
+    pub reserved: [u8; SCHEMA_RESERVED_LEN],
 }
 
 #[account]
 #[derive(InitSpace)]
 pub struct AuditorAccount {
+    /// The registry this membership is for. The same key can audit in
+    /// several registries, with one account in each.
+    pub registry: Pubkey,
     pub authority: Pubkey,
     #[max_len(64)]
     pub name: String,
@@ -101,7 +271,12 @@ pub struct AuditorAccount {
     pub is_active: bool,
     pub registered_at: i64,
     pub updated_at: i64,
+    /// Highest nonce of a signed attestation submitted on the auditor's
+    /// behalf.
+    pub attestation_nonce: u64,
     pub bump: u8,
+    pub schema_version: u8,
+    pub reserved: [u8; SCHEMA_RESERVED_LEN],
 }
 
 #[account]
@@ -115,12 +290,17 @@ pub struct VerificationBadge {
     pub expires_at: i64,
     pub is_revoked: bool,
     pub bump: u8,
+    pub schema_version: u8,
+    pub reserved: [u8; SCHEMA_RESERVED_LEN],
 }
 
 #[account]
 #[derive(InitSpace)]
 pub struct ContextPattern {
+    pub registry: Pubkey,
     pub submitter: Pubkey,
+    /// The registry for built-in rules, otherwise the submitter.
+    pub namespace: Pubkey,
     #[max_len(64)]
     pub pattern_id: String,
     pub severity: Severity,
@@ -134,9 +314,11 @@ pub struct ContextPattern {
     pub updated_at: i64,
     pub is_active: bool,
     pub bump: u8,
+    pub schema_version: u8,
+    pub reserved: [u8; SCHEMA_RESERVED_LEN],
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq, Debug)]
 pub enum Framework {
     Anchor,
     Native,
@@ -144,7 +326,7 @@ pub enum Framework {
     Steel,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq, Debug)]
 pub enum SkillType {
     SecurityAudit,
     BestPractices,
@@ -153,7 +335,7 @@ pub enum SkillType {
     Custom,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq, Debug)]
 pub enum VerificationTier {
     Bronze,
     Silver,
@@ -161,7 +343,7 @@ pub enum VerificationTier {
     Platinum,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq, Debug)]
 pub enum Severity {
     Critical,
     High,
@@ -170,7 +352,7 @@ pub enum Severity {
     Pass,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq, Debug)]
 pub enum PatternType {
     MissingSignerCheck,
     UncheckedArithmetic,
@@ -190,4 +372,7 @@ pub struct RegistryConfig {
     pub min_score_for_badge: u8,
     pub analysis_fee_lamports: u64,
     pub is_paused: bool,
+    pub reveal_delay_seconds: i64,
+    pub reveal_quorum: u8,
+    pub commitment_ttl_seconds: i64,
 }
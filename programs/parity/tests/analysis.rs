@@ -0,0 +1,295 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_anchor_error, assert_ok, assert_parity_error, counts, result, result_with, Fixture};
+use parity::errors::ParityError;
+use parity::findings::{Finding, FindingLocation, FindingsTree};
+use parity::state::{
+    AnalysisFindingsCount, AnalysisReport, ProgramEntry, Registry, Severity, SourceRevision, MAX_SKILLS_PER_ANALYSIS,
+};
+use parity_client::instructions::{self, AnalysisResult};
+use solana_program::instruction::Instruction;
+use solana_sdk::signature::Signer;
+
+fn finding(line: u32) -> Finding {
+    Finding {
+        rule_id: "missing-signer-check".to_string(),
+        severity: Severity::Critical,
+        location: FindingLocation { file: "src/lib.rs".to_string(), line, column: 9 },
+        description_hash: [line as u8; 32],
+    }
+}
+
+#[test]
+fn submit_analysis_records_report_and_updates_counters() {
+    let mut f = Fixture::new().with_auditors(2).with_programs(1);
+    assert_ok(f.submit(0, 0, result(100)));
+    assert_ok(f.submit(0, 1, result(40)));
+
+    let report: AnalysisReport = f.svm.get(&f.analysis(0, 1));
+    assert_eq!(report.program_entry, f.program_entry(0));
+    assert_eq!(report.auditor, f.auditors[1].pubkey());
+    assert_eq!(report.score, 40);
+    assert_eq!(report.skills_used, vec!["security-audit".to_string()]);
+    assert_eq!(report.score_override, Some([0xee; 32]));
+    assert_eq!(report.version, 1);
+
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert_eq!(entry.analysis_count, 2);
+    assert_eq!(entry.latest_score, 40);
+    // A later low score does not unset verification earned by a submission.
+    assert!(entry.is_verified);
+
+    let registry: Registry = f.svm.get(&f.registry());
+    assert_eq!(registry.total_analyses, 2);
+}
+
+#[test]
+fn submit_analysis_below_threshold_does_not_verify() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    assert_ok(f.submit(0, 0, result(69)));
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert!(!entry.is_verified);
+}
+
+#[test]
+fn submit_analysis_cannot_verify_a_flood_of_findings() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    let mut flood = result_with(80, counts(1_000, 0, 0, 0, 0));
+    flood.score_override = None;
+    assert_parity_error(f.submit(0, 0, flood), ParityError::ScoreInconsistentWithFindings);
+
+    assert_ok(f.submit(0, 0, result_with(0, counts(1_000, 0, 0, 0, 0))));
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert_eq!(entry.latest_score, 0);
+    assert!(!entry.is_verified);
+}
+
+#[test]
+fn submit_analysis_cannot_verify_critical_findings() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    for score in [100, 75, 51] {
+        let mut unjustified = result_with(score, counts(10, 0, 0, 0, 0));
+        unjustified.score_override = None;
+        assert_parity_error(f.submit(0, 0, unjustified), ParityError::ScoreInconsistentWithFindings);
+    }
+
+    assert_ok(f.submit(0, 0, result_with(50, counts(10, 0, 0, 0, 0))));
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert!(!entry.is_verified);
+}
+
+#[test]
+fn submit_analysis_once_per_auditor_and_program() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    assert_ok(f.submit(0, 0, result(90)));
+    assert!(f.submit(0, 0, result(90)).is_err());
+
+    let registry: Registry = f.svm.get(&f.registry());
+    assert_eq!(registry.total_analyses, 1);
+}
+
+#[test]
+fn submit_analysis_rejects_invalid_results() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+
+    let mut mismatched = result_with(80, counts(0, 1, 0, 0, 3));
+    mismatched.findings_count.total += 1;
+    assert_parity_error(f.submit(0, 0, mismatched), ParityError::FindingsCountMismatch);
+
+    assert_parity_error(f.submit(0, 0, result(101)), ParityError::InvalidScore);
+
+    let mut skills = result(100);
+    skills.skills_used = vec!["security-audit".to_string(); MAX_SKILLS_PER_ANALYSIS + 1];
+    assert_parity_error(f.submit(0, 0, skills), ParityError::TooManySkills);
+
+    let mut long_name = result(100);
+    long_name.skills_used = vec!["s".repeat(65)];
+    assert_parity_error(f.submit(0, 0, long_name), ParityError::SkillNameTooLong);
+
+    let mut unjustified = result_with(100, counts(3, 0, 0, 0, 0));
+    unjustified.score_override = None;
+    assert_parity_error(f.submit(0, 0, unjustified), ParityError::ScoreInconsistentWithFindings);
+
+    let registry: Registry = f.svm.get(&f.registry());
+    assert_eq!(registry.total_analyses, 0);
+    assert!(f.svm.account(&f.analysis(0, 0)).is_none());
+}
+
+#[test]
+fn submit_analysis_accepts_an_out_of_range_score_with_override() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    let justified = result_with(100, counts(3, 0, 0, 0, 0));
+    assert!(justified.score_override.is_some());
+    assert_ok(f.submit(0, 0, justified));
+}
+
+#[test]
+fn submit_analysis_requires_an_active_registered_auditor() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    let stranger = f.svm.new_funded_keypair();
+    let ix = instructions::submit_analysis(&parity::ID, &f.registry, &stranger.pubkey(), &f.programs[0], result(90));
+    assert_anchor_error(f.send(ix, &stranger), ErrorCode::AccountNotInitialized);
+
+    // Borrowing a registered auditor's account does not help.
+    let accounts = parity::accounts::SubmitAnalysis {
+        auditor: stranger.pubkey(),
+        auditor_account: f.auditor_account(0),
+        registry: f.registry(),
+        program_entry: f.program_entry(0),
+        analysis_report: parity_client::pda::analysis(&parity::ID, &f.program_entry(0), &stranger.pubkey()).0,
+        system_program: solana_program::system_program::ID,
+    }
+    .to_account_metas(None);
+    let data = parity::instruction::SubmitAnalysis {
+        score: 90,
+        findings_hash: [0; 32],
+        skills_used: Vec::new(),
+        findings_count: counts(0, 0, 0, 0, 0),
+        score_override: None,
+        source: SourceRevision::default(),
+    }
+    .data();
+    let ix = Instruction { program_id: parity::ID, accounts, data };
+    assert_anchor_error(f.send(ix, &stranger), ErrorCode::ConstraintSeeds);
+
+    let authority = f.authority.insecure_clone();
+    let ix = instructions::update_auditor_status(&parity::ID, &f.registry, &authority.pubkey(), &f.auditors[0].pubkey(), false);
+    assert_ok(f.send(ix, &authority));
+    assert_parity_error(f.submit(0, 0, result(90)), ParityError::AuditorInactive);
+}
+
+#[test]
+fn update_analysis_replaces_result_and_tracks_verification() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    f.verify(0, 0, 90);
+
+    let mut lower = result_with(60, counts(0, 1, 1, 0, 2));
+    lower.findings_hash = [7; 32];
+    assert_ok(f.update(0, 0, lower));
+
+    let report: AnalysisReport = f.svm.get(&f.analysis(0, 0));
+    assert_eq!(report.score, 60);
+    assert_eq!(report.findings_hash, [7; 32]);
+    assert_eq!(report.findings_count.total, 4);
+    assert_eq!(report.version, 2);
+
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert_eq!(entry.latest_score, 60);
+    assert!(!entry.is_verified);
+    assert_eq!(entry.analysis_count, 1);
+
+    f.svm.warp(60);
+    assert_ok(f.update(0, 0, result(95)));
+    let report: AnalysisReport = f.svm.get(&f.analysis(0, 0));
+    assert_eq!(report.version, 3);
+    assert_eq!(report.updated_at, f.svm.now());
+    assert!(f.svm.get::<ProgramEntry>(&f.program_entry(0)).is_verified);
+}
+
+#[test]
+fn update_analysis_rejects_invalid_results() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    f.verify(0, 0, 90);
+
+    let mut mismatched = result(90);
+    mismatched.findings_count.total = 1;
+    assert_parity_error(f.update(0, 0, mismatched), ParityError::FindingsCountMismatch);
+    assert_parity_error(f.update(0, 0, result(200)), ParityError::InvalidScore);
+
+    let mut unjustified = result_with(100, counts(2, 0, 0, 0, 0));
+    unjustified.score_override = None;
+    assert_parity_error(f.update(0, 0, unjustified), ParityError::ScoreInconsistentWithFindings);
+
+    assert_eq!(f.svm.get::<AnalysisReport>(&f.analysis(0, 0)).version, 1);
+}
+
+#[test]
+fn findings_counts_that_overflow_are_rejected() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    let mut overflowing = result(0);
+    overflowing.findings_count = AnalysisFindingsCount { critical: u16::MAX, high: 1, medium: 0, info: 0, pass: 0, total: 0 };
+    overflowing.score_override = Some([0xee; 32]);
+    assert_parity_error(f.submit(0, 0, overflowing.clone()), ParityError::ArithmeticOverflow);
+
+    f.verify(0, 0, 90);
+    assert_parity_error(f.update(0, 0, overflowing), ParityError::ArithmeticOverflow);
+}
+
+#[test]
+fn update_analysis_versions_past_u8_until_the_limit() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    f.verify(0, 0, 90);
+    let address = f.analysis(0, 0);
+
+    let mut report: AnalysisReport = f.svm.get(&address);
+    report.version = u8::MAX as u32;
+    f.svm.put(&address, &report);
+    assert_ok(f.update(0, 0, result(91)));
+    assert_eq!(f.svm.get::<AnalysisReport>(&address).version, 256);
+
+    report.version = u32::MAX;
+    f.svm.put(&address, &report);
+    assert_parity_error(f.update(0, 0, result(92)), ParityError::TooManyUpdates);
+    assert_eq!(f.svm.get::<AnalysisReport>(&address).score, 90);
+}
+
+#[test]
+fn update_analysis_only_by_the_reporting_auditor() {
+    let mut f = Fixture::new().with_auditors(2).with_programs(1);
+    f.verify(0, 0, 90);
+
+    let intruder = f.auditors[1].insecure_clone();
+    let update = |result: AnalysisResult| parity::instruction::UpdateAnalysis {
+        new_score: result.score,
+        new_findings_hash: result.findings_hash,
+        new_findings_count: result.findings_count,
+        score_override: result.score_override,
+        source: result.source,
+    };
+    let ix = Instruction {
+        program_id: parity::ID,
+        accounts: parity::accounts::UpdateAnalysis {
+            auditor: intruder.pubkey(),
+            registry: f.registry(),
+            program_entry: f.program_entry(0),
+            analysis_report: f.analysis(0, 0),
+        }
+        .to_account_metas(None),
+        data: update(result(10)).data(),
+    };
+    assert_parity_error(f.send(ix, &intruder), ParityError::UnauthorizedAuditor);
+
+    // The intruder's own report does not exist yet.
+    assert_anchor_error(f.update(0, 1, result(10)), ErrorCode::AccountNotInitialized);
+    assert_eq!(f.svm.get::<AnalysisReport>(&f.analysis(0, 0)).score, 90);
+}
+
+#[test]
+fn verify_finding_inclusion_and_exclusion_against_the_report_root() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    let findings: Vec<Finding> = (1..=5).map(finding).collect();
+    let tree = FindingsTree::new(&findings);
+    let mut submitted = result_with(100, counts(5, 0, 0, 0, 0));
+    submitted.findings_hash = tree.root();
+    assert_ok(f.submit(0, 0, submitted));
+    let report = f.analysis(0, 0);
+    let auditor = f.auditors[0].insecure_clone();
+
+    let member = &findings[2];
+    let proof = tree.prove(member).unwrap();
+    let ix = instructions::verify_finding_inclusion(&parity::ID, &report, member.clone(), proof.clone());
+    assert_ok(f.send(ix, &auditor));
+
+    let outsider = finding(42);
+    let ix = instructions::verify_finding_inclusion(&parity::ID, &report, outsider.clone(), proof);
+    assert_parity_error(f.send(ix, &auditor), ParityError::InvalidInclusionProof);
+
+    let absent = tree.prove_absent(&outsider).unwrap();
+    let ix = instructions::verify_finding_exclusion(&parity::ID, &report, outsider, absent.clone());
+    assert_ok(f.send(ix, &auditor));
+
+    let ix = instructions::verify_finding_exclusion(&parity::ID, &report, member.clone(), absent);
+    assert_parity_error(f.send(ix, &auditor), ParityError::InvalidExclusionProof);
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::{assert_ok, assert_parity_error, result, Fixture, TxResult, ONE_DAY};
+use parity::errors::ParityError;
+use parity::state::{AnalysisAttestation, AnalysisReport, AuditorAccount};
+use parity_client::attestation;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction::SystemError;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::TransactionError;
+
+fn attest(f: &Fixture, program: usize, score: u8, nonce: u64) -> AnalysisAttestation {
+    result(score).attestation(&f.registry, f.programs[program], nonce, f.svm.now() + ONE_DAY)
+}
+
+/// Relays `attestation` with `signature` as `auditor`'s, paid by a fresh key.
+fn relay(f: &mut Fixture, auditor: usize, signature: &Signature, attestation: AnalysisAttestation) -> TxResult {
+    let relayer = f.svm.new_funded_keypair();
+    let auditor = f.auditors[auditor].pubkey();
+    let ixs = attestation::instructions(&parity::ID, &f.registry, &relayer.pubkey(), &auditor, signature, attestation);
+    f.svm.process(&ixs, &[&relayer])
+}
+
+fn sign_and_relay(f: &mut Fixture, auditor: usize, attestation: AnalysisAttestation) -> TxResult {
+    let signature = attestation::sign(&f.auditors[auditor], &attestation);
+    relay(f, auditor, &signature, attestation)
+}
+
+#[test]
+fn any_relayer_can_submit_a_signed_analysis() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    let auditor_lamports = f.svm.lamports(&f.auditors[0].pubkey());
+    let signed = attest(&f, 0, 90, 1);
+    assert!(attestation::verify(&f.auditors[0].pubkey(), &attestation::sign(&f.auditors[0], &signed), &signed));
+
+    assert_ok(sign_and_relay(&mut f, 0, signed));
+    let report: AnalysisReport = f.svm.get(&f.analysis(0, 0));
+    assert_eq!((report.auditor, report.score), (f.auditors[0].pubkey(), 90));
+    assert_eq!(report.source, result(90).source);
+    let auditor: AuditorAccount = f.svm.get(&f.auditor_account(0));
+    assert_eq!(auditor.attestation_nonce, 1);
+    // The auditor's key never paid for anything.
+    assert_eq!(f.svm.lamports(&f.auditors[0].pubkey()), auditor_lamports);
+}
+
+#[test]
+fn nonces_only_move_forward() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(3);
+    let first = attest(&f, 0, 90, 5);
+    assert_ok(sign_and_relay(&mut f, 0, first));
+
+    let replayed = attest(&f, 1, 90, 5);
+    assert_parity_error(sign_and_relay(&mut f, 0, replayed), ParityError::AttestationNonceReused);
+    let stale = attest(&f, 1, 90, 4);
+    assert_parity_error(sign_and_relay(&mut f, 0, stale), ParityError::AttestationNonceReused);
+
+    // Gaps are fine, so an auditor can use timestamps as nonces.
+    let later = attest(&f, 1, 90, 1_000);
+    assert_ok(sign_and_relay(&mut f, 0, later));
+    let behind = attest(&f, 2, 90, 999);
+    assert_parity_error(sign_and_relay(&mut f, 0, behind), ParityError::AttestationNonceReused);
+}
+
+#[test]
+fn the_signature_must_be_the_auditors_over_the_exact_attestation() {
+    let mut f = Fixture::new().with_auditors(2).with_programs(1);
+    let signed = attest(&f, 0, 90, 1);
+
+    // No signature check at all.
+    let relayer = f.svm.new_funded_keypair();
+    let ix = parity_client::instructions::submit_signed_analysis(
+        &parity::ID,
+        &f.registry,
+        &relayer.pubkey(),
+        &f.auditors[0].pubkey(),
+        signed.clone(),
+    );
+    assert_parity_error(f.svm.process(&[ix], &[&relayer]), ParityError::AttestationSignatureMissing);
+
+    // A valid signature, but by another auditor.
+    let other = attestation::sign(&f.auditors[1], &signed);
+    let ixs = attestation::instructions(&parity::ID, &f.registry, &relayer.pubkey(), &f.auditors[1].pubkey(), &other, signed.clone());
+    let mut ix = ixs[1].clone();
+    ix.accounts[1].pubkey = f.auditor_account(0);
+    ix.accounts[4].pubkey = f.analysis(0, 0);
+    assert_parity_error(f.svm.process(&[ixs[0].clone(), ix], &[&relayer]), ParityError::AttestationSignatureMissing);
+
+    // The auditor's signature over different contents.
+    let signature = attestation::sign(&f.auditors[0], &signed);
+    let tampered = AnalysisAttestation { score: 95, ..signed.clone() };
+    let auditor = f.auditors[0].pubkey();
+    let mut ixs = attestation::instructions(&parity::ID, &f.registry, &relayer.pubkey(), &auditor, &signature, signed.clone());
+    ixs[1] = parity_client::instructions::submit_signed_analysis(&parity::ID, &f.registry, &relayer.pubkey(), &auditor, tampered);
+    assert_parity_error(f.svm.process(&ixs, &[&relayer]), ParityError::AttestationSignatureMissing);
+
+    // A forged signature fails precompile verification, before parity runs;
+    // the runtime reports that without an instruction index.
+    let forged = Keypair::new().sign_message(&attestation::attestation_message(&signed));
+    let failed = relay(&mut f, 0, &forged, signed).unwrap_err();
+    assert_eq!(failed.error, TransactionError::InvalidAccountIndex);
+    assert!(f.svm.account(&f.analysis(0, 0)).is_none());
+}
+
+#[test]
+fn attestations_expire_and_are_bound_to_one_registry() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+
+    let mut late = attest(&f, 0, 90, 1);
+    late.expires_at = f.svm.now();
+    assert_parity_error(sign_and_relay(&mut f, 0, late), ParityError::AttestationExpired);
+
+    let elsewhere = AnalysisAttestation { registry: Pubkey::new_unique(), ..attest(&f, 0, 90, 1) };
+    assert_parity_error(sign_and_relay(&mut f, 0, elsewhere), ParityError::AttestationRegistryMismatch);
+
+    // Signed results go through the same checks as direct submissions.
+    let mut inconsistent = attest(&f, 0, 90, 1);
+    inconsistent.findings_count.total += 1;
+    assert_parity_error(sign_and_relay(&mut f, 0, inconsistent), ParityError::FindingsCountMismatch);
+
+    let valid = attest(&f, 0, 90, 1);
+    assert_ok(sign_and_relay(&mut f, 0, valid));
+    // A second report for the same program still needs update_analysis.
+    let second = attest(&f, 0, 80, 2);
+    let err = sign_and_relay(&mut f, 0, second).unwrap_err();
+    assert_eq!(err.code(), Some(SystemError::AccountAlreadyInUse as u32));
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{assert_ok, assert_parity_error, Fixture};
+use parity::errors::ParityError;
+use parity::state::{AuditorAccount, Registry, MAX_AUDITOR_NAME_LEN, MAX_CREDENTIALS_URI_LEN};
+use parity_client::instructions;
+use solana_sdk::signature::Signer;
+
+#[test]
+fn register_auditor_creates_an_active_account() {
+    let f = Fixture::new().with_auditors(2);
+    let auditor: AuditorAccount = f.svm.get(&f.auditor_account(1));
+    assert_eq!(auditor.authority, f.auditors[1].pubkey());
+    assert_eq!(auditor.name, "auditor-1");
+    assert_eq!(auditor.credentials_uri, "https://example.com");
+    assert!(auditor.is_active);
+    assert_eq!(auditor.registered_at, f.svm.now());
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_auditors, 2);
+}
+
+#[test]
+fn register_auditor_rejects_long_fields_and_duplicates() {
+    let mut f = Fixture::new().with_auditors(1);
+    let auditor = f.svm.new_funded_keypair();
+
+    let ix = instructions::register_auditor(&parity::ID, &f.registry, &auditor.pubkey(), "a".repeat(MAX_AUDITOR_NAME_LEN + 1), "");
+    assert_parity_error(f.send(ix, &auditor), ParityError::AuditorNameTooLong);
+
+    let uri = "u".repeat(MAX_CREDENTIALS_URI_LEN + 1);
+    let ix = instructions::register_auditor(&parity::ID, &f.registry, &auditor.pubkey(), "a", uri);
+    assert_parity_error(f.send(ix, &auditor), ParityError::CredentialsUriTooLong);
+
+    let existing = f.auditors[0].insecure_clone();
+    let ix = instructions::register_auditor(&parity::ID, &f.registry, &existing.pubkey(), "again", "");
+    assert!(f.send(ix, &existing).is_err());
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_auditors, 1);
+}
+
+#[test]
+fn update_auditor_status_by_registry_authority() {
+    let mut f = Fixture::new().with_auditors(1);
+    let authority = f.authority.insecure_clone();
+    let auditor = f.auditors[0].pubkey();
+
+    f.svm.warp(30);
+    let ix = instructions::update_auditor_status(&parity::ID, &f.registry, &authority.pubkey(), &auditor, false);
+    assert_ok(f.send(ix, &authority));
+    let account: AuditorAccount = f.svm.get(&f.auditor_account(0));
+    assert!(!account.is_active);
+    assert_eq!(account.updated_at, f.svm.now());
+
+    let ix = instructions::update_auditor_status(&parity::ID, &f.registry, &authority.pubkey(), &auditor, true);
+    assert_ok(f.send(ix, &authority));
+    assert!(f.svm.get::<AuditorAccount>(&f.auditor_account(0)).is_active);
+}
+
+#[test]
+fn update_auditor_status_rejects_other_signers() {
+    let mut f = Fixture::new().with_auditors(2);
+    let auditor = f.auditors[0].insecure_clone();
+
+    // Auditors cannot reactivate or deactivate themselves or each other.
+    for target in [f.auditors[0].pubkey(), f.auditors[1].pubkey()] {
+        let ix = instructions::update_auditor_status(&parity::ID, &f.registry, &auditor.pubkey(), &target, false);
+        assert_parity_error(f.send(ix, &auditor), ParityError::UnauthorizedRegistryAuthority);
+    }
+    assert!(f.svm.get::<AuditorAccount>(&f.auditor_account(1)).is_active);
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::{assert_anchor_error, assert_ok, assert_parity_error, default_config, result, Fixture, TxResult, ONE_DAY};
+use parity::errors::ParityError;
+use parity::state::{RegistryConfig, VerificationBadge, VerificationTier};
+use parity_client::instructions;
+use solana_sdk::signature::{Keypair, Signer};
+
+fn issue(f: &mut Fixture, signer: &Keypair, program: usize, tier: VerificationTier, expires_at: i64) -> TxResult {
+    let ix = instructions::create_verification_badge(&parity::ID, &f.registry, &signer.pubkey(), &f.programs[program], tier, expires_at);
+    f.send(ix, signer)
+}
+
+fn revoke(f: &mut Fixture, signer: &Keypair, program: usize) -> TxResult {
+    let ix = instructions::revoke_verification_badge(&parity::ID, &f.registry, &signer.pubkey(), &f.programs[program]);
+    f.send(ix, signer)
+}
+
+#[test]
+fn create_verification_badge_for_a_verified_program() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    f.verify(0, 0, 88);
+    let authority = f.authority.insecure_clone();
+    let expires_at = f.svm.now() + 365 * ONE_DAY;
+    assert_ok(issue(&mut f, &authority, 0, VerificationTier::Gold, expires_at));
+
+    let badge: VerificationBadge = f.svm.get(&f.badge(0));
+    assert_eq!(badge.program_entry, f.program_entry(0));
+    assert_eq!(badge.issuer, authority.pubkey());
+    assert_eq!(badge.tier, VerificationTier::Gold);
+    assert_eq!(badge.score_at_issuance, 88);
+    assert_eq!(badge.issued_at, f.svm.now());
+    assert_eq!(badge.expires_at, expires_at);
+    assert!(!badge.is_revoked);
+}
+
+#[test]
+fn create_verification_badge_requires_the_tier_score() {
+    let cases = [
+        (VerificationTier::Bronze, 50),
+        (VerificationTier::Silver, 70),
+        (VerificationTier::Gold, 85),
+        (VerificationTier::Platinum, 95),
+    ];
+    for (tier, min_score) in cases {
+        // Every score verifies, so the tier minimum alone decides.
+        let config = RegistryConfig { min_score_for_badge: 0, ..default_config() };
+        let mut f = Fixture::with_config(config).with_auditors(1).with_programs(1);
+        let authority = f.authority.insecure_clone();
+        let expires_at = f.svm.now() + ONE_DAY;
+        f.verify(0, 0, min_score - 1);
+        assert_parity_error(issue(&mut f, &authority, 0, tier, expires_at), ParityError::InsufficientScore);
+
+        assert_ok(f.update(0, 0, result(min_score)));
+        assert_ok(issue(&mut f, &authority, 0, tier, expires_at));
+    }
+}
+
+#[test]
+fn create_verification_badge_rejects_past_expiry() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    f.verify(0, 0, 90);
+    let authority = f.authority.insecure_clone();
+    let now = f.svm.now();
+    for expires_at in [now - ONE_DAY, now] {
+        assert_parity_error(issue(&mut f, &authority, 0, VerificationTier::Bronze, expires_at), ParityError::BadgeExpired);
+    }
+    assert!(f.svm.account(&f.badge(0)).is_none());
+}
+
+#[test]
+fn create_verification_badge_requires_a_verified_program() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(2);
+    f.verify(1, 0, 40);
+    let authority = f.authority.insecure_clone();
+    let expires_at = f.svm.now() + ONE_DAY;
+    assert_parity_error(issue(&mut f, &authority, 0, VerificationTier::Bronze, expires_at), ParityError::ProgramNotRegistered);
+    assert_parity_error(issue(&mut f, &authority, 1, VerificationTier::Bronze, expires_at), ParityError::ProgramNotRegistered);
+
+    // An unregistered hash has no entry at all.
+    f.programs.push([0xff; 32]);
+    assert_anchor_error(issue(&mut f, &authority, 2, VerificationTier::Bronze, expires_at), ErrorCode::AccountNotInitialized);
+}
+
+#[test]
+fn badges_are_issued_and_revoked_by_the_registry_authority_only() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    f.verify(0, 0, 90);
+    let auditor = f.auditors[0].insecure_clone();
+    let owner = f.owner.insecure_clone();
+    let authority = f.authority.insecure_clone();
+    let expires_at = f.svm.now() + ONE_DAY;
+
+    for signer in [&auditor, &owner] {
+        assert_parity_error(issue(&mut f, signer, 0, VerificationTier::Silver, expires_at), ParityError::UnauthorizedRegistryAuthority);
+    }
+    assert_ok(issue(&mut f, &authority, 0, VerificationTier::Silver, expires_at));
+    for signer in [&auditor, &owner] {
+        assert_parity_error(revoke(&mut f, signer, 0), ParityError::UnauthorizedRegistryAuthority);
+    }
+    assert!(!f.svm.get::<VerificationBadge>(&f.badge(0)).is_revoked);
+}
+
+#[test]
+fn revoke_verification_badge_once() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    f.verify(0, 0, 90);
+    let authority = f.authority.insecure_clone();
+    let expires_at = f.svm.now() + ONE_DAY;
+    assert_ok(issue(&mut f, &authority, 0, VerificationTier::Silver, expires_at));
+
+    assert_ok(revoke(&mut f, &authority, 0));
+    assert!(f.svm.get::<VerificationBadge>(&f.badge(0)).is_revoked);
+    assert_parity_error(revoke(&mut f, &authority, 0), ParityError::BadgeAlreadyRevoked);
+
+    // A revoked badge still occupies the program's badge address.
+    assert!(issue(&mut f, &authority, 0, VerificationTier::Silver, expires_at).is_err());
+}
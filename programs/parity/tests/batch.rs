@@ -0,0 +1,252 @@
+mod common;
+
+use common::{assert_anchor_error, assert_ok, assert_parity_error, program_elf, program_hash, result, Fixture, TxResult};
+use parity::errors::ParityError;
+use parity::state::{
+    AnalysisReport, Framework, ProgramEntry, ProgramRegistration, Registry, SkillEntry, SkillRegistration, SkillType,
+    MAX_METADATA_URI_LEN, MAX_SKILL_DESC_LEN,
+};
+use parity_client::{batch, instructions, pda};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+
+fn registration(index: usize, metadata_uri: &str) -> ProgramRegistration {
+    ProgramRegistration { program_hash: program_hash(index), framework: Framework::Anchor, metadata_uri: metadata_uri.to_string() }
+}
+
+/// Deploys the fixture's programs `range`, upgradeable by `authority`, and
+/// returns them as batch items.
+fn deploy(f: &mut Fixture, range: std::ops::Range<usize>, authority: &Pubkey) -> Vec<(Pubkey, ProgramRegistration)> {
+    range.map(|i| (f.svm.deploy_upgradeable(Some(*authority), &program_elf(i)), registration(i, "ipfs://meta"))).collect()
+}
+
+fn skill(name: &str, description: &str) -> SkillRegistration {
+    SkillRegistration {
+        name: name.to_string(),
+        version: "0.1.0".to_string(),
+        description: description.to_string(),
+        skill_type: SkillType::Custom,
+    }
+}
+
+/// Sends every transaction, stopping at the first failure.
+fn send_all(f: &mut Fixture, transactions: Vec<Vec<Instruction>>, signer: &Keypair) -> TxResult {
+    for ixs in transactions {
+        f.svm.process(&ixs, &[signer])?;
+    }
+    Ok(())
+}
+
+fn logged(f: &Fixture, line: &str) -> bool {
+    f.svm.logs().iter().any(|log| log.contains(line))
+}
+
+#[test]
+fn register_programs_records_every_item_like_register_program() {
+    let mut f = Fixture::new().with_programs(1);
+    let signer = f.owner.insecure_clone();
+    let owner = signer.pubkey();
+    let items = deploy(&mut f, 1..4, &owner);
+    let ix = instructions::register_programs(&parity::ID, &f.registry, &owner, &owner, items.clone());
+    assert_ok(f.send(ix, &signer));
+
+    let single: ProgramEntry = f.svm.get(&f.program_entry(0));
+    for (i, (program, _)) in items.iter().enumerate() {
+        let entry: ProgramEntry = f.svm.get(&pda::program_entry(&parity::ID, &f.registry, &program_hash(i + 1)).0);
+        assert_eq!((entry.owner, entry.program_id), (owner, *program));
+        assert_eq!(entry.program_hash, program_hash(i + 1));
+        assert_eq!((entry.framework, entry.metadata_uri.as_str()), (single.framework, single.metadata_uri.as_str()));
+        assert_eq!((entry.analysis_count, entry.is_verified, entry.is_retired), (0, false, false));
+    }
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_programs, 4);
+    assert!(logged(&f, "Programs registered: created=3, skipped=0"));
+    assert!(batch::skipped(f.svm.logs()).is_empty());
+}
+
+#[test]
+fn existing_entries_are_skipped_so_batches_can_be_resent() {
+    let mut f = Fixture::new().with_programs(2);
+    let signer = f.owner.insecure_clone();
+    let owner = signer.pubkey();
+    let mut items: Vec<_> = (0..2).map(|i| (f.deployed[i], registration(i, "ipfs://other"))).collect();
+    items.extend(deploy(&mut f, 2..3, &owner));
+
+    let ix = instructions::register_programs(&parity::ID, &f.registry, &owner, &owner, items.clone());
+    assert_ok(f.send(ix.clone(), &signer));
+    assert!(logged(&f, "Programs registered: created=1, skipped=2"));
+    // Each skipped item is logged, for clients to report.
+    assert_eq!(batch::skipped(f.svm.logs()), [f.program_entry(0), f.program_entry(1)]);
+    // Skipped entries keep what they were registered with.
+    assert_eq!(f.svm.get::<ProgramEntry>(&f.program_entry(0)).metadata_uri, "ipfs://meta");
+
+    assert_ok(f.send(ix, &signer));
+    assert!(logged(&f, "Programs registered: created=0, skipped=3"));
+    let third = pda::program_entry(&parity::ID, &f.registry, &program_hash(2)).0;
+    assert_eq!(batch::skipped(f.svm.logs()), [f.program_entry(0), f.program_entry(1), third]);
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_programs, 3);
+}
+
+#[test]
+fn one_invalid_program_fails_the_whole_batch() {
+    let mut f = Fixture::new();
+    let signer = f.owner.insecure_clone();
+    let owner = signer.pubkey();
+    let stranger = Pubkey::new_unique();
+    let mut items = deploy(&mut f, 0..1, &owner);
+    items.extend(deploy(&mut f, 1..2, &stranger));
+    items.extend(deploy(&mut f, 2..3, &owner));
+
+    let ix = instructions::register_programs(&parity::ID, &f.registry, &owner, &owner, items.clone());
+    let err = f.send(ix, &signer);
+    assert!(err.as_ref().unwrap_err().logs.iter().any(|log| log.contains("Batch item 1 failed")));
+    assert_parity_error(err, ParityError::ProgramOwnershipUnproven);
+    assert!(f.svm.account(&pda::program_entry(&parity::ID, &f.registry, &program_hash(0)).0).is_none());
+
+    // The registry authority only vouches for programs nobody can upgrade.
+    let authority = f.authority.insecure_clone();
+    let ix = instructions::register_programs(&parity::ID, &f.registry, &authority.pubkey(), &authority.pubkey(), items);
+    assert_parity_error(f.send(ix, &authority), ParityError::ProgramOwnershipUnproven);
+    let immutable = (0..3).map(|i| (f.svm.deploy_upgradeable(None, &program_elf(i)), registration(i, "ipfs://meta"))).collect();
+    let ix = instructions::register_programs(&parity::ID, &f.registry, &authority.pubkey(), &authority.pubkey(), immutable);
+    assert_ok(f.send(ix, &authority));
+
+    let mut long = deploy(&mut f, 3..4, &owner);
+    long[0].1.metadata_uri = "u".repeat(MAX_METADATA_URI_LEN + 1);
+    let ix = instructions::register_programs(&parity::ID, &f.registry, &owner, &owner, long);
+    assert_parity_error(f.send(ix, &signer), ParityError::MetadataUriTooLong);
+}
+
+#[test]
+fn a_batch_cannot_claim_the_hash_of_another_program() {
+    let mut f = Fixture::new();
+    let squatter = f.svm.new_funded_keypair();
+    let mut items = deploy(&mut f, 0..2, &squatter.pubkey());
+    // The second program runs build 1, but the item claims build 5.
+    items[1].1 = registration(5, "ipfs://meta");
+
+    let ix = instructions::register_programs(&parity::ID, &f.registry, &squatter.pubkey(), &squatter.pubkey(), items);
+    let err = f.send(ix, &squatter);
+    assert!(err.as_ref().unwrap_err().logs.iter().any(|log| log.contains("Batch item 1 failed")));
+    assert_parity_error(err, ParityError::ProgramHashMismatch);
+    assert!(f.svm.account(&pda::program_entry(&parity::ID, &f.registry, &program_hash(5)).0).is_none());
+}
+
+#[test]
+fn remaining_accounts_must_match_the_items() {
+    let mut f = Fixture::new();
+    let signer = f.owner.insecure_clone();
+    let owner = signer.pubkey();
+    let items = deploy(&mut f, 0..2, &owner);
+
+    let empty = instructions::register_programs(&parity::ID, &f.registry, &owner, &owner, Vec::new());
+    assert_parity_error(f.send(empty, &signer), ParityError::BatchAccountMismatch);
+
+    let mut short = instructions::register_programs(&parity::ID, &f.registry, &owner, &owner, items.clone());
+    short.accounts.pop();
+    assert_parity_error(f.send(short, &signer), ParityError::BatchAccountMismatch);
+
+    // Entries swapped between items.
+    let mut swapped = instructions::register_programs(&parity::ID, &f.registry, &owner, &owner, items.clone());
+    let first = swapped.accounts.len() - 6;
+    swapped.accounts.swap(first, first + 3);
+    assert_parity_error(f.send(swapped, &signer), ParityError::BatchAccountMismatch);
+
+    // A ProgramData account that is not the program's.
+    let mut foreign = instructions::register_programs(&parity::ID, &f.registry, &owner, &owner, items);
+    let last = foreign.accounts.len() - 1;
+    foreign.accounts.swap(last, last - 3);
+    assert_anchor_error(f.send(foreign, &signer), anchor_lang::error::ErrorCode::ConstraintSeeds);
+}
+
+#[test]
+fn register_skills_in_one_instruction() {
+    let mut f = Fixture::new();
+    let author = f.svm.new_funded_keypair();
+    let mut items = vec![skill("scan-a", "a"), skill("scan-b", "b")];
+    assert_ok(f.send(instructions::register_skills(&parity::ID, &f.registry, &author.pubkey(), items.clone()), &author));
+
+    items.push(skill("scan-c", "c"));
+    assert_ok(f.send(instructions::register_skills(&parity::ID, &f.registry, &author.pubkey(), items), &author));
+    assert!(logged(&f, "Skills registered: created=1, skipped=2"));
+    let existing = ["scan-a", "scan-b"].map(|name| pda::skill(&parity::ID, &f.registry, &author.pubkey(), name).0);
+    assert_eq!(batch::skipped(f.svm.logs()), existing);
+
+    let entry: SkillEntry = f.svm.get(&pda::skill(&parity::ID, &f.registry, &author.pubkey(), "scan-c").0);
+    assert_eq!((entry.authority, entry.namespace), (author.pubkey(), author.pubkey()));
+    assert_eq!((entry.version.as_str(), entry.description.as_str()), ("0.1.0", "c"));
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_skills, 3);
+
+    let invalid = vec![skill("scan-d", "d"), skill("scan-e", &"d".repeat(MAX_SKILL_DESC_LEN + 1))];
+    let ix = instructions::register_skills(&parity::ID, &f.registry, &author.pubkey(), invalid);
+    assert_parity_error(f.send(ix, &author), ParityError::SkillDescriptionTooLong);
+    assert!(f.svm.account(&pda::skill(&parity::ID, &f.registry, &author.pubkey(), "scan-d").0).is_none());
+}
+
+#[test]
+fn submit_analyses_records_one_report_per_program() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(3);
+    assert_ok(f.submit(0, 0, result(60)));
+
+    let items = (0..3).map(|p| result(90).submission(f.programs[p])).collect();
+    let auditor = f.auditors[0].insecure_clone();
+    assert_ok(f.send(instructions::submit_analyses(&parity::ID, &f.registry, &auditor.pubkey(), items), &auditor));
+    assert!(logged(&f, "Analyses submitted: created=2, skipped=1"));
+    assert!(logged(&f, &format!("Batch item 0 skipped: {} already exists", f.analysis(0, 0))));
+    assert_eq!(batch::skipped(f.svm.logs()), [f.analysis(0, 0)]);
+
+    // The existing report is left for update_analysis.
+    assert_eq!(f.svm.get::<AnalysisReport>(&f.analysis(0, 0)).score, 60);
+    for p in 1..3 {
+        let report: AnalysisReport = f.svm.get(&f.analysis(p, 0));
+        assert_eq!((report.auditor, report.score, report.version), (auditor.pubkey(), 90, 1));
+        let entry: ProgramEntry = f.svm.get(&f.program_entry(p));
+        assert_eq!((entry.analysis_count, entry.latest_score, entry.is_verified), (1, 90, true));
+    }
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_analyses, 3);
+}
+
+#[test]
+fn one_invalid_analysis_fails_the_whole_batch() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(2);
+    let auditor = f.auditors[0].insecure_clone();
+
+    let mut inconsistent = result(90);
+    inconsistent.findings_count.total += 1;
+    let items = vec![result(90).submission(f.programs[0]), inconsistent.submission(f.programs[1])];
+    let err = f.send(instructions::submit_analyses(&parity::ID, &f.registry, &auditor.pubkey(), items), &auditor);
+    assert!(err.as_ref().unwrap_err().logs.iter().any(|log| log.contains("Batch item 1 failed")));
+    assert_parity_error(err, ParityError::FindingsCountMismatch);
+    assert!(f.svm.account(&f.analysis(0, 0)).is_none());
+
+    // The program entry has to be the one registered under the item's hash.
+    let mut ix = instructions::submit_analyses(&parity::ID, &f.registry, &auditor.pubkey(), vec![result(90).submission(f.programs[0])]);
+    let entry = ix.accounts.len() - 2;
+    ix.accounts[entry].pubkey = f.program_entry(1);
+    assert_parity_error(f.send(ix, &auditor), ParityError::BatchAccountMismatch);
+}
+
+#[test]
+fn sized_batches_register_an_ecosystem() {
+    let mut f = Fixture::new().with_auditors(1);
+    let signer = f.owner.insecure_clone();
+    let owner = signer.pubkey();
+    let items: Vec<_> = deploy(&mut f, 0..40, &owner)
+        .into_iter()
+        .map(|(program, registration)| {
+            let len = f.svm.account(&pda::program_data(&program)).unwrap().data.len();
+            (program, len, registration)
+        })
+        .collect();
+
+    let transactions = batch::register_programs(&parity::ID, &f.registry, &owner, &owner, &items);
+    assert!(transactions.len() > 1 && transactions.len() < items.len());
+    assert_ok(send_all(&mut f, transactions, &signer));
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_programs, 40);
+
+    let auditor = f.auditors[0].insecure_clone();
+    let submissions: Vec<_> = (0..40).map(|p| result(90).submission(program_hash(p))).collect();
+    let transactions = batch::submit_analyses(&parity::ID, &f.registry, &auditor.pubkey(), &submissions);
+    assert_ok(send_all(&mut f, transactions, &auditor));
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_analyses, 40);
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use common::{assert_ok, assert_parity_error, svm::Account, Fixture};
+use parity::context_engine::VULNERABILITY_RULES;
+use parity::errors::ParityError;
+use parity::skills::BUILTIN_SKILLS;
+use parity::state::{ContextPattern, Registry, SkillEntry, SkillType};
+use parity_client::{instructions, pda};
+use solana_sdk::signature::{Keypair, Signer};
+
+fn bootstrap(f: &mut Fixture, signer: &Keypair) -> common::TxResult {
+    let ix = instructions::bootstrap_builtins(&parity::ID, &f.registry, &signer.pubkey());
+    f.send(ix, signer)
+}
+
+#[test]
+fn bootstrap_builtins_registers_every_skill_and_rule_under_the_authority() {
+    let mut f = Fixture::new();
+    let authority = f.authority.insecure_clone();
+    assert_ok(bootstrap(&mut f, &authority));
+
+    for definition in BUILTIN_SKILLS {
+        let skill: SkillEntry = f.svm.get(&pda::builtin_skill(&parity::ID, &f.registry, definition.name).0);
+        assert_eq!(skill.authority, authority.pubkey());
+        assert_eq!(skill.namespace, f.registry());
+        assert_eq!((skill.name.as_str(), skill.version.as_str()), (definition.name, definition.version));
+        assert_eq!(skill.skill_type, definition.skill_type);
+        assert!(!skill.is_deprecated);
+    }
+    for rule in VULNERABILITY_RULES {
+        let pattern: ContextPattern = f.svm.get(&pda::builtin_pattern(&parity::ID, &f.registry, rule.id).0);
+        assert_eq!(pattern.submitter, authority.pubkey());
+        assert_eq!(pattern.namespace, f.registry());
+        assert_eq!(pattern.detection_rule, rule.detection_rule);
+        assert!(pattern.is_active);
+    }
+
+    let registry: Registry = f.svm.get(&f.registry());
+    assert_eq!(registry.total_skills, BUILTIN_SKILLS.len() as u64);
+    assert_eq!(registry.total_patterns, VULNERABILITY_RULES.len() as u64);
+}
+
+#[test]
+fn bootstrap_builtins_can_be_rerun() {
+    let mut f = Fixture::new();
+    let authority = f.authority.insecure_clone();
+    assert_ok(bootstrap(&mut f, &authority));
+
+    // A deprecation survives a second bootstrap, which creates nothing new.
+    let name = BUILTIN_SKILLS[0].name;
+    let ix = instructions::deprecate_skill(&parity::ID, &f.registry, &authority.pubkey(), &f.registry(), name);
+    assert_ok(f.send(ix, &authority));
+    f.svm.warp(1);
+    assert_ok(bootstrap(&mut f, &authority));
+
+    assert!(f.svm.get::<SkillEntry>(&pda::builtin_skill(&parity::ID, &f.registry, name).0).is_deprecated);
+    let registry: Registry = f.svm.get(&f.registry());
+    assert_eq!(registry.total_skills, BUILTIN_SKILLS.len() as u64);
+    assert_eq!(registry.total_patterns, VULNERABILITY_RULES.len() as u64);
+}
+
+#[test]
+fn bootstrap_builtins_claims_a_prefunded_address() {
+    let mut f = Fixture::new();
+    let authority = f.authority.insecure_clone();
+    let address = pda::builtin_skill(&parity::ID, &f.registry, BUILTIN_SKILLS[0].name).0;
+    f.svm.set_account(
+        address,
+        Account { lamports: 1, data: Vec::new(), owner: solana_program::system_program::ID, executable: false },
+    );
+
+    assert_ok(bootstrap(&mut f, &authority));
+    assert_eq!(f.svm.get::<SkillEntry>(&address).authority, authority.pubkey());
+}
+
+#[test]
+fn bootstrap_builtins_by_the_registry_authority_only() {
+    let mut f = Fixture::new();
+    let intruder = f.svm.new_funded_keypair();
+    assert_parity_error(bootstrap(&mut f, &intruder), ParityError::UnauthorizedRegistryAuthority);
+    assert!(f.svm.account(&pda::builtin_skill(&parity::ID, &f.registry, BUILTIN_SKILLS[0].name).0).is_none());
+}
+
+#[test]
+fn bootstrap_builtins_rejects_accounts_out_of_order() {
+    let mut f = Fixture::new();
+    let authority = f.authority.insecure_clone();
+
+    let mut swapped = instructions::bootstrap_builtins(&parity::ID, &f.registry, &authority.pubkey());
+    let last = swapped.accounts.len() - 1;
+    swapped.accounts.swap(last - 1, last);
+    assert_parity_error(f.send(swapped, &authority), ParityError::BuiltinAccountMismatch);
+
+    let mut short = instructions::bootstrap_builtins(&parity::ID, &f.registry, &authority.pubkey());
+    short.accounts.pop();
+    assert_parity_error(f.send(short, &authority), ParityError::BuiltinAccountMismatch);
+}
+
+#[test]
+fn community_names_cannot_take_a_builtin() {
+    let mut f = Fixture::new();
+    let authority = f.authority.insecure_clone();
+    let squatter = f.svm.new_funded_keypair();
+    let name = BUILTIN_SKILLS[0].name;
+
+    // Registering first only claims the name in the squatter's own namespace.
+    let ix = instructions::register_skill(&parity::ID, &f.registry, &squatter.pubkey(), name, "6.6.6", "", SkillType::Custom);
+    assert_ok(f.send(ix, &squatter));
+    assert_ok(bootstrap(&mut f, &authority));
+
+    let builtin: SkillEntry = f.svm.get(&pda::builtin_skill(&parity::ID, &f.registry, name).0);
+    assert_eq!(builtin.authority, authority.pubkey());
+    assert_eq!(builtin.version, BUILTIN_SKILLS[0].version);
+
+    let ix = instructions::update_skill(&parity::ID, &f.registry, &squatter.pubkey(), &f.registry(), name, "6.6.6", "");
+    assert_parity_error(f.send(ix, &squatter), ParityError::UnauthorizedAuditor);
+    let ix = instructions::update_skill(&parity::ID, &f.registry, &authority.pubkey(), &f.registry(), name, "1.1.0", "");
+    assert_ok(f.send(ix, &authority));
+    assert_eq!(f.svm.get::<SkillEntry>(&pda::builtin_skill(&parity::ID, &f.registry, name).0).version, "1.1.0");
+}
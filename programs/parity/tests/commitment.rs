@@ -0,0 +1,298 @@
+mod common;
+
+use common::{assert_ok, assert_parity_error, counts, default_config, result, result_with, Fixture, TxResult, ONE_DAY};
+use parity::errors::ParityError;
+use parity::state::{AnalysisCommitment, AnalysisReport, ProgramEntry, Registry, RegistryConfig};
+use parity_client::attestation;
+use parity_client::instructions::{self, AnalysisResult};
+use solana_sdk::signature::Signer;
+
+const SALT: [u8; 32] = [0x5a; 32];
+
+fn sealed(result: &AnalysisResult) -> [u8; 32] {
+    result.commitment(&SALT)
+}
+
+fn commit(f: &mut Fixture, program: usize, auditor: usize, commitment: [u8; 32]) -> TxResult {
+    let auditor = f.auditors[auditor].insecure_clone();
+    let ix = instructions::commit_analysis(&parity::ID, &f.registry, &auditor.pubkey(), &f.programs[program], commitment);
+    f.send(ix, &auditor)
+}
+
+fn reveal(f: &mut Fixture, program: usize, auditor: usize, result: AnalysisResult) -> TxResult {
+    let auditor = f.auditors[auditor].insecure_clone();
+    let ix = instructions::reveal_analysis(&parity::ID, &f.registry, &auditor.pubkey(), &f.programs[program], SALT, result);
+    f.send(ix, &auditor)
+}
+
+fn expire(f: &mut Fixture, program: usize, auditor: usize) -> TxResult {
+    let caller = f.svm.new_funded_keypair();
+    let ix = instructions::expire_commitment(
+        &parity::ID,
+        &caller.pubkey(),
+        &f.program_entry(program),
+        &f.auditors[auditor].pubkey(),
+    );
+    f.send(ix, &caller)
+}
+
+fn delayed() -> Fixture {
+    let config = RegistryConfig { reveal_delay_seconds: ONE_DAY, reveal_quorum: 2, ..default_config() };
+    Fixture::with_config(config).with_auditors(3).with_programs(1)
+}
+
+#[test]
+fn commit_analysis_seals_result_in_order() {
+    let mut f = delayed();
+    let first = result(90);
+    assert_ok(commit(&mut f, 0, 0, sealed(&first)));
+    f.svm.warp(10);
+    assert_ok(commit(&mut f, 0, 1, [1; 32]));
+
+    let sealed_first: AnalysisCommitment = f.svm.get(&f.commitment(0, 0));
+    assert_eq!(sealed_first.commitment, sealed(&first));
+    assert_eq!(sealed_first.auditor, f.auditors[0].pubkey());
+    assert_eq!(sealed_first.commit_index, 0);
+    assert_eq!(sealed_first.expires_at, sealed_first.committed_at + 7 * ONE_DAY);
+
+    let sealed_second: AnalysisCommitment = f.svm.get(&f.commitment(0, 1));
+    assert_eq!(sealed_second.commit_index, 1);
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert_eq!((entry.commit_count, entry.open_commitments), (2, 2));
+}
+
+#[test]
+fn commit_analysis_rejects_an_expiry_past_the_end_of_time() {
+    let config = RegistryConfig { commitment_ttl_seconds: i64::MAX, ..default_config() };
+    let mut f = Fixture::with_config(config).with_auditors(1).with_programs(1);
+    assert_parity_error(commit(&mut f, 0, 0, [1; 32]), ParityError::ArithmeticOverflow);
+    assert_eq!(f.svm.get::<ProgramEntry>(&f.program_entry(0)).commit_count, 0);
+}
+
+#[test]
+fn commit_analysis_requires_an_active_auditor() {
+    let mut f = delayed();
+    let authority = f.authority.insecure_clone();
+    let ix = instructions::update_auditor_status(&parity::ID, &f.registry, &authority.pubkey(), &f.auditors[0].pubkey(), false);
+    assert_ok(f.send(ix, &authority));
+    assert_parity_error(commit(&mut f, 0, 0, [1; 32]), ParityError::AuditorInactive);
+}
+
+#[test]
+fn reveal_after_delay_records_the_analysis_and_refunds_rent() {
+    let mut f = delayed();
+    let revealed = result(90);
+    assert_ok(commit(&mut f, 0, 0, sealed(&revealed)));
+    let commitment = f.commitment(0, 0);
+    let rent = f.svm.lamports(&commitment);
+
+    assert_parity_error(reveal(&mut f, 0, 0, revealed.clone()), ParityError::RevealNotOpen);
+
+    f.svm.warp(ONE_DAY);
+    let auditor = f.auditors[0].pubkey();
+    let before = f.svm.lamports(&auditor);
+    assert_ok(reveal(&mut f, 0, 0, revealed));
+
+    assert!(f.svm.account(&commitment).is_none());
+    let report: AnalysisReport = f.svm.get(&f.analysis(0, 0));
+    assert_eq!(report.score, 90);
+    let report_rent = f.svm.lamports(&f.analysis(0, 0));
+    assert_eq!(f.svm.lamports(&auditor), before + rent - report_rent - f.svm.fee());
+
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert!(entry.is_verified);
+    assert_eq!((entry.analysis_count, entry.open_commitments), (1, 0));
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_analyses, 1);
+}
+
+#[test]
+fn sealed_registries_only_record_revealed_results() {
+    let configs = [
+        RegistryConfig { reveal_delay_seconds: ONE_DAY, ..default_config() },
+        RegistryConfig { reveal_quorum: 2, ..default_config() },
+    ];
+    for config in configs {
+        let mut f = Fixture::with_config(config).with_auditors(1).with_programs(1);
+        let auditor = f.auditors[0].insecure_clone();
+        assert_parity_error(f.submit(0, 0, result(90)), ParityError::CommitRevealRequired);
+        let ix = instructions::submit_analyses(&parity::ID, &f.registry, &auditor.pubkey(), vec![result(90).submission(f.programs[0])]);
+        assert_parity_error(f.send(ix, &auditor), ParityError::CommitRevealRequired);
+        let signed = result(90).attestation(&f.registry, f.programs[0], 1, f.svm.now() + ONE_DAY);
+        let signature = attestation::sign(&auditor, &signed);
+        let ixs = attestation::instructions(&parity::ID, &f.registry, &auditor.pubkey(), &auditor.pubkey(), &signature, signed);
+        assert_parity_error(f.svm.process(&ixs, &[&auditor]), ParityError::CommitRevealRequired);
+        assert!(f.svm.account(&f.analysis(0, 0)).is_none());
+    }
+}
+
+#[test]
+fn reveal_updates_an_existing_report() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    assert_ok(f.submit(0, 0, result(90)));
+    let authority = f.authority.insecure_clone();
+    let config = RegistryConfig { reveal_delay_seconds: ONE_DAY, ..default_config() };
+    let ix = instructions::update_registry_config(&parity::ID, &f.registry, &authority.pubkey(), config);
+    assert_ok(f.send(ix, &authority));
+
+    // Once the registry seals results, the report only changes by reveal.
+    let lower = result_with(40, counts(1, 0, 0, 0, 0));
+    assert_parity_error(f.update(0, 0, lower.clone()), ParityError::CommitRevealRequired);
+    assert_ok(commit(&mut f, 0, 0, sealed(&lower)));
+    f.svm.warp(ONE_DAY);
+    assert_ok(reveal(&mut f, 0, 0, lower));
+
+    let report: AnalysisReport = f.svm.get(&f.analysis(0, 0));
+    assert_eq!((report.score, report.version, report.findings_count.critical), (40, 2, 1));
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert_eq!((entry.latest_score, entry.is_verified, entry.analysis_count), (40, false, 1));
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_analyses, 1);
+}
+
+#[test]
+fn reveal_opens_early_once_the_quorum_has_committed() {
+    let mut f = delayed();
+    let (first, second) = (result(80), result(85));
+    assert_ok(commit(&mut f, 0, 0, sealed(&first)));
+    assert_parity_error(reveal(&mut f, 0, 0, first.clone()), ParityError::RevealNotOpen);
+    assert_ok(commit(&mut f, 0, 1, sealed(&second)));
+
+    // Every committer can open early, whatever order it committed in.
+    assert_ok(reveal(&mut f, 0, 1, second));
+    assert_ok(reveal(&mut f, 0, 0, first));
+}
+
+#[test]
+fn expired_commitments_do_not_count_towards_the_quorum() {
+    let mut f = delayed();
+    assert_ok(commit(&mut f, 0, 0, [1; 32]));
+    f.svm.warp(7 * ONE_DAY + 1);
+    assert_ok(expire(&mut f, 0, 0));
+
+    let late = result(90);
+    assert_ok(commit(&mut f, 0, 1, sealed(&late)));
+    assert_parity_error(reveal(&mut f, 0, 1, late.clone()), ParityError::RevealNotOpen);
+    assert_ok(commit(&mut f, 0, 2, [2; 32]));
+    assert_ok(reveal(&mut f, 0, 1, late));
+
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert_eq!((entry.commit_count, entry.expired_commitments, entry.open_commitments), (3, 1, 1));
+}
+
+#[test]
+fn one_auditor_cycling_commitments_never_reaches_the_quorum() {
+    let mut f = delayed();
+    for score in [80, 81, 82] {
+        let cycled = result(score);
+        assert_ok(commit(&mut f, 0, 0, sealed(&cycled)));
+        f.svm.warp(ONE_DAY);
+        assert_ok(reveal(&mut f, 0, 0, cycled));
+    }
+
+    let next = result(83);
+    assert_ok(commit(&mut f, 0, 0, sealed(&next)));
+    assert_parity_error(reveal(&mut f, 0, 0, next), ParityError::RevealNotOpen);
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert_eq!((entry.commit_count, entry.round_start, entry.round_revealed_at), (4, 3, None));
+}
+
+#[test]
+fn commitments_made_after_a_reveal_wait_out_the_delay() {
+    let mut f = delayed();
+    let (first, second) = (result(80), result(85));
+    assert_ok(commit(&mut f, 0, 0, sealed(&first)));
+    assert_ok(commit(&mut f, 0, 1, sealed(&second)));
+    assert_ok(reveal(&mut f, 0, 0, first.clone()));
+
+    // The round is still open, but a copy of the revealed result joins too
+    // late to count towards or use the quorum.
+    assert_ok(commit(&mut f, 0, 2, sealed(&first)));
+    assert_parity_error(reveal(&mut f, 0, 2, first.clone()), ParityError::RevealNotOpen);
+    assert_ok(reveal(&mut f, 0, 1, second));
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert_eq!((entry.round_start, entry.round_revealed_at), (0, Some(2)));
+
+    f.svm.warp(ONE_DAY);
+    assert_ok(reveal(&mut f, 0, 2, first));
+}
+
+#[test]
+fn reveal_cannot_update_the_report_of_a_deactivated_auditor() {
+    let mut f = delayed();
+    let filed = result(90);
+    assert_ok(commit(&mut f, 0, 0, sealed(&filed)));
+    f.svm.warp(ONE_DAY);
+    assert_ok(reveal(&mut f, 0, 0, filed));
+
+    let lower = result_with(40, counts(1, 0, 0, 0, 0));
+    assert_ok(commit(&mut f, 0, 0, sealed(&lower)));
+    let authority = f.authority.insecure_clone();
+    let ix = instructions::update_auditor_status(&parity::ID, &f.registry, &authority.pubkey(), &f.auditors[0].pubkey(), false);
+    assert_ok(f.send(ix, &authority));
+    f.svm.warp(ONE_DAY);
+    assert_parity_error(reveal(&mut f, 0, 0, lower), ParityError::AuditorInactive);
+
+    let report: AnalysisReport = f.svm.get(&f.analysis(0, 0));
+    assert_eq!((report.score, report.version), (90, 1));
+}
+
+#[test]
+fn reveal_rejects_values_that_do_not_match_the_commitment() {
+    let mut f = delayed();
+    let committed = result(90);
+    assert_ok(commit(&mut f, 0, 0, sealed(&committed)));
+    f.svm.warp(ONE_DAY);
+
+    assert_parity_error(reveal(&mut f, 0, 0, result(91)), ParityError::CommitmentMismatch);
+    let mut other_hash = committed.clone();
+    other_hash.findings_hash = [0; 32];
+    assert_parity_error(reveal(&mut f, 0, 0, other_hash), ParityError::CommitmentMismatch);
+
+    // Every other recorded field is sealed too.
+    let mut other_skills = committed.clone();
+    other_skills.skills_used.push("deep-audit".to_string());
+    let mut other_counts = committed.clone();
+    other_counts.findings_count = counts(0, 0, 0, 1, 0);
+    other_counts.score_override = Some([0xee; 32]);
+    let mut other_override = committed.clone();
+    other_override.score_override = Some([1; 32]);
+    let mut other_source = committed.clone();
+    other_source.source.commit = "f".repeat(40);
+    for changed in [other_skills, other_counts, other_override, other_source] {
+        assert_parity_error(reveal(&mut f, 0, 0, changed), ParityError::CommitmentMismatch);
+    }
+    assert!(f.svm.account(&f.commitment(0, 0)).is_some());
+}
+
+#[test]
+fn reveal_validates_the_result_like_a_direct_submission() {
+    let mut f = delayed();
+    let mut bad = result(90);
+    bad.findings_count.total = 3;
+    assert_ok(commit(&mut f, 0, 0, sealed(&bad)));
+    f.svm.warp(ONE_DAY);
+    assert_parity_error(reveal(&mut f, 0, 0, bad), ParityError::FindingsCountMismatch);
+}
+
+#[test]
+fn expired_commitments_cannot_be_revealed_but_can_be_closed_by_anyone() {
+    let mut f = delayed();
+    let revealed = result(90);
+    assert_ok(commit(&mut f, 0, 0, sealed(&revealed)));
+    let commitment = f.commitment(0, 0);
+    let rent = f.svm.lamports(&commitment);
+
+    assert_parity_error(expire(&mut f, 0, 0), ParityError::CommitmentNotExpired);
+
+    f.svm.warp(7 * ONE_DAY + 1);
+    assert_parity_error(reveal(&mut f, 0, 0, revealed), ParityError::CommitmentExpired);
+
+    let auditor = f.auditors[0].pubkey();
+    let before = f.svm.lamports(&auditor);
+    assert_ok(expire(&mut f, 0, 0));
+    assert!(f.svm.account(&commitment).is_none());
+    assert_eq!(f.svm.lamports(&auditor), before + rent);
+    assert_eq!(f.svm.get::<ProgramEntry>(&f.program_entry(0)).open_commitments, 0);
+
+    // The auditor may commit again afterwards.
+    assert_ok(commit(&mut f, 0, 0, [2; 32]));
+}
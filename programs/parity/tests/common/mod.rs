@@ -0,0 +1,235 @@
+//! Shared fixtures for the instruction tests: a program-test bank and a
+//! registry populated with auditors and programs.
+
+#![allow(dead_code)]
+
+pub mod model;
+pub mod svm;
+
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use parity::errors::ParityError;
+use parity::scoring::ScoringModel;
+use parity::state::{AnalysisFindingsCount, Framework, RegistryConfig, SourceRevision};
+use parity_client::instructions::{self, AnalysisResult};
+use parity_client::pda;
+use solana_sdk::signature::{Keypair, Signer};
+
+pub use svm::{Svm, TxResult};
+
+pub const ONE_DAY: i64 = 24 * 60 * 60;
+
+pub fn default_config() -> RegistryConfig {
+    RegistryConfig {
+        min_score_for_badge: 70,
+        analysis_fee_lamports: 0,
+        is_paused: false,
+        reveal_delay_seconds: 0,
+        reveal_quorum: 0,
+        commitment_ttl_seconds: 7 * ONE_DAY,
+    }
+}
+
+/// Findings counts with `total` filled in.
+pub fn counts(critical: u16, high: u16, medium: u16, info: u16, pass: u16) -> AnalysisFindingsCount {
+    AnalysisFindingsCount { critical, high, medium, info, pass, total: critical + high + medium + info + pass }
+}
+
+/// A result with `score` and the given findings, carrying an override only if
+/// the default scoring model could not have produced the score.
+pub fn result_with(score: u8, findings_count: AnalysisFindingsCount) -> AnalysisResult {
+    let consistent = ScoringModel::DEFAULT.is_consistent(score, &findings_count);
+    AnalysisResult {
+        score,
+        findings_hash: [score; 32],
+        skills_used: vec!["security-audit".to_string()],
+        findings_count,
+        score_override: if consistent { None } else { Some([0xee; 32]) },
+        source: source(),
+    }
+}
+
+/// The revision every result built here claims to have analyzed.
+pub fn source() -> SourceRevision {
+    SourceRevision { commit: "0123456789abcdef0123456789abcdef01234567".to_string(), toolchain: "anchor 0.30.1".to_string() }
+}
+
+/// A result with `score` and no findings.
+pub fn result(score: u8) -> AnalysisResult {
+    result_with(score, counts(0, 0, 0, 0, 0))
+}
+
+/// Stand-in bytecode for the fixture's `index`th program.
+pub fn program_elf(index: usize) -> Vec<u8> {
+    [b"\x7fELF".as_slice(), &(index as u64).to_le_bytes(), &[0xa5; 20]].concat()
+}
+
+/// The canonical hash of [`program_elf`].
+pub fn program_hash(index: usize) -> [u8; 32] {
+    parity::program_hash::program_hash(&program_elf(index))
+}
+
+/// Asserts that a transaction failed with `expected`.
+#[track_caller]
+pub fn assert_parity_error(result: TxResult, expected: ParityError) {
+    let code = u32::from(expected);
+    match result {
+        Ok(()) => panic!("expected {:?}, transaction succeeded", expected),
+        Err(err) => assert_eq!(
+            err.code(),
+            Some(code),
+            "expected {:?} ({}), got {:?}\n{}",
+            expected,
+            code,
+            err.error,
+            err.logs.join("\n")
+        ),
+    }
+}
+
+/// Asserts that a transaction failed with one of Anchor's framework errors.
+#[track_caller]
+pub fn assert_anchor_error(result: TxResult, expected: anchor_lang::error::ErrorCode) {
+    let code = u32::from(expected);
+    match result {
+        Ok(()) => panic!("expected {:?}, transaction succeeded", expected),
+        Err(err) => assert_eq!(err.code(), Some(code), "expected {:?}, got {:?}\n{}", expected, err.error, err.logs.join("\n")),
+    }
+}
+
+#[track_caller]
+pub fn assert_ok(result: TxResult) {
+    if let Err(err) = result {
+        panic!("transaction failed: {:?}\n{}", err.error, err.logs.join("\n"));
+    }
+}
+
+/// A registry with its authority, some registered auditors and some
+/// registered programs, all owned by `owner`.
+pub struct Fixture {
+    pub svm: Svm,
+    pub authority: Keypair,
+    pub owner: Keypair,
+    pub auditors: Vec<Keypair>,
+    pub programs: Vec<[u8; 32]>,
+    /// The deployed program behind each of `programs`, upgradeable by `owner`.
+    pub deployed: Vec<Pubkey>,
+    /// The registry everything above is registered in.
+    pub registry: Pubkey,
+}
+
+impl Fixture {
+    /// An initialized registry with the default config and nothing in it.
+    pub fn new() -> Self {
+        Self::with_config(default_config())
+    }
+
+    pub fn with_config(config: RegistryConfig) -> Self {
+        Self::in_namespace(pda::DEFAULT_NAMESPACE, config)
+    }
+
+    /// An initialized registry under `namespace`.
+    pub fn in_namespace(namespace: &str, config: RegistryConfig) -> Self {
+        let mut svm = Svm::new();
+        let authority = svm.new_funded_keypair();
+        let owner = svm.new_funded_keypair();
+        let registry = pda::registry(&parity::ID, namespace).0;
+        let mut fixture =
+            Self { svm, authority, owner, auditors: Vec::new(), programs: Vec::new(), deployed: Vec::new(), registry };
+        let ix = instructions::initialize_registry(&parity::ID, &fixture.authority.pubkey(), namespace, config);
+        assert_ok(fixture.svm.process(&[ix], &[&fixture.authority]));
+        fixture
+    }
+
+    /// Registers `count` more auditors.
+    pub fn with_auditors(mut self, count: usize) -> Self {
+        for _ in 0..count {
+            self.add_auditor();
+        }
+        self
+    }
+
+    /// Registers `count` more programs owned by `owner`.
+    pub fn with_programs(mut self, count: usize) -> Self {
+        for _ in 0..count {
+            self.add_program();
+        }
+        self
+    }
+
+    pub fn add_auditor(&mut self) -> usize {
+        let auditor = self.svm.new_funded_keypair();
+        let name = format!("auditor-{}", self.auditors.len());
+        let ix = instructions::register_auditor(&parity::ID, &self.registry, &auditor.pubkey(), name, "https://example.com");
+        assert_ok(self.svm.process(&[ix], &[&auditor]));
+        self.auditors.push(auditor);
+        self.auditors.len() - 1
+    }
+
+    pub fn add_program(&mut self) -> usize {
+        let hash = program_hash(self.programs.len());
+        let owner = self.owner.pubkey();
+        let program = self.svm.deploy_upgradeable(Some(owner), &program_elf(self.programs.len()));
+        let ix = instructions::register_program(
+            &parity::ID,
+            &self.registry,
+            &owner,
+            &owner,
+            &program,
+            hash,
+            Framework::Anchor,
+            "ipfs://meta",
+        );
+        assert_ok(self.svm.process(&[ix], &[&self.owner]));
+        self.programs.push(hash);
+        self.deployed.push(program);
+        self.programs.len() - 1
+    }
+
+    /// Sends `ix` signed by `signer` alone.
+    pub fn send(&mut self, ix: Instruction, signer: &Keypair) -> TxResult {
+        self.svm.process(&[ix], &[signer])
+    }
+
+    pub fn registry(&self) -> Pubkey {
+        self.registry
+    }
+
+    pub fn program_entry(&self, program: usize) -> Pubkey {
+        pda::program_entry(&parity::ID, &self.registry, &self.programs[program]).0
+    }
+
+    pub fn auditor_account(&self, auditor: usize) -> Pubkey {
+        pda::auditor(&parity::ID, &self.registry, &self.auditors[auditor].pubkey()).0
+    }
+
+    pub fn analysis(&self, program: usize, auditor: usize) -> Pubkey {
+        pda::analysis(&parity::ID, &self.program_entry(program), &self.auditors[auditor].pubkey()).0
+    }
+
+    pub fn commitment(&self, program: usize, auditor: usize) -> Pubkey {
+        pda::commitment(&parity::ID, &self.program_entry(program), &self.auditors[auditor].pubkey()).0
+    }
+
+    pub fn badge(&self, program: usize) -> Pubkey {
+        pda::badge(&parity::ID, &self.program_entry(program)).0
+    }
+
+    pub fn submit(&mut self, program: usize, auditor: usize, result: AnalysisResult) -> TxResult {
+        let auditor = &self.auditors[auditor];
+        let ix = instructions::submit_analysis(&parity::ID, &self.registry, &auditor.pubkey(), &self.programs[program], result);
+        self.svm.process(&[ix], &[auditor])
+    }
+
+    pub fn update(&mut self, program: usize, auditor: usize, result: AnalysisResult) -> TxResult {
+        let auditor = &self.auditors[auditor];
+        let ix = instructions::update_analysis(&parity::ID, &self.registry, &auditor.pubkey(), &self.programs[program], result);
+        self.svm.process(&[ix], &[auditor])
+    }
+
+    /// Submits a clean analysis scoring `score`, verifying the program if the
+    /// score clears the registry's badge threshold.
+    pub fn verify(&mut self, program: usize, auditor: usize, score: u8) {
+        assert_ok(self.submit(program, auditor, result(score)));
+    }
+}
@@ -0,0 +1,594 @@
+//! Random instruction sequences against a registry, checked against a small
+//! reference model after every step. Shared by the proptest suite and the
+//! fuzz target, which decodes its input with [`decode`].
+
+use std::collections::HashMap;
+
+use parity::scoring::ScoringModel;
+use parity::state::{
+    AnalysisCommitment, AnalysisFindingsCount, AnalysisReport, AuditorAccount, ContextPattern, Framework, PatternType,
+    ProgramEntry, Registry, RegistryConfig, Severity, SkillEntry, SkillType, VerificationBadge, VerificationTier,
+};
+use parity_client::instructions::{self, AnalysisResult};
+use parity_client::pda;
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+use super::{default_config, program_elf, program_hash, Fixture, ONE_DAY};
+
+/// Keypairs that act as auditors, skill authors and pattern submitters.
+pub const ACTORS: u8 = 4;
+pub const PROGRAMS: u8 = 3;
+pub const SKILLS: u8 = 2;
+pub const PATTERNS: u8 = 2;
+/// Longest sequence the fuzz target decodes.
+pub const MAX_OPS: usize = 64;
+
+/// Names community submissions try, including a built-in one each.
+const SKILL_NAMES: [&str; SKILLS as usize] = ["scan", "security-audit"];
+const PATTERN_IDS: [&str; PATTERNS as usize] = ["admin-check", "missing-signer-check"];
+
+const DETECTION_RULE: &str = r#"rule v1 match field where name == "authority" and not signer"#;
+
+/// An analysis result as drawn by the generators.
+#[derive(Clone, Debug)]
+pub struct Analysis {
+    pub score: u8,
+    /// Critical, high, medium, info and pass counts.
+    pub counts: [u16; 5],
+    /// Report a total one higher than the counts add up to.
+    pub miscount: bool,
+    /// Attach a score override.
+    pub justified: bool,
+}
+
+impl Analysis {
+    pub fn findings_count(&self) -> AnalysisFindingsCount {
+        let [critical, high, medium, info, pass] = self.counts;
+        let total = self.counts.iter().map(|&c| c as u32).sum::<u32>() + self.miscount as u32;
+        AnalysisFindingsCount { critical, high, medium, info, pass, total: total as u16 }
+    }
+
+    pub fn result(&self) -> AnalysisResult {
+        let mut findings_hash = [self.score; 32];
+        findings_hash[..10].copy_from_slice(&self.counts.map(u16::to_le_bytes).concat());
+        AnalysisResult {
+            score: self.score,
+            findings_hash,
+            skills_used: vec!["security-audit".to_string()],
+            findings_count: self.findings_count(),
+            score_override: self.justified.then_some([0xee; 32]),
+            source: super::source(),
+        }
+    }
+
+    /// Whether the severity counts add up past what `total` can hold.
+    pub fn overflows(&self) -> bool {
+        self.counts.iter().map(|&c| c as u32).sum::<u32>() > u16::MAX as u32
+    }
+
+    /// Whether `submit_analysis` should accept this result.
+    pub fn is_valid(&self) -> bool {
+        let counts = self.findings_count();
+        self.score <= 100
+            && !self.overflows()
+            && !self.miscount
+            && (self.justified || ScoringModel::DEFAULT.is_consistent(self.score, &counts))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Op {
+    RegisterAuditor { actor: u8 },
+    SetAuditorActive { actor: u8, active: bool },
+    RegisterProgram { program: u8 },
+    Submit { program: u8, actor: u8, analysis: Analysis },
+    Update { program: u8, actor: u8, analysis: Analysis },
+    Commit { program: u8, actor: u8, analysis: Analysis, salt: u8 },
+    /// Reveals what the actor committed, or something else if `tamper`.
+    Reveal { program: u8, actor: u8, tamper: bool },
+    Expire { program: u8, actor: u8 },
+    SetConfig { min_score: u8, reveal_delay: u32, reveal_quorum: u8, ttl: u32 },
+    RegisterSkill { actor: u8, skill: u8 },
+    DeprecateSkill { actor: u8, skill: u8 },
+    SubmitPattern { actor: u8, pattern: u8 },
+    IssueBadge { program: u8, tier: u8, expires_in: i32 },
+    RevokeBadge { program: u8 },
+    Bootstrap,
+    /// Deregisters with at most `reports` of the program's reports.
+    Deregister { program: u8, reports: u8 },
+    Warp { seconds: u32 },
+}
+
+/// Reads fuzzer bytes, yielding zeros once they run out.
+struct Input<'a>(&'a [u8]);
+
+impl Input<'_> {
+    fn u8(&mut self) -> u8 {
+        match self.0.split_first() {
+            Some((&b, rest)) => {
+                self.0 = rest;
+                b
+            }
+            None => 0,
+        }
+    }
+
+    fn u16(&mut self) -> u16 {
+        u16::from_le_bytes([self.u8(), self.u8()])
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes([self.u8(), self.u8(), self.u8(), self.u8()])
+    }
+
+    fn bool(&mut self) -> bool {
+        self.u8() & 1 == 1
+    }
+
+    fn analysis(&mut self) -> Analysis {
+        let flags = self.u8();
+        Analysis {
+            score: self.u8(),
+            counts: [self.u16(), self.u16(), self.u16(), self.u16(), self.u16()],
+            miscount: flags & 1 == 1,
+            justified: flags & 2 == 2,
+        }
+    }
+}
+
+/// Decodes a fuzzer input into at most [`MAX_OPS`] operations.
+pub fn decode(data: &[u8]) -> Vec<Op> {
+    let mut input = Input(data);
+    let mut ops = Vec::new();
+    while !input.0.is_empty() && ops.len() < MAX_OPS {
+        let op = match input.u8() % 17 {
+            0 => Op::RegisterAuditor { actor: input.u8() },
+            1 => Op::SetAuditorActive { actor: input.u8(), active: input.bool() },
+            2 => Op::RegisterProgram { program: input.u8() },
+            3 => Op::Submit { program: input.u8(), actor: input.u8(), analysis: input.analysis() },
+            4 => Op::Update { program: input.u8(), actor: input.u8(), analysis: input.analysis() },
+            5 => Op::Commit { program: input.u8(), actor: input.u8(), analysis: input.analysis(), salt: input.u8() },
+            6 => Op::Reveal { program: input.u8(), actor: input.u8(), tamper: input.bool() },
+            7 => Op::Expire { program: input.u8(), actor: input.u8() },
+            8 => Op::SetConfig {
+                min_score: input.u8(),
+                reveal_delay: input.u32(),
+                reveal_quorum: input.u8(),
+                ttl: input.u32(),
+            },
+            9 => Op::RegisterSkill { actor: input.u8(), skill: input.u8() },
+            10 => Op::DeprecateSkill { actor: input.u8(), skill: input.u8() },
+            11 => Op::SubmitPattern { actor: input.u8(), pattern: input.u8() },
+            12 => Op::IssueBadge { program: input.u8(), tier: input.u8(), expires_in: input.u32() as i32 },
+            13 => Op::RevokeBadge { program: input.u8() },
+            14 => Op::Bootstrap,
+            15 => Op::Deregister { program: input.u8(), reports: input.u8() },
+            _ => Op::Warp { seconds: input.u32() % (3 * ONE_DAY as u32) },
+        };
+        ops.push(op);
+    }
+    ops
+}
+
+fn tier(index: u8) -> VerificationTier {
+    [VerificationTier::Bronze, VerificationTier::Silver, VerificationTier::Gold, VerificationTier::Platinum]
+        [index as usize % 4]
+}
+
+fn tier_minimum(tier: VerificationTier) -> u8 {
+    match tier {
+        VerificationTier::Bronze => 50,
+        VerificationTier::Silver => 70,
+        VerificationTier::Gold => 85,
+        VerificationTier::Platinum => 95,
+    }
+}
+
+/// What the program should have recorded, derived only from which
+/// operations succeeded.
+#[derive(Default)]
+struct Model {
+    min_score: u8,
+    /// A reveal delay or quorum is set, so results are only revealed.
+    sealed: bool,
+    auditors: HashMap<u8, bool>,
+    programs: HashMap<u8, ProgramModel>,
+    /// Committed analysis and salt per (program, actor).
+    commitments: HashMap<(u8, u8), (Analysis, u8)>,
+}
+
+#[derive(Default)]
+struct ProgramModel {
+    latest_score: u8,
+    is_verified: bool,
+    is_retired: bool,
+    reports: HashMap<u8, u8>,
+}
+
+/// A registry driven by [`Op`]s.
+pub struct World {
+    pub f: Fixture,
+    actors: Vec<Keypair>,
+    /// Deployed programs, upgradeable by the fixture's owner.
+    deployed: Vec<Pubkey>,
+    model: Model,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl World {
+    pub fn new() -> Self {
+        let mut f = Fixture::new();
+        let actors = (0..ACTORS).map(|_| f.svm.new_funded_keypair()).collect();
+        let owner = f.owner.pubkey();
+        let deployed = (0..PROGRAMS).map(|p| f.svm.deploy_upgradeable(Some(owner), &program_elf(p as usize))).collect();
+        let model = Model { min_score: default_config().min_score_for_badge, ..Model::default() };
+        Self { f, actors, deployed, model }
+    }
+
+    /// Runs `ops` in order, checking every invariant after each one.
+    pub fn run(ops: &[Op]) {
+        let mut world = Self::new();
+        for (step, op) in ops.iter().enumerate() {
+            world.apply(op);
+            if let Err(violation) = world.check() {
+                panic!("invariant violated after step {} ({:?}): {}", step, op, violation);
+            }
+        }
+    }
+
+    fn hash(program: u8) -> [u8; 32] {
+        program_hash((program % PROGRAMS) as usize)
+    }
+
+    fn actor(&self, actor: u8) -> Keypair {
+        self.actors[(actor % ACTORS) as usize].insecure_clone()
+    }
+
+    fn send(&mut self, ix: Instruction, signer: &Keypair) -> bool {
+        self.f.send(ix, signer).is_ok()
+    }
+
+    pub fn apply(&mut self, op: &Op) {
+        let id = parity::ID;
+        let registry = self.f.registry;
+        match op.clone() {
+            Op::RegisterAuditor { actor } => {
+                let actor = actor % ACTORS;
+                let signer = self.actor(actor);
+                let ix = instructions::register_auditor(&id, &registry, &signer.pubkey(), format!("auditor-{}", actor), "");
+                let ok = self.send(ix, &signer);
+                let expected = !self.model.auditors.contains_key(&actor);
+                assert_eq!(ok, expected, "registering auditor {}", actor);
+                if ok {
+                    self.model.auditors.insert(actor, true);
+                }
+            }
+            Op::SetAuditorActive { actor, active } => {
+                let actor = actor % ACTORS;
+                let authority = self.f.authority.insecure_clone();
+                let ix = instructions::update_auditor_status(&id, &registry, &authority.pubkey(), &self.actor(actor).pubkey(), active);
+                if self.send(ix, &authority) {
+                    self.model.auditors.insert(actor, active);
+                }
+            }
+            Op::RegisterProgram { program } => {
+                let program = program % PROGRAMS;
+                let owner = self.f.owner.insecure_clone();
+                let deployed = self.deployed[program as usize];
+                let ix = instructions::register_program(
+                    &id,
+                    &registry,
+                    &owner.pubkey(),
+                    &owner.pubkey(),
+                    &deployed,
+                    Self::hash(program),
+                    Framework::Anchor,
+                    "",
+                );
+                let ok = self.send(ix, &owner);
+                assert_eq!(ok, !self.model.programs.contains_key(&program), "registering program {}", program);
+                if ok {
+                    self.model.programs.insert(program, ProgramModel::default());
+                }
+            }
+            Op::Submit { program, actor, analysis } => {
+                let (program, actor) = (program % PROGRAMS, actor % ACTORS);
+                let signer = self.actor(actor);
+                let ix = instructions::submit_analysis(&id, &registry, &signer.pubkey(), &Self::hash(program), analysis.result());
+                let ok = self.send(ix, &signer);
+                let expected = analysis.is_valid()
+                    && !self.model.sealed
+                    && self.model.auditors.get(&actor) == Some(&true)
+                    && self.model.programs.get(&program).is_some_and(|p| !p.is_retired && !p.reports.contains_key(&actor));
+                assert_eq!(ok, expected, "submitting {:?}", analysis);
+                if ok {
+                    self.record(program, actor, analysis.score, false);
+                }
+            }
+            Op::Update { program, actor, analysis } => {
+                let (program, actor) = (program % PROGRAMS, actor % ACTORS);
+                let signer = self.actor(actor);
+                let ix = instructions::update_analysis(&id, &registry, &signer.pubkey(), &Self::hash(program), analysis.result());
+                let ok = self.send(ix, &signer);
+                let expected = analysis.is_valid()
+                    && !self.model.sealed
+                    && self.model.programs.get(&program).is_some_and(|p| !p.is_retired && p.reports.contains_key(&actor));
+                assert_eq!(ok, expected, "updating with {:?}", analysis);
+                if ok {
+                    self.record(program, actor, analysis.score, true);
+                }
+            }
+            Op::Commit { program, actor, analysis, salt } => {
+                let (program, actor) = (program % PROGRAMS, actor % ACTORS);
+                let signer = self.actor(actor);
+                let result = analysis.result();
+                let commitment = result.commitment(&[salt; 32]);
+                let ix = instructions::commit_analysis(&id, &registry, &signer.pubkey(), &Self::hash(program), commitment);
+                if self.send(ix, &signer) {
+                    self.model.commitments.insert((program, actor), (analysis, salt));
+                }
+            }
+            Op::Reveal { program, actor, tamper } => {
+                let (program, actor) = (program % PROGRAMS, actor % ACTORS);
+                let signer = self.actor(actor);
+                let (analysis, salt) = match self.model.commitments.get(&(program, actor)) {
+                    Some(sealed) => sealed.clone(),
+                    None => (Analysis { score: 100, counts: [0; 5], miscount: false, justified: false }, 0),
+                };
+                let salt = if tamper { salt.wrapping_add(1) } else { salt };
+                let ix = instructions::reveal_analysis(&id, &registry, &signer.pubkey(), &Self::hash(program), [salt; 32], analysis.result());
+                if self.send(ix, &signer) {
+                    assert!(!tamper, "revealed a tampered commitment");
+                    self.model.commitments.remove(&(program, actor));
+                    let is_update = self.model.programs[&program].reports.contains_key(&actor);
+                    self.record(program, actor, analysis.score, is_update);
+                }
+            }
+            Op::Expire { program, actor } => {
+                let (program, actor) = (program % PROGRAMS, actor % ACTORS);
+                let caller = self.f.authority.insecure_clone();
+                let entry = pda::program_entry(&id, &registry, &Self::hash(program)).0;
+                let ix = instructions::expire_commitment(&id, &caller.pubkey(), &entry, &self.actor(actor).pubkey());
+                if self.send(ix, &caller) {
+                    self.model.commitments.remove(&(program, actor));
+                }
+            }
+            Op::SetConfig { min_score, reveal_delay, reveal_quorum, ttl } => {
+                let config = RegistryConfig {
+                    min_score_for_badge: min_score,
+                    reveal_delay_seconds: reveal_delay as i64,
+                    reveal_quorum: reveal_quorum % 4,
+                    commitment_ttl_seconds: ttl as i64,
+                    ..default_config()
+                };
+                let authority = self.f.authority.insecure_clone();
+                let ix = instructions::update_registry_config(&id, &registry, &authority.pubkey(), config);
+                let ok = self.send(ix, &authority);
+                assert_eq!(ok, ttl > reveal_delay, "config window {}..{}", reveal_delay, ttl);
+                if ok {
+                    self.model.min_score = min_score;
+                    self.model.sealed = reveal_delay > 0 || reveal_quorum % 4 > 0;
+                }
+            }
+            Op::RegisterSkill { actor, skill } => {
+                let signer = self.actor(actor);
+                let name = SKILL_NAMES[(skill % SKILLS) as usize];
+                let ix = instructions::register_skill(&id, &registry, &signer.pubkey(), name, "0.1.0", "", SkillType::Custom);
+                self.send(ix, &signer);
+            }
+            Op::DeprecateSkill { actor, skill } => {
+                let signer = self.actor(actor);
+                let name = SKILL_NAMES[(skill % SKILLS) as usize];
+                let ix = instructions::deprecate_skill(&id, &registry, &signer.pubkey(), &signer.pubkey(), name);
+                self.send(ix, &signer);
+            }
+            Op::SubmitPattern { actor, pattern } => {
+                let signer = self.actor(actor);
+                let ix = instructions::submit_context_pattern(
+                    &id,
+                    &registry,
+                    &signer.pubkey(),
+                    PATTERN_IDS[(pattern % PATTERNS) as usize],
+                    Severity::High,
+                    PatternType::MissingSignerCheck,
+                    "",
+                    DETECTION_RULE,
+                );
+                self.send(ix, &signer);
+            }
+            Op::IssueBadge { program, tier: index, expires_in } => {
+                let authority = self.f.authority.insecure_clone();
+                let expires_at = self.f.svm.now().saturating_add(expires_in as i64);
+                let ix = instructions::create_verification_badge(&id, &registry, &authority.pubkey(), &Self::hash(program), tier(index), expires_at);
+                self.send(ix, &authority);
+            }
+            Op::RevokeBadge { program } => {
+                let authority = self.f.authority.insecure_clone();
+                let ix = instructions::revoke_verification_badge(&id, &registry, &authority.pubkey(), &Self::hash(program));
+                self.send(ix, &authority);
+            }
+            Op::Bootstrap => {
+                let authority = self.f.authority.insecure_clone();
+                let ix = instructions::bootstrap_builtins(&id, &registry, &authority.pubkey());
+                assert!(self.send(ix, &authority), "bootstrapping built-ins");
+            }
+            Op::Deregister { program, reports } => {
+                let program = program % PROGRAMS;
+                let owner = self.f.owner.insecure_clone();
+                let mut actors: Vec<u8> = match self.model.programs.get(&program) {
+                    Some(entry) => entry.reports.keys().copied().collect(),
+                    None => Vec::new(),
+                };
+                actors.sort_unstable();
+                actors.truncate(reports as usize);
+                let auditors: Vec<Pubkey> = actors.iter().map(|&a| self.actor(a).pubkey()).collect();
+                let ix = instructions::deregister_program(
+                    &id,
+                    &registry,
+                    &owner.pubkey(),
+                    &Self::hash(program),
+                    &self.f.authority.pubkey(),
+                    &auditors,
+                );
+                let ok = self.send(ix, &owner);
+                assert_eq!(ok, self.model.programs.contains_key(&program), "deregistering program {}", program);
+                if ok {
+                    let entry = self.model.programs.get_mut(&program).expect("deregistered a registered program");
+                    entry.is_retired = true;
+                    entry.is_verified = false;
+                    for actor in actors {
+                        entry.reports.remove(&actor);
+                    }
+                    let committed = self.model.commitments.keys().any(|&(p, _)| p == program);
+                    if entry.reports.is_empty() && !committed {
+                        self.model.programs.remove(&program);
+                    }
+                }
+            }
+            Op::Warp { seconds } => self.f.svm.warp(seconds as i64),
+        }
+    }
+
+    /// Applies a successful submission, reveal or update to the model.
+    fn record(&mut self, program: u8, actor: u8, score: u8, is_update: bool) {
+        let min_score = self.model.min_score;
+        let entry = self.model.programs.get_mut(&program).expect("recorded against a registered program");
+        entry.latest_score = score;
+        entry.reports.insert(actor, score);
+        entry.is_verified = if is_update { score >= min_score } else { entry.is_verified || score >= min_score };
+    }
+
+    /// Checks the accounts against each other and against the model.
+    pub fn check(&self) -> Result<(), String> {
+        let svm = &self.f.svm;
+        let registry: Registry = svm.get(&self.f.registry());
+        let entries = svm.program_accounts::<ProgramEntry>();
+        let reports = svm.program_accounts::<AnalysisReport>();
+        let auditors = svm.program_accounts::<AuditorAccount>();
+        let commitments = svm.program_accounts::<AnalysisCommitment>();
+        let skills = svm.program_accounts::<SkillEntry>();
+        let patterns = svm.program_accounts::<ContextPattern>();
+
+        let counters = [
+            ("programs", registry.total_programs, entries.len()),
+            ("analyses", registry.total_analyses, reports.len()),
+            ("auditors", registry.total_auditors, auditors.len()),
+            ("skills", registry.total_skills, skills.len()),
+            ("patterns", registry.total_patterns, patterns.len()),
+        ];
+        for (name, counter, accounts) in counters {
+            if counter != accounts as u64 {
+                return Err(format!("registry counts {} {} but {} exist", counter, name, accounts));
+            }
+        }
+        if registry.min_score_for_badge != self.model.min_score {
+            return Err(format!("threshold {} != model {}", registry.min_score_for_badge, self.model.min_score));
+        }
+
+        for (address, entry) in &entries {
+            let program = (0..PROGRAMS).find(|&p| Self::hash(p) == entry.program_hash).ok_or("unknown program hash")?;
+            let model = &self.model.programs[&program];
+            let own_reports = reports.iter().filter(|(_, r)| r.program_entry == *address).count();
+            if entry.analysis_count as usize != own_reports {
+                return Err(format!("program {} counts {} analyses, {} exist", program, entry.analysis_count, own_reports));
+            }
+            if entry.latest_score != model.latest_score
+                || entry.is_verified != model.is_verified
+                || entry.is_retired != model.is_retired
+            {
+                return Err(format!(
+                    "program {} has score {} verified {} retired {}, model has {} {} {}",
+                    program,
+                    entry.latest_score,
+                    entry.is_verified,
+                    entry.is_retired,
+                    model.latest_score,
+                    model.is_verified,
+                    model.is_retired
+                ));
+            }
+            let own_commitments: Vec<_> = commitments.iter().filter(|(_, c)| c.program_entry == *address).collect();
+            if entry.open_commitments as usize != own_commitments.len() {
+                return Err(format!(
+                    "program {} counts {} open commitments, {} exist",
+                    program,
+                    entry.open_commitments,
+                    own_commitments.len()
+                ));
+            }
+            if entry.open_commitments as u64 + entry.expired_commitments as u64 > entry.commit_count as u64 {
+                return Err(format!(
+                    "program {} has {} open and {} expired of {} commitments",
+                    program, entry.open_commitments, entry.expired_commitments, entry.commit_count
+                ));
+            }
+            for (_, sealed) in own_commitments {
+                if sealed.commit_index >= entry.commit_count {
+                    return Err(format!("commitment index {} >= commit count {}", sealed.commit_index, entry.commit_count));
+                }
+                // A round starts only while no commitment is open.
+                if sealed.commit_index < entry.round_start {
+                    return Err(format!("open commitment {} predates round {}", sealed.commit_index, entry.round_start));
+                }
+            }
+            if let Some(revealed_at) = entry.round_revealed_at {
+                if !(entry.round_start..=entry.commit_count).contains(&revealed_at) {
+                    return Err(format!(
+                        "round {} revealed at {} of {} commitments",
+                        entry.round_start, revealed_at, entry.commit_count
+                    ));
+                }
+            }
+        }
+
+        for (_, auditor) in &auditors {
+            let actor = self.actors.iter().position(|k| k.pubkey() == auditor.authority).ok_or("unknown auditor")?;
+            if self.model.auditors.get(&(actor as u8)) != Some(&auditor.is_active) {
+                return Err(format!("auditor {} active {} disagrees with the model", actor, auditor.is_active));
+            }
+        }
+
+        for (address, report) in &reports {
+            let counts = &report.findings_count;
+            let sum = counts.critical as u32 + counts.high as u32 + counts.medium as u32 + counts.info as u32 + counts.pass as u32;
+            if report.score > 100 || sum != counts.total as u32 {
+                return Err(format!("report {} stores an invalid result", address));
+            }
+            if report.score_override.is_none() && !ScoringModel::DEFAULT.is_consistent(report.score, counts) {
+                return Err(format!("report {} has an unjustified score {}", address, report.score));
+            }
+            if !auditors.iter().any(|(_, a)| a.authority == report.auditor) {
+                return Err(format!("report {} was filed by an unregistered auditor", address));
+            }
+        }
+
+        // Only the registry authority holds names in the registry's namespace.
+        let registry_address = self.f.registry();
+        for (_, skill) in &skills {
+            let owner = if skill.namespace == registry_address { registry.authority } else { skill.namespace };
+            if skill.authority != owner {
+                return Err(format!("skill {} in namespace {} is held by {}", skill.name, skill.namespace, skill.authority));
+            }
+        }
+        for (_, pattern) in &patterns {
+            let owner = if pattern.namespace == registry_address { registry.authority } else { pattern.namespace };
+            if pattern.submitter != owner {
+                return Err(format!("pattern {} in namespace {} is held by {}", pattern.pattern_id, pattern.namespace, pattern.submitter));
+            }
+        }
+
+        for (address, badge) in svm.program_accounts::<VerificationBadge>() {
+            if badge.score_at_issuance < tier_minimum(badge.tier) || badge.expires_at <= badge.issued_at {
+                return Err(format!("badge {} was issued without meeting its tier", address));
+            }
+        }
+        Ok(())
+    }
+}
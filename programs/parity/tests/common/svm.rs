@@ -0,0 +1,326 @@
+//! A synchronous wrapper around `solana-program-test` for the parity program.
+//!
+//! Transactions run through a real bank, so the system program, precompiles,
+//! the instructions sysvar, fees and signature checks are the runtime's own.
+//! Under `cargo test-sbf` the compiled `parity.so` is loaded and executed by
+//! the SBF loader; under plain `cargo test` `parity::entry` is registered as
+//! a native builtin instead.
+
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Mutex;
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, Discriminator};
+use solana_program::account_info::AccountInfo;
+use solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_program;
+use solana_program_test::{processor, BanksClient, ProgramTest, ProgramTestContext};
+use solana_sdk::account::{Account as SdkAccount, AccountSharedData};
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::feature_set::FeatureSet;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use tokio::runtime::Runtime;
+
+/// Unix time the clock starts at.
+pub const GENESIS_TIMESTAMP: i64 = 1_700_000_000;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Account {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+impl From<SdkAccount> for Account {
+    fn from(account: SdkAccount) -> Self {
+        Self { lamports: account.lamports, data: account.data, owner: account.owner, executable: account.executable }
+    }
+}
+
+impl From<Account> for AccountSharedData {
+    fn from(account: Account) -> Self {
+        SdkAccount {
+            lamports: account.lamports,
+            data: account.data,
+            owner: account.owner,
+            executable: account.executable,
+            rent_epoch: 0,
+        }
+        .into()
+    }
+}
+
+/// Why a transaction failed.
+#[derive(Debug)]
+pub struct TxError {
+    pub error: TransactionError,
+    pub logs: Vec<String>,
+}
+
+impl TxError {
+    /// Index of the failing instruction, if the transaction got that far.
+    pub fn instruction(&self) -> Option<usize> {
+        match self.error {
+            TransactionError::InstructionError(index, _) => Some(index as usize),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> Option<u32> {
+        match self.error {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(code),
+            _ => None,
+        }
+    }
+}
+
+pub type TxResult = Result<(), TxError>;
+
+/// The native stand-in for `parity.so`. `parity::entry` ties the slice's
+/// lifetime to the infos inside it, which a builtin's signature cannot
+/// promise, so the infos are copied into a leaked slice that lives long
+/// enough. The copies share their lamports and data with the originals.
+fn entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts: &[AccountInfo] = Box::leak(accounts.to_vec().into_boxed_slice());
+    parity::entry(program_id, accounts, data)
+}
+
+/// A started program-test bank. Every bank a `ProgramTestContext` starts
+/// stays alive until the process exits, so banks are pooled and each [`Svm`]
+/// borrows one, wiping what it wrote when it is dropped.
+struct Bank {
+    runtime: Runtime,
+    context: ProgramTestContext,
+    /// Signatures already sent, so an identical retry is signed over a fresh
+    /// blockhash instead of being rejected as a duplicate.
+    sent: HashSet<Signature>,
+}
+
+static BANKS: Mutex<Vec<Bank>> = Mutex::new(Vec::new());
+
+impl Bank {
+    fn start() -> Self {
+        // program-test logs every failed instruction at debug level straight
+        // to stderr; failures are reported through `TxError` instead.
+        if std::env::var_os("RUST_LOG").is_none() {
+            std::env::set_var("RUST_LOG", "error");
+        }
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let context = runtime.block_on(ProgramTest::new("parity", parity::ID, processor!(entry)).start_with_context());
+        Self { runtime, context, sent: HashSet::new() }
+    }
+}
+
+pub struct Svm {
+    bank: Option<Bank>,
+    clock: Clock,
+    /// Every address a transaction or a test has touched. The banks client
+    /// cannot scan accounts by owner, so [`Svm::program_accounts`] looks here,
+    /// and dropping the `Svm` deletes what is left of them.
+    touched: BTreeSet<Pubkey>,
+    logs: Vec<String>,
+    fee: u64,
+}
+
+impl Default for Svm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Svm {
+    fn drop(&mut self) {
+        // A test that panicked may have left the bank mid-transaction.
+        if std::thread::panicking() {
+            return;
+        }
+        for address in std::mem::take(&mut self.touched) {
+            let owner = self.account(&address).map(|account| account.owner);
+            if owner.is_some_and(|owner| [parity::ID, system_program::ID, bpf_loader_upgradeable::ID].contains(&owner)) {
+                self.bank_mut().context.set_account(&address, &AccountSharedData::default());
+            }
+        }
+        BANKS.lock().unwrap().extend(self.bank.take());
+    }
+}
+
+impl Svm {
+    pub fn new() -> Self {
+        let bank = BANKS.lock().unwrap().pop().unwrap_or_else(Bank::start);
+        let mut svm = Self { bank: Some(bank), clock: Clock::default(), touched: BTreeSet::new(), logs: Vec::new(), fee: 0 };
+        let mut clock: Clock = svm.block_on(svm.client().get_sysvar()).unwrap();
+        clock.unix_timestamp = GENESIS_TIMESTAMP;
+        clock.epoch_start_timestamp = GENESIS_TIMESTAMP;
+        svm.set_clock(clock);
+        svm
+    }
+
+    fn bank(&self) -> &Bank {
+        self.bank.as_ref().unwrap()
+    }
+
+    fn bank_mut(&mut self) -> &mut Bank {
+        self.bank.as_mut().unwrap()
+    }
+
+    fn block_on<F: std::future::Future>(&self, future: F) -> F::Output {
+        self.bank().runtime.block_on(future)
+    }
+
+    fn client(&self) -> BanksClient {
+        self.bank().context.banks_client.clone()
+    }
+
+    fn set_clock(&mut self, clock: Clock) {
+        self.bank().context.set_sysvar(&clock);
+        self.clock = clock;
+    }
+
+    pub fn airdrop(&mut self, address: &Pubkey, lamports: u64) {
+        let mut account = self.account(address).unwrap_or(Account { owner: system_program::ID, ..Account::default() });
+        account.lamports += lamports;
+        self.set_account(*address, account);
+    }
+
+    pub fn new_funded_keypair(&mut self) -> Keypair {
+        let keypair = Keypair::new();
+        self.airdrop(&keypair.pubkey(), 100_000_000_000);
+        keypair
+    }
+
+    /// Creates the accounts the upgradeable loader would for a program with
+    /// bytecode `elf`, and returns the program's address. The bytecode is
+    /// never executed.
+    pub fn deploy_upgradeable(&mut self, upgrade_authority: Option<Pubkey>, elf: &[u8]) -> Pubkey {
+        let program = Pubkey::new_unique();
+        let (programdata_address, _) = Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::ID);
+        let state = UpgradeableLoaderState::Program { programdata_address };
+        self.set_loader_account(program, bincode::serialize(&state).unwrap(), true);
+
+        let state = UpgradeableLoaderState::ProgramData { slot: 0, upgrade_authority_address: upgrade_authority };
+        let mut data = bincode::serialize(&state).unwrap();
+        data.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+        data.extend_from_slice(elf);
+        // Deployments reserve room to grow, zero-filled like the loader does.
+        data.resize(data.len() + elf.len(), 0);
+        self.set_loader_account(programdata_address, data, false);
+        program
+    }
+
+    fn set_loader_account(&mut self, address: Pubkey, data: Vec<u8>, executable: bool) {
+        let lamports = Rent::default().minimum_balance(data.len());
+        self.set_account(address, Account { lamports, data, owner: bpf_loader_upgradeable::ID, executable });
+    }
+
+    /// Writes `account` straight into the bank, bypassing the runtime.
+    pub fn set_account(&mut self, address: Pubkey, account: Account) {
+        self.bank_mut().context.set_account(&address, &account.into());
+        self.touched.insert(address);
+    }
+
+    pub fn account(&self, address: &Pubkey) -> Option<Account> {
+        self.block_on(self.client().get_account_with_commitment(*address, CommitmentLevel::Processed)).unwrap().map(Account::from)
+    }
+
+    pub fn lamports(&self, address: &Pubkey) -> u64 {
+        self.account(address).map_or(0, |a| a.lamports)
+    }
+
+    /// Deserializes a program account, panicking if it is missing or not a `T`.
+    pub fn get<T: AccountDeserialize>(&self, address: &Pubkey) -> T {
+        let account = self.account(address).unwrap_or_else(|| panic!("account {} does not exist", address));
+        assert_eq!(account.owner, parity::ID, "account {} is not owned by parity", address);
+        T::try_deserialize(&mut account.data.as_slice())
+            .unwrap_or_else(|e| panic!("account {} failed to deserialize: {}", address, e))
+    }
+
+    /// Overwrites an existing program account with `value`, keeping its size.
+    /// Used to reach states that would take too many transactions to build.
+    pub fn put<T: AccountSerialize>(&mut self, address: &Pubkey, value: &T) {
+        let mut account = self.account(address).unwrap_or_else(|| panic!("account {} does not exist", address));
+        let mut data = Vec::new();
+        value.try_serialize(&mut data).unwrap();
+        account.data[..data.len()].copy_from_slice(&data);
+        self.set_account(*address, account);
+    }
+
+    /// Every parity account of type `T`, in no particular order.
+    pub fn program_accounts<T: AccountDeserialize + Discriminator>(&self) -> Vec<(Pubkey, T)> {
+        self.touched
+            .iter()
+            .filter_map(|address| Some((*address, self.account(address)?)))
+            .filter(|(_, account)| account.owner == parity::ID && account.data.starts_with(&T::DISCRIMINATOR))
+            .map(|(address, account)| (address, T::try_deserialize(&mut account.data.as_slice()).unwrap()))
+            .collect()
+    }
+
+    pub fn now(&self) -> i64 {
+        self.clock.unix_timestamp
+    }
+
+    /// Moves the clock forward by `seconds`.
+    pub fn warp(&mut self, seconds: i64) {
+        let mut clock = self.clock.clone();
+        clock.unix_timestamp += seconds;
+        clock.slot += (seconds.max(0) as u64) * 5 / 2;
+        self.set_clock(clock);
+    }
+
+    /// Logs of the most recent transaction.
+    pub fn logs(&self) -> &[String] {
+        &self.logs
+    }
+
+    /// Fee the most recent transaction's payer was charged.
+    pub fn fee(&self) -> u64 {
+        self.fee
+    }
+
+    /// Sends `instructions` as one transaction paid for by the first signer.
+    pub fn process(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> TxResult {
+        let payer = signers[0].pubkey();
+        let (blockhash, _) =
+            self.block_on(self.client().get_latest_blockhash_with_commitment(CommitmentLevel::Processed)).unwrap().unwrap();
+        let mut transaction = Transaction::new_signed_with_payer(instructions, Some(&payer), signers, blockhash);
+        while self.bank().sent.contains(&transaction.signatures[0]) {
+            self.refresh_blockhash();
+            transaction.sign(signers, self.bank().context.last_blockhash);
+        }
+        self.bank_mut().sent.insert(transaction.signatures[0]);
+        self.touched.extend(transaction.message.account_keys.iter().copied());
+
+        // The banks server executes without a sigverify stage, so reject what
+        // a validator's would before the transaction ever reached a bank.
+        if let Err(error) = transaction.verify().and_then(|()| transaction.verify_precompiles(&FeatureSet::all_enabled())) {
+            self.logs = Vec::new();
+            return Err(TxError { error, logs: Vec::new() });
+        }
+
+        let mut client = self.client();
+        self.fee = self.block_on(client.get_fee_for_message(transaction.message.clone())).unwrap().unwrap_or(0);
+        let outcome = self.block_on(client.process_transaction_with_metadata(transaction)).unwrap();
+        self.logs = outcome.metadata.map(|metadata| metadata.log_messages).unwrap_or_default();
+        outcome.result.map_err(|error| TxError { error, logs: self.logs.clone() })
+    }
+
+    /// Moves the bank to the next slot so the next transaction gets a new
+    /// blockhash, then puts the test's clock back.
+    fn refresh_blockhash(&mut self) {
+        // The test clock's slot runs ahead of the bank's, so find the next
+        // slot the bank will accept.
+        let mut slot = self.block_on(self.client().get_root_slot()).unwrap();
+        loop {
+            slot += 1;
+            if self.bank_mut().context.warp_to_slot(slot).is_ok() {
+                break;
+            }
+        }
+        self.set_clock(self.clock.clone());
+    }
+}
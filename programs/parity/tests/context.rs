@@ -0,0 +1,121 @@
+mod common;
+
+use common::{assert_ok, assert_parity_error, Fixture, TxResult};
+use parity::context_engine::VULNERABILITY_RULES;
+use parity::detection_rule::MAX_RULE_DEPTH;
+use parity::errors::ParityError;
+use parity::state::{
+    ContextPattern, PatternType, Registry, Severity, MAX_DETECTION_RULE_LEN, MAX_PATTERN_DESC_LEN,
+};
+use parity_client::{instructions, pda};
+use solana_sdk::signature::{Keypair, Signer};
+
+fn submit(f: &mut Fixture, submitter: &Keypair, id: &str, description: &str, rule: &str) -> TxResult {
+    let ix = instructions::submit_context_pattern(
+        &parity::ID,
+        &f.registry,
+        &submitter.pubkey(),
+        id,
+        Severity::High,
+        PatternType::MissingSignerCheck,
+        description,
+        rule,
+    );
+    f.send(ix, submitter)
+}
+
+#[test]
+fn submit_context_pattern_records_an_active_pattern() {
+    let mut f = Fixture::new();
+    let submitter = f.svm.new_funded_keypair();
+    let rule = VULNERABILITY_RULES[0].detection_rule;
+    assert_ok(submit(&mut f, &submitter, "unchecked-admin", "Admin without signer", rule));
+
+    let pattern: ContextPattern = f.svm.get(&pda::pattern(&parity::ID, &f.registry, &submitter.pubkey(), "unchecked-admin").0);
+    assert_eq!(pattern.submitter, submitter.pubkey());
+    assert_eq!(pattern.namespace, submitter.pubkey());
+    assert_eq!(pattern.severity, Severity::High);
+    assert_eq!(pattern.pattern_type, PatternType::MissingSignerCheck);
+    assert_eq!(pattern.detection_rule, rule);
+    assert!(pattern.is_active);
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_patterns, 1);
+}
+
+#[test]
+fn submit_context_pattern_rejects_invalid_patterns() {
+    let mut f = Fixture::new();
+    let submitter = f.svm.new_funded_keypair();
+    let rule = VULNERABILITY_RULES[0].detection_rule;
+
+    assert_parity_error(
+        submit(&mut f, &submitter, "p", &"d".repeat(MAX_PATTERN_DESC_LEN + 1), rule),
+        ParityError::PatternDescriptionTooLong,
+    );
+    assert_parity_error(
+        submit(&mut f, &submitter, "p", "", &"x".repeat(MAX_DETECTION_RULE_LEN + 1)),
+        ParityError::DetectionRuleTooLong,
+    );
+    assert_parity_error(
+        submit(&mut f, &submitter, "p", "", "rule v1 match nothing"),
+        ParityError::InvalidDetectionRule,
+    );
+    let nested = format!("rule v1 match field where {}", "(".repeat(MAX_DETECTION_RULE_LEN - 26));
+    assert_parity_error(submit(&mut f, &submitter, "p", "", &nested), ParityError::InvalidDetectionRule);
+    assert!(f
+        .svm
+        .logs()
+        .iter()
+        .any(|line| line.contains("Invalid detection rule")));
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_patterns, 0);
+}
+
+#[test]
+fn flat_chains_are_limited_only_by_the_rule_length() {
+    let mut f = Fixture::new();
+    let submitter = f.svm.new_funded_keypair();
+    let head = "rule v1 match field where signer";
+    let chain = format!("{}{}", head, " and mut".repeat((MAX_DETECTION_RULE_LEN - head.len()) / 8));
+    assert_ok(submit(&mut f, &submitter, "long-chain", "", &chain));
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_patterns, 1);
+}
+
+/// Under `cargo test-sbf` this runs the parser at its deepest inside the
+/// runtime's call depth and stack limits.
+#[test]
+fn rules_nested_to_the_limit_parse_on_chain() {
+    let mut f = Fixture::new();
+    let submitter = f.svm.new_funded_keypair();
+    let nots = format!("rule v1 match field where {}signer", "not ".repeat(MAX_RULE_DEPTH));
+    let parens = format!("rule v1 match field where {}signer{}", "(".repeat(MAX_RULE_DEPTH), ")".repeat(MAX_RULE_DEPTH));
+    // The `and` chain, then an `or` chain and a parenthesis per group, then `not`.
+    let groups = (MAX_RULE_DEPTH - 2) / 2;
+    let chains = format!(
+        "rule v1 match field where not {}signer and mut{}",
+        "(mut or ".repeat(groups),
+        ")".repeat(groups)
+    );
+    for (id, rule) in [("nots", &nots), ("parens", &parens), ("chains", &chains)] {
+        assert_ok(submit(&mut f, &submitter, id, "", rule));
+    }
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_patterns, 3);
+
+    let deeper = format!("rule v1 match field where not {}", &nots["rule v1 match field where ".len()..]);
+    assert_parity_error(submit(&mut f, &submitter, "deeper", "", &deeper), ParityError::InvalidDetectionRule);
+}
+
+#[test]
+fn pattern_ids_are_scoped_to_their_submitter() {
+    let mut f = Fixture::new();
+    let first = f.svm.new_funded_keypair();
+    let second = f.svm.new_funded_keypair();
+    let rule = VULNERABILITY_RULES[0].detection_rule;
+    assert_ok(submit(&mut f, &first, "p", "first", rule));
+    assert!(submit(&mut f, &first, "p", "again", rule).is_err());
+    assert_ok(submit(&mut f, &second, "p", "second", rule));
+
+    let pattern: ContextPattern = f.svm.get(&pda::pattern(&parity::ID, &f.registry, &first.pubkey(), "p").0);
+    assert_eq!(pattern.description, "first");
+    let pattern: ContextPattern = f.svm.get(&pda::pattern(&parity::ID, &f.registry, &second.pubkey(), "p").0);
+    assert_eq!(pattern.description, "second");
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_patterns, 2);
+}
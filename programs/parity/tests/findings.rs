@@ -0,0 +1,73 @@
+use parity::findings::{
+    findings_root, verify_exclusion, verify_inclusion, ExclusionProof, Finding, FindingLocation, FindingsTree,
+    LeafProof,
+};
+use parity::state::Severity;
+
+fn finding(line: u32) -> Finding {
+    Finding {
+        rule_id: "insecure-cpi".to_string(),
+        severity: Severity::High,
+        location: FindingLocation { file: "src/lib.rs".to_string(), line, column: 5 },
+        description_hash: [line as u8; 32],
+    }
+}
+
+#[test]
+fn every_member_has_an_inclusion_proof() {
+    for size in 0..12u32 {
+        let findings: Vec<Finding> = (0..size).map(finding).collect();
+        let tree = FindingsTree::new(&findings);
+        let root = tree.root();
+        assert_eq!(root, findings_root(&findings));
+
+        for f in &findings {
+            let proof = tree.prove(f).expect("member");
+            assert!(verify_inclusion(&root, f, &proof), "size {} line {}", size, f.location.line);
+            assert!(tree.prove_absent(f).is_none());
+        }
+
+        let outsider = finding(1000);
+        assert!(tree.prove(&outsider).is_none());
+        let proof = tree.prove_absent(&outsider).expect("non-member");
+        assert!(verify_exclusion(&root, &outsider, &proof), "size {}", size);
+    }
+}
+
+#[test]
+fn root_ignores_order_and_duplicates() {
+    let findings: Vec<Finding> = (0..5).map(finding).collect();
+    let mut shuffled = findings.clone();
+    shuffled.reverse();
+    shuffled.push(finding(2));
+    assert_eq!(findings_root(&findings), findings_root(&shuffled));
+    assert_ne!(findings_root(&findings), findings_root(&findings[..4]));
+}
+
+#[test]
+fn forged_proofs_are_rejected() {
+    let findings: Vec<Finding> = (0..6).map(finding).collect();
+    let tree = FindingsTree::new(&findings);
+    let root = tree.root();
+
+    let mut tampered = finding(3);
+    tampered.severity = Severity::Info;
+    let proof = tree.prove(&finding(3)).unwrap();
+    assert!(!verify_inclusion(&root, &tampered, &proof));
+
+    let mut wrong_count = proof.clone();
+    wrong_count.leaf_count += 1;
+    assert!(!verify_inclusion(&root, &finding(3), &wrong_count));
+
+    // A member cannot be passed off as absent by skipping over its leaf.
+    let target = finding(3);
+    let position = tree.prove(&target).unwrap().index;
+    let neighbour = |index: u32| {
+        let f = findings.iter().find(|f| tree.prove(f).unwrap().index == index).unwrap();
+        LeafProof { leaf: f.leaf(), proof: tree.prove(f).unwrap() }
+    };
+    let lower = position.checked_sub(1).map(neighbour);
+    let upper = (position + 1 < findings.len() as u32).then(|| neighbour(position + 1));
+    let forged = ExclusionProof { leaf_count: findings.len() as u32, lower, upper };
+    assert!(!verify_exclusion(&root, &target, &forged));
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::{assert_ok, assert_parity_error, result, source, Fixture, TxResult};
+use parity::errors::ParityError;
+use parity::state::{AnalysisReport, ProgramDetails, ProgramMetadata, SourceRevision, MAX_PROGRAM_NAME_LEN, MAX_TOOLCHAIN_LEN};
+use parity_client::instructions::{self, AnalysisResult};
+use parity_client::pda;
+use solana_sdk::signature::{Keypair, Signer};
+
+fn details(release_tag: &str) -> ProgramDetails {
+    ProgramDetails {
+        name: "vault".to_string(),
+        repository_url: "https://github.com/acme/vault".to_string(),
+        source: source(),
+        release_tag: release_tag.to_string(),
+        security_contact: "email:security@acme.dev".to_string(),
+    }
+}
+
+fn set(f: &mut Fixture, signer: &Keypair, details: ProgramDetails) -> TxResult {
+    let ix = instructions::set_program_metadata(&parity::ID, &f.registry, &signer.pubkey(), &f.programs[0], details);
+    f.send(ix, signer)
+}
+
+fn metadata_address(f: &Fixture) -> solana_program::pubkey::Pubkey {
+    pda::program_metadata(&parity::ID, &f.program_entry(0)).0
+}
+
+#[test]
+fn set_program_metadata_creates_then_replaces_the_account() {
+    let mut f = Fixture::new().with_programs(1);
+    let owner = f.owner.insecure_clone();
+    assert_ok(set(&mut f, &owner, details("v1.0.0")));
+
+    let metadata: ProgramMetadata = f.svm.get(&metadata_address(&f));
+    assert_eq!(metadata.program_entry, f.program_entry(0));
+    assert_eq!(metadata.details, details("v1.0.0"));
+
+    f.svm.warp(60);
+    let sha256_commit = SourceRevision { commit: "ab".repeat(32), toolchain: "anchor 0.30.1".to_string() };
+    assert_ok(set(&mut f, &owner, ProgramDetails { source: sha256_commit.clone(), ..details("v1.1.0") }));
+    let metadata: ProgramMetadata = f.svm.get(&metadata_address(&f));
+    assert_eq!((metadata.details.release_tag.as_str(), &metadata.details.source), ("v1.1.0", &sha256_commit));
+    assert_eq!(metadata.updated_at, f.svm.now());
+}
+
+#[test]
+fn set_program_metadata_by_the_owner_only() {
+    let mut f = Fixture::new().with_programs(1);
+    let intruder = f.svm.new_funded_keypair();
+    assert_parity_error(set(&mut f, &intruder, details("v1.0.0")), ParityError::UnauthorizedProgramOwner);
+    assert!(f.svm.account(&metadata_address(&f)).is_none());
+}
+
+#[test]
+fn set_program_metadata_validates_every_field() {
+    let mut f = Fixture::new().with_programs(1);
+    let owner = f.owner.insecure_clone();
+
+    let long_name = ProgramDetails { name: "n".repeat(MAX_PROGRAM_NAME_LEN + 1), ..details("v1") };
+    assert_parity_error(set(&mut f, &owner, long_name), ParityError::ProgramNameTooLong);
+    let long_toolchain = SourceRevision { toolchain: "t".repeat(MAX_TOOLCHAIN_LEN + 1), ..source() };
+    assert_parity_error(
+        set(&mut f, &owner, ProgramDetails { source: long_toolchain, ..details("v1") }),
+        ParityError::ToolchainTooLong,
+    );
+    for commit in ["abc123", &"AB".repeat(20), &"zz".repeat(20)] {
+        let bad = SourceRevision { commit: commit.to_string(), ..source() };
+        assert_parity_error(
+            set(&mut f, &owner, ProgramDetails { source: bad, ..details("v1") }),
+            ParityError::InvalidSourceCommit,
+        );
+    }
+
+    // Everything may be left unknown.
+    assert_ok(set(&mut f, &owner, ProgramDetails::default()));
+}
+
+#[test]
+fn reports_record_the_revision_they_analyzed() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    f.verify(0, 0, 90);
+    assert_eq!(f.svm.get::<AnalysisReport>(&f.analysis(0, 0)).source, source());
+
+    let next = SourceRevision { commit: "fe".repeat(20), toolchain: "anchor 0.31.0".to_string() };
+    assert_ok(f.update(0, 0, AnalysisResult { source: next.clone(), ..result(90) }));
+    assert_eq!(f.svm.get::<AnalysisReport>(&f.analysis(0, 0)).source, next);
+
+    let bad = SourceRevision { commit: "main".to_string(), ..source() };
+    assert_parity_error(
+        f.update(0, 0, AnalysisResult { source: bad, ..result(90) }),
+        ParityError::InvalidSourceCommit,
+    );
+}
+
+#[test]
+fn deregistration_closes_the_metadata() {
+    let mut f = Fixture::new().with_programs(1);
+    let owner = f.owner.insecure_clone();
+    assert_ok(set(&mut f, &owner, details("v1.0.0")));
+    let address = metadata_address(&f);
+    let rent = f.svm.lamports(&address);
+    let entry_rent = f.svm.lamports(&f.program_entry(0));
+
+    let before = f.svm.lamports(&owner.pubkey());
+    let ix = instructions::deregister_program(&parity::ID, &f.registry, &owner.pubkey(), &f.programs[0], &owner.pubkey(), &[]);
+    assert_ok(f.send(ix, &owner));
+    assert!(f.svm.account(&address).is_none());
+    assert_eq!(f.svm.lamports(&owner.pubkey()), before + rent + entry_rent - f.svm.fee());
+}
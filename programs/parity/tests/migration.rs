@@ -0,0 +1,580 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::Discriminator;
+use common::svm::Account;
+use common::{
+    assert_anchor_error, assert_ok, assert_parity_error, program_elf, program_hash, result, Fixture, TxResult,
+};
+use parity::errors::ParityError;
+use parity::schema::v0::{self, Legacy, MIGRATED_COMMITMENT_TTL_SECONDS};
+use parity::schema::v1;
+use parity::schema::{self, Versioned, CURRENT_SCHEMA_VERSION};
+use parity::state::{
+    AnalysisFindingsCount, AnalysisReport, AuditorAccount, ContextPattern, Framework, PatternType, ProgramEntry,
+    Registry, Severity, SkillEntry, SkillType, SourceRevision, VerificationBadge, VerificationTier,
+    SCHEMA_RESERVED_LEN,
+};
+use parity_client::instructions::{self, LegacyMigration};
+use parity_client::pda;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+
+/// The legacy entry's program, deployable with `program_elf(PROGRAM)`.
+const PROGRAM: usize = 7;
+const CREATED_AT: i64 = 1_650_000_000;
+
+/// Writes `value` at `address` the way the first release allocated it.
+fn put_legacy<L: Legacy>(f: &mut Fixture, address: Pubkey, value: &L) {
+    let data = value.to_account_data();
+    let lamports = Rent::default().minimum_balance(data.len());
+    f.svm.set_account(address, Account { lamports, data, owner: parity::ID, executable: false });
+}
+
+fn legacy_address(seeds: &[&[u8]]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, &parity::ID)
+}
+
+/// A deployment as the first release left it: the single registry, owned by
+/// the fixture's authority, with one program, auditor, report, badge, skill
+/// and pattern, all at their old addresses.
+struct LegacyDeployment {
+    f: Fixture,
+    auditor: Keypair,
+    registry: Pubkey,
+    entry: Pubkey,
+    auditor_account: Pubkey,
+    report: Pubkey,
+    badge: Pubkey,
+    skill: Pubkey,
+    pattern: Pubkey,
+}
+
+impl LegacyDeployment {
+    fn new() -> Self {
+        let mut f = Fixture::new();
+        let auditor = f.svm.new_funded_keypair();
+        let authority = f.authority.pubkey();
+        let owner = f.owner.pubkey();
+
+        let (registry, bump) = legacy_address(&[b"registry"]);
+        let value = v0::Registry {
+            authority,
+            total_programs: 1,
+            total_analyses: 1,
+            total_skills: 1,
+            total_auditors: 1,
+            total_patterns: 1,
+            min_score_for_badge: 70,
+            analysis_fee_lamports: 0,
+            is_paused: false,
+            bump,
+            created_at: CREATED_AT,
+            updated_at: CREATED_AT + 1,
+        };
+        put_legacy(&mut f, registry, &value);
+
+        let (entry, bump) = legacy_address(&[b"program", &program_hash(PROGRAM)]);
+        let value = v0::ProgramEntry {
+            owner,
+            program_hash: program_hash(PROGRAM),
+            framework: Framework::Native,
+            metadata_uri: "ipfs://legacy".to_string(),
+            registered_at: CREATED_AT,
+            updated_at: CREATED_AT + 2,
+            analysis_count: 1,
+            latest_score: 85,
+            is_verified: true,
+            bump,
+        };
+        put_legacy(&mut f, entry, &value);
+
+        let (auditor_account, bump) = legacy_address(&[b"auditor", auditor.pubkey().as_ref()]);
+        let value = v0::AuditorAccount {
+            authority: auditor.pubkey(),
+            name: "legacy-auditor".to_string(),
+            credentials_uri: "https://example.com/legacy".to_string(),
+            total_analyses: 1,
+            average_score: 85,
+            is_active: true,
+            registered_at: CREATED_AT,
+            updated_at: CREATED_AT,
+            bump,
+        };
+        put_legacy(&mut f, auditor_account, &value);
+
+        let (report, bump) = legacy_address(&[b"analysis", entry.as_ref(), auditor.pubkey().as_ref()]);
+        let value = v0::AnalysisReport {
+            program_entry: entry,
+            auditor: auditor.pubkey(),
+            score: 85,
+            findings_hash: [3; 32],
+            skills_used: vec!["security-audit".to_string()],
+            findings_count: AnalysisFindingsCount { critical: 0, high: 0, medium: 0, info: 2, pass: 5, total: 7 },
+            submitted_at: CREATED_AT,
+            updated_at: CREATED_AT + 3,
+            version: 3,
+            bump,
+        };
+        put_legacy(&mut f, report, &value);
+
+        let (badge, bump) = legacy_address(&[b"badge", entry.as_ref()]);
+        let value = v0::VerificationBadge {
+            program_entry: entry,
+            issuer: authority,
+            tier: VerificationTier::Silver,
+            score_at_issuance: 85,
+            issued_at: CREATED_AT,
+            expires_at: CREATED_AT + 365 * 24 * 60 * 60,
+            is_revoked: false,
+            bump,
+        };
+        put_legacy(&mut f, badge, &value);
+
+        let (skill, bump) = legacy_address(&[b"skill", b"scan"]);
+        let value = v0::SkillEntry {
+            authority: auditor.pubkey(),
+            name: "scan".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Legacy scanner".to_string(),
+            skill_type: SkillType::Custom,
+            usage_count: 4,
+            is_deprecated: false,
+            registered_at: CREATED_AT,
+            updated_at: CREATED_AT,
+            bump,
+        };
+        put_legacy(&mut f, skill, &value);
+
+        let (pattern, bump) = legacy_address(&[b"pattern", b"legacy-rule"]);
+        let value = v0::ContextPattern {
+            submitter: auditor.pubkey(),
+            pattern_id: "legacy-rule".to_string(),
+            severity: Severity::High,
+            pattern_type: PatternType::OwnerCheck,
+            description: "Owner not checked".to_string(),
+            detection_rule: "missing owner check".to_string(),
+            usage_count: 2,
+            submitted_at: CREATED_AT,
+            updated_at: CREATED_AT,
+            is_active: true,
+            bump,
+        };
+        put_legacy(&mut f, pattern, &value);
+
+        LegacyDeployment { f, auditor, registry, entry, auditor_account, report, badge, skill, pattern }
+    }
+
+    fn migrate_registry(&mut self) {
+        let registry = self.registry;
+        assert_ok(migrate(&mut self.f, &registry));
+    }
+
+    fn program_entry(&self) -> Pubkey {
+        pda::program_entry(&parity::ID, &self.registry, &program_hash(PROGRAM)).0
+    }
+
+    /// Every legacy account but the registry, with its new address, entries
+    /// ahead of the reports and badges that need them.
+    fn items(&self) -> Vec<LegacyMigration> {
+        let auditor = self.auditor.pubkey();
+        let entry = self.program_entry();
+        let item = |legacy, destination, program_entry| LegacyMigration { legacy, destination, program_entry };
+        vec![
+            item(self.entry, entry, None),
+            item(self.auditor_account, pda::auditor(&parity::ID, &self.registry, &auditor).0, None),
+            item(self.report, pda::analysis(&parity::ID, &entry, &auditor).0, Some(entry)),
+            item(self.badge, pda::badge(&parity::ID, &entry).0, Some(entry)),
+            item(self.skill, pda::skill(&parity::ID, &self.registry, &auditor, "scan").0, None),
+            item(self.pattern, pda::pattern(&parity::ID, &self.registry, &auditor, "legacy-rule").0, None),
+        ]
+    }
+
+    fn migrate_legacy(&mut self, item: LegacyMigration) -> TxResult {
+        let payer = self.f.svm.new_funded_keypair();
+        let ix = instructions::migrate_legacy_account(
+            &parity::ID,
+            &payer.pubkey(),
+            &item.legacy,
+            &item.destination,
+            item.program_entry.as_ref(),
+        );
+        self.f.send(ix, &payer)
+    }
+}
+
+fn data(f: &Fixture, address: &Pubkey) -> Vec<u8> {
+    f.svm.account(address).unwrap().data
+}
+
+fn migrate(f: &mut Fixture, address: &Pubkey) -> TxResult {
+    let payer = f.svm.new_funded_keypair();
+    f.send(instructions::migrate_account(&parity::ID, &payer.pubkey(), address), &payer)
+}
+
+#[test]
+fn legacy_layouts_are_the_first_release_sizes() {
+    // Worked out by hand from the first release's `state.rs`.
+    assert_eq!(v0::Registry::SPACE, 107);
+    assert_eq!(v0::ProgramEntry::SPACE, 300);
+    assert_eq!(v0::AnalysisReport::SPACE, 683);
+    assert_eq!(v0::SkillEntry::SPACE, 415);
+    assert_eq!(v0::AuditorAccount::SPACE, 346);
+    assert_eq!(v0::VerificationBadge::SPACE, 92);
+    assert_eq!(v0::ContextPattern::SPACE, 912);
+
+    // Every current layout is larger, so an account's size tells the two
+    // apart.
+    let sizes = [
+        (v0::Registry::SPACE, Registry::SPACE),
+        (v0::ProgramEntry::SPACE, ProgramEntry::SPACE),
+        (v0::AnalysisReport::SPACE, AnalysisReport::SPACE),
+        (v0::SkillEntry::SPACE, SkillEntry::SPACE),
+        (v0::AuditorAccount::SPACE, AuditorAccount::SPACE),
+        (v0::VerificationBadge::SPACE, VerificationBadge::SPACE),
+        (v0::ContextPattern::SPACE, ContextPattern::SPACE),
+    ];
+    assert!(sizes.iter().all(|(legacy, current)| legacy < current));
+}
+
+#[test]
+fn new_accounts_are_written_at_the_current_version() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    f.verify(0, 0, 90);
+
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert_eq!((entry.schema_version, entry.reserved), (CURRENT_SCHEMA_VERSION, [0; SCHEMA_RESERVED_LEN]));
+    assert_eq!(data(&f, &f.program_entry(0)).len(), ProgramEntry::SPACE);
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).schema_version, CURRENT_SCHEMA_VERSION);
+    assert_eq!(f.svm.get::<AuditorAccount>(&f.auditor_account(0)).schema_version, CURRENT_SCHEMA_VERSION);
+    assert_eq!(f.svm.get::<AnalysisReport>(&f.analysis(0, 0)).schema_version, CURRENT_SCHEMA_VERSION);
+}
+
+#[test]
+fn unversioned_accounts_are_never_misread_as_current() {
+    let mut legacy = LegacyDeployment::new();
+    let f = &mut legacy.f;
+
+    let registry = data(f, &legacy.registry);
+    assert_eq!((registry.len(), &registry[..8]), (v0::Registry::SPACE, &Registry::DISCRIMINATOR[..]));
+    assert!(schema::deserialize::<Registry>(&registry).is_err());
+    let entry = data(f, &legacy.entry);
+    assert!(schema::deserialize::<ProgramEntry>(&entry).is_err());
+    let entry = v0::ProgramEntry::try_from_account_data(&entry).unwrap();
+    assert_eq!((entry.owner, entry.metadata_uri.as_str()), (f.owner.pubkey(), "ipfs://legacy"));
+
+    // The unmigrated registry takes no new members.
+    let auditor = f.svm.new_funded_keypair();
+    let ix = instructions::register_auditor(&parity::ID, &legacy.registry, &auditor.pubkey(), "late", "");
+    assert_anchor_error(f.send(ix, &auditor), ErrorCode::AccountDidNotDeserialize);
+
+    // Nor do the other accounts migrate in place.
+    let address = legacy.entry;
+    assert_parity_error(migrate(f, &address), ParityError::UnversionedAccount);
+}
+
+#[test]
+fn the_legacy_registry_migrates_in_place() {
+    let mut legacy = LegacyDeployment::new();
+    let address = legacy.registry;
+    assert_eq!(address, pda::registry(&parity::ID, pda::LEGACY_NAMESPACE).0);
+
+    let payer = legacy.f.svm.new_funded_keypair();
+    let balance = legacy.f.svm.lamports(&payer.pubkey());
+    assert_ok(legacy.f.send(instructions::migrate_account(&parity::ID, &payer.pubkey(), &address), &payer));
+    let f = &mut legacy.f;
+    let logged = format!("migrated from schema version 0 to {}", CURRENT_SCHEMA_VERSION);
+    assert!(f.svm.logs().iter().any(|log| log.contains(&logged)));
+
+    // Beyond the fee, the payer covers exactly the rent for the added bytes.
+    let rent = Rent::default();
+    assert_eq!(data(f, &address).len(), Registry::SPACE);
+    let added = rent.minimum_balance(Registry::SPACE) - rent.minimum_balance(v0::Registry::SPACE);
+    assert_eq!(f.svm.lamports(&payer.pubkey()), balance - added - f.svm.fee());
+
+    let registry: Registry = f.svm.get(&address);
+    assert_eq!((registry.authority, registry.namespace.as_str()), (f.authority.pubkey(), ""));
+    assert_eq!((registry.total_programs, registry.total_auditors, registry.total_patterns), (1, 1, 1));
+    assert_eq!((registry.min_score_for_badge, registry.is_paused), (70, false));
+    assert_eq!((registry.created_at, registry.updated_at), (CREATED_AT, CREATED_AT + 1));
+    assert_eq!((registry.reveal_delay_seconds, registry.reveal_quorum), (0, 0));
+    assert_eq!(registry.commitment_ttl_seconds, MIGRATED_COMMITMENT_TTL_SECONDS);
+    assert_eq!((registry.schema_version, registry.reserved), (CURRENT_SCHEMA_VERSION, [0; SCHEMA_RESERVED_LEN]));
+
+    // The migrated registry works as any other.
+    let auditor = f.svm.new_funded_keypair();
+    let ix = instructions::register_auditor(&parity::ID, &address, &auditor.pubkey(), "late", "");
+    assert_ok(f.send(ix, &auditor));
+    assert_eq!(f.svm.get::<Registry>(&address).total_auditors, 2);
+
+    assert_parity_error(migrate(f, &address), ParityError::AccountAlreadyMigrated);
+}
+
+#[test]
+fn legacy_accounts_move_to_their_current_addresses() {
+    let mut legacy = LegacyDeployment::new();
+    legacy.migrate_registry();
+    let (owner, auditor) = (legacy.f.owner.pubkey(), legacy.auditor.pubkey());
+    let registry = legacy.registry;
+    let items = legacy.items();
+
+    // The payer's cost is the rent for the space the entry grows by: the
+    // legacy account's rent comes back when it is closed.
+    let payer = legacy.f.svm.new_funded_keypair();
+    let balance = legacy.f.svm.lamports(&payer.pubkey());
+    let ix = instructions::migrate_legacy_account(&parity::ID, &payer.pubkey(), &items[0].legacy, &items[0].destination, None);
+    assert_ok(legacy.f.send(ix, &payer));
+    let rent = Rent::default();
+    let added = rent.minimum_balance(ProgramEntry::SPACE) - rent.minimum_balance(v0::ProgramEntry::SPACE);
+    assert_eq!(legacy.f.svm.lamports(&payer.pubkey()), balance - added - legacy.f.svm.fee());
+
+    for item in &items[1..] {
+        assert_ok(legacy.migrate_legacy(*item));
+    }
+    let f = &mut legacy.f;
+    for item in &items {
+        assert!(f.svm.account(&item.legacy).is_none());
+    }
+
+    let entry: ProgramEntry = f.svm.get(&items[0].destination);
+    assert_eq!((entry.registry, entry.owner, entry.program_hash), (registry, owner, program_hash(PROGRAM)));
+    assert_eq!((entry.program_id, entry.framework), (Pubkey::default(), Framework::Native));
+    assert_eq!(entry.metadata_uri, "ipfs://legacy");
+    assert_eq!((entry.registered_at, entry.updated_at), (CREATED_AT, CREATED_AT + 2));
+    assert_eq!((entry.analysis_count, entry.latest_score, entry.is_verified), (1, 85, true));
+    assert_eq!((entry.commit_count, entry.open_commitments, entry.expired_commitments), (0, 0, 0));
+    assert_eq!((entry.pending_owner, entry.is_retired), (None, false));
+    assert_eq!(entry.bump, pda::program_entry(&parity::ID, &registry, &program_hash(PROGRAM)).1);
+    assert_eq!((entry.schema_version, entry.reserved), (CURRENT_SCHEMA_VERSION, [0; SCHEMA_RESERVED_LEN]));
+
+    let account: AuditorAccount = f.svm.get(&items[1].destination);
+    assert_eq!((account.registry, account.authority, account.name.as_str()), (registry, auditor, "legacy-auditor"));
+    assert_eq!(account.credentials_uri, "https://example.com/legacy");
+    assert_eq!((account.total_analyses, account.average_score, account.is_active), (1, 85, true));
+    assert_eq!((account.attestation_nonce, account.schema_version), (0, CURRENT_SCHEMA_VERSION));
+
+    let report: AnalysisReport = f.svm.get(&items[2].destination);
+    assert_eq!((report.program_entry, report.auditor, report.score), (items[0].destination, auditor, 85));
+    assert_eq!((report.findings_hash, report.findings_count.total), ([3; 32], 7));
+    assert_eq!(report.skills_used, vec!["security-audit".to_string()]);
+    assert_eq!((report.score_override, report.source), (None, SourceRevision::default()));
+    assert_eq!((report.submitted_at, report.updated_at), (CREATED_AT, CREATED_AT + 3));
+    assert_eq!((report.version, report.schema_version), (3, CURRENT_SCHEMA_VERSION));
+
+    let badge: VerificationBadge = f.svm.get(&items[3].destination);
+    assert_eq!((badge.program_entry, badge.issuer), (items[0].destination, f.authority.pubkey()));
+    assert_eq!((badge.tier, badge.score_at_issuance, badge.is_revoked), (VerificationTier::Silver, 85, false));
+    assert_eq!(badge.schema_version, CURRENT_SCHEMA_VERSION);
+
+    let skill: SkillEntry = f.svm.get(&items[4].destination);
+    assert_eq!((skill.registry, skill.authority, skill.namespace), (registry, auditor, auditor));
+    assert_eq!((skill.name.as_str(), skill.version.as_str(), skill.usage_count), ("scan", "1.0.0", 4));
+    assert_eq!(skill.schema_version, CURRENT_SCHEMA_VERSION);
+
+    let pattern: ContextPattern = f.svm.get(&items[5].destination);
+    assert_eq!((pattern.registry, pattern.submitter, pattern.namespace), (registry, auditor, auditor));
+    assert_eq!((pattern.pattern_id.as_str(), pattern.severity), ("legacy-rule", Severity::High));
+    assert_eq!((pattern.detection_rule.as_str(), pattern.usage_count), ("missing owner check", 2));
+    assert_eq!(pattern.schema_version, CURRENT_SCHEMA_VERSION);
+
+    // The auditor carries on where the first release left off.
+    let ix = instructions::update_analysis(&parity::ID, &registry, &auditor, &program_hash(PROGRAM), result(90));
+    assert_ok(legacy.f.send(ix, &legacy.auditor));
+    let report: AnalysisReport = legacy.f.svm.get(&items[2].destination);
+    assert_eq!((report.score, report.version), (90, 4));
+}
+
+#[test]
+fn migrated_entries_verify_once_their_program_is_bound() {
+    let mut legacy = LegacyDeployment::new();
+    legacy.migrate_registry();
+    let item = legacy.items()[0];
+    assert_ok(legacy.migrate_legacy(item));
+    let registry = legacy.registry;
+    let hash = program_hash(PROGRAM);
+    let f = &mut legacy.f;
+    let owner = f.owner.insecure_clone();
+    let program = f.svm.deploy_upgradeable(Some(owner.pubkey()), &program_elf(PROGRAM));
+
+    // Until the entry records its program there is no ProgramData to check.
+    let verify = instructions::verify_program_hash(&parity::ID, &registry, &hash, &program);
+    assert_anchor_error(f.send(verify.clone(), &owner), ErrorCode::ConstraintSeeds);
+
+    let stranger = f.svm.new_funded_keypair();
+    let bind = |signer: &Keypair, program: &Pubkey| {
+        instructions::bind_program_id(&parity::ID, &registry, &signer.pubkey(), &signer.pubkey(), &hash, program)
+    };
+    assert_parity_error(f.send(bind(&stranger, &program), &stranger), ParityError::UnauthorizedProgramOwner);
+    let other = f.svm.deploy_upgradeable(Some(owner.pubkey()), &program_elf(PROGRAM + 1));
+    assert_parity_error(f.send(bind(&owner, &other), &owner), ParityError::ProgramHashMismatch);
+    let foreign = f.svm.deploy_upgradeable(Some(stranger.pubkey()), &program_elf(PROGRAM));
+    assert_parity_error(f.send(bind(&owner, &foreign), &owner), ParityError::ProgramOwnershipUnproven);
+
+    assert_ok(f.send(bind(&owner, &program), &owner));
+    assert_eq!(f.svm.get::<ProgramEntry>(&item.destination).program_id, program);
+    assert_ok(f.send(verify, &owner));
+
+    let again = f.svm.deploy_upgradeable(Some(owner.pubkey()), &program_elf(PROGRAM));
+    assert_parity_error(f.send(bind(&owner, &again), &owner), ParityError::ProgramIdAlreadyBound);
+}
+
+#[test]
+fn migrate_legacy_accounts_sweeps_and_skips_moved_accounts() {
+    let mut legacy = LegacyDeployment::new();
+    legacy.migrate_registry();
+    let items = legacy.items();
+    let f = &mut legacy.f;
+
+    let payer = f.svm.new_funded_keypair();
+    assert_ok(f.send(instructions::migrate_legacy_accounts(&parity::ID, &payer.pubkey(), &items), &payer));
+    assert!(f.svm.logs().iter().any(|log| log.contains("Legacy accounts migrated: migrated=6, skipped=0")));
+    for item in &items {
+        assert!(f.svm.account(&item.legacy).is_none());
+        assert_eq!(f.svm.account(&item.destination).unwrap().owner, parity::ID);
+    }
+    assert_eq!(f.svm.get::<ProgramEntry>(&items[0].destination).program_hash, program_hash(PROGRAM));
+    assert_eq!(f.svm.get::<AnalysisReport>(&items[2].destination).version, 3);
+
+    // Re-sending the sweep finds nothing left to move.
+    assert_ok(f.send(instructions::migrate_legacy_accounts(&parity::ID, &payer.pubkey(), &items), &payer));
+    assert!(f.svm.logs().iter().any(|log| log.contains("Legacy accounts migrated: migrated=0, skipped=6")));
+
+    assert_parity_error(
+        f.send(instructions::migrate_legacy_accounts(&parity::ID, &payer.pubkey(), &[]), &payer),
+        ParityError::BatchAccountMismatch,
+    );
+}
+
+#[test]
+fn legacy_migration_checks_every_address() {
+    let mut legacy = LegacyDeployment::new();
+    let items = legacy.items();
+
+    // The registry goes first.
+    assert_anchor_error(legacy.migrate_legacy(items[0]), ErrorCode::AccountDidNotDeserialize);
+    legacy.migrate_registry();
+
+    // Only into the legacy registry, at the address the current seeds derive.
+    let payer = legacy.f.svm.new_funded_keypair();
+    let mut ix = instructions::migrate_legacy_account(&parity::ID, &payer.pubkey(), &items[0].legacy, &items[0].destination, None);
+    ix.accounts[1].pubkey = legacy.f.registry();
+    assert_parity_error(legacy.f.send(ix, &payer), ParityError::LegacyRegistryRequired);
+    let wrong = LegacyMigration { destination: Pubkey::new_unique(), ..items[0] };
+    assert_parity_error(legacy.migrate_legacy(wrong), ParityError::MigrationAccountMismatch);
+
+    // A report needs its own entry, already migrated.
+    assert_anchor_error(legacy.migrate_legacy(items[2]), ErrorCode::AccountNotInitialized);
+    let without = LegacyMigration { program_entry: None, ..items[2] };
+    assert_parity_error(legacy.migrate_legacy(without), ParityError::MigrationAccountMismatch);
+    assert_ok(legacy.migrate_legacy(items[0]));
+    legacy.f.add_program();
+    let elsewhere = legacy.f.program_entry(0);
+    assert_parity_error(
+        legacy.migrate_legacy(LegacyMigration { program_entry: Some(elsewhere), ..items[2] }),
+        ParityError::MigrationAccountMismatch,
+    );
+    assert_ok(legacy.migrate_legacy(items[2]));
+
+    // Moved accounts and current ones are not legacy.
+    assert_anchor_error(legacy.migrate_legacy(items[0]), ErrorCode::ConstraintOwner);
+    let current = LegacyMigration { legacy: items[0].destination, ..items[0] };
+    assert_parity_error(legacy.migrate_legacy(current), ParityError::AccountAlreadyMigrated);
+
+    // An auditor who joined the migrated registry before their legacy
+    // account moved keeps the new membership.
+    let ix = instructions::register_auditor(&parity::ID, &legacy.registry, &legacy.auditor.pubkey(), "rejoined", "");
+    let auditor = legacy.auditor.insecure_clone();
+    assert_ok(legacy.f.send(ix, &auditor));
+    assert_parity_error(legacy.migrate_legacy(items[1]), ParityError::MigrationDestinationInUse);
+}
+
+#[test]
+fn version_1_entries_grow_to_the_current_layout() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    f.verify(0, 0, 90);
+    let address = f.program_entry(0);
+    let current: ProgramEntry = f.svm.get(&address);
+
+    // The entry as version 1 wrote it, before the reveal round fields.
+    let entry = v1::ProgramEntry {
+        registry: current.registry,
+        owner: current.owner,
+        program_hash: current.program_hash,
+        program_id: current.program_id,
+        framework: current.framework,
+        metadata_uri: current.metadata_uri.clone(),
+        registered_at: current.registered_at,
+        updated_at: current.updated_at,
+        analysis_count: current.analysis_count,
+        latest_score: current.latest_score,
+        is_verified: current.is_verified,
+        commit_count: 3,
+        open_commitments: 1,
+        expired_commitments: 1,
+        pending_owner: None,
+        is_retired: false,
+        bump: current.bump,
+        schema_version: v1::SCHEMA_VERSION,
+        reserved: [0; SCHEMA_RESERVED_LEN],
+    };
+    let rent = Rent::default();
+    let lamports = rent.minimum_balance(v1::ProgramEntry::SPACE);
+    let account = Account { lamports, data: entry.to_account_data(), owner: parity::ID, executable: false };
+    f.svm.set_account(address, account);
+    let read = schema::deserialize::<ProgramEntry>(&data(&f, &address)).unwrap();
+    assert_eq!((read.schema_version, read.commit_count, read.round_start), (1, 3, 0));
+
+    let payer = f.svm.new_funded_keypair();
+    let balance = f.svm.lamports(&payer.pubkey());
+    assert_ok(f.send(instructions::migrate_account(&parity::ID, &payer.pubkey(), &address), &payer));
+    let logged = format!("migrated from schema version 1 to {}", CURRENT_SCHEMA_VERSION);
+    assert!(f.svm.logs().iter().any(|log| log.contains(&logged)));
+
+    // Beyond the fee, the payer covers exactly the rent for the added bytes.
+    assert_eq!(data(&f, &address).len(), ProgramEntry::SPACE);
+    let added = rent.minimum_balance(ProgramEntry::SPACE) - lamports;
+    assert_eq!(f.svm.lamports(&payer.pubkey()), balance - added - f.svm.fee());
+
+    let migrated: ProgramEntry = f.svm.get(&address);
+    assert_eq!((migrated.owner, migrated.metadata_uri.as_str()), (current.owner, current.metadata_uri.as_str()));
+    assert_eq!((migrated.analysis_count, migrated.latest_score, migrated.is_verified), (1, 90, true));
+    assert_eq!((migrated.commit_count, migrated.open_commitments, migrated.expired_commitments), (3, 1, 1));
+    assert_eq!((migrated.round_start, migrated.round_revealed_at), (0, None));
+    assert_eq!((migrated.schema_version, migrated.reserved), (CURRENT_SCHEMA_VERSION, [0; SCHEMA_RESERVED_LEN]));
+    assert_parity_error(migrate(&mut f, &address), ParityError::AccountAlreadyMigrated);
+
+    // Data of the version 1 size recording any other version was never
+    // written by the program.
+    for version in [0, CURRENT_SCHEMA_VERSION] {
+        let forged = v1::ProgramEntry { schema_version: version, ..entry.clone() };
+        assert!(schema::deserialize::<ProgramEntry>(&forged.to_account_data()).is_err());
+    }
+}
+
+#[test]
+fn migration_rejects_accounts_it_cannot_upgrade() {
+    let mut f = Fixture::new().with_programs(1);
+
+    // Written by a newer program.
+    let address = f.program_entry(0);
+    let mut entry: ProgramEntry = f.svm.get(&address);
+    entry.schema_version = CURRENT_SCHEMA_VERSION + 1;
+    f.svm.put(&address, &entry);
+    assert_parity_error(migrate(&mut f, &address), ParityError::UnsupportedSchemaVersion);
+
+    let wallet = f.svm.new_funded_keypair().pubkey();
+    assert_anchor_error(migrate(&mut f, &wallet), ErrorCode::ConstraintOwner);
+
+    let unknown = Pubkey::new_unique();
+    f.svm.set_account(unknown, Account { lamports: 1_000_000, data: vec![7; 64], owner: parity::ID, executable: false });
+    assert_anchor_error(migrate(&mut f, &unknown), ErrorCode::AccountDiscriminatorMismatch);
+
+    let payer = f.svm.new_funded_keypair();
+    let empty = instructions::migrate_accounts(&parity::ID, &payer.pubkey(), &[]);
+    assert_parity_error(f.send(empty, &payer), ParityError::BatchAccountMismatch);
+    let batch = instructions::migrate_accounts(&parity::ID, &payer.pubkey(), &[f.registry(), unknown]);
+    let err = f.send(batch, &payer);
+    assert!(err.as_ref().unwrap_err().logs.iter().any(|log| log.contains("Batch item 1 failed")));
+    assert_anchor_error(err, ErrorCode::AccountDiscriminatorMismatch);
+}
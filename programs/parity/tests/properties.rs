@@ -0,0 +1,133 @@
+mod common;
+
+use common::model::{Analysis, Op, World, ACTORS, PATTERNS, PROGRAMS, SKILLS};
+use common::ONE_DAY;
+use parity::scoring::{Confidence, ScoredFinding, ScoringModel};
+use parity::state::{AnalysisFindingsCount, PatternType, Severity};
+use proptest::prelude::*;
+
+/// Mostly a handful of findings, sometimes enough to overflow the total.
+fn counts() -> impl Strategy<Value = [u16; 5]> {
+    prop_oneof![
+        4 => prop::array::uniform5(0..4u16),
+        1 => prop::array::uniform5(any::<u16>()),
+    ]
+}
+
+/// Mostly results the scoring model accepts, some it does not.
+fn analysis() -> impl Strategy<Value = Analysis> {
+    let consistent = counts().prop_flat_map(|counts| {
+        let findings_count = Analysis { score: 0, counts, miscount: false, justified: false }.findings_count();
+        let (low, high) = ScoringModel::DEFAULT.score_bounds(&findings_count);
+        (low..=high).prop_map(move |score| Analysis { score, counts, miscount: false, justified: false })
+    });
+    let arbitrary = (any::<u8>(), counts(), prop::bool::weighted(0.2), any::<bool>())
+        .prop_map(|(score, counts, miscount, justified)| Analysis { score, counts, miscount, justified });
+    prop_oneof![3 => consistent, 1 => arbitrary]
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let program = 0..PROGRAMS;
+    let actor = 0..ACTORS;
+    prop_oneof![
+        2 => actor.clone().prop_map(|actor| Op::RegisterAuditor { actor }),
+        1 => (actor.clone(), prop::bool::weighted(0.7)).prop_map(|(actor, active)| Op::SetAuditorActive { actor, active }),
+        2 => program.clone().prop_map(|program| Op::RegisterProgram { program }),
+        4 => (program.clone(), actor.clone(), analysis()).prop_map(|(program, actor, analysis)| Op::Submit { program, actor, analysis }),
+        3 => (program.clone(), actor.clone(), analysis()).prop_map(|(program, actor, analysis)| Op::Update { program, actor, analysis }),
+        2 => (program.clone(), actor.clone(), analysis(), any::<u8>())
+            .prop_map(|(program, actor, analysis, salt)| Op::Commit { program, actor, analysis, salt }),
+        2 => (program.clone(), actor.clone(), prop::bool::weighted(0.2))
+            .prop_map(|(program, actor, tamper)| Op::Reveal { program, actor, tamper }),
+        1 => (program.clone(), actor.clone()).prop_map(|(program, actor)| Op::Expire { program, actor }),
+        1 => (0..=100u8, 0..ONE_DAY as u32, 0..4u8, 0..3 * ONE_DAY as u32).prop_map(
+            |(min_score, reveal_delay, reveal_quorum, ttl)| Op::SetConfig { min_score, reveal_delay, reveal_quorum, ttl }
+        ),
+        1 => (actor.clone(), 0..SKILLS).prop_map(|(actor, skill)| Op::RegisterSkill { actor, skill }),
+        1 => (actor.clone(), 0..SKILLS).prop_map(|(actor, skill)| Op::DeprecateSkill { actor, skill }),
+        1 => (actor, 0..PATTERNS).prop_map(|(actor, pattern)| Op::SubmitPattern { actor, pattern }),
+        1 => (program.clone(), 0..4u8, -(ONE_DAY as i32)..30 * ONE_DAY as i32)
+            .prop_map(|(program, tier, expires_in)| Op::IssueBadge { program, tier, expires_in }),
+        1 => program.clone().prop_map(|program| Op::RevokeBadge { program }),
+        1 => (program, 0..3u8).prop_map(|(program, reports)| Op::Deregister { program, reports }),
+        1 => Just(Op::Bootstrap),
+        2 => (0..2 * ONE_DAY as u32).prop_map(|seconds| Op::Warp { seconds }),
+    ]
+}
+
+fn scored_finding() -> impl Strategy<Value = ScoredFinding> {
+    let severity = prop::sample::select(vec![
+        Severity::Critical,
+        Severity::High,
+        Severity::Medium,
+        Severity::Info,
+        Severity::Pass,
+    ]);
+    let pattern_type = prop::option::of(prop::sample::select(vec![
+        PatternType::MissingSignerCheck,
+        PatternType::UncheckedArithmetic,
+        PatternType::UnvalidatedPda,
+        PatternType::InsecureCpi,
+        PatternType::AccountDeserialization,
+        PatternType::RentExemption,
+        PatternType::CloseAccount,
+        PatternType::TypeCosplay,
+        PatternType::ReinitiallizationAttack,
+        PatternType::OwnerCheck,
+        PatternType::Custom,
+    ]));
+    let confidence = prop::sample::select(vec![Confidence::High, Confidence::Medium, Confidence::Low]);
+    (severity, pattern_type, confidence).prop_map(|(severity, pattern_type, confidence)| ScoredFinding {
+        severity,
+        pattern_type,
+        confidence,
+    })
+}
+
+fn counts_of(findings: &[ScoredFinding]) -> AnalysisFindingsCount {
+    let count = |severity| findings.iter().filter(|f| f.severity == severity).count() as u16;
+    AnalysisFindingsCount {
+        critical: count(Severity::Critical),
+        high: count(Severity::High),
+        medium: count(Severity::Medium),
+        info: count(Severity::Info),
+        pass: count(Severity::Pass),
+        total: findings.len() as u16,
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn random_instruction_sequences_preserve_invariants(ops in prop::collection::vec(op(), 1..48)) {
+        World::run(&ops);
+    }
+
+    #[test]
+    fn fuzz_inputs_decode_and_run(data in prop::collection::vec(any::<u8>(), 0..256)) {
+        World::run(&common::model::decode(&data));
+    }
+
+    #[test]
+    fn scores_stay_within_the_bounds_of_their_counts(findings in prop::collection::vec(scored_finding(), 0..32)) {
+        let model = ScoringModel::DEFAULT;
+        let score = model.score(&findings).score;
+        let counts = counts_of(&findings);
+        let (low, high) = model.score_bounds(&counts);
+        prop_assert!(low <= score && score <= high, "{} not in {}..={}", score, low, high);
+        prop_assert!(model.is_consistent(score, &counts));
+    }
+
+    #[test]
+    fn another_finding_never_raises_the_score(
+        findings in prop::collection::vec(scored_finding(), 0..32),
+        extra in scored_finding(),
+    ) {
+        let model = ScoringModel::DEFAULT;
+        let before = model.score(&findings).score;
+        let mut more = findings;
+        more.push(extra);
+        prop_assert!(model.score(&more).score <= before);
+    }
+}
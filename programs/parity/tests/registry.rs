@@ -0,0 +1,595 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::{
+    assert_anchor_error, assert_ok, assert_parity_error, default_config, program_elf, program_hash, result, Fixture, Svm, ONE_DAY,
+};
+use parity::errors::ParityError;
+use parity::state::{
+    AuditorAccount, Framework, ProgramEntry, Registry, VerificationTier, MAX_METADATA_URI_LEN, MAX_REGISTRY_NAMESPACE_LEN,
+};
+use parity_client::{instructions, pda};
+use solana_program::system_instruction::SystemError;
+use solana_program::instruction::AccountMeta;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+#[test]
+fn initialize_registry_records_authority_and_config() {
+    let f = Fixture::new();
+    let registry: Registry = f.svm.get(&f.registry());
+    let config = default_config();
+
+    assert_eq!(registry.authority, f.authority.pubkey());
+    assert_eq!(registry.namespace, pda::DEFAULT_NAMESPACE);
+    assert_eq!(registry.min_score_for_badge, config.min_score_for_badge);
+    assert_eq!(registry.commitment_ttl_seconds, config.commitment_ttl_seconds);
+    assert_eq!(registry.created_at, f.svm.now());
+    assert_eq!(
+        (registry.total_programs, registry.total_analyses, registry.total_auditors),
+        (0, 0, 0)
+    );
+}
+
+#[test]
+fn initialize_registry_rejects_a_window_that_expires_before_it_opens() {
+    let mut svm = Svm::new();
+    let authority = svm.new_funded_keypair();
+    for (delay, ttl) in [(-1, 60), (60, 60), (120, 60)] {
+        let config = parity::state::RegistryConfig {
+            reveal_delay_seconds: delay,
+            commitment_ttl_seconds: ttl,
+            ..default_config()
+        };
+        let ix = instructions::initialize_registry(&parity::ID, &authority.pubkey(), pda::DEFAULT_NAMESPACE, config);
+        assert_parity_error(svm.process(&[ix], &[&authority]), ParityError::InvalidCommitRevealWindow);
+    }
+}
+
+#[test]
+fn initialize_registry_only_once() {
+    let mut f = Fixture::new();
+    let intruder = f.svm.new_funded_keypair();
+    let ix = instructions::initialize_registry(&parity::ID, &intruder.pubkey(), pda::DEFAULT_NAMESPACE, default_config());
+    let err = f.send(ix, &intruder).unwrap_err();
+    assert_eq!(err.code(), Some(SystemError::AccountAlreadyInUse as u32));
+
+    let registry: Registry = f.svm.get(&f.registry());
+    assert_eq!(registry.authority, f.authority.pubkey());
+}
+
+#[test]
+fn initialize_registry_requires_a_namespace_that_fits_the_seeds() {
+    let mut svm = Svm::new();
+    let authority = svm.new_funded_keypair();
+    let ix = instructions::initialize_registry(&parity::ID, &authority.pubkey(), "", default_config());
+    assert_parity_error(svm.process(&[ix], &[&authority]), ParityError::InvalidRegistryNamespace);
+
+    let namespace = "n".repeat(MAX_REGISTRY_NAMESPACE_LEN);
+    let ix = instructions::initialize_registry(&parity::ID, &authority.pubkey(), namespace.as_str(), default_config());
+    assert_ok(svm.process(&[ix], &[&authority]));
+    let registry: Registry = svm.get(&pda::registry(&parity::ID, &namespace).0);
+    assert_eq!(registry.namespace, namespace);
+}
+
+#[test]
+fn registries_on_one_deployment_are_independent() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    let team_authority = f.svm.new_funded_keypair();
+    let config = parity::state::RegistryConfig { min_score_for_badge: 95, ..default_config() };
+    let ix = instructions::initialize_registry(&parity::ID, &team_authority.pubkey(), "team", config);
+    assert_ok(f.send(ix, &team_authority));
+    let team = pda::registry(&parity::ID, "team").0;
+
+    // The same build can be registered in both, and the auditor joins both.
+    let owner = f.owner.insecure_clone();
+    let ix = instructions::register_program(
+        &parity::ID,
+        &team,
+        &owner.pubkey(),
+        &owner.pubkey(),
+        &f.deployed[0],
+        f.programs[0],
+        Framework::Anchor,
+        "ipfs://team",
+    );
+    assert_ok(f.send(ix, &owner));
+    let auditor = f.auditors[0].insecure_clone();
+    let ix = instructions::register_auditor(&parity::ID, &team, &auditor.pubkey(), "auditor-0", "");
+    assert_ok(f.send(ix, &auditor));
+
+    let ix = instructions::submit_analysis(&parity::ID, &team, &auditor.pubkey(), &f.programs[0], result(90));
+    assert_ok(f.send(ix, &auditor));
+    let team_entry: ProgramEntry = f.svm.get(&pda::program_entry(&parity::ID, &team, &f.programs[0]).0);
+    assert_eq!((team_entry.registry, team_entry.analysis_count, team_entry.is_verified), (team, 1, false));
+    let public_entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert_eq!((public_entry.registry, public_entry.analysis_count), (f.registry, 0));
+
+    let membership: AuditorAccount = f.svm.get(&pda::auditor(&parity::ID, &team, &auditor.pubkey()).0);
+    assert_eq!((membership.registry, membership.authority), (team, auditor.pubkey()));
+    assert_eq!(f.svm.get::<AuditorAccount>(&f.auditor_account(0)).registry, f.registry);
+    let totals = |registry: Registry| (registry.total_programs, registry.total_analyses, registry.total_auditors);
+    assert_eq!(totals(f.svm.get(&team)), (1, 1, 1));
+    assert_eq!(totals(f.svm.get(&f.registry())), (1, 0, 1));
+
+    // Each registry's authority only governs its own registry.
+    let ix = instructions::update_registry_config(&parity::ID, &team, &f.authority.pubkey(), default_config());
+    let authority = f.authority.insecure_clone();
+    assert_parity_error(f.send(ix, &authority), ParityError::UnauthorizedRegistryAuthority);
+}
+
+#[test]
+fn accounts_from_another_registry_are_rejected() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    let team_authority = f.svm.new_funded_keypair();
+    let ix = instructions::initialize_registry(&parity::ID, &team_authority.pubkey(), "team", default_config());
+    assert_ok(f.send(ix, &team_authority));
+    let team = pda::registry(&parity::ID, "team").0;
+
+    let owner = f.owner.insecure_clone();
+    let ix = instructions::register_program(
+        &parity::ID,
+        &team,
+        &owner.pubkey(),
+        &owner.pubkey(),
+        &f.deployed[0],
+        f.programs[0],
+        Framework::Anchor,
+        "",
+    );
+    assert_ok(f.send(ix, &owner));
+
+    // A membership in one registry does not admit the auditor to another.
+    let auditor = f.auditors[0].insecure_clone();
+    let hash = f.programs[0];
+    let submit = || instructions::submit_analysis(&parity::ID, &team, &auditor.pubkey(), &hash, result(90));
+    assert_anchor_error(f.send(submit(), &auditor), ErrorCode::AccountNotInitialized);
+    let mut ix = submit();
+    ix.accounts[1].pubkey = f.auditor_account(0);
+    assert_anchor_error(f.send(ix, &auditor), ErrorCode::ConstraintSeeds);
+
+    // Nor can its program entries be analyzed through another registry.
+    let ix = instructions::register_auditor(&parity::ID, &team, &auditor.pubkey(), "auditor-0", "");
+    assert_ok(f.send(ix, &auditor));
+    let mut ix = submit();
+    ix.accounts[3].pubkey = f.program_entry(0);
+    ix.accounts[4].pubkey = f.analysis(0, 0);
+    assert_anchor_error(f.send(ix, &auditor), ErrorCode::ConstraintSeeds);
+    assert_eq!(f.svm.get::<Registry>(&team).total_analyses, 0);
+}
+
+#[test]
+fn update_registry_config_by_authority() {
+    let mut f = Fixture::new();
+    let config = parity::state::RegistryConfig {
+        min_score_for_badge: 90,
+        reveal_delay_seconds: 3600,
+        reveal_quorum: 3,
+        ..default_config()
+    };
+    let ix = instructions::update_registry_config(&parity::ID, &f.registry, &f.authority.pubkey(), config);
+    assert_ok(f.svm.process(&[ix], &[&f.authority]));
+
+    let registry: Registry = f.svm.get(&f.registry());
+    assert_eq!(registry.min_score_for_badge, 90);
+    assert_eq!(registry.reveal_delay_seconds, 3600);
+    assert_eq!(registry.reveal_quorum, 3);
+}
+
+#[test]
+fn update_registry_config_rejects_other_signers_and_bad_windows() {
+    let mut f = Fixture::new();
+    let intruder = f.svm.new_funded_keypair();
+    let ix = instructions::update_registry_config(&parity::ID, &f.registry, &intruder.pubkey(), default_config());
+    assert_parity_error(f.send(ix, &intruder), ParityError::UnauthorizedRegistryAuthority);
+
+    let config = parity::state::RegistryConfig { commitment_ttl_seconds: 0, ..default_config() };
+    let ix = instructions::update_registry_config(&parity::ID, &f.registry, &f.authority.pubkey(), config);
+    let authority = f.authority.insecure_clone();
+    assert_parity_error(f.send(ix, &authority), ParityError::InvalidCommitRevealWindow);
+}
+
+#[test]
+fn register_program_creates_entry_and_counts_it() {
+    let f = Fixture::new().with_programs(3);
+    let registry: Registry = f.svm.get(&f.registry());
+    assert_eq!(registry.total_programs, 3);
+
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(1));
+    assert_eq!(entry.owner, f.owner.pubkey());
+    assert_eq!(entry.program_hash, program_hash(1));
+    assert_eq!(entry.program_id, f.deployed[1]);
+    assert_eq!(entry.framework, Framework::Anchor);
+    assert_eq!(entry.metadata_uri, "ipfs://meta");
+    assert_eq!((entry.analysis_count, entry.latest_score, entry.is_verified), (0, 0, false));
+}
+
+#[test]
+fn register_program_rejects_duplicates_and_long_uris() {
+    let mut f = Fixture::new().with_programs(1);
+    let owner = f.owner.insecure_clone();
+
+    let program = f.deployed[0];
+    let ix = instructions::register_program(&parity::ID, &f.registry, &owner.pubkey(), &owner.pubkey(), &program, program_hash(0), Framework::Native, "");
+    let err = f.send(ix, &owner).unwrap_err();
+    assert_eq!(err.code(), Some(SystemError::AccountAlreadyInUse as u32));
+
+    let uri = "u".repeat(MAX_METADATA_URI_LEN + 1);
+    let program = f.svm.deploy_upgradeable(Some(owner.pubkey()), &program_elf(1));
+    let ix = instructions::register_program(&parity::ID, &f.registry, &owner.pubkey(), &owner.pubkey(), &program, program_hash(1), Framework::Native, uri);
+    assert_parity_error(f.send(ix, &owner), ParityError::MetadataUriTooLong);
+
+    let registry: Registry = f.svm.get(&f.registry());
+    assert_eq!(registry.total_programs, 1);
+}
+
+#[test]
+fn registry_counters_fail_with_an_overflow_error() {
+    let mut f = Fixture::new().with_programs(1);
+    let owner = f.owner.insecure_clone();
+    let mut registry: Registry = f.svm.get(&f.registry());
+    registry.total_programs = u64::MAX;
+    f.svm.put(&f.registry(), &registry);
+
+    let program = f.svm.deploy_upgradeable(Some(owner.pubkey()), &program_elf(1));
+    let ix = instructions::register_program(&parity::ID, &f.registry, &owner.pubkey(), &owner.pubkey(), &program, program_hash(1), Framework::Native, "");
+    assert_parity_error(f.send(ix, &owner), ParityError::ArithmeticOverflow);
+    assert!(f.svm.account(&pda::program_entry(&parity::ID, &f.registry, &program_hash(1)).0).is_none());
+}
+
+/// Registers `program_hash(1)` for `owner`, signed by `authority`, as the
+/// deployed `program`.
+fn register_as(
+    f: &mut Fixture,
+    owner: &Keypair,
+    authority: &Keypair,
+    program: &Pubkey,
+) -> common::TxResult {
+    let ix = instructions::register_program(
+        &parity::ID,
+        &f.registry,
+        &owner.pubkey(),
+        &authority.pubkey(),
+        program,
+        program_hash(1),
+        Framework::Anchor,
+        "",
+    );
+    f.svm.process(&[ix], &[owner, authority])
+}
+
+#[test]
+fn register_program_requires_the_upgrade_authority() {
+    let mut f = Fixture::new();
+    let owner = f.owner.insecure_clone();
+    let upgrader = f.svm.new_funded_keypair();
+    let program = f.svm.deploy_upgradeable(Some(upgrader.pubkey()), &program_elf(1));
+
+    assert_parity_error(register_as(&mut f, &owner, &owner, &program), ParityError::ProgramOwnershipUnproven);
+    let entry = pda::program_entry(&parity::ID, &f.registry, &program_hash(1)).0;
+    assert!(f.svm.account(&entry).is_none());
+
+    // Nor can the registry authority vouch for a program someone can upgrade.
+    let authority = f.authority.insecure_clone();
+    assert_parity_error(register_as(&mut f, &owner, &authority, &program), ParityError::ProgramOwnershipUnproven);
+
+    // The upgrade authority can sign on behalf of a separate owner.
+    assert_ok(register_as(&mut f, &owner, &upgrader, &program));
+    let entry: ProgramEntry = f.svm.get(&entry);
+    assert_eq!((entry.owner, entry.program_id), (owner.pubkey(), program));
+}
+
+#[test]
+fn immutable_programs_need_the_registry_authority() {
+    let mut f = Fixture::new();
+    let owner = f.owner.insecure_clone();
+    let authority = f.authority.insecure_clone();
+    let program = f.svm.deploy_upgradeable(None, &program_elf(1));
+
+    assert_parity_error(register_as(&mut f, &owner, &owner, &program), ParityError::ProgramOwnershipUnproven);
+    assert_ok(register_as(&mut f, &owner, &authority, &program));
+    let entry: ProgramEntry = f.svm.get(&pda::program_entry(&parity::ID, &f.registry, &program_hash(1)).0);
+    assert_eq!(entry.owner, owner.pubkey());
+}
+
+#[test]
+fn register_program_binds_the_hash_to_the_deployed_bytecode() {
+    // The victim has a build of program 1 but has not registered it yet.
+    let mut f = Fixture::new();
+    let squatter = f.svm.new_funded_keypair();
+    let unrelated = f.svm.deploy_upgradeable(Some(squatter.pubkey()), &program_elf(2));
+
+    assert_parity_error(register_as(&mut f, &squatter, &squatter, &unrelated), ParityError::ProgramHashMismatch);
+    let entry = pda::program_entry(&parity::ID, &f.registry, &program_hash(1)).0;
+    assert!(f.svm.account(&entry).is_none());
+
+    // Not even the registry authority can vouch for bytes that are not deployed.
+    let authority = f.authority.insecure_clone();
+    let immutable = f.svm.deploy_upgradeable(None, &program_elf(2));
+    assert_parity_error(register_as(&mut f, &squatter, &authority, &immutable), ParityError::ProgramHashMismatch);
+
+    let owner = f.owner.insecure_clone();
+    let program = f.svm.deploy_upgradeable(Some(owner.pubkey()), &program_elf(1));
+    assert_ok(register_as(&mut f, &owner, &owner, &program));
+    assert_eq!(f.svm.get::<ProgramEntry>(&entry).program_id, program);
+}
+
+#[test]
+fn register_program_rejects_program_data_of_another_program() {
+    let mut f = Fixture::new();
+    let owner = f.owner.insecure_clone();
+    let program = f.svm.deploy_upgradeable(Some(owner.pubkey()), &program_elf(1));
+    let other = f.svm.deploy_upgradeable(Some(owner.pubkey()), &program_elf(2));
+
+    let mut ix = instructions::register_program(
+        &parity::ID,
+        &f.registry,
+        &owner.pubkey(),
+        &owner.pubkey(),
+        &program,
+        program_hash(1),
+        Framework::Anchor,
+        "",
+    );
+    ix.accounts[5].pubkey = pda::program_data(&other);
+    assert_anchor_error(f.send(ix, &owner), ErrorCode::ConstraintSeeds);
+}
+
+#[test]
+fn reassign_program_owner_by_the_registry_authority_only() {
+    let mut f = Fixture::new().with_programs(1);
+    let authority = f.authority.insecure_clone();
+    let owner = f.owner.insecure_clone();
+    let new_owner = Pubkey::new_unique();
+
+    let ix = instructions::reassign_program_owner(&parity::ID, &f.registry, &owner.pubkey(), &program_hash(0), &new_owner);
+    assert_parity_error(f.send(ix, &owner), ParityError::UnauthorizedRegistryAuthority);
+
+    f.svm.warp(1);
+    let ix = instructions::reassign_program_owner(&parity::ID, &f.registry, &authority.pubkey(), &program_hash(0), &new_owner);
+    assert_ok(f.send(ix, &authority));
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert_eq!(entry.owner, new_owner);
+    assert_eq!(entry.updated_at, f.svm.now());
+}
+
+#[test]
+fn update_program_metadata_by_the_owner_only() {
+    let mut f = Fixture::new().with_programs(1);
+    let owner = f.owner.insecure_clone();
+    let intruder = f.svm.new_funded_keypair();
+
+    let ix = instructions::update_program_metadata(&parity::ID, &f.registry, &intruder.pubkey(), &program_hash(0), Framework::Native, "x");
+    assert_parity_error(f.send(ix, &intruder), ParityError::UnauthorizedProgramOwner);
+    let uri = "u".repeat(MAX_METADATA_URI_LEN + 1);
+    let ix = instructions::update_program_metadata(&parity::ID, &f.registry, &owner.pubkey(), &program_hash(0), Framework::Native, uri);
+    assert_parity_error(f.send(ix, &owner), ParityError::MetadataUriTooLong);
+
+    f.svm.warp(1);
+    let ix = instructions::update_program_metadata(&parity::ID, &f.registry, &owner.pubkey(), &program_hash(0), Framework::Steel, "ar://v2");
+    assert_ok(f.send(ix, &owner));
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert_eq!((entry.framework, entry.metadata_uri.as_str()), (Framework::Steel, "ar://v2"));
+    assert_eq!(entry.updated_at, f.svm.now());
+}
+
+#[test]
+fn ownership_moves_once_the_new_owner_accepts() {
+    let mut f = Fixture::new().with_programs(1);
+    let owner = f.owner.insecure_clone();
+    let multisig = f.svm.new_funded_keypair();
+    let stranger = f.svm.new_funded_keypair();
+    let hash = program_hash(0);
+
+    let ix = instructions::transfer_program_ownership(&parity::ID, &f.registry, &stranger.pubkey(), &hash, Some(stranger.pubkey()));
+    assert_parity_error(f.send(ix, &stranger), ParityError::UnauthorizedProgramOwner);
+    let ix = instructions::transfer_program_ownership(&parity::ID, &f.registry, &owner.pubkey(), &hash, Some(multisig.pubkey()));
+    assert_ok(f.send(ix, &owner));
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert_eq!((entry.owner, entry.pending_owner), (owner.pubkey(), Some(multisig.pubkey())));
+
+    let ix = instructions::accept_program_ownership(&parity::ID, &f.registry, &stranger.pubkey(), &hash);
+    assert_parity_error(f.send(ix, &stranger), ParityError::NotPendingOwner);
+    let ix = instructions::accept_program_ownership(&parity::ID, &f.registry, &multisig.pubkey(), &hash);
+    assert_ok(f.send(ix, &multisig));
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert_eq!((entry.owner, entry.pending_owner), (multisig.pubkey(), None));
+
+    // The previous owner has no say any more.
+    let ix = instructions::update_program_metadata(&parity::ID, &f.registry, &owner.pubkey(), &hash, Framework::Native, "");
+    assert_parity_error(f.send(ix, &owner), ParityError::UnauthorizedProgramOwner);
+}
+
+#[test]
+fn a_withdrawn_transfer_cannot_be_accepted() {
+    let mut f = Fixture::new().with_programs(1);
+    let owner = f.owner.insecure_clone();
+    let multisig = f.svm.new_funded_keypair();
+    let hash = program_hash(0);
+
+    let ix = instructions::transfer_program_ownership(&parity::ID, &f.registry, &owner.pubkey(), &hash, Some(multisig.pubkey()));
+    assert_ok(f.send(ix, &owner));
+    let ix = instructions::transfer_program_ownership(&parity::ID, &f.registry, &owner.pubkey(), &hash, None);
+    assert_ok(f.send(ix, &owner));
+
+    let ix = instructions::accept_program_ownership(&parity::ID, &f.registry, &multisig.pubkey(), &hash);
+    assert_parity_error(f.send(ix, &multisig), ParityError::NotPendingOwner);
+    assert_eq!(f.svm.get::<ProgramEntry>(&f.program_entry(0)).owner, owner.pubkey());
+}
+
+#[test]
+fn verify_program_hash_recomputes_the_deployed_bytecode() {
+    let mut f = Fixture::new().with_programs(1);
+    let payer = f.svm.new_funded_keypair();
+    let program_data = pda::program_data(&f.deployed[0]);
+    let verify = |f: &mut Fixture| {
+        let ix = instructions::verify_program_hash(&parity::ID, &f.registry, &program_hash(0), &f.deployed[0]);
+        f.send(ix, &payer)
+    };
+    assert_ok(verify(&mut f));
+
+    // Growing the zero padding leaves the hash unchanged.
+    let mut account = f.svm.account(&program_data).unwrap();
+    account.data.resize(account.data.len() + 1024, 0);
+    f.svm.set_account(program_data, account.clone());
+    assert_ok(verify(&mut f));
+
+    // An upgrade to different bytes does not.
+    let last = account.data.iter().rposition(|b| *b != 0).unwrap();
+    account.data[last] ^= 1;
+    f.svm.set_account(program_data, account);
+    assert_parity_error(verify(&mut f), ParityError::ProgramHashMismatch);
+
+    // The ProgramData has to be the one of the registered program.
+    let other = f.svm.deploy_upgradeable(None, &program_elf(0));
+    let mut ix = instructions::verify_program_hash(&parity::ID, &f.registry, &program_hash(0), &f.deployed[0]);
+    ix.accounts[1].pubkey = pda::program_data(&other);
+    assert_anchor_error(f.send(ix, &payer), ErrorCode::ConstraintSeeds);
+}
+
+fn deregister(f: &mut Fixture, program: usize, auditors: &[usize]) -> common::TxResult {
+    let owner = f.owner.insecure_clone();
+    let auditors: Vec<Pubkey> = auditors.iter().map(|&a| f.auditors[a].pubkey()).collect();
+    let ix = instructions::deregister_program(&parity::ID, &f.registry, &owner.pubkey(), &f.programs[program], &f.authority.pubkey(), &auditors);
+    f.send(ix, &owner)
+}
+
+#[test]
+fn deregister_program_closes_reports_badge_and_entry() {
+    let mut f = Fixture::new().with_auditors(2).with_programs(2);
+    f.verify(0, 0, 90);
+    f.verify(0, 1, 90);
+    f.verify(1, 0, 90);
+    let authority = f.authority.insecure_clone();
+    let ix = instructions::create_verification_badge(
+        &parity::ID,
+        &f.registry,
+        &authority.pubkey(),
+        &f.programs[0],
+        VerificationTier::Gold,
+        f.svm.now() + ONE_DAY,
+    );
+    assert_ok(f.send(ix, &authority));
+
+    let auditor = f.auditors[0].pubkey();
+    let (auditor_before, report_rent) = (f.svm.lamports(&auditor), f.svm.lamports(&f.analysis(0, 0)));
+    let (authority_before, badge_rent) = (f.svm.lamports(&authority.pubkey()), f.svm.lamports(&f.badge(0)));
+    assert_ok(deregister(&mut f, 0, &[0, 1]));
+
+    for address in [f.program_entry(0), f.analysis(0, 0), f.analysis(0, 1), f.badge(0)] {
+        assert!(f.svm.account(&address).is_none());
+    }
+    assert_eq!(f.svm.lamports(&auditor), auditor_before + report_rent);
+    assert_eq!(f.svm.lamports(&authority.pubkey()), authority_before + badge_rent);
+    let registry: Registry = f.svm.get(&f.registry());
+    assert_eq!((registry.total_programs, registry.total_analyses), (1, 1));
+    assert!(f.svm.account(&f.analysis(1, 0)).is_some());
+
+    // The build can be registered again from scratch.
+    let owner = f.owner.insecure_clone();
+    let ix = instructions::register_program(&parity::ID, &f.registry, &owner.pubkey(), &owner.pubkey(), &f.deployed[0], program_hash(0), Framework::Anchor, "");
+    assert_ok(f.send(ix, &owner));
+    assert_eq!(f.svm.get::<ProgramEntry>(&f.program_entry(0)).analysis_count, 0);
+}
+
+#[test]
+fn auditor_statistics_outlive_the_reports_they_count() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(2);
+    f.verify(0, 0, 90);
+    assert_ok(f.submit(1, 0, result(81)));
+    let stats = |f: &Fixture| {
+        let account: AuditorAccount = f.svm.get(&f.auditor_account(0));
+        (account.total_analyses, account.average_score)
+    };
+    assert_eq!(stats(&f), (2, 85));
+
+    assert_ok(f.update(1, 0, result(40)));
+    assert_ok(deregister(&mut f, 0, &[0]));
+    assert!(f.svm.account(&f.analysis(0, 0)).is_none());
+    assert_eq!(stats(&f), (2, 85));
+}
+
+#[test]
+fn deregistration_retires_the_entry_until_everything_is_closed() {
+    let mut f = Fixture::new().with_auditors(3).with_programs(1);
+    f.verify(0, 0, 90);
+    f.verify(0, 1, 90);
+    let auditor = f.auditors[2].insecure_clone();
+    let ix = instructions::commit_analysis(&parity::ID, &f.registry, &auditor.pubkey(), &f.programs[0], [7; 32]);
+    assert_ok(f.send(ix, &auditor));
+
+    assert_ok(deregister(&mut f, 0, &[0]));
+    let entry: ProgramEntry = f.svm.get(&f.program_entry(0));
+    assert!(entry.is_retired && !entry.is_verified);
+    assert_eq!((entry.analysis_count, entry.open_commitments), (1, 1));
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_programs, 1);
+
+    assert_parity_error(f.submit(0, 2, result(90)), ParityError::ProgramRetired);
+    assert_parity_error(f.update(0, 1, result(90)), ParityError::ProgramRetired);
+    let owner = f.owner.insecure_clone();
+    let ix = instructions::update_program_metadata(&parity::ID, &f.registry, &owner.pubkey(), &f.programs[0], Framework::Native, "");
+    assert_parity_error(f.send(ix, &owner), ParityError::ProgramRetired);
+
+    // The last report is closed, but the commitment keeps the entry open
+    // until it expires.
+    assert_ok(deregister(&mut f, 0, &[1]));
+    assert_eq!(f.svm.get::<ProgramEntry>(&f.program_entry(0)).analysis_count, 0);
+    f.svm.warp(7 * ONE_DAY + 1);
+    let ix = instructions::expire_commitment(&parity::ID, &owner.pubkey(), &f.program_entry(0), &auditor.pubkey());
+    assert_ok(f.send(ix, &owner));
+    assert_ok(deregister(&mut f, 0, &[]));
+
+    assert!(f.svm.account(&f.program_entry(0)).is_none());
+    let registry: Registry = f.svm.get(&f.registry());
+    assert_eq!((registry.total_programs, registry.total_analyses), (0, 0));
+}
+
+#[test]
+fn deregister_program_rejects_accounts_of_other_programs() {
+    let mut f = Fixture::new().with_auditors(2).with_programs(2);
+    f.verify(0, 0, 90);
+    f.verify(1, 1, 90);
+    let owner = f.owner.insecure_clone();
+    let entry = f.program_entry(0);
+
+    // A report of another program.
+    let mut ix = instructions::deregister_program(&parity::ID, &f.registry, &owner.pubkey(), &f.programs[0], &owner.pubkey(), &[]);
+    ix.accounts.push(AccountMeta::new(f.analysis(1, 1), false));
+    ix.accounts.push(AccountMeta::new(f.auditors[1].pubkey(), false));
+    assert_parity_error(f.send(ix, &owner), ParityError::DeregistrationAccountMismatch);
+
+    // A report refunded to someone other than its auditor.
+    let mut ix = instructions::deregister_program(&parity::ID, &f.registry, &owner.pubkey(), &f.programs[0], &owner.pubkey(), &[]);
+    ix.accounts.push(AccountMeta::new(f.analysis(0, 0), false));
+    ix.accounts.push(AccountMeta::new(owner.pubkey(), false));
+    assert_parity_error(f.send(ix, &owner), ParityError::DeregistrationAccountMismatch);
+
+    let intruder = f.svm.new_funded_keypair();
+    let ix = instructions::deregister_program(&parity::ID, &f.registry, &intruder.pubkey(), &f.programs[0], &owner.pubkey(), &[]);
+    assert_parity_error(f.send(ix, &intruder), ParityError::UnauthorizedProgramOwner);
+
+    assert!(!f.svm.get::<ProgramEntry>(&entry).is_retired);
+    assert!(f.svm.account(&f.analysis(0, 0)).is_some());
+}
+
+#[test]
+fn deregister_program_refunds_the_badge_to_its_issuer_only() {
+    let mut f = Fixture::new().with_auditors(1).with_programs(1);
+    f.verify(0, 0, 90);
+    let authority = f.authority.insecure_clone();
+    let ix = instructions::create_verification_badge(
+        &parity::ID,
+        &f.registry,
+        &authority.pubkey(),
+        &f.programs[0],
+        VerificationTier::Bronze,
+        f.svm.now() + ONE_DAY,
+    );
+    assert_ok(f.send(ix, &authority));
+
+    let owner = f.owner.insecure_clone();
+    let auditors = [f.auditors[0].pubkey()];
+    let ix = instructions::deregister_program(&parity::ID, &f.registry, &owner.pubkey(), &f.programs[0], &owner.pubkey(), &auditors);
+    assert_parity_error(f.send(ix, &owner), ParityError::DeregistrationAccountMismatch);
+    assert!(f.svm.account(&f.badge(0)).is_some());
+}
@@ -0,0 +1,123 @@
+use parity::scoring::{Category, Confidence, ScoredFinding, ScoringModel, SeverityPenalties};
+use parity::state::{AnalysisFindingsCount, PatternType, Severity};
+
+fn counts(critical: u16, high: u16, medium: u16, info: u16) -> AnalysisFindingsCount {
+    AnalysisFindingsCount {
+        critical,
+        high,
+        medium,
+        info,
+        pass: 0,
+        total: critical + high + medium + info,
+    }
+}
+
+#[test]
+fn repeated_findings_are_discounted() {
+    let model = ScoringModel::DEFAULT;
+    let findings = vec![ScoredFinding::new(Severity::Critical); 4];
+    let breakdown = model.score(&findings);
+
+    let points: Vec<u64> = breakdown.deductions.iter().map(|d| d.milli_points).collect();
+    assert_eq!(points, [25_000, 12_500, 6_250, 3_125]);
+    assert_eq!(breakdown.milli_points, 46_875);
+}
+
+#[test]
+fn repeats_are_discounted_down_to_a_floor() {
+    let model = ScoringModel::DEFAULT;
+    let findings = vec![ScoredFinding::new(Severity::Critical); 6];
+    let points: Vec<u64> = model.score(&findings).deductions.iter().map(|d| d.milli_points).collect();
+    assert_eq!(points, [25_000, 12_500, 6_250, 3_125, 2_500, 2_500]);
+
+    let flood = vec![ScoredFinding::new(Severity::Critical); 1_000];
+    assert_eq!(model.score(&flood).score, 0);
+}
+
+#[test]
+fn weights_and_confidence_scale_deductions() {
+    let model = ScoringModel::DEFAULT;
+    let findings = [
+        ScoredFinding::new(Severity::High).with_pattern_type(PatternType::InsecureCpi),
+        ScoredFinding::new(Severity::High)
+            .with_pattern_type(PatternType::UncheckedArithmetic)
+            .with_confidence(Confidence::Low),
+    ];
+    let breakdown = model.score(&findings);
+
+    assert_eq!(breakdown.deductions[0].milli_points, 18_000);
+    assert_eq!(breakdown.deductions[1].milli_points, 7_500);
+    assert_eq!(breakdown.score, 74);
+
+    let cpi = breakdown.categories.iter().find(|c| c.category == Category::Cpi).unwrap();
+    assert_eq!((cpi.findings, cpi.score), (1, 82));
+    let other = breakdown.categories.iter().find(|c| c.category == Category::Other).unwrap();
+    assert_eq!((other.findings, other.score), (0, 100));
+}
+
+#[test]
+fn scores_fall_within_bounds_of_their_counts() {
+    let model = ScoringModel::DEFAULT;
+    let findings = [
+        ScoredFinding::new(Severity::Critical).with_pattern_type(PatternType::MissingSignerCheck),
+        ScoredFinding::new(Severity::Critical).with_pattern_type(PatternType::OwnerCheck),
+        ScoredFinding::new(Severity::Medium).with_confidence(Confidence::Medium),
+        ScoredFinding::new(Severity::Info),
+    ];
+    let score = model.score(&findings).score;
+    let counts = counts(2, 0, 1, 1);
+
+    let (low, high) = model.score_bounds(&counts);
+    assert!(low <= score && score <= high, "{} not in {}..={}", score, low, high);
+    assert!(model.is_consistent(score, &counts));
+    assert!(!model.is_consistent(100, &counts));
+    assert!(!model.is_consistent(score, &AnalysisFindingsCount { total: 9, ..counts }));
+}
+
+#[test]
+fn clean_reports_must_score_full_marks() {
+    let model = ScoringModel::DEFAULT;
+    assert_eq!(model.score_bounds(&counts(0, 0, 0, 0)), (100, 100));
+    assert_eq!(model.score_bounds(&counts(u16::MAX, 0, 0, 0)).0, 0);
+}
+
+#[test]
+fn many_findings_cannot_keep_a_passing_score() {
+    let model = ScoringModel::DEFAULT;
+    // Even read as low-confidence, lightly weighted repeats of one issue.
+    assert_eq!(model.score_bounds(&counts(1_000, 0, 0, 0)), (0, 0));
+    assert_eq!(model.score_bounds(&counts(0, 1_000, 0, 0)).1, 0);
+    assert_eq!(model.score_bounds(&counts(0, 0, 0, 1_000)).1, 0);
+    assert!(!model.is_consistent(80, &counts(1_000, 0, 0, 0)));
+
+    // Without the floor the same thousand critical findings could score 80.
+    let unfloored = ScoringModel { repeat_floor: 0, critical_ceiling: 100, ..ScoringModel::DEFAULT };
+    assert_eq!(unfloored.score_bounds(&counts(1_000, 0, 0, 0)).1, 80);
+}
+
+#[test]
+fn critical_findings_cap_the_score() {
+    let model = ScoringModel::DEFAULT;
+    let light = ScoredFinding::new(Severity::Critical)
+        .with_pattern_type(PatternType::RentExemption)
+        .with_confidence(Confidence::Low);
+    // Ten lightly weighted repeats of one critical deduct under 25 points.
+    let breakdown = model.score(&[light; 10]);
+    assert_eq!((breakdown.milli_points, breakdown.score), (24_750, 50));
+
+    let ten = counts(10, 0, 0, 0);
+    assert_eq!(model.score_bounds(&ten).1, 50);
+    assert!(!model.is_consistent(100, &ten));
+    assert!(!model.is_consistent(75, &ten));
+    assert!(model.is_consistent(50, &ten));
+    assert_eq!(model.score_bounds(&counts(1, 0, 0, 0)), (50, 50));
+}
+
+#[test]
+fn oversized_penalties_saturate() {
+    let penalties = SeverityPenalties { critical: u64::MAX, high: u64::MAX / 2, medium: 0, info: 0 };
+    let model = ScoringModel { penalties, ..ScoringModel::DEFAULT };
+    let findings = [ScoredFinding::new(Severity::Critical), ScoredFinding::new(Severity::High)];
+    assert_eq!(model.score(&findings).score, 0);
+    assert_eq!(model.score_bounds(&counts(1, 1, 0, 0)), (0, 0));
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::{assert_ok, assert_parity_error, Fixture, TxResult};
+use parity::errors::ParityError;
+use parity::state::{Registry, SkillEntry, SkillType, MAX_SKILL_DESC_LEN, MAX_SKILL_VERSION_LEN};
+use parity_client::{instructions, pda};
+use solana_sdk::signature::{Keypair, Signer};
+
+fn register(f: &mut Fixture, authority: &Keypair, name: &str, version: &str, description: &str) -> TxResult {
+    let ix = instructions::register_skill(&parity::ID, &f.registry, &authority.pubkey(), name, version, description, SkillType::Custom);
+    f.send(ix, authority)
+}
+
+fn skill(f: &Fixture, author: &Keypair, name: &str) -> SkillEntry {
+    f.svm.get(&pda::skill(&parity::ID, &f.registry, &author.pubkey(), name).0)
+}
+
+#[test]
+fn register_skill_records_the_definition() {
+    let mut f = Fixture::new();
+    let author = f.svm.new_funded_keypair();
+    assert_ok(register(&mut f, &author, "reentrancy-scan", "0.1.0", "Looks for CPI reentrancy"));
+
+    let entry = skill(&f, &author, "reentrancy-scan");
+    assert_eq!(entry.authority, author.pubkey());
+    assert_eq!(entry.namespace, author.pubkey());
+    assert_eq!(entry.version, "0.1.0");
+    assert_eq!(entry.description, "Looks for CPI reentrancy");
+    assert_eq!(entry.skill_type, SkillType::Custom);
+    assert!(!entry.is_deprecated);
+    assert_eq!(f.svm.get::<Registry>(&f.registry()).total_skills, 1);
+}
+
+#[test]
+fn register_skill_rejects_long_fields() {
+    let mut f = Fixture::new();
+    let author = f.svm.new_funded_keypair();
+    // A name over MAX_SKILL_NAME_LEN cannot be a 32-byte PDA seed, so the
+    // account constraint rejects it before the handler's length check.
+    assert_parity_error(
+        register(&mut f, &author, "scan", &"1".repeat(MAX_SKILL_VERSION_LEN + 1), ""),
+        ParityError::SkillVersionTooLong,
+    );
+    assert_parity_error(
+        register(&mut f, &author, "scan", "1", &"d".repeat(MAX_SKILL_DESC_LEN + 1)),
+        ParityError::SkillDescriptionTooLong,
+    );
+}
+
+#[test]
+fn update_skill_by_its_authority() {
+    let mut f = Fixture::new();
+    let author = f.svm.new_funded_keypair();
+    assert_ok(register(&mut f, &author, "scan", "0.1.0", "v1"));
+
+    f.svm.warp(5);
+    let ix = instructions::update_skill(&parity::ID, &f.registry, &author.pubkey(), &author.pubkey(), "scan", "0.2.0", "v2");
+    assert_ok(f.send(ix, &author));
+    let entry = skill(&f, &author, "scan");
+    assert_eq!((entry.version.as_str(), entry.description.as_str()), ("0.2.0", "v2"));
+    assert_eq!(entry.updated_at, f.svm.now());
+
+    let ix = instructions::update_skill(&parity::ID, &f.registry, &author.pubkey(), &author.pubkey(), "scan", "1".repeat(MAX_SKILL_VERSION_LEN + 1), "");
+    assert_parity_error(f.send(ix, &author), ParityError::SkillVersionTooLong);
+    let ix = instructions::update_skill(&parity::ID, &f.registry, &author.pubkey(), &author.pubkey(), "scan", "1", "d".repeat(MAX_SKILL_DESC_LEN + 1));
+    assert_parity_error(f.send(ix, &author), ParityError::SkillDescriptionTooLong);
+}
+
+#[test]
+fn skills_are_only_changed_by_their_authority() {
+    let mut f = Fixture::new();
+    let author = f.svm.new_funded_keypair();
+    let other = f.svm.new_funded_keypair();
+    assert_ok(register(&mut f, &author, "scan", "0.1.0", "v1"));
+
+    let ix = instructions::update_skill(&parity::ID, &f.registry, &other.pubkey(), &author.pubkey(), "scan", "6.6.6", "hijacked");
+    assert_parity_error(f.send(ix, &other), ParityError::UnauthorizedAuditor);
+    let ix = instructions::deprecate_skill(&parity::ID, &f.registry, &other.pubkey(), &author.pubkey(), "scan");
+    assert_parity_error(f.send(ix, &other), ParityError::UnauthorizedAuditor);
+
+    let entry = skill(&f, &author, "scan");
+    assert_eq!(entry.version, "0.1.0");
+    assert!(!entry.is_deprecated);
+}
+
+#[test]
+fn deprecated_skills_are_frozen() {
+    let mut f = Fixture::new();
+    let author = f.svm.new_funded_keypair();
+    assert_ok(register(&mut f, &author, "scan", "0.1.0", "v1"));
+
+    let ix = instructions::deprecate_skill(&parity::ID, &f.registry, &author.pubkey(), &author.pubkey(), "scan");
+    assert_ok(f.send(ix.clone(), &author));
+    assert!(skill(&f, &author, "scan").is_deprecated);
+
+    assert_parity_error(f.send(ix, &author), ParityError::SkillAlreadyDeprecated);
+
+    let ix = instructions::update_skill(&parity::ID, &f.registry, &author.pubkey(), &author.pubkey(), "scan", "0.2.0", "v2");
+    assert_parity_error(f.send(ix, &author), ParityError::CannotUpdateDeprecatedSkill);
+    assert_eq!(skill(&f, &author, "scan").version, "0.1.0");
+}
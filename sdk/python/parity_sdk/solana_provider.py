@@ -5,7 +5,7 @@ from solders.pubkey import Pubkey
 from parity_sdk.constants import DEFAULT_SOLANA_RPC
 
 
-PROGRAM_ID = Pubkey.from_string("PARTYxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx1")
+PROGRAM_ID = Pubkey.from_string("Parity1111111111111111111111111111111111111")
 
 
 class SolanaProvider:  # On-chain PDA derivation and account helpers
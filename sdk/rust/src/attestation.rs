@@ -0,0 +1,70 @@
+//! Off-chain signing of the attestations `submit_signed_analysis` accepts.
+//!
+//! The auditor signs with [`sign`] wherever its key lives; anyone holding the
+//! attestation and signature can then send [`instructions`] and pay for it.
+
+use anchor_lang::AnchorDeserialize;
+use parity::state::AnalysisAttestation;
+use solana_sdk::ed25519_program;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Signature, Signer};
+
+pub use parity::instructions::attestation::{attestation_message, ATTESTATION_DOMAIN};
+
+/// Parses a message built by [`attestation_message`]; `None` if it is not one.
+pub fn parse_message(message: &[u8]) -> Option<AnalysisAttestation> {
+    let body = message.strip_prefix(ATTESTATION_DOMAIN)?;
+    AnalysisAttestation::try_from_slice(body).ok()
+}
+
+pub fn sign(auditor: &dyn Signer, attestation: &AnalysisAttestation) -> Signature {
+    auditor.sign_message(&attestation_message(attestation))
+}
+
+pub fn verify(auditor: &Pubkey, signature: &Signature, attestation: &AnalysisAttestation) -> bool {
+    signature.verify(auditor.as_ref(), &attestation_message(attestation))
+}
+
+/// The Ed25519 program instruction that checks `signature`, with the key,
+/// signature and message all inside its own data as the program requires.
+pub fn ed25519_instruction(auditor: &Pubkey, signature: &Signature, attestation: &AnalysisAttestation) -> Instruction {
+    const DATA_START: u16 = 16;
+    let message = attestation_message(attestation);
+    let public_key_offset = DATA_START;
+    let signature_offset = public_key_offset + 32;
+    let message_offset = signature_offset + 64;
+
+    let mut data = vec![1, 0];
+    for field in [
+        signature_offset,
+        u16::MAX,
+        public_key_offset,
+        u16::MAX,
+        message_offset,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(auditor.as_ref());
+    data.extend_from_slice(signature.as_ref());
+    data.extend_from_slice(&message);
+    Instruction { program_id: ed25519_program::ID, accounts: Vec::new(), data }
+}
+
+/// The signature check followed by `submit_signed_analysis`, in the order
+/// the program expects.
+pub fn instructions(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    payer: &Pubkey,
+    auditor: &Pubkey,
+    signature: &Signature,
+    attestation: AnalysisAttestation,
+) -> [Instruction; 2] {
+    [
+        ed25519_instruction(auditor, signature, &attestation),
+        crate::instructions::submit_signed_analysis(program_id, registry, payer, auditor, attestation),
+    ]
+}
@@ -0,0 +1,202 @@
+//! Sizing of the batched instructions. A batch has to fit one transaction:
+//! its serialized size, its account locks and its compute budget. The
+//! helpers here split a list of items into as few transactions as those
+//! limits allow, each a compute budget request followed by the batch.
+//!
+//! The batched instructions skip items that already exist, so a list can be
+//! re-sent as is after some of its transactions failed. Each skipped item is
+//! logged; [`skipped`] reads them back from a transaction's logs, such as
+//! those returned by `ParityClient::transaction_logs`.
+
+use std::str::FromStr;
+
+use parity::state::{AnalysisSubmission, ProgramRegistration, SkillRegistration};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::SIGNATURE_BYTES;
+
+use crate::instructions::{self, LegacyMigration};
+
+/// The most compute units a transaction can request.
+pub const MAX_TRANSACTION_COMPUTE_UNITS: u32 = 1_400_000;
+/// The most accounts a transaction can lock.
+pub const MAX_TRANSACTION_ACCOUNTS: usize = 64;
+
+/// Compute units requested for a batch besides its items: account
+/// validation, the registry write-back and logging.
+///
+/// This and the per-item budgets below are round figures, not measurements
+/// of the SBF build. A batch that runs out can be re-split with larger
+/// budgets through [`split`] or [`split_by`].
+pub const BATCH_BASE_COMPUTE_UNITS: u32 = 20_000;
+/// Compute units requested per item, covering PDA derivation, the account
+/// creation CPI and serialization. Registering a program also hashes its
+/// `ProgramData`; see [`register_program_compute_units`].
+pub const REGISTER_PROGRAM_COMPUTE_UNITS: u32 = 30_000;
+pub const REGISTER_SKILL_COMPUTE_UNITS: u32 = 20_000;
+pub const SUBMIT_ANALYSIS_COMPUTE_UNITS: u32 = 35_000;
+pub const MIGRATE_ACCOUNT_COMPUTE_UNITS: u32 = 25_000;
+/// Moving an account also derives its old and new addresses and closes it.
+pub const MIGRATE_LEGACY_ACCOUNT_COMPUTE_UNITS: u32 = 40_000;
+
+/// Compute units requested for hashing a `ProgramData` account of
+/// `program_data_len` bytes: one per byte. The sha256 syscall charges one
+/// per two bytes hashed, leaving the other half for finding where the
+/// executable ends in the zero padding.
+pub fn program_hash_compute_units(program_data_len: usize) -> u32 {
+    u32::try_from(program_data_len).unwrap_or(u32::MAX)
+}
+
+/// Compute units requested for registering a program whose `ProgramData`
+/// account is `program_data_len` bytes long.
+pub fn register_program_compute_units(program_data_len: usize) -> u32 {
+    REGISTER_PROGRAM_COMPUTE_UNITS.saturating_add(program_hash_compute_units(program_data_len))
+}
+
+/// The compute unit limit to request for a batch of `items` items.
+pub fn compute_units(per_item: u32, items: usize) -> u32 {
+    let items = u32::try_from(items).unwrap_or(u32::MAX);
+    per_item.saturating_mul(items).saturating_add(BATCH_BASE_COMPUTE_UNITS)
+}
+
+/// The serialized size of a transaction carrying `instructions`, paid by
+/// `payer`.
+pub fn transaction_size(payer: &Pubkey, instructions: &[Instruction]) -> usize {
+    let message = Message::new(instructions, Some(payer));
+    let signatures = usize::from(message.header.num_required_signatures);
+    // Both counts are short vectors, a single byte below 128 entries.
+    1 + signatures * SIGNATURE_BYTES + message.serialize().len()
+}
+
+/// Whether a transaction carrying `instructions` is within the size and
+/// account lock limits.
+pub fn fits(payer: &Pubkey, instructions: &[Instruction]) -> bool {
+    let message = Message::new(instructions, Some(payer));
+    message.account_keys.len() <= MAX_TRANSACTION_ACCOUNTS && transaction_size(payer, instructions) <= PACKET_DATA_SIZE
+}
+
+/// Splits `items` into consecutive batches, each built by `build` and
+/// returned as the instructions of one transaction: a compute unit limit
+/// for `per_item` units an item, then the batch. Every batch is as large as
+/// the limits allow; an item too large for any transaction still gets one of
+/// its own, which the cluster will reject.
+pub fn split<T: Clone>(
+    payer: &Pubkey,
+    items: &[T],
+    per_item: u32,
+    build: impl Fn(Vec<T>) -> Instruction,
+) -> Vec<Vec<Instruction>> {
+    split_by(payer, items, |_| per_item, build)
+}
+
+/// [`split`] for items whose cost varies: `cost` gives the compute units
+/// to request for each.
+pub fn split_by<T: Clone>(
+    payer: &Pubkey,
+    items: &[T],
+    cost: impl Fn(&T) -> u32,
+    build: impl Fn(Vec<T>) -> Instruction,
+) -> Vec<Vec<Instruction>> {
+    let units = |items: &[T]| {
+        items.iter().fold(BATCH_BASE_COMPUTE_UNITS, |units, item| units.saturating_add(cost(item)))
+    };
+    let transaction = |items: &[T]| {
+        vec![ComputeBudgetInstruction::set_compute_unit_limit(units(items)), build(items.to_vec())]
+    };
+
+    let mut transactions = Vec::new();
+    let mut start = 0;
+    while start < items.len() {
+        let mut end = start + 1;
+        let mut current = transaction(&items[start..end]);
+        while end < items.len() && units(&items[start..end + 1]) <= MAX_TRANSACTION_COMPUTE_UNITS {
+            let next = transaction(&items[start..end + 1]);
+            if !fits(payer, &next) {
+                break;
+            }
+            current = next;
+            end += 1;
+        }
+        transactions.push(current);
+        start = end;
+    }
+    transactions
+}
+
+/// The accounts that `register_programs`, `register_skills` and
+/// `submit_analyses` left as they were because they already existed, in the
+/// order the program logged them.
+pub fn skipped(logs: &[String]) -> Vec<Pubkey> {
+    logs.iter()
+        .filter_map(|log| {
+            let rest = log.strip_prefix("Program log: Batch item ")?;
+            let (_, rest) = rest.split_once(" skipped: ")?;
+            Pubkey::from_str(rest.strip_suffix(" already exists")?).ok()
+        })
+        .collect()
+}
+
+/// [`instructions::register_programs`] split into transactions paid by
+/// `owner`. Each item is the program, the length of its `ProgramData`
+/// account and its registration.
+pub fn register_programs(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    owner: &Pubkey,
+    authority: &Pubkey,
+    items: &[(Pubkey, usize, ProgramRegistration)],
+) -> Vec<Vec<Instruction>> {
+    split_by(owner, items, |(_, len, _)| register_program_compute_units(*len), |items| {
+        let items = items.into_iter().map(|(program, _, registration)| (program, registration)).collect();
+        instructions::register_programs(program_id, registry, owner, authority, items)
+    })
+}
+
+/// [`instructions::register_skills`] split into transactions paid by
+/// `authority`.
+pub fn register_skills(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    authority: &Pubkey,
+    items: &[SkillRegistration],
+) -> Vec<Vec<Instruction>> {
+    split(authority, items, REGISTER_SKILL_COMPUTE_UNITS, |items| {
+        instructions::register_skills(program_id, registry, authority, items)
+    })
+}
+
+/// [`instructions::submit_analyses`] split into transactions paid by
+/// `auditor`.
+pub fn submit_analyses(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    auditor: &Pubkey,
+    items: &[AnalysisSubmission],
+) -> Vec<Vec<Instruction>> {
+    split(auditor, items, SUBMIT_ANALYSIS_COMPUTE_UNITS, |items| {
+        instructions::submit_analyses(program_id, registry, auditor, items)
+    })
+}
+
+/// [`instructions::migrate_accounts`] split into transactions paid by
+/// `payer`.
+pub fn migrate_accounts(program_id: &Pubkey, payer: &Pubkey, accounts: &[Pubkey]) -> Vec<Vec<Instruction>> {
+    split(payer, accounts, MIGRATE_ACCOUNT_COMPUTE_UNITS, |accounts| {
+        instructions::migrate_accounts(program_id, payer, &accounts)
+    })
+}
+
+/// [`instructions::migrate_legacy_accounts`] split into transactions paid by
+/// `payer`.
+pub fn migrate_legacy_accounts(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    items: &[LegacyMigration],
+) -> Vec<Vec<Instruction>> {
+    split(payer, items, MIGRATE_LEGACY_ACCOUNT_COMPUTE_UNITS, |items| {
+        instructions::migrate_legacy_accounts(program_id, payer, &items)
+    })
+}
@@ -0,0 +1,144 @@
+use anchor_lang::error::ERROR_CODE_OFFSET;
+use parity::errors::ParityError;
+use solana_client::client_error::{ClientError as RpcError, ClientErrorKind};
+use solana_client::rpc_request::{RpcError as RpcRequestError, RpcResponseErrorData};
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The program rejected the transaction with one of its own errors.
+    Parity(ParityError),
+    /// Any other custom error raised by the program, such as an Anchor
+    /// constraint violation, with the program's log output.
+    Program { code: u32, logs: Vec<String> },
+    Rpc(Box<RpcError>),
+    AccountNotFound(Pubkey),
+    AccountDecode(Pubkey, Box<anchor_lang::error::Error>),
+}
+
+impl std::fmt::Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Parity(err) => write!(f, "{}: {}", err.name(), err),
+            ClientError::Program { code, logs } => {
+                write!(f, "program error {:#x}", code)?;
+                for line in logs {
+                    write!(f, "\n  {}", line)?;
+                }
+                Ok(())
+            }
+            ClientError::Rpc(err) => write!(f, "{}", err),
+            ClientError::AccountNotFound(address) => write!(f, "account {} not found", address),
+            ClientError::AccountDecode(address, err) => write!(f, "decoding account {}: {}", address, err),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<RpcError> for ClientError {
+    fn from(err: RpcError) -> Self {
+        ClientError::Rpc(Box::new(err))
+    }
+}
+
+/// Every `ParityError`, in declaration order.
+pub const PARITY_ERRORS: &[ParityError] = &[
+    ParityError::InvalidScore,
+    ParityError::ProgramAlreadyRegistered,
+    ParityError::AnalysisAlreadyExists,
+    ParityError::SkillNameTooLong,
+    ParityError::SkillVersionTooLong,
+    ParityError::SkillDescriptionTooLong,
+    ParityError::TooManySkills,
+    ParityError::MetadataUriTooLong,
+    ParityError::UnauthorizedAuditor,
+    ParityError::AuditorInactive,
+    ParityError::BadgeExpired,
+    ParityError::BadgeAlreadyRevoked,
+    ParityError::InvalidTierForScore,
+    ParityError::PatternIdTooLong,
+    ParityError::DetectionRuleTooLong,
+    ParityError::PatternDescriptionTooLong,
+    ParityError::ProgramNotRegistered,
+    ParityError::InsufficientScore,
+    ParityError::RegistryFull,
+    ParityError::UnsupportedFramework,
+    ParityError::CredentialsUriTooLong,
+    ParityError::AuditorNameTooLong,
+    ParityError::FindingsCountMismatch,
+    ParityError::SkillAlreadyDeprecated,
+    ParityError::CannotUpdateDeprecatedSkill,
+    ParityError::InvalidDetectionRule,
+    ParityError::ScoreInconsistentWithFindings,
+    ParityError::InvalidCommitRevealWindow,
+    ParityError::CommitmentMismatch,
+    ParityError::RevealNotOpen,
+    ParityError::CommitmentExpired,
+    ParityError::CommitmentNotExpired,
+    ParityError::InvalidInclusionProof,
+    ParityError::InvalidExclusionProof,
+    ParityError::ArithmeticOverflow,
+    ParityError::TooManyUpdates,
+    ParityError::BuiltinAccountMismatch,
+    ParityError::ProgramOwnershipUnproven,
+    ParityError::UnauthorizedProgramOwner,
+    ParityError::NotPendingOwner,
+    ParityError::ProgramRetired,
+    ParityError::DeregistrationAccountMismatch,
+    ParityError::ProgramNameTooLong,
+    ParityError::RepositoryUrlTooLong,
+    ParityError::InvalidSourceCommit,
+    ParityError::ToolchainTooLong,
+    ParityError::ReleaseTagTooLong,
+    ParityError::SecurityContactTooLong,
+    ParityError::ProgramHashMismatch,
+    ParityError::AttestationSignatureMissing,
+    ParityError::AttestationExpired,
+    ParityError::AttestationNonceReused,
+    ParityError::AttestationRegistryMismatch,
+    ParityError::BatchAccountMismatch,
+    ParityError::InvalidRegistryNamespace,
+    ParityError::AccountAlreadyMigrated,
+    ParityError::UnsupportedSchemaVersion,
+    ParityError::UnversionedAccount,
+    ParityError::LegacyRegistryRequired,
+    ParityError::MigrationAccountMismatch,
+    ParityError::MigrationDestinationInUse,
+    ParityError::CommitRevealRequired,
+    ParityError::UnauthorizedRegistryAuthority,
+    ParityError::ProgramIdAlreadyBound,
+];
+
+/// The `ParityError` behind a custom program error code.
+pub fn parity_error(code: u32) -> Option<ParityError> {
+    let index = code.checked_sub(ERROR_CODE_OFFSET)?;
+    PARITY_ERRORS.get(index as usize).copied()
+}
+
+/// Maps a failed send to a typed error when an instruction for `program_id`
+/// raised a custom error.
+pub fn from_send_error(err: RpcError, instructions: &[Instruction], program_id: &Pubkey) -> ClientError {
+    let Some(TransactionError::InstructionError(index, InstructionError::Custom(code))) = err.get_transaction_error() else {
+        return ClientError::Rpc(Box::new(err));
+    };
+    let ours = instructions.get(index as usize).is_some_and(|ix| ix.program_id == *program_id);
+    if !ours {
+        return ClientError::Rpc(Box::new(err));
+    }
+    if let Some(parity) = parity_error(code) {
+        return ClientError::Parity(parity);
+    }
+    let logs = match err.kind() {
+        ClientErrorKind::RpcError(RpcRequestError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+            ..
+        }) => result.logs.clone().unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    ClientError::Program { code, logs }
+}
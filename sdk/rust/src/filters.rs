@@ -0,0 +1,123 @@
+//! `getProgramAccounts` filters over fixed-offset account fields.
+//!
+//! Offsets count the 8-byte discriminator. Only fields ahead of the first
+//! variable-length field can be filtered on.
+
+use anchor_lang::Discriminator;
+use parity::schema::v0::Legacy;
+use parity::state::VerificationTier;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+
+pub const PROGRAM_ENTRY_REGISTRY: usize = 8;
+pub const PROGRAM_ENTRY_OWNER: usize = 40;
+pub const PROGRAM_ENTRY_HASH: usize = 72;
+pub const PROGRAM_ENTRY_PROGRAM_ID: usize = 104;
+pub const ANALYSIS_REPORT_PROGRAM_ENTRY: usize = 8;
+pub const ANALYSIS_REPORT_AUDITOR: usize = 40;
+pub const COMMITMENT_PROGRAM_ENTRY: usize = 8;
+pub const COMMITMENT_AUDITOR: usize = 40;
+pub const BADGE_PROGRAM_ENTRY: usize = 8;
+pub const BADGE_ISSUER: usize = 40;
+pub const BADGE_TIER: usize = 72;
+pub const SKILL_REGISTRY: usize = 8;
+pub const SKILL_AUTHORITY: usize = 40;
+pub const AUDITOR_REGISTRY: usize = 8;
+pub const AUDITOR_AUTHORITY: usize = 40;
+pub const PATTERN_REGISTRY: usize = 8;
+pub const PATTERN_SUBMITTER: usize = 40;
+
+fn pubkey_at(offset: usize, key: &Pubkey) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(offset, key.as_ref()))
+}
+
+pub fn programs_in_registry(registry: &Pubkey) -> RpcFilterType {
+    pubkey_at(PROGRAM_ENTRY_REGISTRY, registry)
+}
+
+pub fn programs_by_owner(owner: &Pubkey) -> RpcFilterType {
+    pubkey_at(PROGRAM_ENTRY_OWNER, owner)
+}
+
+/// Every registered build of the deployed program `program`.
+pub fn programs_by_program_id(program: &Pubkey) -> RpcFilterType {
+    pubkey_at(PROGRAM_ENTRY_PROGRAM_ID, program)
+}
+
+pub fn reports_for_program(program_entry: &Pubkey) -> RpcFilterType {
+    pubkey_at(ANALYSIS_REPORT_PROGRAM_ENTRY, program_entry)
+}
+
+/// `auditor` is the auditor's signing key, not its `AuditorAccount`.
+pub fn reports_by_auditor(auditor: &Pubkey) -> RpcFilterType {
+    pubkey_at(ANALYSIS_REPORT_AUDITOR, auditor)
+}
+
+pub fn commitments_for_program(program_entry: &Pubkey) -> RpcFilterType {
+    pubkey_at(COMMITMENT_PROGRAM_ENTRY, program_entry)
+}
+
+pub fn badges_for_program(program_entry: &Pubkey) -> RpcFilterType {
+    pubkey_at(BADGE_PROGRAM_ENTRY, program_entry)
+}
+
+pub fn badges_by_issuer(issuer: &Pubkey) -> RpcFilterType {
+    pubkey_at(BADGE_ISSUER, issuer)
+}
+
+/// Includes revoked and expired badges.
+pub fn badges_by_tier(tier: VerificationTier) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(BADGE_TIER, &[tier as u8]))
+}
+
+pub fn auditors_in_registry(registry: &Pubkey) -> RpcFilterType {
+    pubkey_at(AUDITOR_REGISTRY, registry)
+}
+
+/// The `AuditorAccount`s of `authority`, one for each registry it audits in.
+pub fn auditor_memberships(authority: &Pubkey) -> RpcFilterType {
+    pubkey_at(AUDITOR_AUTHORITY, authority)
+}
+
+pub fn skills_in_registry(registry: &Pubkey) -> RpcFilterType {
+    pubkey_at(SKILL_REGISTRY, registry)
+}
+
+pub fn skills_by_authority(authority: &Pubkey) -> RpcFilterType {
+    pubkey_at(SKILL_AUTHORITY, authority)
+}
+
+pub fn patterns_in_registry(registry: &Pubkey) -> RpcFilterType {
+    pubkey_at(PATTERN_REGISTRY, registry)
+}
+
+pub fn patterns_by_submitter(submitter: &Pubkey) -> RpcFilterType {
+    pubkey_at(PATTERN_SUBMITTER, submitter)
+}
+
+/// Accounts still laid out as `L`, from before layouts were versioned.
+/// Combine with the discriminator filter of `L::Current`.
+pub fn unversioned<L: Legacy>() -> RpcFilterType {
+    RpcFilterType::DataSize(L::SPACE as u64)
+}
+
+/// Restricts a query to accounts of type `T`, then applies `filters`.
+pub fn program_accounts_config<T: Discriminator>(
+    filters: Vec<RpcFilterType>,
+    commitment: CommitmentConfig,
+) -> RpcProgramAccountsConfig {
+    let mut all = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &T::DISCRIMINATOR))];
+    all.extend(filters);
+    RpcProgramAccountsConfig {
+        filters: Some(all),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(commitment),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    }
+}
@@ -0,0 +1,865 @@
+//! Instruction builders. Each takes the signer keys and the arguments the
+//! program expects, and derives every PDA the instruction touches.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use parity::context_engine::VULNERABILITY_RULES;
+use parity::findings::{ExclusionProof, Finding, MerkleProof};
+use parity::instructions::commitment::analysis_commitment;
+use parity::skills::BUILTIN_SKILLS;
+use parity::state::{
+    AnalysisAttestation, AnalysisFindingsCount, AnalysisSubmission, Framework, PatternType, ProgramDetails,
+    ProgramRegistration, RegistryConfig, Severity, SkillRegistration, SkillType, SourceRevision, VerificationTier,
+};
+use parity::{accounts, instruction};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{system_program, sysvar};
+
+use crate::pda;
+
+fn build(program_id: &Pubkey, accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+/// The result an auditor submits, reveals or updates.
+#[derive(Clone)]
+pub struct AnalysisResult {
+    pub score: u8,
+    pub findings_hash: [u8; 32],
+    pub skills_used: Vec<String>,
+    pub findings_count: AnalysisFindingsCount,
+    pub score_override: Option<[u8; 32]>,
+    /// The commit and toolchain that were analyzed.
+    pub source: SourceRevision,
+}
+
+impl AnalysisResult {
+    /// The commitment [`commit_analysis`] seals this result under, to be
+    /// opened by [`reveal_analysis`] with the same `salt`.
+    pub fn commitment(&self, salt: &[u8; 32]) -> [u8; 32] {
+        analysis_commitment(
+            self.score,
+            &self.findings_hash,
+            &self.skills_used,
+            &self.findings_count,
+            &self.score_override,
+            &self.source,
+            salt,
+        )
+    }
+
+    /// The attestation an auditor signs to have this result submitted by
+    /// someone else, see [`crate::attestation`].
+    pub fn attestation(self, registry: &Pubkey, program_hash: [u8; 32], nonce: u64, expires_at: i64) -> AnalysisAttestation {
+        AnalysisAttestation {
+            registry: *registry,
+            program_hash,
+            score: self.score,
+            findings_hash: self.findings_hash,
+            skills_used: self.skills_used,
+            findings_count: self.findings_count,
+            score_override: self.score_override,
+            source: self.source,
+            nonce,
+            expires_at,
+        }
+    }
+
+    /// This result as a [`submit_analyses`] item for `program_hash`.
+    pub fn submission(self, program_hash: [u8; 32]) -> AnalysisSubmission {
+        AnalysisSubmission {
+            program_hash,
+            score: self.score,
+            findings_hash: self.findings_hash,
+            skills_used: self.skills_used,
+            findings_count: self.findings_count,
+            score_override: self.score_override,
+            source: self.source,
+        }
+    }
+}
+
+/// Creates the registry under `namespace`, see [`pda::registry`].
+pub fn initialize_registry(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    namespace: impl Into<String>,
+    config: RegistryConfig,
+) -> Instruction {
+    let namespace = namespace.into();
+    build(
+        program_id,
+        accounts::InitializeRegistry {
+            authority: *authority,
+            registry: pda::registry(program_id, &namespace).0,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeRegistry { namespace, config },
+    )
+}
+
+pub fn update_registry_config(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    authority: &Pubkey,
+    new_config: RegistryConfig,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::UpdateRegistryConfig {
+            authority: *authority,
+            registry: *registry,
+        },
+        instruction::UpdateRegistryConfig { new_config },
+    )
+}
+
+/// `authority` is the upgrade authority of `program`, or the registry
+/// authority registering on the team's behalf.
+#[allow(clippy::too_many_arguments)]
+pub fn register_program(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    owner: &Pubkey,
+    authority: &Pubkey,
+    program: &Pubkey,
+    program_hash: [u8; 32],
+    framework: Framework,
+    metadata_uri: impl Into<String>,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::RegisterProgram {
+            owner: *owner,
+            registry: *registry,
+            program_entry: pda::program_entry(program_id, registry, &program_hash).0,
+            authority: *authority,
+            program: *program,
+            program_data: pda::program_data(program),
+            system_program: system_program::ID,
+        },
+        instruction::RegisterProgram {
+            program_hash,
+            framework,
+            metadata_uri: metadata_uri.into(),
+        },
+    )
+}
+
+/// Registers each `(program, registration)` pair in a single instruction,
+/// with `authority` proving control of every program. Entries that already
+/// exist are skipped; [`crate::batch::skipped`] lists them.
+pub fn register_programs(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    owner: &Pubkey,
+    authority: &Pubkey,
+    items: Vec<(Pubkey, ProgramRegistration)>,
+) -> Instruction {
+    let mut remaining = Vec::with_capacity(3 * items.len());
+    for (program, item) in &items {
+        remaining.push(AccountMeta::new(pda::program_entry(program_id, registry, &item.program_hash).0, false));
+        remaining.push(AccountMeta::new_readonly(*program, false));
+        remaining.push(AccountMeta::new_readonly(pda::program_data(program), false));
+    }
+    let mut ix = build(
+        program_id,
+        accounts::RegisterPrograms {
+            owner: *owner,
+            registry: *registry,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterPrograms {
+            items: items.into_iter().map(|(_, item)| item).collect(),
+        },
+    );
+    ix.accounts.extend(remaining);
+    ix
+}
+
+pub fn reassign_program_owner(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    authority: &Pubkey,
+    program_hash: &[u8; 32],
+    new_owner: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::ReassignProgramOwner {
+            authority: *authority,
+            registry: *registry,
+            program_entry: pda::program_entry(program_id, registry, program_hash).0,
+        },
+        instruction::ReassignProgramOwner { new_owner: *new_owner },
+    )
+}
+
+pub fn update_program_metadata(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    owner: &Pubkey,
+    program_hash: &[u8; 32],
+    framework: Framework,
+    metadata_uri: impl Into<String>,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::UpdateProgramEntry {
+            owner: *owner,
+            program_entry: pda::program_entry(program_id, registry, program_hash).0,
+        },
+        instruction::UpdateProgramMetadata { framework, metadata_uri: metadata_uri.into() },
+    )
+}
+
+/// Proposes `new_owner`, or withdraws the pending proposal with `None`.
+pub fn transfer_program_ownership(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    owner: &Pubkey,
+    program_hash: &[u8; 32],
+    new_owner: Option<Pubkey>,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::UpdateProgramEntry {
+            owner: *owner,
+            program_entry: pda::program_entry(program_id, registry, program_hash).0,
+        },
+        instruction::TransferProgramOwnership { new_owner },
+    )
+}
+
+pub fn accept_program_ownership(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    new_owner: &Pubkey,
+    program_hash: &[u8; 32],
+) -> Instruction {
+    build(
+        program_id,
+        accounts::AcceptProgramOwnership {
+            new_owner: *new_owner,
+            program_entry: pda::program_entry(program_id, registry, program_hash).0,
+        },
+        instruction::AcceptProgramOwnership {},
+    )
+}
+
+/// Checks the bytecode deployed at `program` against the entry registered
+/// under `program_hash`. Large programs need a raised compute unit limit.
+pub fn verify_program_hash(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    program_hash: &[u8; 32],
+    program: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::VerifyProgramHash {
+            program_entry: pda::program_entry(program_id, registry, program_hash).0,
+            program_data: pda::program_data(program),
+        },
+        instruction::VerifyProgramHash {},
+    )
+}
+
+/// Records `program` as the deployment behind a migrated entry. `authority`
+/// is the program's upgrade authority, or the registry authority if it has
+/// none.
+pub fn bind_program_id(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    owner: &Pubkey,
+    authority: &Pubkey,
+    program_hash: &[u8; 32],
+    program: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::BindProgramId {
+            owner: *owner,
+            registry: *registry,
+            program_entry: pda::program_entry(program_id, registry, program_hash).0,
+            authority: *authority,
+            program: *program,
+            program_data: pda::program_data(program),
+        },
+        instruction::BindProgramId {},
+    )
+}
+
+/// Closes the reports filed by `auditors` and the badge issued by
+/// `badge_issuer`, and the entry once no reports remain. Pass any key as
+/// `badge_issuer` for a program without a badge.
+pub fn deregister_program(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    owner: &Pubkey,
+    program_hash: &[u8; 32],
+    badge_issuer: &Pubkey,
+    auditors: &[Pubkey],
+) -> Instruction {
+    let program_entry = pda::program_entry(program_id, registry, program_hash).0;
+    let mut ix = build(
+        program_id,
+        accounts::DeregisterProgram {
+            owner: *owner,
+            registry: *registry,
+            program_entry,
+            verification_badge: pda::badge(program_id, &program_entry).0,
+            badge_issuer: *badge_issuer,
+            program_metadata: pda::program_metadata(program_id, &program_entry).0,
+        },
+        instruction::DeregisterProgram {},
+    );
+    for auditor in auditors {
+        ix.accounts.push(AccountMeta::new(pda::analysis(program_id, &program_entry, auditor).0, false));
+        ix.accounts.push(AccountMeta::new(*auditor, false));
+    }
+    ix
+}
+
+/// Creates the program's metadata account, or replaces its contents.
+pub fn set_program_metadata(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    owner: &Pubkey,
+    program_hash: &[u8; 32],
+    details: ProgramDetails,
+) -> Instruction {
+    let program_entry = pda::program_entry(program_id, registry, program_hash).0;
+    build(
+        program_id,
+        accounts::SetProgramMetadata {
+            owner: *owner,
+            program_entry,
+            program_metadata: pda::program_metadata(program_id, &program_entry).0,
+            system_program: system_program::ID,
+        },
+        instruction::SetProgramMetadata { details },
+    )
+}
+
+pub fn submit_analysis(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    auditor: &Pubkey,
+    program_hash: &[u8; 32],
+    result: AnalysisResult,
+) -> Instruction {
+    let program_entry = pda::program_entry(program_id, registry, program_hash).0;
+    build(
+        program_id,
+        accounts::SubmitAnalysis {
+            auditor: *auditor,
+            auditor_account: pda::auditor(program_id, registry, auditor).0,
+            registry: *registry,
+            program_entry,
+            analysis_report: pda::analysis(program_id, &program_entry, auditor).0,
+            system_program: system_program::ID,
+        },
+        instruction::SubmitAnalysis {
+            score: result.score,
+            findings_hash: result.findings_hash,
+            skills_used: result.skills_used,
+            findings_count: result.findings_count,
+            score_override: result.score_override,
+            source: result.source,
+        },
+    )
+}
+
+/// Submits one report per item in a single instruction. Reports that
+/// already exist are skipped, and listed by [`crate::batch::skipped`]; see
+/// [`crate::batch`] to size batches.
+pub fn submit_analyses(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    auditor: &Pubkey,
+    items: Vec<AnalysisSubmission>,
+) -> Instruction {
+    let mut remaining = Vec::with_capacity(2 * items.len());
+    for item in &items {
+        let program_entry = pda::program_entry(program_id, registry, &item.program_hash).0;
+        remaining.push(AccountMeta::new(program_entry, false));
+        remaining.push(AccountMeta::new(pda::analysis(program_id, &program_entry, auditor).0, false));
+    }
+    let mut ix = build(
+        program_id,
+        accounts::SubmitAnalyses {
+            auditor: *auditor,
+            auditor_account: pda::auditor(program_id, registry, auditor).0,
+            registry: *registry,
+            system_program: system_program::ID,
+        },
+        instruction::SubmitAnalyses { items },
+    );
+    ix.accounts.extend(remaining);
+    ix
+}
+
+/// Submits an attestation `auditor` signed off-chain; `payer` pays for the
+/// report. Must directly follow the signature check built by
+/// [`crate::attestation::ed25519_instruction`]. The program rejects an
+/// attestation signed for another registry than `registry`.
+pub fn submit_signed_analysis(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    payer: &Pubkey,
+    auditor: &Pubkey,
+    attestation: AnalysisAttestation,
+) -> Instruction {
+    let program_entry = pda::program_entry(program_id, registry, &attestation.program_hash).0;
+    build(
+        program_id,
+        accounts::SubmitSignedAnalysis {
+            payer: *payer,
+            auditor_account: pda::auditor(program_id, registry, auditor).0,
+            registry: *registry,
+            program_entry,
+            analysis_report: pda::analysis(program_id, &program_entry, auditor).0,
+            instructions: sysvar::instructions::ID,
+            system_program: system_program::ID,
+        },
+        instruction::SubmitSignedAnalysis { attestation },
+    )
+}
+
+/// Replaces the auditor's existing report; `skills_used` is not updated.
+pub fn update_analysis(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    auditor: &Pubkey,
+    program_hash: &[u8; 32],
+    result: AnalysisResult,
+) -> Instruction {
+    let program_entry = pda::program_entry(program_id, registry, program_hash).0;
+    build(
+        program_id,
+        accounts::UpdateAnalysis {
+            auditor: *auditor,
+            registry: *registry,
+            program_entry,
+            analysis_report: pda::analysis(program_id, &program_entry, auditor).0,
+        },
+        instruction::UpdateAnalysis {
+            new_score: result.score,
+            new_findings_hash: result.findings_hash,
+            new_findings_count: result.findings_count,
+            score_override: result.score_override,
+            source: result.source,
+        },
+    )
+}
+
+pub fn verify_finding_inclusion(
+    program_id: &Pubkey,
+    analysis_report: &Pubkey,
+    finding: Finding,
+    proof: MerkleProof,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::VerifyFinding { analysis_report: *analysis_report },
+        instruction::VerifyFindingInclusion { finding, proof },
+    )
+}
+
+pub fn verify_finding_exclusion(
+    program_id: &Pubkey,
+    analysis_report: &Pubkey,
+    finding: Finding,
+    proof: ExclusionProof,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::VerifyFinding { analysis_report: *analysis_report },
+        instruction::VerifyFindingExclusion { finding, proof },
+    )
+}
+
+/// `commitment` is [`AnalysisResult::commitment`] of the result to reveal later.
+pub fn commit_analysis(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    auditor: &Pubkey,
+    program_hash: &[u8; 32],
+    commitment: [u8; 32],
+) -> Instruction {
+    let program_entry = pda::program_entry(program_id, registry, program_hash).0;
+    build(
+        program_id,
+        accounts::CommitAnalysis {
+            auditor: *auditor,
+            auditor_account: pda::auditor(program_id, registry, auditor).0,
+            registry: *registry,
+            program_entry,
+            analysis_commitment: pda::commitment(program_id, &program_entry, auditor).0,
+            system_program: system_program::ID,
+        },
+        instruction::CommitAnalysis { commitment },
+    )
+}
+
+pub fn reveal_analysis(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    auditor: &Pubkey,
+    program_hash: &[u8; 32],
+    salt: [u8; 32],
+    result: AnalysisResult,
+) -> Instruction {
+    let program_entry = pda::program_entry(program_id, registry, program_hash).0;
+    build(
+        program_id,
+        accounts::RevealAnalysis {
+            auditor: *auditor,
+            auditor_account: pda::auditor(program_id, registry, auditor).0,
+            registry: *registry,
+            program_entry,
+            analysis_commitment: pda::commitment(program_id, &program_entry, auditor).0,
+            analysis_report: pda::analysis(program_id, &program_entry, auditor).0,
+            system_program: system_program::ID,
+        },
+        instruction::RevealAnalysis {
+            score: result.score,
+            findings_hash: result.findings_hash,
+            salt,
+            skills_used: result.skills_used,
+            findings_count: result.findings_count,
+            score_override: result.score_override,
+            source: result.source,
+        },
+    )
+}
+
+/// Closes an expired commitment; any account may sign as `caller`.
+pub fn expire_commitment(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    program_entry: &Pubkey,
+    auditor: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::ExpireCommitment {
+            caller: *caller,
+            auditor: *auditor,
+            program_entry: *program_entry,
+            analysis_commitment: pda::commitment(program_id, program_entry, auditor).0,
+        },
+        instruction::ExpireCommitment {},
+    )
+}
+
+pub fn register_skill(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    authority: &Pubkey,
+    name: impl Into<String>,
+    version: impl Into<String>,
+    description: impl Into<String>,
+    skill_type: SkillType,
+) -> Instruction {
+    let name = name.into();
+    build(
+        program_id,
+        accounts::RegisterSkill {
+            authority: *authority,
+            registry: *registry,
+            skill_entry: pda::skill(program_id, registry, authority, &name).0,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterSkill {
+            name,
+            version: version.into(),
+            description: description.into(),
+            skill_type,
+        },
+    )
+}
+
+/// Registers every skill in `authority`'s namespace in a single instruction.
+/// Skills that already exist are skipped; [`crate::batch::skipped`] lists
+/// them.
+pub fn register_skills(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    authority: &Pubkey,
+    items: Vec<SkillRegistration>,
+) -> Instruction {
+    let remaining: Vec<AccountMeta> = items
+        .iter()
+        .map(|item| AccountMeta::new(pda::skill(program_id, registry, authority, &item.name).0, false))
+        .collect();
+    let mut ix = build(
+        program_id,
+        accounts::RegisterSkills {
+            authority: *authority,
+            registry: *registry,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterSkills { items },
+    );
+    ix.accounts.extend(remaining);
+    ix
+}
+
+/// `namespace` is the submitter for community skills and the registry for
+/// built-ins.
+pub fn update_skill(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    authority: &Pubkey,
+    namespace: &Pubkey,
+    name: &str,
+    new_version: impl Into<String>,
+    new_description: impl Into<String>,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::UpdateSkill {
+            authority: *authority,
+            skill_entry: pda::skill(program_id, registry, namespace, name).0,
+        },
+        instruction::UpdateSkill {
+            new_version: new_version.into(),
+            new_description: new_description.into(),
+        },
+    )
+}
+
+pub fn deprecate_skill(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    authority: &Pubkey,
+    namespace: &Pubkey,
+    name: &str,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::DeprecateSkill {
+            authority: *authority,
+            skill_entry: pda::skill(program_id, registry, namespace, name).0,
+        },
+        instruction::DeprecateSkill {},
+    )
+}
+
+/// Creates every built-in skill and vulnerability rule that does not exist
+/// yet, owned by the registry `authority`.
+pub fn bootstrap_builtins(program_id: &Pubkey, registry: &Pubkey, authority: &Pubkey) -> Instruction {
+    let mut ix = build(
+        program_id,
+        accounts::BootstrapBuiltins {
+            authority: *authority,
+            registry: *registry,
+            system_program: system_program::ID,
+        },
+        instruction::BootstrapBuiltins {},
+    );
+    let skills = BUILTIN_SKILLS.iter().map(|skill| pda::builtin_skill(program_id, registry, skill.name).0);
+    let patterns = VULNERABILITY_RULES.iter().map(|rule| pda::builtin_pattern(program_id, registry, rule.id).0);
+    ix.accounts.extend(skills.chain(patterns).map(|address| AccountMeta::new(address, false)));
+    ix
+}
+
+pub fn register_auditor(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    authority: &Pubkey,
+    name: impl Into<String>,
+    credentials_uri: impl Into<String>,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::RegisterAuditor {
+            authority: *authority,
+            registry: *registry,
+            auditor_account: pda::auditor(program_id, registry, authority).0,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterAuditor {
+            name: name.into(),
+            credentials_uri: credentials_uri.into(),
+        },
+    )
+}
+
+/// Activates or deactivates the auditor signing with `auditor`; registry
+/// authority only.
+pub fn update_auditor_status(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    authority: &Pubkey,
+    auditor: &Pubkey,
+    is_active: bool,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::UpdateAuditorStatus {
+            authority: *authority,
+            registry: *registry,
+            auditor_account: pda::auditor(program_id, registry, auditor).0,
+        },
+        instruction::UpdateAuditorStatus { is_active },
+    )
+}
+
+pub fn create_verification_badge(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    authority: &Pubkey,
+    program_hash: &[u8; 32],
+    tier: VerificationTier,
+    expires_at: i64,
+) -> Instruction {
+    let program_entry = pda::program_entry(program_id, registry, program_hash).0;
+    build(
+        program_id,
+        accounts::CreateVerificationBadge {
+            authority: *authority,
+            registry: *registry,
+            program_entry,
+            verification_badge: pda::badge(program_id, &program_entry).0,
+            system_program: system_program::ID,
+        },
+        instruction::CreateVerificationBadge { tier, expires_at },
+    )
+}
+
+pub fn revoke_verification_badge(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    authority: &Pubkey,
+    program_hash: &[u8; 32],
+) -> Instruction {
+    let program_entry = pda::program_entry(program_id, registry, program_hash).0;
+    build(
+        program_id,
+        accounts::RevokeVerificationBadge {
+            authority: *authority,
+            registry: *registry,
+            verification_badge: pda::badge(program_id, &program_entry).0,
+        },
+        instruction::RevokeVerificationBadge {},
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn submit_context_pattern(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    submitter: &Pubkey,
+    pattern_id: impl Into<String>,
+    severity: Severity,
+    pattern_type: PatternType,
+    description: impl Into<String>,
+    detection_rule: impl Into<String>,
+) -> Instruction {
+    let pattern_id = pattern_id.into();
+    build(
+        program_id,
+        accounts::SubmitContextPattern {
+            submitter: *submitter,
+            registry: *registry,
+            context_pattern: pda::pattern(program_id, registry, submitter, &pattern_id).0,
+            system_program: system_program::ID,
+        },
+        instruction::SubmitContextPattern {
+            pattern_id,
+            severity,
+            pattern_type,
+            description: description.into(),
+            detection_rule: detection_rule.into(),
+        },
+    )
+}
+
+/// Upgrades `account`, of any parity account type, to the current layout;
+/// `payer` covers the rent for any space it grows by.
+pub fn migrate_account(program_id: &Pubkey, payer: &Pubkey, account: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        accounts::MigrateAccount {
+            payer: *payer,
+            account: *account,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateAccount {},
+    )
+}
+
+/// Batched [`migrate_account`]; accounts already current are skipped.
+pub fn migrate_accounts(program_id: &Pubkey, payer: &Pubkey, accounts: &[Pubkey]) -> Instruction {
+    let mut ix = build(
+        program_id,
+        accounts::MigrateAccounts {
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateAccounts {},
+    );
+    ix.accounts.extend(accounts.iter().map(|account| AccountMeta::new(*account, false)));
+    ix
+}
+
+/// Moves `legacy`, an account from before versioning, to `destination`, its
+/// address under the current seeds in the legacy registry. A report or badge
+/// also takes the migrated `program_entry` it belongs to.
+pub fn migrate_legacy_account(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    legacy: &Pubkey,
+    destination: &Pubkey,
+    program_entry: Option<&Pubkey>,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::MigrateLegacyAccount {
+            payer: *payer,
+            registry: pda::registry(program_id, pda::LEGACY_NAMESPACE).0,
+            legacy: *legacy,
+            destination: *destination,
+            program_entry: program_entry.copied(),
+            system_program: system_program::ID,
+        },
+        instruction::MigrateLegacyAccount {},
+    )
+}
+
+/// One [`migrate_legacy_accounts`] item, the accounts of
+/// [`migrate_legacy_account`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LegacyMigration {
+    pub legacy: Pubkey,
+    pub destination: Pubkey,
+    pub program_entry: Option<Pubkey>,
+}
+
+/// Batched [`migrate_legacy_account`]; items whose legacy account is already
+/// closed are skipped.
+pub fn migrate_legacy_accounts(program_id: &Pubkey, payer: &Pubkey, items: &[LegacyMigration]) -> Instruction {
+    let mut ix = build(
+        program_id,
+        accounts::MigrateLegacyAccounts {
+            payer: *payer,
+            registry: pda::registry(program_id, pda::LEGACY_NAMESPACE).0,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateLegacyAccounts {},
+    );
+    for item in items {
+        ix.accounts.push(AccountMeta::new(item.legacy, false));
+        ix.accounts.push(AccountMeta::new(item.destination, false));
+        ix.accounts.push(AccountMeta::new_readonly(item.program_entry.unwrap_or(*program_id), false));
+    }
+    ix
+}
@@ -0,0 +1,34 @@
+//! Rust client for the Parity on-chain registry: PDA derivation, instruction
+//! builders, account fetchers and typed program errors.
+//!
+//! [`ParityClient`] wraps a blocking `RpcClient`; [`nonblocking::ParityClient`]
+//! has the same interface over the async one.
+
+pub mod attestation;
+pub mod batch;
+pub mod error;
+pub mod filters;
+pub mod instructions;
+pub mod nonblocking;
+pub mod pda;
+pub mod program_hash;
+
+mod client;
+
+use parity::schema::v0::Legacy;
+use parity::schema::{self, Versioned};
+use solana_sdk::pubkey::Pubkey;
+
+pub use client::ParityClient;
+pub use error::{ClientError, Result};
+pub use parity::ID;
+
+/// Accounts from before versioning fail with `UnversionedAccount`; read
+/// them with [`decode_legacy`].
+pub(crate) fn decode<T: Versioned>(address: &Pubkey, data: &[u8]) -> Result<T> {
+    schema::deserialize(data).map_err(|err| ClientError::AccountDecode(*address, Box::new(err)))
+}
+
+pub(crate) fn decode_legacy<L: Legacy>(address: &Pubkey, data: &[u8]) -> Result<L> {
+    L::try_from_account_data(data).map_err(|err| ClientError::AccountDecode(*address, Box::new(err)))
+}
@@ -0,0 +1,205 @@
+//! Async client, mirroring [`crate::ParityClient`].
+
+use parity::schema::v0::Legacy;
+use parity::schema::Versioned;
+use parity::state::{
+    AnalysisCommitment, AnalysisReport, AuditorAccount, ContextPattern, ProgramEntry, ProgramMetadata, Registry,
+    SkillEntry, VerificationBadge, VerificationTier,
+};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_client::rpc_filter::RpcFilterType;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::error::{from_send_error, ClientError, Result};
+use crate::{decode, decode_legacy, filters, pda, program_hash};
+
+pub struct ParityClient {
+    rpc: RpcClient,
+    program_id: Pubkey,
+    registry: Pubkey,
+}
+
+impl ParityClient {
+    pub fn new(rpc: RpcClient) -> Self {
+        Self::with_program_id(rpc, parity::ID)
+    }
+
+    pub fn with_program_id(rpc: RpcClient, program_id: Pubkey) -> Self {
+        let registry = pda::registry(&program_id, pda::DEFAULT_NAMESPACE).0;
+        ParityClient { rpc, program_id, registry }
+    }
+
+    /// Targets the registry created under `namespace` rather than the
+    /// default one.
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.registry = pda::registry(&self.program_id, namespace).0;
+        self
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    /// The address of the registry this client reads.
+    pub fn registry_address(&self) -> Pubkey {
+        self.registry
+    }
+
+    /// Signs and sends `instructions` in one transaction; the first signer
+    /// pays the fee.
+    pub async fn send(&self, instructions: &[Instruction], signers: &[&dyn Signer]) -> Result<Signature> {
+        let payer = signers.first().map(|s| s.pubkey());
+        let blockhash = self.rpc.get_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(instructions, payer.as_ref(), signers, blockhash);
+        self.rpc
+            .send_and_confirm_transaction(&transaction)
+            .await
+            .map_err(|err| from_send_error(err, instructions, &self.program_id))
+    }
+
+    /// The log messages of a confirmed transaction, e.g. to list the items a
+    /// batch skipped with [`crate::batch::skipped`].
+    pub async fn transaction_logs(&self, signature: &Signature) -> Result<Vec<String>> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(self.rpc.commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+        let transaction = self.rpc.get_transaction_with_config(signature, config).await?;
+        let logs = transaction.transaction.meta.and_then(|meta| Option::from(meta.log_messages));
+        Ok(logs.unwrap_or_default())
+    }
+
+    /// Fetches and decodes an account; `None` if it does not exist.
+    pub async fn try_account<T: Versioned>(&self, address: &Pubkey) -> Result<Option<T>> {
+        let response = self.rpc.get_account_with_commitment(address, self.rpc.commitment()).await?;
+        response.value.map(|account| decode(address, &account.data)).transpose()
+    }
+
+    pub async fn account<T: Versioned>(&self, address: &Pubkey) -> Result<T> {
+        self.try_account(address).await?.ok_or(ClientError::AccountNotFound(*address))
+    }
+
+    /// Every account of type `T` matching `filters`, see [`crate::filters`].
+    pub async fn accounts<T: Versioned>(&self, filters: Vec<RpcFilterType>) -> Result<Vec<(Pubkey, T)>> {
+        let config = filters::program_accounts_config::<T>(filters, self.rpc.commitment());
+        self.rpc
+            .get_program_accounts_with_config(&self.program_id, config)
+            .await?
+            .into_iter()
+            .map(|(address, account)| Ok((address, decode(&address, &account.data)?)))
+            .collect()
+    }
+
+    pub async fn registry(&self) -> Result<Registry> {
+        self.account(&self.registry).await
+    }
+
+    pub async fn program_entry(&self, program_hash: &[u8; 32]) -> Result<ProgramEntry> {
+        self.account(&pda::program_entry(&self.program_id, &self.registry, program_hash).0).await
+    }
+
+    /// The canonical hash of the bytecode currently deployed at `program`,
+    /// read from its `ProgramData` account.
+    pub async fn deployed_program_hash(&self, program: &Pubkey) -> Result<[u8; 32]> {
+        let address = pda::program_data(program);
+        let response = self.rpc.get_account_with_commitment(&address, self.rpc.commitment()).await?;
+        let account = response.value.ok_or(ClientError::AccountNotFound(address))?;
+        program_hash::program_data_hash(&account.data).ok_or_else(|| {
+            ClientError::AccountDecode(address, Box::new(anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into()))
+        })
+    }
+
+    pub async fn program_metadata(&self, program_hash: &[u8; 32]) -> Result<ProgramMetadata> {
+        let program_entry = pda::program_entry(&self.program_id, &self.registry, program_hash).0;
+        self.account(&pda::program_metadata(&self.program_id, &program_entry).0).await
+    }
+
+    pub async fn analysis_report(&self, program_hash: &[u8; 32], auditor: &Pubkey) -> Result<AnalysisReport> {
+        let program_entry = pda::program_entry(&self.program_id, &self.registry, program_hash).0;
+        self.account(&pda::analysis(&self.program_id, &program_entry, auditor).0).await
+    }
+
+    pub async fn auditor(&self, authority: &Pubkey) -> Result<AuditorAccount> {
+        self.account(&pda::auditor(&self.program_id, &self.registry, authority).0).await
+    }
+
+    pub async fn badge(&self, program_hash: &[u8; 32]) -> Result<VerificationBadge> {
+        let program_entry = pda::program_entry(&self.program_id, &self.registry, program_hash).0;
+        self.account(&pda::badge(&self.program_id, &program_entry).0).await
+    }
+
+    pub async fn skill(&self, namespace: &Pubkey, name: &str) -> Result<SkillEntry> {
+        self.account(&pda::skill(&self.program_id, &self.registry, namespace, name).0).await
+    }
+
+    pub async fn pattern(&self, namespace: &Pubkey, pattern_id: &str) -> Result<ContextPattern> {
+        self.account(&pda::pattern(&self.program_id, &self.registry, namespace, pattern_id).0).await
+    }
+
+    /// Every registry on the deployment.
+    pub async fn registries(&self) -> Result<Vec<(Pubkey, Registry)>> {
+        self.accounts(Vec::new()).await
+    }
+
+    /// Accounts created before layouts were versioned, still laid out as
+    /// `L` at their old addresses, which need `migrate_legacy_account` (or
+    /// `migrate_account`, for the registry).
+    pub async fn unversioned_accounts<L: Legacy>(&self) -> Result<Vec<(Pubkey, L)>> {
+        let config =
+            filters::program_accounts_config::<L::Current>(vec![filters::unversioned::<L>()], self.rpc.commitment());
+        self.rpc
+            .get_program_accounts_with_config(&self.program_id, config).await?
+            .into_iter()
+            .map(|(address, account)| Ok((address, decode_legacy(&address, &account.data)?)))
+            .collect()
+    }
+
+    /// `authority`'s auditor accounts in every registry it has joined.
+    pub async fn auditor_memberships(&self, authority: &Pubkey) -> Result<Vec<(Pubkey, AuditorAccount)>> {
+        self.accounts(vec![filters::auditor_memberships(authority)]).await
+    }
+
+    pub async fn programs_by_owner(&self, owner: &Pubkey) -> Result<Vec<(Pubkey, ProgramEntry)>> {
+        let filters = vec![filters::programs_in_registry(&self.registry), filters::programs_by_owner(owner)];
+        self.accounts(filters).await
+    }
+
+    pub async fn programs_by_program_id(&self, program: &Pubkey) -> Result<Vec<(Pubkey, ProgramEntry)>> {
+        let filters = vec![filters::programs_in_registry(&self.registry), filters::programs_by_program_id(program)];
+        self.accounts(filters).await
+    }
+
+    pub async fn reports_for_program(&self, program_hash: &[u8; 32]) -> Result<Vec<(Pubkey, AnalysisReport)>> {
+        let program_entry = pda::program_entry(&self.program_id, &self.registry, program_hash).0;
+        self.accounts(vec![filters::reports_for_program(&program_entry)]).await
+    }
+
+    /// Spans every registry on the deployment.
+    pub async fn reports_by_auditor(&self, auditor: &Pubkey) -> Result<Vec<(Pubkey, AnalysisReport)>> {
+        self.accounts(vec![filters::reports_by_auditor(auditor)]).await
+    }
+
+    pub async fn commitments_for_program(&self, program_hash: &[u8; 32]) -> Result<Vec<(Pubkey, AnalysisCommitment)>> {
+        let program_entry = pda::program_entry(&self.program_id, &self.registry, program_hash).0;
+        self.accounts(vec![filters::commitments_for_program(&program_entry)]).await
+    }
+
+    /// Spans every registry on the deployment.
+    pub async fn badges_by_tier(&self, tier: VerificationTier) -> Result<Vec<(Pubkey, VerificationBadge)>> {
+        self.accounts(vec![filters::badges_by_tier(tier)]).await
+    }
+
+    pub async fn patterns(&self) -> Result<Vec<(Pubkey, ContextPattern)>> {
+        self.accounts(vec![filters::patterns_in_registry(&self.registry)]).await
+    }
+}
@@ -0,0 +1,82 @@
+//! Program-derived addresses, matching the `seeds` constraints on-chain.
+
+use solana_sdk::bpf_loader_upgradeable;
+use solana_sdk::pubkey::Pubkey;
+
+pub const REGISTRY_SEED: &[u8] = b"registry";
+pub const PROGRAM_SEED: &[u8] = b"program";
+pub const ANALYSIS_SEED: &[u8] = b"analysis";
+pub const COMMITMENT_SEED: &[u8] = b"commitment";
+pub const AUDITOR_SEED: &[u8] = b"auditor";
+pub const BADGE_SEED: &[u8] = b"badge";
+pub const SKILL_SEED: &[u8] = b"skill";
+pub const PATTERN_SEED: &[u8] = b"pattern";
+pub const METADATA_SEED: &[u8] = b"metadata";
+
+/// The namespace clients use when none is given.
+pub const DEFAULT_NAMESPACE: &str = "public";
+/// The namespace of the registry from before namespaces. Its old seeds,
+/// `[b"registry"]`, derive the same address, and no new registry can take it.
+pub const LEGACY_NAMESPACE: &str = "";
+
+/// The registry created under `namespace`.
+pub fn registry(program_id: &Pubkey, namespace: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REGISTRY_SEED, namespace.as_bytes()], program_id)
+}
+
+pub fn program_entry(program_id: &Pubkey, registry: &Pubkey, program_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROGRAM_SEED, registry.as_ref(), program_hash], program_id)
+}
+
+/// The `ProgramData` account of an upgradeable `program`, holding its upgrade
+/// authority and bytecode.
+pub fn program_data(program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+pub fn program_metadata(program_id: &Pubkey, program_entry: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[METADATA_SEED, program_entry.as_ref()], program_id)
+}
+
+pub fn analysis(program_id: &Pubkey, program_entry: &Pubkey, auditor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ANALYSIS_SEED, program_entry.as_ref(), auditor.as_ref()], program_id)
+}
+
+pub fn commitment(program_id: &Pubkey, program_entry: &Pubkey, auditor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COMMITMENT_SEED, program_entry.as_ref(), auditor.as_ref()], program_id)
+}
+
+/// The `AuditorAccount` of the auditor signing with `authority`, one per
+/// registry it takes part in.
+pub fn auditor(program_id: &Pubkey, registry: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUDITOR_SEED, registry.as_ref(), authority.as_ref()], program_id)
+}
+
+pub fn badge(program_id: &Pubkey, program_entry: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BADGE_SEED, program_entry.as_ref()], program_id)
+}
+
+/// A skill in `namespace`: the submitter for community skills, the
+/// registry for built-ins.
+pub fn skill(program_id: &Pubkey, registry: &Pubkey, namespace: &Pubkey, name: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SKILL_SEED, registry.as_ref(), namespace.as_ref(), name.as_bytes()], program_id)
+}
+
+/// A context pattern in `namespace`: the submitter for community patterns,
+/// the registry for built-in vulnerability rules.
+pub fn pattern(program_id: &Pubkey, registry: &Pubkey, namespace: &Pubkey, pattern_id: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PATTERN_SEED, registry.as_ref(), namespace.as_ref(), pattern_id.as_bytes()],
+        program_id,
+    )
+}
+
+/// The `SkillEntry` `bootstrap_builtins` creates for a built-in skill.
+pub fn builtin_skill(program_id: &Pubkey, registry: &Pubkey, name: &str) -> (Pubkey, u8) {
+    skill(program_id, registry, registry, name)
+}
+
+/// The `ContextPattern` `bootstrap_builtins` creates for a vulnerability rule.
+pub fn builtin_pattern(program_id: &Pubkey, registry: &Pubkey, pattern_id: &str) -> (Pubkey, u8) {
+    pattern(program_id, registry, registry, pattern_id)
+}
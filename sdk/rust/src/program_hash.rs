@@ -0,0 +1,32 @@
+//! Canonical program hashes, as stored in `ProgramEntry.program_hash`. See
+//! [`parity::program_hash`] for the definition.
+
+use std::io;
+use std::path::Path;
+
+pub use parity::program_hash::{executable_bytes, program_data_hash, program_hash, PROGRAM_DATA_METADATA_LEN};
+
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+
+/// `UpgradeableLoaderState::ProgramData`'s bincode tag.
+const PROGRAM_DATA_TAG: &[u8] = &[3, 0, 0, 0];
+
+/// Hashes a program's `.so`, or a raw dump of its `ProgramData` account as
+/// written by `solana account <PROGRAM_DATA> --output-file`.
+pub fn from_file(path: &Path) -> io::Result<[u8; 32]> {
+    let bytes = std::fs::read(path)?;
+    from_bytes(&bytes).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("{} is neither an ELF nor a ProgramData account", path.display()))
+    })
+}
+
+/// Like [`from_file`], for bytes already in memory.
+pub fn from_bytes(bytes: &[u8]) -> Option<[u8; 32]> {
+    if bytes.starts_with(ELF_MAGIC) {
+        Some(program_hash(bytes))
+    } else if bytes.starts_with(PROGRAM_DATA_TAG) {
+        program_data_hash(bytes)
+    } else {
+        None
+    }
+}
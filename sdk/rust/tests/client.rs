@@ -0,0 +1,328 @@
+use anchor_lang::{AccountSerialize, Discriminator};
+use parity::context_engine::VULNERABILITY_RULES;
+use parity::errors::ParityError;
+use parity::schema::v0::{self, Legacy};
+use parity::schema::CURRENT_SCHEMA_VERSION;
+use parity::skills::BUILTIN_SKILLS;
+use parity::state::{
+    AnalysisAttestation, AnalysisFindingsCount, AnalysisReport, AuditorAccount, Framework, ProgramEntry, ProgramRegistration,
+    SkillRegistration, SkillType, SourceRevision, VerificationBadge, VerificationTier, SCHEMA_RESERVED_LEN,
+};
+use parity_client::error::{from_send_error, parity_error, PARITY_ERRORS};
+use parity_client::filters;
+use parity_client::instructions::{self, AnalysisResult, LegacyMigration};
+use parity_client::{attestation, batch, pda, program_hash, ClientError};
+use solana_client::client_error::ClientError as RpcError;
+use solana_client::rpc_filter::RpcFilterType;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use solana_sdk::transaction::TransactionError;
+
+fn serialized<T: AccountSerialize>(account: &T) -> Vec<u8> {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    data
+}
+
+fn result() -> AnalysisResult {
+    AnalysisResult {
+        score: 90,
+        findings_hash: [7; 32],
+        skills_used: vec!["security-audit".to_string()],
+        findings_count: AnalysisFindingsCount { critical: 0, high: 0, medium: 1, info: 2, pass: 0, total: 3 },
+        score_override: None,
+        source: SourceRevision::default(),
+    }
+}
+
+#[test]
+fn builders_derive_the_expected_accounts() {
+    let program_id = parity::ID;
+    let auditor = Pubkey::new_unique();
+    let hash = [3u8; 32];
+    let registry = pda::registry(&program_id, pda::DEFAULT_NAMESPACE).0;
+    let program_entry = pda::program_entry(&program_id, &registry, &hash).0;
+
+    let ix = instructions::submit_analysis(&program_id, &registry, &auditor, &hash, result());
+    assert_eq!(ix.program_id, program_id);
+    assert_eq!(&ix.data[..8], &parity::instruction::SubmitAnalysis::DISCRIMINATOR);
+    let keys: Vec<Pubkey> = ix.accounts.iter().map(|m| m.pubkey).collect();
+    assert_eq!(
+        keys,
+        [
+            auditor,
+            pda::auditor(&program_id, &registry, &auditor).0,
+            registry,
+            program_entry,
+            pda::analysis(&program_id, &program_entry, &auditor).0,
+            solana_sdk::system_program::ID,
+        ]
+    );
+    assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+    assert!(ix.accounts[4].is_writable && !ix.accounts[4].is_signer);
+
+    let ix = instructions::revoke_verification_badge(&program_id, &registry, &auditor, &hash);
+    assert_eq!(ix.accounts[2].pubkey, pda::badge(&program_id, &program_entry).0);
+
+    let deployed = Pubkey::new_unique();
+    let ix = instructions::register_program(&program_id, &registry, &auditor, &auditor, &deployed, hash, Framework::Anchor, "");
+    assert_eq!(ix.accounts[4].pubkey, deployed);
+    assert_eq!(ix.accounts[5].pubkey, Pubkey::find_program_address(&[deployed.as_ref()], &solana_sdk::bpf_loader_upgradeable::ID).0);
+
+    let ix = instructions::update_skill(&program_id, &registry, &auditor, &auditor, "scan", "1.1.0", "");
+    assert_eq!(ix.accounts[1].pubkey, pda::skill(&program_id, &registry, &auditor, "scan").0);
+
+    // Built-ins follow the fixed accounts, skills first, all writable.
+    let ix = instructions::bootstrap_builtins(&program_id, &registry, &auditor);
+    assert_eq!(ix.accounts.len(), 3 + BUILTIN_SKILLS.len() + VULNERABILITY_RULES.len());
+    assert_eq!(ix.accounts[3].pubkey, pda::builtin_skill(&program_id, &registry, BUILTIN_SKILLS[0].name).0);
+    assert_eq!(ix.accounts.last().unwrap().pubkey, pda::builtin_pattern(&program_id, &registry, VULNERABILITY_RULES.last().unwrap().id).0);
+    assert!(ix.accounts[3..].iter().all(|meta| meta.is_writable && !meta.is_signer));
+
+    let ix = instructions::migrate_accounts(&program_id, &auditor, &[program_entry, registry]);
+    assert_eq!(&ix.data[..8], &parity::instruction::MigrateAccounts::DISCRIMINATOR);
+    assert!(ix.accounts[2..].iter().map(|meta| meta.pubkey).eq([program_entry, registry]));
+    assert!(ix.accounts[2..].iter().all(|meta| meta.is_writable && !meta.is_signer));
+
+    // Legacy accounts come in threes, the program ID standing in for a
+    // missing program entry.
+    let (legacy, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let items = [
+        LegacyMigration { legacy, destination, program_entry: None },
+        LegacyMigration { legacy, destination, program_entry: Some(program_entry) },
+    ];
+    let ix = instructions::migrate_legacy_accounts(&program_id, &auditor, &items);
+    assert_eq!(ix.accounts[1].pubkey, pda::registry(&program_id, pda::LEGACY_NAMESPACE).0);
+    assert!(ix.accounts[3..].iter().map(|meta| meta.pubkey).eq([
+        legacy,
+        destination,
+        program_id,
+        legacy,
+        destination,
+        program_entry
+    ]));
+    assert!(ix.accounts[3..].chunks(3).all(|item| item[0].is_writable && item[1].is_writable && !item[2].is_writable));
+
+    // The same key in another registry is another account.
+    let other = pda::registry(&program_id, "other").0;
+    assert_ne!(pda::program_entry(&program_id, &other, &hash).0, program_entry);
+    assert_ne!(pda::auditor(&program_id, &other, &auditor).0, pda::auditor(&program_id, &registry, &auditor).0);
+}
+
+#[test]
+fn filter_offsets_match_the_account_layouts() {
+    let (registry, owner, program) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let entry = ProgramEntry {
+        registry,
+        owner,
+        program_hash: [9; 32],
+        program_id: program,
+        framework: Framework::Anchor,
+        metadata_uri: "ipfs://x".to_string(),
+        registered_at: 0,
+        updated_at: 0,
+        analysis_count: 0,
+        latest_score: 0,
+        is_verified: false,
+        commit_count: 0,
+        open_commitments: 0,
+        expired_commitments: 0,
+        pending_owner: None,
+        is_retired: false,
+        bump: 0,
+        schema_version: CURRENT_SCHEMA_VERSION,
+        round_start: 0,
+        round_revealed_at: None,
+        reserved: [0; SCHEMA_RESERVED_LEN],
+    };
+    let data = serialized(&entry);
+    assert_eq!(&data[filters::PROGRAM_ENTRY_REGISTRY..][..32], registry.as_ref());
+    assert_eq!(&data[filters::PROGRAM_ENTRY_OWNER..][..32], owner.as_ref());
+    assert_eq!(&data[filters::PROGRAM_ENTRY_HASH..][..32], &[9; 32]);
+    assert_eq!(&data[filters::PROGRAM_ENTRY_PROGRAM_ID..][..32], program.as_ref());
+
+    let (program_entry, auditor) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let report = AnalysisReport {
+        program_entry,
+        auditor,
+        score: 0,
+        findings_hash: [0; 32],
+        skills_used: Vec::new(),
+        findings_count: result().findings_count,
+        score_override: None,
+        source: SourceRevision::default(),
+        submitted_at: 0,
+        updated_at: 0,
+        version: 1,
+        bump: 0,
+        schema_version: CURRENT_SCHEMA_VERSION,
+        reserved: [0; SCHEMA_RESERVED_LEN],
+    };
+    let data = serialized(&report);
+    assert_eq!(&data[filters::ANALYSIS_REPORT_PROGRAM_ENTRY..][..32], program_entry.as_ref());
+    assert_eq!(&data[filters::ANALYSIS_REPORT_AUDITOR..][..32], auditor.as_ref());
+
+    let badge = VerificationBadge {
+        program_entry,
+        issuer: auditor,
+        tier: VerificationTier::Gold,
+        score_at_issuance: 90,
+        issued_at: 0,
+        expires_at: 0,
+        is_revoked: false,
+        bump: 0,
+        schema_version: CURRENT_SCHEMA_VERSION,
+        reserved: [0; SCHEMA_RESERVED_LEN],
+    };
+    let data = serialized(&badge);
+    assert_eq!(&data[filters::BADGE_PROGRAM_ENTRY..][..32], program_entry.as_ref());
+    assert_eq!(&data[filters::BADGE_ISSUER..][..32], auditor.as_ref());
+    assert_eq!(data[filters::BADGE_TIER], VerificationTier::Gold as u8);
+
+    let membership = AuditorAccount {
+        registry,
+        authority: auditor,
+        name: "a".to_string(),
+        credentials_uri: String::new(),
+        total_analyses: 0,
+        average_score: 0,
+        is_active: true,
+        registered_at: 0,
+        updated_at: 0,
+        attestation_nonce: 0,
+        bump: 0,
+        schema_version: CURRENT_SCHEMA_VERSION,
+        reserved: [0; SCHEMA_RESERVED_LEN],
+    };
+    let data = serialized(&membership);
+    assert_eq!(&data[filters::AUDITOR_REGISTRY..][..32], registry.as_ref());
+    assert_eq!(&data[filters::AUDITOR_AUTHORITY..][..32], auditor.as_ref());
+
+    // Legacy accounts are found by their first-release size.
+    let RpcFilterType::DataSize(size) = filters::unversioned::<v0::ProgramEntry>() else {
+        panic!("expected a size filter")
+    };
+    assert_eq!(size as usize, v0::ProgramEntry::SPACE);
+}
+
+#[test]
+fn the_legacy_registry_keeps_its_first_release_address() {
+    let program_id = parity::ID;
+    let legacy = Pubkey::find_program_address(&[b"registry"], &program_id);
+    assert_eq!(pda::registry(&program_id, pda::LEGACY_NAMESPACE), legacy);
+}
+
+#[test]
+fn error_codes_map_back_to_parity_errors() {
+    for (i, error) in PARITY_ERRORS.iter().enumerate() {
+        assert_eq!(u32::from(*error), 6000 + i as u32);
+        assert_eq!(parity_error(u32::from(*error)).map(|e| e.name()), Some(error.name()));
+    }
+    assert!(parity_error(5999).is_none());
+    assert!(parity_error(6000 + PARITY_ERRORS.len() as u32).is_none());
+
+    let program_id = parity::ID;
+    let author = Pubkey::new_unique();
+    let registry = pda::registry(&program_id, pda::DEFAULT_NAMESPACE).0;
+    let ix = instructions::deprecate_skill(&program_id, &registry, &author, &author, "x");
+    let code = u32::from(ParityError::SkillAlreadyDeprecated);
+    let failed = || RpcError::from(TransactionError::InstructionError(0, InstructionError::Custom(code)));
+
+    let mapped = from_send_error(failed(), std::slice::from_ref(&ix), &program_id);
+    assert!(matches!(mapped, ClientError::Parity(ParityError::SkillAlreadyDeprecated)));
+
+    // Custom codes from another program are not ours to interpret.
+    let other = from_send_error(failed(), &[ix], &Pubkey::new_unique());
+    assert!(matches!(other, ClientError::Rpc(_)));
+}
+
+#[test]
+fn program_hash_ignores_zero_padding_and_loader_state() {
+    let elf = [b"\x7fELF".as_slice(), &[1, 2, 0, 3]].concat();
+    let hash = program_hash::program_hash(&elf);
+    let padded = [elf.as_slice(), &[0; 64]].concat();
+    assert_eq!(program_hash::program_hash(&padded), hash);
+    assert_ne!(program_hash::program_hash(&elf[..elf.len() - 1]), hash);
+
+    let mut program_data = vec![0; program_hash::PROGRAM_DATA_METADATA_LEN];
+    program_data[0] = 3;
+    program_data.extend_from_slice(&padded);
+    assert_eq!(program_hash::program_data_hash(&program_data), Some(hash));
+    assert_eq!(program_hash::from_bytes(&program_data), Some(hash));
+    assert_eq!(program_hash::from_bytes(&padded), Some(hash));
+    assert_eq!(program_hash::from_bytes(b"not a program"), None);
+    assert_eq!(program_hash::program_data_hash(&[3, 0, 0, 0]), None);
+}
+
+#[test]
+fn attestation_messages_round_trip_and_verify() {
+    let auditor = solana_sdk::signature::Keypair::new();
+    let signed = result().attestation(&Pubkey::new_unique(), [5; 32], 7, 1_900_000_000);
+    let message = attestation::attestation_message(&signed);
+    assert!(message.starts_with(attestation::ATTESTATION_DOMAIN));
+    assert!(attestation::parse_message(&message) == Some(signed.clone()));
+    assert!(attestation::parse_message(&message[1..]).is_none());
+
+    let signature = attestation::sign(&auditor, &signed);
+    assert!(attestation::verify(&auditor.pubkey(), &signature, &signed));
+    let other = AnalysisAttestation { nonce: 8, ..signed.clone() };
+    assert!(!attestation::verify(&auditor.pubkey(), &signature, &other));
+
+    // Key, signature and message all sit inside the Ed25519 instruction.
+    let ix = attestation::ed25519_instruction(&auditor.pubkey(), &signature, &signed);
+    assert_eq!(ix.program_id, solana_sdk::ed25519_program::ID);
+    assert_eq!(&ix.data[16..48], auditor.pubkey().as_ref());
+    assert_eq!(&ix.data[112..], message.as_slice());
+}
+
+#[test]
+fn batches_are_split_to_fit_a_transaction() {
+    let program_id = parity::ID;
+    let registry = pda::registry(&program_id, pda::DEFAULT_NAMESPACE).0;
+    let auditor = Pubkey::new_unique();
+    let submissions: Vec<_> = (0..100u8).map(|i| result().submission([i; 32])).collect();
+    let transactions = batch::submit_analyses(&program_id, &registry, &auditor, &submissions);
+    assert!(transactions.len() > 1);
+
+    let mut covered = Vec::new();
+    for ixs in &transactions {
+        assert!(batch::fits(&auditor, ixs));
+        let [limit, ix] = ixs.as_slice() else { panic!("expected a compute budget and a batch instruction") };
+        let items = (ix.accounts.len() - 4) / 2;
+        let expected = batch::compute_units(batch::SUBMIT_ANALYSIS_COMPUTE_UNITS, items);
+        assert_eq!(*limit, ComputeBudgetInstruction::set_compute_unit_limit(expected));
+        assert!(expected <= batch::MAX_TRANSACTION_COMPUTE_UNITS);
+        let entries = ix.accounts[4..].iter().step_by(2).map(|meta| meta.pubkey);
+        covered.extend(entries);
+    }
+    // Items stay in order, each in exactly one transaction.
+    let expected: Vec<_> = (0..100u8).map(|i| pda::program_entry(&program_id, &registry, &[i; 32]).0).collect();
+    assert_eq!(covered, expected);
+
+    // Every batch but the last is full: one more item would not fit.
+    let first = &transactions[0][1];
+    let items = (first.accounts.len() - 4) / 2;
+    let grown = batch::submit_analyses(&program_id, &registry, &auditor, &submissions[..items + 1]);
+    assert_eq!(grown.len(), 2);
+
+    // Large items get transactions of their own rather than being dropped.
+    let authority = Pubkey::new_unique();
+    let skill = |name: &str| SkillRegistration {
+        name: name.to_string(),
+        version: "1".to_string(),
+        description: "d".repeat(1000),
+        skill_type: SkillType::Custom,
+    };
+    let oversized = batch::register_skills(&program_id, &registry, &authority, &[skill("a"), skill("b")]);
+    assert_eq!(oversized.len(), 2);
+
+    // Program registrations are budgeted by the size of the ProgramData they hash.
+    let registration = |i: u8| ProgramRegistration { program_hash: [i; 32], framework: Framework::Anchor, metadata_uri: String::new() };
+    let programs: Vec<_> = (0..3u8).map(|i| (Pubkey::new_unique(), 500_000, registration(i))).collect();
+    let transactions = batch::register_programs(&program_id, &registry, &authority, &authority, &programs);
+    let expected = batch::BATCH_BASE_COMPUTE_UNITS + 2 * batch::register_program_compute_units(500_000);
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0][0], ComputeBudgetInstruction::set_compute_unit_limit(expected));
+}
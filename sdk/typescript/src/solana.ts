@@ -11,7 +11,7 @@ import {
     AnalysisFindingsCount,
 } from "./types";
 
-const PROGRAM_ID = new PublicKey("PARTYxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx1");
+const PROGRAM_ID = new PublicKey("Parity1111111111111111111111111111111111111");
 
 export class SolanaProvider {
     private readonly connection: Connection;